@@ -1,8 +1,14 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::io::Write;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug, Clone)]
 #[command(version, about = "Rust replacement for ddclient - Multi-provider Dynamic DNS updater", long_about = None)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// DNS protocol/provider (cloudflare, dyndns2, namecheap, etc.) - ddclient compatible
     #[arg(long)]
     pub protocol: Option<String>,
@@ -27,10 +33,26 @@ pub struct Args {
     #[arg(long)]
     pub host: Option<String>,
 
-    /// TTL for DNS records
-    #[arg(long)]
+    /// TTL for DNS records in seconds, or "auto"
+    #[arg(long, value_parser = parse_ttl)]
     pub ttl: Option<u32>,
 
+    /// Timeout in seconds for each provider HTTP request (default: 30)
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+
+    /// PEM file with an extra CA certificate to trust for provider HTTPS requests
+    #[arg(long, value_name = "PATH")]
+    pub ca_cert: Option<PathBuf>,
+
+    /// User-Agent header for provider requests (default: rddclient/<version>)
+    #[arg(long, value_name = "STRING")]
+    pub user_agent: Option<String>,
+
+    /// Don't warn when the config file is readable by other users
+    #[arg(long, default_value = "false")]
+    pub ignore_permissions: bool,
+
     /// Manually specify IP address (instead of auto-detection)
     #[arg(long)]
     pub ip: Option<String>,
@@ -43,10 +65,109 @@ pub struct Args {
     #[arg(long)]
     pub cache: Option<String>,
 
+    /// State file format used when saving: 'ddclient' or 'json' (format is auto-detected on load)
+    #[arg(long, default_value = "ddclient")]
+    pub state_format: String,
+
+    /// Refuse to start if the state file's checksum doesn't match (default: warn and continue)
+    #[arg(long, default_value = "false")]
+    pub strict_state: bool,
+
+    /// Write the state file to PATH in --state-format, then exit
+    #[arg(long, value_name = "PATH")]
+    pub convert_state: Option<String>,
+
+    /// Don't take an advisory lock on the state file while reading or writing it
+    #[arg(long, default_value = "false")]
+    pub no_lock: bool,
+
+    /// Number of previous state files kept as <cache>.bak, <cache>.bak.1, ...
+    #[arg(long, value_name = "N", default_value = "1")]
+    pub backup_count: u32,
+
+    /// Don't back up the state file before overwriting it
+    #[arg(long, default_value = "false")]
+    pub no_backup: bool,
+
+    /// Forget state entries for hosts no longer in the config once their last update is this many days old
+    #[arg(long, value_name = "DAYS")]
+    pub state_max_age: Option<u64>,
+
+    /// Store state in a SQLite database instead of the cache file (imports the cache file on first use)
+    #[arg(long)]
+    pub db: Option<String>,
+
+    /// Import host states from a ddclient cache file into the current state, then exit
+    /// Hosts already tracked are skipped unless --force is given
+    #[arg(long)]
+    pub import_ddclient_cache: Option<String>,
+
+    /// Show what --import-ddclient-cache would import without writing anything
+    #[arg(long, default_value = "false", requires = "import_ddclient_cache")]
+    pub dry_run_import: bool,
+
+    /// Run continuously, repeating the update every --interval until SIGINT/SIGTERM
+    #[arg(long, default_value = "false")]
+    pub daemon: bool,
+
+    /// In daemon mode, re-check the IP as soon as an interface address changes (Linux only;
+    /// limited to --if-name when given)
+    #[arg(long, default_value = "false", requires = "daemon")]
+    pub network_events: bool,
+
+    /// Time between updates in daemon mode (e.g., 300, 5m; default: min-interval)
+    #[arg(long)]
+    pub interval: Option<String>,
+
+    /// Write the process ID to this file while running (removed on clean shutdown)
+    #[arg(long)]
+    pub pid_file: Option<String>,
+
+    /// Re-run updates whenever the config file changes (reloading it first); keeps running like --daemon
+    #[arg(long, default_value = "false")]
+    pub watch: bool,
+
     /// Test mode - validate config and show what would happen without updating (ddclient compatible)
     #[arg(long, default_value = "false")]
     pub test: bool,
 
+    /// Print the requests each update would send (auth masked) instead of sending them
+    #[arg(long, default_value = "false")]
+    pub dry_run: bool,
+
+    /// Show all hosts tracked in the state file with their next allowed update time, then exit
+    #[arg(long, default_value = "false")]
+    pub status: bool,
+
+    /// Detect the IP and report which hosts would be updated, without contacting the provider.
+    /// Exits 0 if nothing needs updating, 1 if any host would be updated, 2 on error
+    #[arg(long, default_value = "false")]
+    pub check: bool,
+
+    /// Print the merged configuration (config file plus command line) in ddclient format, then exit
+    #[arg(long, default_value = "false")]
+    pub print_config: bool,
+
+    /// Print credentials in full with --print-config instead of masking them
+    #[arg(long, default_value = "false", requires = "print_config")]
+    pub show_secrets: bool,
+
+    /// Print --check results and the update summary as JSON
+    #[arg(long, default_value = "false")]
+    pub json: bool,
+
+    /// Shell command run before each update pass (gets RDDCLIENT_IP and RDDCLIENT_HOSTNAMES)
+    #[arg(long, value_name = "CMD")]
+    pub pre_update_cmd: Option<String>,
+
+    /// Shell command run after each update pass (also gets RDDCLIENT_UPDATED and RDDCLIENT_FAILED)
+    #[arg(long, value_name = "CMD")]
+    pub post_update_cmd: Option<String>,
+
+    /// Log hook failures instead of aborting the update pass
+    #[arg(long, default_value = "false")]
+    pub ignore_hook_errors: bool,
+
     /// Verbose output
     #[arg(long, default_value = "false")]
     pub verbose: bool,
@@ -59,11 +180,20 @@ pub struct Args {
     #[arg(long, default_value = "false")]
     pub quiet: bool,
 
+    /// Send log messages to the local syslog daemon instead of the terminal (Unix only)
+    #[cfg(unix)]
+    #[arg(long, default_value = "false")]
+    pub syslog: bool,
+
+    /// Format of log messages on stderr: 'text' or 'json' (one JSON object per line)
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    pub log_format: String,
+
     /// Force DNS update even if IP hasn't changed - ddclient compatible
     #[arg(long, default_value = "false")]
     pub force: bool,
 
-    /// IP detection method: 'ip', 'web', 'if', 'cmd' - ddclient compatible
+    /// IP detection method: 'ip', 'web', 'if', 'default-if', 'cmd', 'dhcp' - ddclient compatible
     #[arg(long)]
     pub use_method: Option<String>,
 
@@ -75,9 +205,70 @@ pub struct Args {
     #[arg(long)]
     pub cmd: Option<String>,
 
-    /// Web service URL for IP detection (use with --use=web) - ddclient compatible
+    /// Web service URL for IP detection (use with --use=web), tried before the built-in
+    /// services; may be given more than once - ddclient compatible
+    #[arg(long)]
+    pub web: Vec<String>,
+
+    /// Regex whose first capture group is the IP in the --web page (use with --use=web)
+    #[arg(long, value_name = "PATTERN")]
+    pub web_regex: Option<String>,
+
+    /// dhclient lease file to read the address from (use with --use=dhcp)
+    #[arg(long, value_name = "PATH")]
+    pub dhcp_lease: Option<std::path::PathBuf>,
+
+    /// Don't warn when the detected IP is private, CGNAT or link-local (loopback is always rejected)
+    #[arg(long, default_value = "false")]
+    pub allow_private: bool,
+
+    /// Send updates to the provider's test/sandbox API where one exists (GoDaddy OTE)
+    #[arg(long, default_value = "false")]
+    pub provider_test: bool,
+
+    /// Fetch all Cloudflare zone IDs before the first update instead of looking zones up one by one
+    #[arg(long, default_value = "false")]
+    pub cf_preload_zones: bool,
+
+    /// Ask DynDNS2 servers to mark the hosts offline (offline=YES)
+    #[arg(long, default_value = "false")]
+    pub offline: bool,
+
+    /// Send readiness and status notifications to systemd (requires the 'systemd' feature)
+    #[arg(long, default_value = "false")]
+    pub systemd: bool,
+
+    /// Extra HTTP header sent with every provider request, as NAME:VALUE (repeatable)
+    #[arg(long, value_name = "NAME:VALUE", value_parser = parse_header)]
+    pub header: Vec<(String, String)>,
+
+    /// Webhook URL to POST a JSON notification to after each successful update
+    #[arg(long)]
+    pub webhook: Option<String>,
+
+    /// ntfy topic to publish a push notification to after each successful update
+    #[arg(long)]
+    pub ntfy_topic: Option<String>,
+
+    /// ntfy server URL (use with --ntfy-topic)
+    #[arg(long, default_value = "https://ntfy.sh")]
+    pub ntfy_server: String,
+
+    /// ntfy access token for protected topics (use with --ntfy-topic)
     #[arg(long)]
-    pub web: Option<String>,
+    pub ntfy_token: Option<String>,
+
+    /// After each successful update, check that the record resolves to the new IP
+    #[arg(long, default_value = "false")]
+    pub verify_after_update: bool,
+
+    /// DNS resolver queried by --verify-after-update (IP, optionally with :port)
+    #[arg(long, value_name = "ADDR", default_value = "8.8.8.8")]
+    pub verify_resolver: String,
+
+    /// Re-checks (5s apart) when the record doesn't match yet
+    #[arg(long, value_name = "N", default_value = "3")]
+    pub verify_retries: u32,
 
     /// Minimum interval between updates (e.g., 30s, 5m, 1h) - ddclient compatible
     #[arg(long)]
@@ -87,13 +278,118 @@ pub struct Args {
     #[arg(long)]
     pub max_interval: Option<String>,
 
+    /// Consecutive failures after which a host is disabled until --reenable (0 = never disable)
+    #[arg(long, default_value = "10")]
+    pub max_retries: u32,
+
+    /// Re-enable a host disabled after too many failures, then exit
+    #[arg(long, value_name = "HOSTNAME")]
+    pub reenable: Option<String>,
+
+    /// Print the recorded IP address changes of a host, then exit
+    #[arg(long, value_name = "HOSTNAME")]
+    pub history: Option<String>,
+
     /// Minimum interval between update attempts after an error (e.g., 5m, 10m) - ddclient compatible
     #[arg(long)]
     pub min_error_interval: Option<String>,
 }
 
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
+    /// Check the configuration for mistakes without contacting any provider
+    Validate,
+    /// Create a config file by answering a few questions
+    Init {
+        /// Where to write the config file; an existing file is kept as <output>.bak
+        #[arg(long, default_value = "rddclient.conf")]
+        output: std::path::PathBuf,
+    },
+}
+
 impl Args {
     pub fn new() -> Self {
         Self::parse()
     }
 }
+
+/// Parse a `--ttl` argument
+fn parse_ttl(ttl: &str) -> Result<u32, String> {
+    crate::config::parse_ttl(ttl).map_err(|e| e.to_string())
+}
+
+/// Parse a `--header NAME:VALUE` argument
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    let (name, value) = header.split_once(':')
+        .ok_or_else(|| format!("invalid header '{}', expected NAME:VALUE", header))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("invalid header '{}', name is empty", header));
+    }
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+/// Write the completion script for `shell`
+pub fn write_completions(shell: Shell, out: &mut dyn Write) {
+    clap_complete::generate(shell, &mut Args::command(), env!("CARGO_PKG_NAME"), out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completions_for(shell: &str) -> String {
+        let args = Args::try_parse_from(["rddclient", "completions", shell]).unwrap();
+        let Some(Command::Completions { shell }) = args.command else {
+            panic!("expected completions subcommand");
+        };
+
+        let mut out = Vec::new();
+        write_completions(shell, &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_bash_completions() {
+        let script = completions_for("bash");
+        assert!(!script.is_empty());
+        assert!(script.contains("--host"));
+    }
+
+    #[test]
+    fn test_completions_for_all_shells() {
+        for shell in ["zsh", "fish", "powershell"] {
+            assert!(completions_for(shell).contains("host"), "{} completions missing --host", shell);
+        }
+    }
+
+    #[test]
+    fn test_unknown_shell_rejected() {
+        assert!(Args::try_parse_from(["rddclient", "completions", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn test_header_flag() {
+        let args = Args::try_parse_from([
+            "rddclient", "--header", "X-Tenant: home", "--header", "Authorization:Basic a2V5OnZhbHVl",
+        ]).unwrap();
+        assert_eq!(args.header, vec![
+            ("X-Tenant".to_string(), "home".to_string()),
+            ("Authorization".to_string(), "Basic a2V5OnZhbHVl".to_string()),
+        ]);
+        assert!(Args::try_parse_from(["rddclient", "--header", "no-colon"]).is_err());
+        assert!(Args::try_parse_from(["rddclient", "--header", ":value"]).is_err());
+    }
+
+    #[test]
+    fn test_ttl_flag() {
+        assert_eq!(Args::try_parse_from(["rddclient", "--ttl", "auto"]).unwrap().ttl, Some(1));
+        assert_eq!(Args::try_parse_from(["rddclient", "--ttl", "300"]).unwrap().ttl, Some(300));
+        assert!(Args::try_parse_from(["rddclient", "--ttl", "soon"]).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+/// `--check` report: which hosts would be updated on this run, without contacting the provider
+///
+/// Only the IP detection touches the network; decisions come from the state file.
+use crate::state::StateStore;
+use crate::RateLimits;
+use serde::Serialize;
+use std::net::IpAddr;
+
+/// Outcome of checking one host
+#[derive(Debug, Serialize)]
+pub struct HostCheck {
+    pub hostname: String,
+    pub detected_ip: IpAddr,
+    pub cached_ip: Option<IpAddr>,
+    pub would_update: bool,
+    /// Why the update is skipped (or forced), as reported by the rate limiter
+    pub reason: Option<String>,
+}
+
+/// Decide for every host whether an update would fire. `intervals_for` returns the
+/// per-host (min, max) interval overrides. The state is not saved afterwards.
+pub fn evaluate(
+    state: &mut dyn StateStore,
+    hostnames: &[String],
+    ip: IpAddr,
+    force: bool,
+    limits: &RateLimits,
+    intervals_for: impl Fn(&str) -> (Option<u64>, Option<u64>),
+) -> Vec<HostCheck> {
+    hostnames
+        .iter()
+        .map(|hostname| {
+            let cached_ip = state.get(hostname).and_then(|host| host.ip);
+            let ip_changed = cached_ip != Some(ip);
+            let (host_min, host_max) = intervals_for(hostname);
+            let (would_update, reason) = state.should_update(
+                hostname,
+                ip_changed,
+                force,
+                host_min.or(limits.min),
+                host_max.or(limits.max),
+                limits.min_error,
+                limits.max_retries,
+            );
+            HostCheck {
+                hostname: hostname.clone(),
+                detected_ip: ip,
+                cached_ip,
+                would_update,
+                reason,
+            }
+        })
+        .collect()
+}
+
+/// One line per host
+pub fn render_text(checks: &[HostCheck]) -> String {
+    checks
+        .iter()
+        .map(|check| {
+            let cached = check.cached_ip.map(|ip| ip.to_string()).unwrap_or_else(|| "none".to_string());
+            let decision = if check.would_update { "update needed" } else { "no update" };
+            let reason = check.reason.as_deref().map(|reason| format!(" ({})", reason)).unwrap_or_default();
+            format!("{}: detected {}, cached {}: {}{}\n", check.hostname, check.detected_ip, cached, decision, reason)
+        })
+        .collect()
+}
+
+/// JSON array of per-host objects
+pub fn render_json(checks: &[HostCheck]) -> String {
+    serde_json::to_string_pretty(checks).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// 0 when nothing needs updating, 1 when any host would be updated
+pub fn exit_code(checks: &[HostCheck]) -> i32 {
+    if checks.iter().any(|check| check.would_update) { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{current_timestamp, StateFormat, StateManager};
+
+    fn limits() -> RateLimits {
+        RateLimits { min: Some(30), max: Some(25 * 86400), min_error: Some(300), max_retries: None }
+    }
+
+    fn state_with(hostname: &str, ip: &str) -> StateManager {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = StateManager::new(Some(dir.path().join("cache")), StateFormat::DdClient, false, true).unwrap();
+        state.get_mut(hostname).update_success(ip.parse().unwrap(), "good".to_string());
+        state.get_mut(hostname).mtime = Some(current_timestamp() - 3600);
+        state
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut state = state_with("a.example.com", "203.0.113.1");
+        let hosts = vec!["a.example.com".to_string(), "b.example.com".to_string()];
+        let ip = "203.0.113.1".parse().unwrap();
+
+        let checks = evaluate(&mut state, &hosts, ip, false, &limits(), |_| (None, None));
+        assert!(!checks[0].would_update);
+        assert_eq!(checks[0].reason.as_deref(), Some("IP address hasn't changed"));
+        assert_eq!(checks[0].cached_ip, Some(ip));
+        assert!(checks[1].would_update);
+        assert_eq!(checks[1].cached_ip, None);
+        assert_eq!(exit_code(&checks), 1);
+        assert_eq!(exit_code(&checks[..1]), 0);
+
+        let checks = evaluate(&mut state, &hosts[..1], ip, true, &limits(), |_| (None, None));
+        assert!(checks[0].would_update);
+    }
+
+    #[test]
+    fn test_render() {
+        let checks = vec![
+            HostCheck {
+                hostname: "a.example.com".to_string(),
+                detected_ip: "203.0.113.1".parse().unwrap(),
+                cached_ip: Some("198.51.100.1".parse().unwrap()),
+                would_update: true,
+                reason: None,
+            },
+            HostCheck {
+                hostname: "b.example.com".to_string(),
+                detected_ip: "203.0.113.1".parse().unwrap(),
+                cached_ip: None,
+                would_update: false,
+                reason: Some("max retries exceeded, host disabled".to_string()),
+            },
+        ];
+        assert_eq!(
+            render_text(&checks),
+            "a.example.com: detected 203.0.113.1, cached 198.51.100.1: update needed\n\
+             b.example.com: detected 203.0.113.1, cached none: no update (max retries exceeded, host disabled)\n"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&render_json(&checks)).unwrap();
+        assert_eq!(json[0]["hostname"], "a.example.com");
+        assert_eq!(json[0]["cached_ip"], "198.51.100.1");
+        assert_eq!(json[0]["would_update"], true);
+        assert_eq!(json[1]["cached_ip"], serde_json::Value::Null);
+    }
+}
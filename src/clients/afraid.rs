@@ -1,11 +1,12 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use sha1::{Digest, Sha1};
 use std::error::Error;
 use std::net::IpAddr;
 
 /// Type alias for Afraid.org record: (hostname, record_type, update_url)
-type AfraidRecord = (String, String, String);
+pub type AfraidRecord = (String, String, String);
 
 /// Afraid.org (FreeDNS) DNS client using v2 API
 ///
@@ -13,22 +14,30 @@ type AfraidRecord = (String, String, String);
 /// 1. Get record list with SHA1(login|password)
 /// 2. Call record-specific update URL
 ///
+/// With `afraid_update_url=` set, that URL is called directly instead and
+/// login/password aren't needed.
+///
 /// API docs: <https://freedns.afraid.org/api/>
 pub struct AfraidClient {
     server: String,
     login: String,
     password: String,
+    /// Pre-computed per-record update URL (`afraid_update_url=`)
+    update_url: Option<String>,
+    http: http::HttpClient,
 }
 
 impl AfraidClient {
     pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
-        let login = config.login.as_ref()
-            .ok_or("login is required for Afraid.org")?
-            .clone();
-        
-        let password = config.password.as_ref()
-            .ok_or("password is required for Afraid.org")?
-            .clone();
+        let update_url = config.afraid_update_url.clone();
+
+        // Credentials are only needed to look up the update URL
+        let (login, password) = match (&update_url, &config.login, &config.password) {
+            (Some(_), login, password) => (login.clone().unwrap_or_default(), password.clone().unwrap_or_default()),
+            (None, None, _) => return Err("login is required for Afraid.org (or set afraid_update_url)".into()),
+            (None, _, None) => return Err("password is required for Afraid.org (or set afraid_update_url)".into()),
+            (None, Some(login), Some(password)) => (login.clone(), password.clone()),
+        };
         
         let server = config.server.clone()
             .unwrap_or_else(|| "https://freedns.afraid.org".to_string());
@@ -37,93 +46,17 @@ impl AfraidClient {
             server,
             login,
             password,
+            update_url,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
 
-    /// Get list of all records and their update URLs
-    /// Returns Vec of (hostname, record_type, update_url)
-    fn get_record_list(&self) -> Result<Vec<AfraidRecord>, Box<dyn Error>> {
-        // Step 1: Generate SHA1 hash of "login|password"
-        let credentials = format!("{}|{}", self.login, self.password);
-        let hash = hex::encode(Sha1::digest(credentials.as_bytes()));
-        
-        // Step 2: Request record list
-        let url = format!("{}/api/?action=getdyndns&v=2&sha={}", self.server, hash);
-        
-        log::debug!("Fetching record list from Afraid.org");
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .send()?;
-        
-        if response.status_code != 200 {
-            return Err(format!("HTTP {} error fetching record list", response.status_code).into());
-        }
-        
-        let body = response.as_str()?;
-        log::debug!("Record list response: {} lines", body.lines().count());
-        
-        // Step 3: Parse pipe-delimited response
-        // Format: hostname|current_ip|update_url
-        let mut records = Vec::new();
-        for line in body.lines() {
-            let parts: Vec<&str> = line.split('|').collect();
-            if parts.len() >= 3 {
-                records.push((
-                    parts[0].to_string(),
-                    parts[1].to_string(),
-                    parts[2].to_string(),
-                ));
-                log::debug!("Found record: {} -> {} (update URL present)", parts[0], parts[1]);
-            }
-        }
-        
-        if records.is_empty() {
-            return Err("No records found in Afraid.org account".into());
-        }
-        
-        Ok(records)
-    }
-}
-
-impl DnsClient for AfraidClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        log::info!("Updating {} with Afraid.org", hostname);
-
-        // Get all records
-        let records = self.get_record_list()?;
-        
-        // Find matching record for this hostname and IP type
-        let is_ipv6 = ip.is_ipv6();
-        let matching_record = records.iter()
-            .find(|(host, current_ip, _)| {
-                if host != hostname {
-                    return false;
-                }
-                // Match IP type: NULL can be updated with IPv4, otherwise must match
-                if current_ip == "NULL" && !is_ipv6 {
-                    return true;
-                }
-                // Check if current IP matches our IP type
-                current_ip.contains(':') == is_ipv6
-            });
-        
-        let (_, current_ip, update_url) = matching_record
-            .ok_or_else(|| format!("No matching {} record found for {}", 
-                                  if is_ipv6 { "AAAA" } else { "A" }, 
-                                  hostname))?;
-        
-        // Check if update is needed
-        if current_ip == &ip.to_string() {
-            log::info!("Record {} already set to {}, no update needed", hostname, ip);
-            return Ok(());
-        }
-        
-        // Call update URL with new address
-        let update_url = format!("{}&address={}", update_url, ip);
+    /// Call an update URL and check the response
+    fn send_update(&self, update_url: &str, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let update_url = address_url(update_url, ip);
         log::debug!("Calling update URL (credentials redacted)");
         
-        let response = minreq::get(&update_url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(&update_url))
             .send()?;
         
         let status_code = response.status_code;
@@ -145,8 +78,115 @@ impl DnsClient for AfraidClient {
             Err(format!("Unexpected response: {}", body).into())
         }
     }
+}
+
+/// Get list of all records and their update URLs, authenticated with SHA1(login|password).
+/// Returns Vec of (hostname, current_ip, update_url); also used by the freedns account mode.
+pub fn get_record_list(http: &http::HttpClient, server: &str, login: &str, password: &str) -> Result<Vec<AfraidRecord>, Box<dyn Error>> {
+    let url = record_list_url(server, &credentials_hash(login, password));
+
+    log::debug!("Fetching record list from Afraid.org");
+    let response = http.prepare(minreq::get(&url))
+        .send()?;
+
+    if response.status_code != 200 {
+        return Err(format!("HTTP {} error fetching record list", response.status_code).into());
+    }
+
+    let body = response.as_str()?;
+    log::debug!("Record list response: {} lines", body.lines().count());
+
+    // Step 3: Parse pipe-delimited response
+    // Format: hostname|current_ip|update_url
+    let mut records = Vec::new();
+    for line in body.lines() {
+        let parts: Vec<&str> = line.split('|').collect();
+        if parts.len() >= 3 {
+            records.push((
+                parts[0].to_string(),
+                parts[1].to_string(),
+                parts[2].to_string(),
+            ));
+            log::debug!("Found record: {} -> {} (update URL present)", parts[0], parts[1]);
+        }
+    }
+
+    if records.is_empty() {
+        return Err("No records found in Afraid.org account".into());
+    }
+
+    Ok(records)
+}
+
+/// SHA1 hash of "login|password", which authenticates the record list request
+pub fn credentials_hash(login: &str, password: &str) -> String {
+    hex::encode(Sha1::digest(format!("{}|{}", login, password).as_bytes()))
+}
+
+/// URL of the v2 record list
+pub fn record_list_url(server: &str, hash: &str) -> String {
+    format!("{}/api/?action=getdyndns&v=2&sha={}", server, hash)
+}
+
+/// The token of an update URL (its query), masked in --dry-run output
+pub fn update_token(update_url: &str) -> &str {
+    update_url.split_once('?').map_or("", |(_, token)| token)
+}
+
+/// Find the record for `hostname` matching the IP type of `ip`
+pub fn find_record<'a>(records: &'a [AfraidRecord], hostname: &str, ip: IpAddr) -> Result<&'a AfraidRecord, Box<dyn Error>> {
+    let is_ipv6 = ip.is_ipv6();
+    records.iter()
+        .find(|(host, current_ip, _)| {
+            if host != hostname {
+                return false;
+            }
+            // Match IP type: NULL can be updated with IPv4, otherwise must match
+            if current_ip == "NULL" && !is_ipv6 {
+                return true;
+            }
+            // Check if current IP matches our IP type
+            current_ip.contains(':') == is_ipv6
+        })
+        .ok_or_else(|| format!("No matching {} record found for {}",
+                               if is_ipv6 { "AAAA" } else { "A" },
+                               hostname).into())
+}
+
+/// `update_url` with the new address appended
+pub fn address_url(update_url: &str, ip: IpAddr) -> String {
+    let separator = if update_url.contains('?') { '&' } else { '?' };
+    format!("{}{}address={}", update_url, separator, ip)
+}
+
+impl DnsClient for AfraidClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with Afraid.org", hostname);
+
+        if let Some(update_url) = &self.update_url {
+            return self.send_update(update_url, hostname, ip);
+        }
+
+        let records = get_record_list(&self.http, &self.server, &self.login, &self.password)?;
+        let (_, current_ip, update_url) = find_record(&records, hostname, ip)?;
+
+        // Check if update is needed
+        if current_ip == &ip.to_string() {
+            log::info!("Record {} already set to {}, no update needed", hostname, ip);
+            return Ok(());
+        }
+        
+        // Call update URL with new address
+        self.send_update(update_url, hostname, ip)
+    }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(update_url) = &self.update_url {
+            if !update_url.starts_with("http://") && !update_url.starts_with("https://") {
+                return Err("afraid_update_url must be an http(s) URL".into());
+            }
+            return Ok(());
+        }
         if self.login.is_empty() {
             return Err("login is required for Afraid.org".into());
         }
@@ -159,4 +199,124 @@ impl DnsClient for AfraidClient {
     fn provider_name(&self) -> &str {
         "Afraid.org"
     }
+
+    fn preview_update(&self, _hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        if let Some(update_url) = &self.update_url {
+            return Ok(vec![self.http.preview("GET", address_url(update_url, ip))
+                .with_secret(update_token(update_url))]);
+        }
+
+        let hash = credentials_hash(&self.login, &self.password);
+        Ok(vec![
+            self.http.preview("GET", record_list_url(&self.server, &hash)).with_secret(&hash),
+            self.http.preview("GET", address_url("{update_url}", ip)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_config(server: &str) -> Config {
+        Config {
+            protocol: Some("afraid".to_string()),
+            login: Some("user".to_string()),
+            password: Some("secret".to_string()),
+            server: Some(server.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_afraid_address_url() {
+        assert_eq!(
+            address_url("https://freedns.afraid.org/dynamic/update.php?abc", "203.0.113.1".parse().unwrap()),
+            "https://freedns.afraid.org/dynamic/update.php?abc&address=203.0.113.1"
+        );
+        assert_eq!(
+            address_url("https://sync.afraid.org/u/abc/", "2001:db8::1".parse().unwrap()),
+            "https://sync.afraid.org/u/abc/?address=2001:db8::1"
+        );
+    }
+
+    #[test]
+    fn test_afraid_direct_update_url() {
+        let mut server = mockito::Server::new();
+        let api = server.mock("GET", "/api/")
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create();
+        let update = server.mock("GET", "/dynamic/update.php")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("address".to_string(), "203.0.113.1".to_string()),
+                Matcher::Regex("^token123&".to_string()),
+            ]))
+            .with_body("Updated home.example.com to 203.0.113.1")
+            .create();
+
+        let config = Config {
+            afraid_update_url: Some(format!("{}/dynamic/update.php?token123", server.url())),
+            ..test_config(&server.url())
+        };
+        let client = AfraidClient::new(&config).unwrap();
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+
+        update.assert();
+        api.assert();
+
+        // Login and password aren't required in this mode
+        let config = Config {
+            protocol: Some("afraid".to_string()),
+            afraid_update_url: Some("https://freedns.afraid.org/dynamic/update.php?token123".to_string()),
+            ..Default::default()
+        };
+        assert!(AfraidClient::new(&config).unwrap().validate_config().is_ok());
+    }
+
+    #[test]
+    fn test_afraid_credentials_flow() {
+        let mut server = mockito::Server::new();
+        let sha = hex::encode(Sha1::digest(b"user|secret"));
+        let api = server.mock("GET", "/api/")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("action".to_string(), "getdyndns".to_string()),
+                Matcher::UrlEncoded("sha".to_string(), sha),
+            ]))
+            .with_body(format!("home.example.com|198.51.100.1|{}/dynamic/update.php?rec1\n", server.url()))
+            .create();
+        let update = server.mock("GET", "/dynamic/update.php")
+            .match_query(Matcher::UrlEncoded("address".to_string(), "203.0.113.1".to_string()))
+            .with_body("Updated home.example.com to 203.0.113.1")
+            .create();
+
+        let client = AfraidClient::new(&test_config(&server.url())).unwrap();
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+
+        api.assert();
+        update.assert();
+        assert!(AfraidClient::new(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_afraid_preview_update() {
+        let ip = "203.0.113.1".parse().unwrap();
+        let client = AfraidClient::new(&test_config("https://freedns.afraid.org")).unwrap();
+        let requests = client.preview_update("home.example.com", ip).unwrap();
+        let sha = hex::encode(Sha1::digest(b"user|secret"));
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, format!("https://freedns.afraid.org/api/?action=getdyndns&v=2&sha={}", sha));
+        assert!(!requests[0].to_string().contains(&sha));
+        assert_eq!(requests[1].url, "{update_url}?address=203.0.113.1");
+
+        let config = Config {
+            afraid_update_url: Some("https://freedns.afraid.org/dynamic/update.php?token123456".to_string()),
+            ..Default::default()
+        };
+        let requests = AfraidClient::new(&config).unwrap().preview_update("home.example.com", ip).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://freedns.afraid.org/dynamic/update.php?token123456&address=203.0.113.1");
+        assert!(requests[0].to_string().contains("update.php?****3456&address=203.0.113.1"));
+    }
 }
@@ -0,0 +1,305 @@
+use crate::clients::{DnsClient, RequestPreview};
+use crate::config::Config;
+use crate::http;
+use serde_json::json;
+use std::error::Error;
+use std::net::IpAddr;
+
+const API_VERSION: &str = "2018-05-01";
+
+/// Azure DNS client
+/// Authenticates with an Azure AD app registration (OAuth 2.0 client credentials)
+/// API documentation: https://learn.microsoft.com/en-us/rest/api/dns/record-sets/create-or-update
+///
+/// Configuration:
+/// - login: application (client) ID
+/// - password: client secret
+/// - zone: DNS zone name (e.g., example.com)
+/// - tenant_id, subscription_id, resource_group: location of the zone
+pub struct AzureDnsClient {
+    subscription_id: String,
+    resource_group: String,
+    zone_name: String,
+    client_id: String,
+    client_secret: String,
+    tenant_id: String,
+    server: String,
+    auth_server: String,
+    ttl: u32,
+    http: http::HttpClient,
+}
+
+impl AzureDnsClient {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let client_id = config.login.as_ref()
+            .ok_or("login (client ID) is required for Azure DNS")?
+            .clone();
+        let client_secret = config.password.as_ref()
+            .ok_or("password (client secret) is required for Azure DNS")?
+            .clone();
+        let zone_name = config.zone.as_ref()
+            .ok_or("zone is required for Azure DNS")?
+            .clone();
+        let tenant_id = config.tenant_id.as_ref()
+            .ok_or("tenant_id is required for Azure DNS")?
+            .clone();
+        let subscription_id = config.subscription_id.as_ref()
+            .ok_or("subscription_id is required for Azure DNS")?
+            .clone();
+        let resource_group = config.resource_group.as_ref()
+            .ok_or("resource_group is required for Azure DNS")?
+            .clone();
+        let server = config.server.clone()
+            .unwrap_or_else(|| "https://management.azure.com".to_string());
+
+        Ok(Self {
+            subscription_id,
+            resource_group,
+            zone_name,
+            client_id,
+            client_secret,
+            tenant_id,
+            server,
+            auth_server: "https://login.microsoftonline.com".to_string(),
+            ttl: config.ttl.unwrap_or(300),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
+    }
+
+    fn token_url(&self) -> String {
+        format!("{}/{}/oauth2/v2.0/token", self.auth_server, self.tenant_id)
+    }
+
+    /// Form body for the client credentials grant
+    fn token_request_body(&self) -> String {
+        format!(
+            "grant_type=client_credentials&client_id={}&client_secret={}&scope={}",
+            urlencoding::encode(&self.client_id),
+            urlencoding::encode(&self.client_secret),
+            urlencoding::encode(&format!("{}/.default", self.server)),
+        )
+    }
+
+    fn access_token(&self) -> Result<String, Box<dyn Error>> {
+        log::debug!("Requesting Azure AD token for client {}", self.client_id);
+
+        let response = self.http.prepare(minreq::post(self.token_url()))
+            .with_header("Content-Type", "application/x-www-form-urlencoded")
+            .with_body(self.token_request_body())
+            .send()?;
+
+        if response.status_code != 200 {
+            return Err(format!("Azure AD token request failed: HTTP {} {}",
+                response.status_code, response.as_str().unwrap_or("")).into());
+        }
+
+        let json: serde_json::Value = response.json()?;
+        json["access_token"].as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "No access_token in Azure AD response".into())
+    }
+
+    /// Relative record set name within the zone ("@" for the apex)
+    fn record_name(&self, hostname: &str) -> String {
+        if hostname == self.zone_name {
+            "@".to_string()
+        } else {
+            hostname
+                .strip_suffix(&format!(".{}", self.zone_name))
+                .unwrap_or(hostname)
+                .to_string()
+        }
+    }
+
+    fn record_set_url(&self, hostname: &str, record_type: &str) -> String {
+        format!(
+            "{}/subscriptions/{}/resourceGroups/{}/providers/Microsoft.Network/dnsZones/{}/{}/{}?api-version={}",
+            self.server,
+            self.subscription_id,
+            self.resource_group,
+            self.zone_name,
+            record_type,
+            self.record_name(hostname),
+            API_VERSION
+        )
+    }
+
+    fn record_set_body(ip: IpAddr, ttl: u32) -> serde_json::Value {
+        match ip {
+            IpAddr::V4(addr) => json!({
+                "properties": {
+                    "TTL": ttl,
+                    "ARecords": [{"ipv4Address": addr.to_string()}],
+                }
+            }),
+            IpAddr::V6(addr) => json!({
+                "properties": {
+                    "TTL": ttl,
+                    "AAAARecords": [{"ipv6Address": addr.to_string()}],
+                }
+            }),
+        }
+    }
+}
+
+impl DnsClient for AzureDnsClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+
+        log::info!("Updating {} {} record with Azure DNS", hostname, record_type);
+
+        let token = self.access_token()?;
+
+        let response = self.http.prepare(minreq::put(self.record_set_url(hostname, record_type)))
+            .with_header("Authorization", format!("Bearer {}", token))
+            .with_json(&Self::record_set_body(ip, self.ttl))?
+            .send()?;
+
+        match response.status_code {
+            // 200 = updated, 201 = created
+            200 | 201 => {
+                log::info!("Successfully updated {} to {}", hostname, ip);
+                Ok(())
+            }
+            status => Err(format!("Azure DNS error (HTTP {}): {}", status, response.as_str().unwrap_or("")).into()),
+        }
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn Error>> {
+        if self.client_id.is_empty() || self.client_secret.is_empty() {
+            return Err("login (client ID) and password (client secret) are required for Azure DNS".into());
+        }
+        if self.tenant_id.is_empty() || self.subscription_id.is_empty() || self.resource_group.is_empty() {
+            return Err("tenant_id, subscription_id and resource_group are required for Azure DNS".into());
+        }
+        if self.zone_name.is_empty() {
+            return Err("zone is required for Azure DNS".into());
+        }
+        Ok(())
+    }
+
+    fn provider_name(&self) -> &str {
+        "Azure DNS"
+    }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        Ok(vec![
+            self.http.preview("POST", self.token_url())
+                .with_header("Content-Type", "application/x-www-form-urlencoded")
+                .with_body(self.token_request_body())
+                .with_secret(&urlencoding::encode(&self.client_secret)),
+            self.http.preview("PUT", self.record_set_url(hostname, record_type))
+                .with_header("Authorization", "Bearer {access_token}")
+                .with_header("Content-Type", "application/json")
+                .with_body(Self::record_set_body(ip, self.ttl).to_string()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(server: Option<String>) -> Config {
+        Config {
+            protocol: Some("azure".to_string()),
+            login: Some("app-id".to_string()),
+            password: Some("s3cret+/=".to_string()),
+            zone: Some("example.com".to_string()),
+            tenant_id: Some("tenant-id".to_string()),
+            subscription_id: Some("sub-id".to_string()),
+            resource_group: Some("dns-rg".to_string()),
+            server,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_azure_missing_fields() {
+        let mut config = test_config(None);
+        config.tenant_id = None;
+        assert!(AzureDnsClient::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_azure_token_request() {
+        let client = AzureDnsClient::new(&test_config(None)).unwrap();
+
+        assert_eq!(client.token_url(), "https://login.microsoftonline.com/tenant-id/oauth2/v2.0/token");
+        assert_eq!(
+            client.token_request_body(),
+            "grant_type=client_credentials&client_id=app-id&client_secret=s3cret%2B%2F%3D\
+             &scope=https%3A%2F%2Fmanagement.azure.com%2F.default"
+        );
+    }
+
+    #[test]
+    fn test_azure_record_set_body() {
+        assert_eq!(
+            AzureDnsClient::record_set_body("203.0.113.1".parse().unwrap(), 300),
+            json!({"properties": {"TTL": 300, "ARecords": [{"ipv4Address": "203.0.113.1"}]}})
+        );
+        assert_eq!(
+            AzureDnsClient::record_set_body("2001:db8::1".parse().unwrap(), 60),
+            json!({"properties": {"TTL": 60, "AAAARecords": [{"ipv6Address": "2001:db8::1"}]}})
+        );
+    }
+
+    #[test]
+    fn test_azure_record_name() {
+        let client = AzureDnsClient::new(&test_config(None)).unwrap();
+        assert_eq!(client.record_name("home.example.com"), "home");
+        assert_eq!(client.record_name("example.com"), "@");
+    }
+
+    #[test]
+    fn test_azure_update_record() {
+        let mut server = mockito::Server::new();
+        let token_mock = server.mock("POST", "/tenant-id/oauth2/v2.0/token")
+            .match_body(mockito::Matcher::Regex("^grant_type=client_credentials&".to_string()))
+            .with_body(r#"{"token_type":"Bearer","expires_in":3599,"access_token":"eyJ0eXAi"}"#)
+            .create();
+        let put_mock = server.mock("PUT", "/subscriptions/sub-id/resourceGroups/dns-rg/providers/Microsoft.Network/dnsZones/example.com/AAAA/home")
+            .match_query(mockito::Matcher::UrlEncoded("api-version".to_string(), "2018-05-01".to_string()))
+            .match_header("authorization", "Bearer eyJ0eXAi")
+            .match_body(mockito::Matcher::Json(json!({
+                "properties": {"TTL": 300, "AAAARecords": [{"ipv6Address": "2001:db8::1"}]}
+            })))
+            .with_status(200)
+            .with_body("{}")
+            .create();
+
+        let mut client = AzureDnsClient::new(&test_config(Some(server.url()))).unwrap();
+        client.auth_server = server.url();
+
+        assert!(client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).is_ok());
+        token_mock.assert();
+        put_mock.assert();
+    }
+
+    #[test]
+    fn test_azure_preview_update() {
+        let mut config = test_config(None);
+        config.password = Some("client-secret-1234".to_string());
+        let client = AzureDnsClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].url, "https://login.microsoftonline.com/tenant-id/oauth2/v2.0/token");
+        assert!(!requests[0].to_string().contains("client-secret-1234"));
+        assert_eq!(requests[1].method, "PUT");
+        assert_eq!(
+            requests[1].url,
+            "https://management.azure.com/subscriptions/sub-id/resourceGroups/dns-rg/providers/Microsoft.Network/dnsZones/example.com/A/home?api-version=2018-05-01"
+        );
+        assert_eq!(
+            requests[1].body.as_deref(),
+            Some(r#"{"properties":{"ARecords":[{"ipv4Address":"203.0.113.1"}],"TTL":300}}"#)
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::clients::{zone_for, DnsClient, RequestPreview};
+use crate::config::Config;
+use crate::http;
+use serde_json::json;
+use std::error::Error;
+use std::fmt::Display;
+use std::net::IpAddr;
+
+/// Bunny DNS record type codes
+const TYPE_A: u64 = 0;
+const TYPE_AAAA: u64 = 1;
+
+/// Bunny.net DNS client
+/// API documentation: https://docs.bunny.net/reference/dnszonepublic_index
+///
+/// Configuration:
+/// - password: Bunny.net account API key
+/// - zone: DNS zone name (optional, defaults to the last two labels of the hostname)
+/// - ttl: record TTL in seconds (default 300)
+pub struct BunnyDnsClient {
+    api_key: String,
+    server: String,
+    zone: Option<String>,
+    ttl: u32,
+    http: http::HttpClient,
+}
+
+impl BunnyDnsClient {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let api_key = config.password.as_ref()
+            .ok_or("password (API key) is required for Bunny DNS")?
+            .clone();
+        let server = config.server.clone()
+            .unwrap_or_else(|| "https://api.bunny.net".to_string());
+
+        Ok(BunnyDnsClient {
+            api_key,
+            server,
+            zone: config.zone.clone(),
+            ttl: config.ttl.unwrap_or(300),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
+    }
+
+    /// Record name relative to the zone (Bunny uses "" for the apex)
+    fn record_name<'a>(hostname: &'a str, zone: &str) -> &'a str {
+        if hostname == zone {
+            ""
+        } else {
+            hostname.strip_suffix(&format!(".{}", zone)).unwrap_or(hostname)
+        }
+    }
+
+    fn zone_search_url(&self, zone: &str) -> String {
+        format!("{}/dnszone?search={}", self.server, urlencoding::encode(zone))
+    }
+
+    fn records_url(&self, zone_id: impl Display) -> String {
+        format!("{}/dnszone/{}/records", self.server, zone_id)
+    }
+
+    /// Body updating an existing record's address
+    fn update_body(&self, record_id: impl Into<serde_json::Value>, ip: IpAddr) -> serde_json::Value {
+        json!({
+            "Id": record_id.into(),
+            "Value": ip.to_string(),
+            "Ttl": self.ttl,
+        })
+    }
+
+    fn get(&self, url: &str) -> Result<serde_json::Value, Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(url))
+            .with_header("AccessKey", &self.api_key)
+            .send()?;
+
+        if response.status_code != 200 {
+            return Err(format!("Bunny DNS API error (HTTP {}): {}",
+                response.status_code, response.as_str().unwrap_or("")).into());
+        }
+        Ok(response.json()?)
+    }
+
+    fn post(&self, url: &str, body: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+        let response = self.http.prepare(minreq::post(url))
+            .with_header("AccessKey", &self.api_key)
+            .with_json(body)?
+            .send()?;
+
+        match response.status_code {
+            200 | 201 | 204 => Ok(()),
+            status => Err(format!("Bunny DNS API error (HTTP {}): {}", status, response.as_str().unwrap_or("")).into()),
+        }
+    }
+
+    fn find_zone_id(&self, zone: &str) -> Result<u64, Box<dyn Error>> {
+        let json = self.get(&self.zone_search_url(zone))?;
+
+        json["Items"].as_array()
+            .into_iter()
+            .flatten()
+            .find(|item| item["Domain"].as_str().is_some_and(|domain| domain.eq_ignore_ascii_case(zone)))
+            .and_then(|item| item["Id"].as_u64())
+            .ok_or_else(|| format!("Bunny DNS zone {} not found", zone).into())
+    }
+
+    /// ID of the existing record of `record_type` named `name`, if any
+    fn find_record(&self, zone_id: u64, name: &str, record_type: u64) -> Result<Option<u64>, Box<dyn Error>> {
+        let json = self.get(&self.records_url(zone_id))?;
+        let records = json.as_array().or_else(|| json["Records"].as_array());
+
+        let id = records
+            .into_iter()
+            .flatten()
+            .find(|record| record["Type"] == record_type && record["Name"] == name)
+            .and_then(|record| record["Id"].as_u64());
+        Ok(id)
+    }
+}
+
+impl DnsClient for BunnyDnsClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let record_type = match ip {
+            IpAddr::V4(_) => TYPE_A,
+            IpAddr::V6(_) => TYPE_AAAA,
+        };
+
+        log::info!("Updating {} with Bunny DNS", hostname);
+
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let zone_id = self.find_zone_id(&zone)?;
+        let name = Self::record_name(hostname, &zone);
+
+        match self.find_record(zone_id, name, record_type)? {
+            Some(record_id) => {
+                log::debug!("Found record ID {} for {}", record_id, hostname);
+                let url = format!("{}/{}", self.records_url(zone_id), record_id);
+                self.post(&url, &self.update_body(record_id, ip))?;
+            }
+            None => {
+                log::info!("No record for {} in zone {}, creating it", hostname, zone);
+                self.post(&self.records_url(zone_id), &json!({
+                    "Type": record_type,
+                    "Name": name,
+                    "Value": ip.to_string(),
+                    "Ttl": self.ttl,
+                }))?;
+            }
+        }
+
+        log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+        Ok(())
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn Error>> {
+        if self.api_key.is_empty() {
+            return Err("password (API key) is required for Bunny DNS".into());
+        }
+        Ok(())
+    }
+
+    fn provider_name(&self) -> &str {
+        "Bunny DNS"
+    }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let request = |method, url| self.http.preview(method, url).with_header("AccessKey", &self.api_key);
+        Ok(vec![
+            request("GET", self.zone_search_url(&zone_for(self.zone.as_deref(), hostname))),
+            request("GET", self.records_url("{zone_id}")),
+            request("POST", format!("{}/{{record_id}}", self.records_url("{zone_id}")))
+                .with_header("Content-Type", "application/json")
+                .with_body(self.update_body("{record_id}", ip).to_string()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(server: Option<String>) -> Config {
+        Config {
+            protocol: Some("bunny".to_string()),
+            password: Some("bunny-key".to_string()),
+            server,
+            ..Default::default()
+        }
+    }
+
+    fn mock_zone(server: &mut mockito::Server) -> mockito::Mock {
+        server.mock("GET", "/dnszone")
+            .match_query(mockito::Matcher::UrlEncoded("search".to_string(), "example.com".to_string()))
+            .match_header("accesskey", "bunny-key")
+            .with_body(r#"{"Items":[{"Id":7,"Domain":"sub.example.com"},{"Id":42,"Domain":"example.com"}],
+                           "CurrentPage":1,"TotalItems":2,"HasMoreItems":false}"#)
+            .create()
+    }
+
+    #[test]
+    fn test_bunny_client_creation() {
+        let client = BunnyDnsClient::new(&test_config(None)).unwrap();
+        assert_eq!(client.server, "https://api.bunny.net");
+        assert_eq!(client.ttl, 300);
+        assert_eq!(client.provider_name(), "Bunny DNS");
+        assert!(client.validate_config().is_ok());
+        assert!(BunnyDnsClient::new(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_bunny_zone_and_record_name() {
+        let client = BunnyDnsClient::new(&test_config(None)).unwrap();
+        assert_eq!(zone_for(client.zone.as_deref(), "home.example.com"), "example.com");
+        assert_eq!(zone_for(client.zone.as_deref(), "example.com"), "example.com");
+
+        let mut config = test_config(None);
+        config.zone = Some("example.co.uk".to_string());
+        let client = BunnyDnsClient::new(&config).unwrap();
+        assert_eq!(zone_for(client.zone.as_deref(), "home.example.co.uk"), "example.co.uk");
+
+        assert_eq!(BunnyDnsClient::record_name("home.example.com", "example.com"), "home");
+        assert_eq!(BunnyDnsClient::record_name("example.com", "example.com"), "");
+    }
+
+    #[test]
+    fn test_bunny_update_existing_record() {
+        let mut server = mockito::Server::new();
+        let zone = mock_zone(&mut server);
+        let records = server.mock("GET", "/dnszone/42/records")
+            .with_body(r#"[
+                {"Id":100,"Type":0,"Name":"home","Value":"198.51.100.1","Ttl":300},
+                {"Id":101,"Type":1,"Name":"home","Value":"2001:db8::5","Ttl":300}
+            ]"#)
+            .create();
+        let update = server.mock("POST", "/dnszone/42/records/101")
+            .match_header("accesskey", "bunny-key")
+            .match_body(mockito::Matcher::Json(json!({"Id": 101, "Value": "2001:db8::1", "Ttl": 300})))
+            .with_status(204)
+            .create();
+
+        let client = BunnyDnsClient::new(&test_config(Some(server.url()))).unwrap();
+        assert!(client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).is_ok());
+        zone.assert();
+        records.assert();
+        update.assert();
+    }
+
+    #[test]
+    fn test_bunny_creates_missing_record() {
+        let mut server = mockito::Server::new();
+        let _zone = mock_zone(&mut server);
+        let _records = server.mock("GET", "/dnszone/42/records")
+            .with_body(r#"[{"Id":101,"Type":1,"Name":"home","Value":"2001:db8::5","Ttl":300}]"#)
+            .create();
+        let create = server.mock("POST", "/dnszone/42/records")
+            .match_body(mockito::Matcher::Json(json!({
+                "Type": 0,
+                "Name": "home",
+                "Value": "203.0.113.1",
+                "Ttl": 300,
+            })))
+            .with_status(201)
+            .with_body(r#"{"Id":102}"#)
+            .create();
+
+        let client = BunnyDnsClient::new(&test_config(Some(server.url()))).unwrap();
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        create.assert();
+    }
+
+    #[test]
+    fn test_bunny_zone_not_found() {
+        let mut server = mockito::Server::new();
+        let _zone = server.mock("GET", "/dnszone")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"Items":[],"HasMoreItems":false}"#)
+            .create();
+
+        let client = BunnyDnsClient::new(&test_config(Some(server.url()))).unwrap();
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("zone example.com not found"));
+    }
+
+    #[test]
+    fn test_bunny_preview_update() {
+        let client = BunnyDnsClient::new(&test_config(None)).unwrap();
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        let urls: Vec<_> = requests.iter().map(|r| (r.method, r.url.as_str())).collect();
+        assert_eq!(urls, vec![
+            ("GET", "https://api.bunny.net/dnszone?search=example.com"),
+            ("GET", "https://api.bunny.net/dnszone/{zone_id}/records"),
+            ("POST", "https://api.bunny.net/dnszone/{zone_id}/records/{record_id}"),
+        ]);
+        assert_eq!(requests[2].body.as_deref(), Some(r#"{"Id":"{record_id}","Ttl":300,"Value":"203.0.113.1"}"#));
+        assert!(!requests[0].to_string().contains("bunny-key"));
+    }
+}
@@ -1,13 +1,65 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 use base64::{Engine as _, engine::general_purpose};
 
+/// Result of a ChangeIP update, sent back as `N:message`
+#[derive(Debug, PartialEq)]
+enum ChangeIpResponse {
+    /// 1: address unchanged
+    NoChange,
+    /// 2: record updated
+    Good,
+    /// -1: wrong username or password
+    BadAuth,
+    /// -2: hostname is not a fully-qualified domain name
+    NotFqdn,
+    /// -3: hostname doesn't exist
+    NoHost,
+    /// -4: hostname belongs to another account
+    NotYours,
+    /// -5: account blocked for abuse
+    Abuse,
+    Unknown(String),
+}
+
+impl ChangeIpResponse {
+    fn parse(body: &str) -> Self {
+        let body = body.trim();
+        let code = body.split_once(':').map_or(body, |(code, _)| code).trim();
+        match code {
+            "1" => ChangeIpResponse::NoChange,
+            "2" => ChangeIpResponse::Good,
+            "-1" => ChangeIpResponse::BadAuth,
+            "-2" => ChangeIpResponse::NotFqdn,
+            "-3" => ChangeIpResponse::NoHost,
+            "-4" => ChangeIpResponse::NotYours,
+            "-5" => ChangeIpResponse::Abuse,
+            _ => ChangeIpResponse::Unknown(body.to_string()),
+        }
+    }
+
+    fn into_result(self) -> Result<(), Box<dyn Error>> {
+        let message = match self {
+            ChangeIpResponse::NoChange | ChangeIpResponse::Good => return Ok(()),
+            ChangeIpResponse::BadAuth => "authentication failed - check username and password".to_string(),
+            ChangeIpResponse::NotFqdn => "hostname is not a fully-qualified domain name".to_string(),
+            ChangeIpResponse::NoHost => "hostname does not exist".to_string(),
+            ChangeIpResponse::NotYours => "hostname belongs to another account".to_string(),
+            ChangeIpResponse::Abuse => "account blocked for abuse".to_string(),
+            ChangeIpResponse::Unknown(body) => format!("unexpected response: {}", body),
+        };
+        Err(format!("ChangeIP error: {}", message).into())
+    }
+}
+
 pub struct ChangeipClient {
     username: String,
     password: String,
     server: String,
+    http: http::HttpClient,
 }
 
 impl ChangeipClient {
@@ -16,56 +68,49 @@ impl ChangeipClient {
             .ok_or("username is required for ChangeIP")?;
         let password = config.password.as_ref()
             .ok_or("password is required for ChangeIP")?;
-        let server = config.server.as_deref()
-            .unwrap_or("nic.changeip.com");
+        // A bare host name (the old default was nic.changeip.com) gets https
+        let server = match config.server.as_deref() {
+            Some(server) if server.starts_with("http://") || server.starts_with("https://") => server.to_string(),
+            Some(server) => format!("https://{}", server),
+            None => "https://nic.changeip.com".to_string(),
+        };
 
         Ok(ChangeipClient {
             username: username.to_string(),
             password: password.to_string(),
-            server: server.to_string(),
+            server,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn build_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/nic/update?hostname={}&myip={}", self.server, hostname, ip)
+    }
+
+    fn auth_header(&self) -> String {
+        let auth = general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password));
+        format!("Basic {}", auth)
+    }
 }
 
 impl DnsClient for ChangeipClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating ChangeIP record for {} to {}", hostname, ip);
 
-        let url = format!(
-            "https://{}/nic/update?hostname={}&myip={}",
-            self.server, hostname, ip
-        );
-
-        let auth = general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password));
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", auth))
+        let response = self.http.prepare(minreq::get(self.build_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         if response.status_code != 200 {
             return Err(format!("ChangeIP API error: HTTP {}", response.status_code).into());
         }
 
-        let body = response.as_str()?;
-        
-        // ChangeIP returns JSON response
-        if body.contains("\"ok\":true") || body.contains("\"msg\":\"unaltered\"") {
-            if body.contains("unaltered") {
-                log::info!("IP address already set to {}", ip);
-            } else {
-                log::info!("Successfully updated DNS record for {} to {}", hostname, ip);
-            }
-            Ok(())
-        } else if body.contains("\"ok\":false") {
-            let error_msg = body
-                .split("\"msg\":\"")
-                .nth(1)
-                .and_then(|s| s.split("\"").next())
-                .unwrap_or("Unknown error");
-            Err(format!("ChangeIP error: {}", error_msg).into())
-        } else {
-            Err(format!("Unexpected ChangeIP response: {}", body).into())
+        match ChangeIpResponse::parse(response.as_str()?) {
+            ChangeIpResponse::NoChange => log::info!("IP address already set to {}", ip),
+            ChangeIpResponse::Good => log::info!("Successfully updated DNS record for {} to {}", hostname, ip),
+            error => return error.into_result(),
         }
+        Ok(())
     }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
@@ -81,4 +126,88 @@ impl DnsClient for ChangeipClient {
     fn provider_name(&self) -> &'static str {
         "ChangeIP"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.build_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client(server: Option<String>) -> ChangeipClient {
+        let config = Config {
+            protocol: Some("changeip".to_string()),
+            login: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            server,
+            ..Default::default()
+        };
+        ChangeipClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_changeip_response_codes() {
+        assert_eq!(ChangeIpResponse::parse("1:nochg"), ChangeIpResponse::NoChange);
+        assert_eq!(ChangeIpResponse::parse("2:good\n"), ChangeIpResponse::Good);
+        assert_eq!(ChangeIpResponse::parse("-1:badauth"), ChangeIpResponse::BadAuth);
+        assert_eq!(ChangeIpResponse::parse("-2:notfqdn"), ChangeIpResponse::NotFqdn);
+        assert_eq!(ChangeIpResponse::parse("-3:nohost"), ChangeIpResponse::NoHost);
+        assert_eq!(ChangeIpResponse::parse("-4:!yours"), ChangeIpResponse::NotYours);
+        assert_eq!(ChangeIpResponse::parse("-5:abuse"), ChangeIpResponse::Abuse);
+        assert_eq!(ChangeIpResponse::parse("oops"), ChangeIpResponse::Unknown("oops".to_string()));
+    }
+
+    #[test]
+    fn test_changeip_response_results() {
+        assert!(ChangeIpResponse::NoChange.into_result().is_ok());
+        assert!(ChangeIpResponse::Good.into_result().is_ok());
+        assert!(ChangeIpResponse::BadAuth.into_result().unwrap_err().to_string().contains("authentication failed"));
+        assert!(ChangeIpResponse::NoHost.into_result().unwrap_err().to_string().contains("does not exist"));
+        assert!(ChangeIpResponse::NotYours.into_result().unwrap_err().to_string().contains("another account"));
+        assert!(ChangeIpResponse::Abuse.into_result().unwrap_err().to_string().contains("abuse"));
+    }
+
+    #[test]
+    fn test_changeip_urls() {
+        let client = test_client(None);
+        assert_eq!(
+            client.build_url("home.example.com", "203.0.113.1".parse().unwrap()),
+            "https://nic.changeip.com/nic/update?hostname=home.example.com&myip=203.0.113.1"
+        );
+        assert_eq!(
+            client.build_url("home.example.com", "2001:db8::1".parse().unwrap()),
+            "https://nic.changeip.com/nic/update?hostname=home.example.com&myip=2001:db8::1"
+        );
+        assert_eq!(test_client(Some("nic.example.net".to_string())).server, "https://nic.example.net");
+    }
+
+    #[test]
+    fn test_changeip_auth_header_and_update() {
+        let client = test_client(None);
+        // base64("user:pass")
+        assert_eq!(client.auth_header(), "Basic dXNlcjpwYXNz");
+
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/nic/update")
+            .match_query(mockito::Matcher::Any)
+            .match_header("authorization", "Basic dXNlcjpwYXNz")
+            .with_body("-3:nohost")
+            .create();
+
+        let client = test_client(Some(server.url()));
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "ChangeIP error: hostname does not exist");
+        mock.assert();
+    }
+
+    #[test]
+    fn test_changeip_preview_update() {
+        let requests = test_client(None).preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://nic.changeip.com/nic/update?hostname=home.example.com&myip=203.0.113.1");
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string())));
+    }
 }
@@ -1,16 +1,53 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use crate::utils::mask_credential;
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 use std::net::IpAddr;
+use std::sync::{Mutex, Once};
 use serde_json::json;
 
-#[derive(Debug)]
+/// Page size for the zone listing used by --cf-preload-zones
+const ZONES_PER_PAGE: u64 = 50;
+
 pub struct CloudflareClient {
     login: String,
     password: String,
-    zone: String,
+    /// Configured zones (`zone=example.com;other.com`)
+    zones: Vec<String>,
     server: String,
     ttl: u32,
+    /// Zone name -> zone ID, so hosts sharing a zone only look it up once
+    zone_id_cache: Mutex<HashMap<String, String>>,
+    /// Fetch every zone ID in the account before the first update (--cf-preload-zones)
+    preload_zones: bool,
+    /// Fills `zone_id_cache` before the first update
+    zone_cache_warmed: Once,
+    /// (zone ID, hostname, record type) -> record ID, so repeated updates skip the lookup
+    record_cache: Mutex<HashMap<(String, String, String), String>>,
+    record_cache_stats: Mutex<CacheStats>,
+    http: http::HttpClient,
+}
+
+/// Record ID cache hits and misses in this session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl fmt::Debug for CloudflareClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CloudflareClient")
+            .field("login", &self.login)
+            .field("password", &mask_credential(&self.password))
+            .field("zones", &self.zones)
+            .field("server", &self.server)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
 }
 
 impl CloudflareClient {
@@ -21,9 +58,12 @@ impl CloudflareClient {
         let password = config.password.as_ref()
             .ok_or("password is required for Cloudflare (API token or global API key)")?
             .clone();
-        let zone = config.zone.as_ref()
+        let zones: Vec<String> = config.zone.as_ref()
             .ok_or("zone is required for Cloudflare (e.g., example.com)")?
-            .clone();
+            .split([',', ';'])
+            .map(|zone| zone.trim().to_string())
+            .filter(|zone| !zone.is_empty())
+            .collect();
         let server = config.server.clone()
             .unwrap_or_else(|| "api.cloudflare.com/client/v4".to_string());
         let ttl = config.ttl.unwrap_or(1);
@@ -31,27 +71,116 @@ impl CloudflareClient {
         Ok(CloudflareClient {
             login,
             password,
-            zone,
+            zones,
             server,
             ttl,
+            preload_zones: config.cf_preload_zones,
+            zone_id_cache: Mutex::new(HashMap::new()),
+            zone_cache_warmed: Once::new(),
+            record_cache: Mutex::new(HashMap::new()),
+            record_cache_stats: Mutex::new(CacheStats::default()),
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
 
-    fn get_zone_id(&self) -> Result<String, Box<dyn Error>> {
-        log::info!("Getting Cloudflare Zone ID for zone: {}", self.zone);
+    /// On the first update, preload the account's zones (--cf-preload-zones) or, with several
+    /// zones configured, look them all up; failures are retried on use.
+    /// Done lazily so building the client (validate, --dry-run) makes no API calls.
+    fn warm_zone_cache(&self) {
+        self.zone_cache_warmed.call_once(|| {
+            if self.preload_zones {
+                match self.preload_zones() {
+                    Ok(count) => log::info!("Loaded {} Cloudflare zone IDs", count),
+                    Err(e) => log::warn!("Failed to preload Cloudflare zones: {}", e),
+                }
+            }
+            if self.zones.len() > 1 {
+                for zone in &self.zones {
+                    if let Err(e) = self.get_zone_id(zone) {
+                        log::warn!("Failed to look up Cloudflare zone {}: {}", zone, e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Configured zone containing `hostname`; the longest match wins.
+    /// With a single zone configured it is used for every hostname.
+    fn zone_for(&self, hostname: &str) -> Result<&str, Box<dyn Error>> {
+        let hostname = hostname.trim_end_matches('.').to_ascii_lowercase();
+        let matched = self.zones.iter()
+            .filter(|zone| {
+                let zone = zone.to_ascii_lowercase();
+                hostname == zone || hostname.ends_with(&format!(".{}", zone))
+            })
+            .max_by_key(|zone| zone.len());
+
+        match (matched, self.zones.as_slice()) {
+            (Some(zone), _) => Ok(zone),
+            (None, [zone]) => Ok(zone),
+            (None, zones) => Err(format!(
+                "{} is not in any configured Cloudflare zone ({})", hostname, zones.join(", ")
+            ).into()),
+        }
+    }
 
-        let url = format!("https://{}/zones/?name={}", self.server, self.zone);
-        
-        let mut request = minreq::get(&url)
-            .with_header("Content-Type", "application/json");
-        
-        // ddclient authentication: login=token uses Bearer, otherwise X-Auth-Email/Key
+    /// API base URL; `server` may include a scheme, https is assumed otherwise
+    fn base_url(&self) -> String {
+        if self.server.starts_with("http://") || self.server.starts_with("https://") {
+            self.server.clone()
+        } else {
+            format!("https://{}", self.server)
+        }
+    }
+
+    /// ddclient authentication: login=token uses Bearer, otherwise X-Auth-Email/Key
+    fn auth_headers(&self) -> Vec<(String, String)> {
         if self.login == "token" {
-            request = request.with_header("Authorization", format!("Bearer {}", self.password));
+            vec![("Authorization".to_string(), format!("Bearer {}", self.password))]
         } else {
-            request = request
-                .with_header("X-Auth-Email", &self.login)
-                .with_header("X-Auth-Key", &self.password);
+            vec![
+                ("X-Auth-Email".to_string(), self.login.clone()),
+                ("X-Auth-Key".to_string(), self.password.clone()),
+            ]
+        }
+    }
+
+    fn zone_lookup_url(&self, zone: &str) -> String {
+        format!("{}/zones/?name={}", self.base_url(), zone)
+    }
+
+    fn record_lookup_url(&self, zone_id: &str, hostname: &str, record_type: &str) -> String {
+        format!("{}/zones/{}/dns_records?type={}&name={}", self.base_url(), zone_id, record_type, hostname)
+    }
+
+    fn record_url(&self, zone_id: &str, record_id: &str) -> String {
+        format!("{}/zones/{}/dns_records/{}", self.base_url(), zone_id, record_id)
+    }
+
+    fn update_body(&self, hostname: &str, ip: IpAddr) -> serde_json::Value {
+        json!({
+            "type": record_type(ip),
+            "name": hostname,
+            "content": ip.to_string(),
+            "ttl": self.ttl,
+        })
+    }
+
+    fn get_zone_id(&self, zone: &str) -> Result<String, Box<dyn Error>> {
+        if let Some(zone_id) = self.zone_id_cache.lock().unwrap().get(zone) {
+            log::debug!("Using cached Zone ID {} for zone: {}", zone_id, zone);
+            return Ok(zone_id.clone());
+        }
+
+        log::info!("Getting Cloudflare Zone ID for zone: {}", zone);
+
+        let url = self.zone_lookup_url(zone);
+        
+        let mut request = self.http.prepare(minreq::get(&url))
+            .with_header("Content-Type", "application/json");
+
+        for (name, value) in self.auth_headers() {
+            request = request.with_header(name, value);
         }
 
         let res = request.send()?;
@@ -68,26 +197,74 @@ impl CloudflareClient {
             .to_string();
 
         log::info!("Zone ID is {}", zone_id);
+        self.zone_id_cache.lock().unwrap().insert(zone.to_string(), zone_id.clone());
         Ok(zone_id)
     }
 
+    /// Fill the zone ID cache with every zone in the account, following the pagination.
+    /// Returns the number of zones loaded.
+    fn preload_zones(&self) -> Result<usize, Box<dyn Error>> {
+        let mut loaded = 0;
+        let mut page = 1;
+
+        loop {
+            let url = format!("{}/zones?per_page={}&page={}", self.base_url(), ZONES_PER_PAGE, page);
+            let mut request = self.http.prepare(minreq::get(&url))
+                .with_header("Content-Type", "application/json");
+            for (name, value) in self.auth_headers() {
+                request = request.with_header(name, value);
+            }
+
+            let json: serde_json::Value = request.send()?.json()?;
+            if !json["success"].as_bool().unwrap_or(false) {
+                return Err(format!("Error listing zones: {}", json).into());
+            }
+
+            let zones = json["result"].as_array().ok_or("Zone list has no result")?;
+            let mut cache = self.zone_id_cache.lock().unwrap();
+            for zone in zones {
+                if let (Some(name), Some(id)) = (zone["name"].as_str(), zone["id"].as_str()) {
+                    cache.insert(name.to_string(), id.to_string());
+                    loaded += 1;
+                }
+            }
+
+            let total_count = json["result_info"]["total_count"].as_u64().unwrap_or(0);
+            let fetched = json["result_info"]["page"].as_u64().unwrap_or(page) * ZONES_PER_PAGE;
+            if zones.is_empty() || fetched >= total_count {
+                return Ok(loaded);
+            }
+            page += 1;
+        }
+    }
+
+    /// Record ID cache hits and misses so far
+    pub fn cache_stats(&self) -> CacheStats {
+        *self.record_cache_stats.lock().unwrap()
+    }
+
+    fn record_key(zone_id: &str, hostname: &str, record_type: &str) -> (String, String, String) {
+        (zone_id.to_string(), hostname.to_ascii_lowercase(), record_type.to_string())
+    }
+
     fn get_record_id(&self, zone_id: &str, hostname: &str, record_type: &str) -> Result<String, Box<dyn Error>> {
+        let key = Self::record_key(zone_id, hostname, record_type);
+        if let Some(record_id) = self.record_cache.lock().unwrap().get(&key) {
+            self.record_cache_stats.lock().unwrap().hits += 1;
+            log::debug!("Using cached DNS {} Record ID {} for {}", record_type, record_id, hostname);
+            return Ok(record_id.clone());
+        }
+        self.record_cache_stats.lock().unwrap().misses += 1;
+
         log::info!("Fetching DNS {} record for: {}", record_type, hostname);
 
-        let url = format!(
-            "https://{}/zones/{}/dns_records?type={}&name={}",
-            self.server, zone_id, record_type, hostname
-        );
+        let url = self.record_lookup_url(zone_id, hostname, record_type);
 
-        let mut request = minreq::get(&url)
+        let mut request = self.http.prepare(minreq::get(&url))
             .with_header("Content-Type", "application/json");
-        
-        if self.login == "token" {
-            request = request.with_header("Authorization", format!("Bearer {}", self.password));
-        } else {
-            request = request
-                .with_header("X-Auth-Email", &self.login)
-                .with_header("X-Auth-Key", &self.password);
+
+        for (name, value) in self.auth_headers() {
+            request = request.with_header(name, value);
         }
 
         let res = request.send()?;
@@ -104,46 +281,29 @@ impl CloudflareClient {
             .to_string();
 
         log::info!("DNS {} Record ID for {} is {}", record_type, hostname, record_id);
+        self.record_cache.lock().unwrap().insert(key, record_id.clone());
         Ok(record_id)
     }
 }
 
 impl DnsClient for CloudflareClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        // Determine record type based on IP version
-        let record_type = match ip {
-            IpAddr::V4(_) => "A",
-            IpAddr::V6(_) => "AAAA",
-        };
+        let record_type = record_type(ip);
         
         log::info!("Setting {} address to {}", 
                    if record_type == "A" { "IPv4" } else { "IPv6" }, ip);
         
-        let zone_id = self.get_zone_id()?;
+        self.warm_zone_cache();
+        let zone_id = self.get_zone_id(self.zone_for(hostname)?)?;
         let record_id = self.get_record_id(&zone_id, hostname, record_type)?;
 
-        let body = json!({
-            "type": record_type,
-            "name": hostname,
-            "content": ip.to_string(),
-            "ttl": self.ttl,
-        });
-
-        let url = format!(
-            "https://{}/zones/{}/dns_records/{}",
-            self.server, zone_id, record_id
-        );
-
-        let mut request = minreq::put(&url)
+        let url = self.record_url(&zone_id, &record_id);
+        let mut request = self.http.prepare(minreq::put(&url))
             .with_header("Content-Type", "application/json")
-            .with_json(&body)?;
-        
-        if self.login == "token" {
-            request = request.with_header("Authorization", format!("Bearer {}", self.password));
-        } else {
-            request = request
-                .with_header("X-Auth-Email", &self.login)
-                .with_header("X-Auth-Key", &self.password);
+            .with_json(&self.update_body(hostname, ip))?;
+
+        for (name, value) in self.auth_headers() {
+            request = request.with_header(name, value);
         }
 
         let update_res = request.send()?;
@@ -152,8 +312,11 @@ impl DnsClient for CloudflareClient {
         
         if !update_json["success"].as_bool().unwrap_or(false) {
             log::error!("Failed to update DNS record: {}", update_json);
+            // The record may have been deleted or recreated; look it up again next time
+            self.record_cache.lock().unwrap().remove(&Self::record_key(&zone_id, hostname, record_type));
             return Err("Failed to update DNS record".into());
         }
+        log::debug!("Cloudflare record cache: {:?}", self.cache_stats());
 
         log::info!("DNS {} Record for {} successfully updated to IP: {}", record_type, hostname, ip);
         Ok(())
@@ -166,7 +329,7 @@ impl DnsClient for CloudflareClient {
         if self.password.is_empty() {
             return Err("password is required for Cloudflare (API token or global API key)".into());
         }
-        if self.zone.is_empty() {
+        if self.zones.is_empty() {
             return Err("zone is required for Cloudflare (e.g., example.com)".into());
         }
         Ok(())
@@ -175,6 +338,30 @@ impl DnsClient for CloudflareClient {
     fn provider_name(&self) -> &str {
         "Cloudflare"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let zone = self.zone_for(hostname)?;
+        let request = |method, url| {
+            self.auth_headers().into_iter()
+                .fold(self.http.preview(method, url).with_header("Content-Type", "application/json"),
+                      |request, (name, value)| request.with_header(&name, value))
+        };
+
+        Ok(vec![
+            request("GET", self.zone_lookup_url(zone)),
+            request("GET", self.record_lookup_url("{zone_id}", hostname, record_type(ip))),
+            request("PUT", self.record_url("{zone_id}", "{record_id}"))
+                .with_body(self.update_body(hostname, ip).to_string()),
+        ])
+    }
+}
+
+/// DNS record type for an address
+fn record_type(ip: IpAddr) -> &'static str {
+    match ip {
+        IpAddr::V4(_) => "A",
+        IpAddr::V6(_) => "AAAA",
+    }
 }
 
 #[cfg(test)]
@@ -202,7 +389,7 @@ mod tests {
         let client = client.unwrap();
         assert_eq!(client.login, "token");
         assert_eq!(client.password, "test_api_token_12345");
-        assert_eq!(client.zone, "example.com");
+        assert_eq!(client.zones, vec!["example.com".to_string()]);
         assert_eq!(client.server, "api.cloudflare.com/client/v4");
         assert_eq!(client.ttl, 300);
     }
@@ -316,9 +503,15 @@ mod tests {
         let client = CloudflareClient {
             login: String::new(),
             password: String::new(),
-            zone: String::new(),
+            zones: Vec::new(),
             server: "api.cloudflare.com/client/v4".to_string(),
             ttl: 1,
+            preload_zones: false,
+            zone_id_cache: Mutex::new(HashMap::new()),
+            zone_cache_warmed: Once::new(),
+            record_cache: Mutex::new(HashMap::new()),
+            record_cache_stats: Mutex::new(CacheStats::default()),
+            http: http::HttpClientBuilder::new(&Config::default()).build(),
         };
         
         let result = client.validate_config();
@@ -341,7 +534,7 @@ mod tests {
         
         // Test zone lookup URL construction
         let expected_zone_url = format!("https://{}/zones/?name={}", 
-            client.server, client.zone);
+            client.server, client.zones[0]);
         assert_eq!(expected_zone_url, 
             "https://api.cloudflare.com/client/v4/zones/?name=example.com");
         
@@ -362,6 +555,22 @@ mod tests {
             "https://api.cloudflare.com/client/v4/zones/test_zone_id/dns_records?type=AAAA&name=ddns.example.com");
     }
 
+    #[test]
+    fn test_cloudflare_preview_shows_all_three_calls() {
+        let client = CloudflareClient::new(&create_test_config()).unwrap();
+        let requests = client.preview_update("ddns.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+
+        let calls: Vec<(&str, &str)> = requests.iter().map(|r| (r.method, r.url.as_str())).collect();
+        assert_eq!(calls, vec![
+            ("GET", "https://api.cloudflare.com/client/v4/zones/?name=example.com"),
+            ("GET", "https://api.cloudflare.com/client/v4/zones/{zone_id}/dns_records?type=AAAA&name=ddns.example.com"),
+            ("PUT", "https://api.cloudflare.com/client/v4/zones/{zone_id}/dns_records/{record_id}"),
+        ]);
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), format!("Bearer {}", client.password))));
+        assert_eq!(requests[2].body.as_deref(),
+            Some(r#"{"content":"2001:db8::1","name":"ddns.example.com","ttl":300,"type":"AAAA"}"#));
+    }
+
     #[test]
     fn test_cloudflare_ipv6_support() {
         use std::str::FromStr;
@@ -373,4 +582,234 @@ mod tests {
         let ipv4 = IpAddr::from_str("192.0.2.1").unwrap();
         assert!(matches!(ipv4, IpAddr::V4(_)));
     }
+
+    #[test]
+    fn test_cloudflare_zone_id_cached_across_hosts() {
+        let mut server = mockito::Server::new();
+        let zone_mock = server.mock("GET", "/zones/")
+            .match_query(mockito::Matcher::UrlEncoded("name".to_string(), "example.com".to_string()))
+            .with_body(r#"{"success":true,"result":[{"id":"zone123"}]}"#)
+            .expect(1)
+            .create();
+        let record_mock = server.mock("GET", "/zones/zone123/dns_records")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"success":true,"result":[{"id":"rec456"}]}"#)
+            .expect(2)
+            .create();
+        let update_mock = server.mock("PUT", "/zones/zone123/dns_records/rec456")
+            .with_body(r#"{"success":true}"#)
+            .expect(2)
+            .create();
+
+        let config = Config {
+            server: Some(server.url()),
+            ..create_test_config()
+        };
+        let client = CloudflareClient::new(&config).unwrap();
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+
+        assert!(client.update_record("a.example.com", ip).is_ok());
+        assert!(client.update_record("b.example.com", ip).is_ok());
+
+        zone_mock.assert();
+        record_mock.assert();
+        update_mock.assert();
+    }
+
+    #[test]
+    fn test_cloudflare_zone_for() {
+        let config = Config {
+            zone: Some("example.com, sub.example.com,other.org".to_string()),
+            server: Some("http://127.0.0.1:9".to_string()),
+            ..create_test_config()
+        };
+        let client = CloudflareClient::new(&config).unwrap();
+
+        assert_eq!(client.zone_for("home.example.com").unwrap(), "example.com");
+        assert_eq!(client.zone_for("a.sub.example.com").unwrap(), "sub.example.com");
+        assert_eq!(client.zone_for("Other.ORG").unwrap(), "other.org");
+        assert!(client.zone_for("notexample.com").is_err());
+
+        // A single zone is used for every hostname, as before
+        let client = CloudflareClient::new(&create_test_config()).unwrap();
+        assert_eq!(client.zone_for("host.elsewhere.net").unwrap(), "example.com");
+    }
+
+    #[test]
+    fn test_cloudflare_multiple_zones_looked_up_once() {
+        let mut server = mockito::Server::new();
+        let mut mocks = Vec::new();
+        for (zone, zone_id) in [("example.com", "zone1"), ("other.org", "zone2")] {
+            mocks.push(server.mock("GET", "/zones/")
+                .match_query(mockito::Matcher::UrlEncoded("name".to_string(), zone.to_string()))
+                .with_body(format!(r#"{{"success":true,"result":[{{"id":"{}"}}]}}"#, zone_id))
+                .expect(1)
+                .create());
+            mocks.push(server.mock("GET", format!("/zones/{}/dns_records", zone_id).as_str())
+                .match_query(mockito::Matcher::Any)
+                .with_body(r#"{"success":true,"result":[{"id":"rec"}]}"#)
+                .expect(2)
+                .create());
+            mocks.push(server.mock("PUT", format!("/zones/{}/dns_records/rec", zone_id).as_str())
+                .with_body(r#"{"success":true}"#)
+                .expect(2)
+                .create());
+        }
+
+        let config = Config {
+            zone: Some("example.com;other.org".to_string()),
+            server: Some(server.url()),
+            ..create_test_config()
+        };
+        let client = CloudflareClient::new(&config).unwrap();
+        client.validate_config().unwrap();
+        // Nothing is looked up until the first update
+        assert!(client.zone_id_cache.lock().unwrap().is_empty());
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+
+        for hostname in ["a.example.com", "b.example.com", "a.other.org", "other.org"] {
+            assert!(client.update_record(hostname, ip).is_ok(), "{}", hostname);
+        }
+
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    /// A page of the zone listing with `count` zones out of `total`
+    fn zones_page(server: &mut mockito::Server, page: u64, count: u64, total: u64) -> mockito::Mock {
+        let first = (page - 1) * ZONES_PER_PAGE;
+        let zones: Vec<_> = (first..first + count)
+            .map(|n| json!({"id": format!("id{}", n), "name": format!("zone{}.example", n)}))
+            .collect();
+        server.mock("GET", "/zones")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("per_page".to_string(), "50".to_string()),
+                mockito::Matcher::UrlEncoded("page".to_string(), page.to_string()),
+            ]))
+            .with_body(json!({
+                "success": true,
+                "result": zones,
+                "result_info": {"page": page, "per_page": 50, "count": count, "total_count": total},
+            }).to_string())
+            .expect(1)
+            .create()
+    }
+
+    #[test]
+    fn test_cloudflare_preload_zones_across_pages() {
+        let mut server = mockito::Server::new();
+        let page1 = zones_page(&mut server, 1, 50, 120);
+        let page2 = zones_page(&mut server, 2, 50, 120);
+        let page3 = zones_page(&mut server, 3, 20, 120);
+
+        let config = Config {
+            server: Some(server.url()),
+            cf_preload_zones: true,
+            ..create_test_config()
+        };
+        let client = CloudflareClient::new(&config).unwrap();
+        client.warm_zone_cache();
+        page1.assert();
+        page2.assert();
+        page3.assert();
+
+        let cache = client.zone_id_cache.lock().unwrap();
+        assert_eq!(cache.len(), 120);
+        assert_eq!(cache["zone0.example"], "id0");
+        assert_eq!(cache["zone119.example"], "id119");
+    }
+
+    #[test]
+    fn test_cloudflare_preloaded_zone_skips_lookup() {
+        let mut server = mockito::Server::new();
+        let page1 = zones_page(&mut server, 1, 1, 1);
+        let lookup = server.mock("GET", "/zones/").match_query(mockito::Matcher::Any).expect(0).create();
+        let _record = server.mock("GET", "/zones/id0/dns_records")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"success":true,"result":[{"id":"rec"}]}"#)
+            .create();
+        let _update = server.mock("PUT", "/zones/id0/dns_records/rec").with_body(r#"{"success":true}"#).create();
+
+        let config = Config {
+            zone: Some("zone0.example".to_string()),
+            server: Some(server.url()),
+            cf_preload_zones: true,
+            ..create_test_config()
+        };
+        let client = CloudflareClient::new(&config).unwrap();
+        client.validate_config().unwrap();
+        // Building and validating the client makes no API calls
+        assert!(client.zone_id_cache.lock().unwrap().is_empty());
+
+        client.update_record("home.zone0.example", "192.0.2.1".parse().unwrap()).unwrap();
+        page1.assert();
+        lookup.assert();
+    }
+
+    #[test]
+    fn test_cloudflare_record_id_cached() {
+        let mut server = mockito::Server::new();
+        let _zone_mock = server.mock("GET", "/zones/")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"success":true,"result":[{"id":"zone123"}]}"#)
+            .create();
+        let record_mock = server.mock("GET", "/zones/zone123/dns_records")
+            .match_query(mockito::Matcher::UrlEncoded("type".to_string(), "A".to_string()))
+            .with_body(r#"{"success":true,"result":[{"id":"rec456"}]}"#)
+            .expect(1)
+            .create();
+        let record_v6_mock = server.mock("GET", "/zones/zone123/dns_records")
+            .match_query(mockito::Matcher::UrlEncoded("type".to_string(), "AAAA".to_string()))
+            .with_body(r#"{"success":true,"result":[{"id":"rec789"}]}"#)
+            .expect(1)
+            .create();
+        let _update_mock = server.mock("PUT", mockito::Matcher::Regex("^/zones/zone123/dns_records/rec".to_string()))
+            .with_body(r#"{"success":true}"#)
+            .create();
+
+        let config = Config {
+            server: Some(server.url()),
+            ..create_test_config()
+        };
+        let client = CloudflareClient::new(&config).unwrap();
+
+        client.update_record("home.example.com", "192.0.2.1".parse().unwrap()).unwrap();
+        client.update_record("home.example.com", "192.0.2.2".parse().unwrap()).unwrap();
+        client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        client.update_record("HOME.example.com", "192.0.2.3".parse().unwrap()).unwrap();
+
+        record_mock.assert();
+        record_v6_mock.assert();
+        assert_eq!(client.cache_stats(), CacheStats { hits: 2, misses: 2 });
+    }
+
+    #[test]
+    fn test_cloudflare_record_cache_dropped_after_failed_update() {
+        let mut server = mockito::Server::new();
+        let _zone_mock = server.mock("GET", "/zones/")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"success":true,"result":[{"id":"zone123"}]}"#)
+            .create();
+        let record_mock = server.mock("GET", "/zones/zone123/dns_records")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"success":true,"result":[{"id":"rec456"}]}"#)
+            .expect(2)
+            .create();
+        let _update_mock = server.mock("PUT", "/zones/zone123/dns_records/rec456")
+            .with_body(r#"{"success":false,"errors":[{"code":81044,"message":"Record does not exist."}]}"#)
+            .create();
+
+        let config = Config {
+            server: Some(server.url()),
+            ..create_test_config()
+        };
+        let client = CloudflareClient::new(&config).unwrap();
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+
+        assert!(client.update_record("home.example.com", ip).is_err());
+        assert!(client.update_record("home.example.com", ip).is_err());
+        record_mock.assert();
+        assert_eq!(client.cache_stats(), CacheStats { hits: 0, misses: 2 });
+    }
 }
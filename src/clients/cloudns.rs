@@ -1,10 +1,12 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
 pub struct CloudnsClient {
     dynurl: String,
+    http: http::HttpClient,
 }
 
 impl CloudnsClient {
@@ -17,23 +19,29 @@ impl CloudnsClient {
 
         Ok(Self {
             dynurl,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// Dynamic URL with the address and its record type (A or AAAA) appended
+    fn build_url(&self, ip: IpAddr) -> String {
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+        let separator = if self.dynurl.contains('?') { '&' } else { '?' };
+        format!("{}{}myip={}&record-type={}", self.dynurl, separator, ip, record_type)
+    }
 }
 
 impl DnsClient for CloudnsClient {
     fn update_record(&self, _hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         // ClouDNS dynurl already contains the hostname, just append IP
-        let url = if self.dynurl.contains('?') {
-            format!("{}&myip={}", self.dynurl, ip)
-        } else {
-            format!("{}?myip={}", self.dynurl, ip)
-        };
+        let url = self.build_url(ip);
         
         log::info!("Updating ClouDNS record to {}", ip);
         
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(&url))
             .send()?;
 
         if response.status_code != 200 {
@@ -71,4 +79,59 @@ impl DnsClient for CloudnsClient {
     fn provider_name(&self) -> &str {
         "ClouDNS"
     }
+
+    fn preview_update(&self, _hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        // The query of the dynamic URL identifies the host, like a token
+        let token = self.dynurl.split_once('?').map_or("", |(_, query)| query);
+        Ok(vec![self.http.preview("GET", self.build_url(ip)).with_secret(token)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client(dynurl: &str) -> CloudnsClient {
+        let config = Config {
+            protocol: Some("cloudns".to_string()),
+            password: Some(dynurl.to_string()),
+            ..Default::default()
+        };
+        CloudnsClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_cloudns_record_type_ipv4() {
+        let client = test_client("https://ipv4.cloudns.net/api/dynamicURL/?q=abc");
+        assert_eq!(
+            client.build_url("203.0.113.1".parse().unwrap()),
+            "https://ipv4.cloudns.net/api/dynamicURL/?q=abc&myip=203.0.113.1&record-type=A"
+        );
+    }
+
+    #[test]
+    fn test_cloudns_record_type_ipv6() {
+        let client = test_client("https://ipv6.cloudns.net/api/dynamicURL/");
+        assert_eq!(
+            client.build_url("2001:db8::1".parse().unwrap()),
+            "https://ipv6.cloudns.net/api/dynamicURL/?myip=2001:db8::1&record-type=AAAA"
+        );
+    }
+
+    #[test]
+    fn test_cloudns_validate_config() {
+        assert!(test_client("https://ipv4.cloudns.net/api/dynamicURL/?q=abc").validate_config().is_ok());
+        assert!(test_client("").validate_config().is_err());
+        assert!(test_client("ipv4.cloudns.net/api/dynamicURL/?q=abc").validate_config().is_err());
+        assert!(CloudnsClient::new(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_cloudns_preview_update() {
+        let client = test_client("https://ipv4.cloudns.net/api/dynamicURL/?q=abcdef123456");
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://ipv4.cloudns.net/api/dynamicURL/?q=abcdef123456&myip=203.0.113.1&record-type=A");
+        assert!(requests[0].to_string().contains("dynamicURL/?****3456&myip=203.0.113.1"));
+    }
 }
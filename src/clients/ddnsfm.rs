@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -8,6 +9,7 @@ use std::net::IpAddr;
 pub struct DdnsfmClient {
     server: String,
     token: String,
+    http: http::HttpClient,
 }
 
 impl DdnsfmClient {
@@ -22,22 +24,20 @@ impl DdnsfmClient {
         Ok(DdnsfmClient {
             server,
             token,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/update?token={}&hostname={}&ip={}", self.server, self.token, hostname, ip)
+    }
 }
 
 impl DnsClient for DdnsfmClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with DDNS.FM", hostname);
 
-        // DDNS.FM API endpoint
-        let url = format!("{}/update", self.server);
-
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_param("token", &self.token)
-            .with_param("hostname", hostname)
-            .with_param("ip", ip.to_string())
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
             .send()?;
 
         let status_code = response.status_code;
@@ -70,4 +70,28 @@ impl DnsClient for DdnsfmClient {
     fn provider_name(&self) -> &str {
         "DDNS.FM"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ddnsfm_preview_update() {
+        let config = Config {
+            protocol: Some("ddnsfm".to_string()),
+            password: Some("token123456".to_string()),
+            ..Default::default()
+        };
+        let client = DdnsfmClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://api.ddns.fm/update?token=token123456&hostname=home.example.com&ip=203.0.113.1");
+        assert!(!requests[0].to_string().contains("token123456"));
+    }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -8,6 +9,7 @@ use std::net::IpAddr;
 pub struct DdnssClient {
     server: String,
     token: String,
+    http: http::HttpClient,
 }
 
 impl DdnssClient {
@@ -23,21 +25,22 @@ impl DdnssClient {
         Ok(DdnssClient {
             server,
             token,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
 }
 
 impl DdnssClient {
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/upd.php?key={}&host={}&ip={}", self.server, self.token, hostname, ip)
+    }
+
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
-            "{}/upd.php?key={}&host={}&ip={}",
-            self.server, self.token, hostname, ip
-        );
+        let url = self.update_url(hostname, ip);
 
         log::info!("Updating {} with DDNSS", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(&url))
             .send()?;
 
         let status_code = response.status_code;
@@ -86,4 +89,28 @@ impl DnsClient for DdnssClient {
     fn provider_name(&self) -> &str {
         DdnssClient::provider_name(self)
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ddnss_preview_update() {
+        let config = Config {
+            protocol: Some("ddnss".to_string()),
+            password: Some("token123456".to_string()),
+            ..Default::default()
+        };
+        let client = DdnssClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.ddnss.de", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://www.ddnss.de/upd.php?key=token123456&host=home.ddnss.de&ip=203.0.113.1");
+        assert!(!requests[0].to_string().contains("token123456"));
+    }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -9,6 +10,7 @@ pub struct DesecClient {
     server: String,
     token: String,
     domain: String,
+    http: http::HttpClient,
 }
 
 impl DesecClient {
@@ -28,14 +30,12 @@ impl DesecClient {
             server,
             token,
             domain,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
-
-impl DnsClient for DesecClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        log::info!("Updating {} with deSEC", hostname);
 
+    /// Update URL; the zone apex is sent as the domain itself
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
         // Extract subdomain from hostname
         let subdomain = if hostname.ends_with(&self.domain) {
             hostname.strip_suffix(&format!(".{}", self.domain))
@@ -45,16 +45,24 @@ impl DnsClient for DesecClient {
         };
 
         // deSEC update endpoint (DynDNS2 compatible)
-        let url = format!("{}/update", self.server);
+        format!(
+            "{}/update?hostname={}&myip={}",
+            self.server, if subdomain.is_empty() { &self.domain } else { hostname }, ip
+        )
+    }
 
+    fn auth_header(&self) -> String {
         let auth = format!("{}:{}", self.domain, self.token);
-        let encoded_auth = format!("Basic {}", base64::encode(&auth));
+        format!("Basic {}", base64::encode(&auth))
+    }
+}
+
+impl DnsClient for DesecClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with deSEC", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", &encoded_auth)
-            .with_param("hostname", if subdomain.is_empty() { &self.domain } else { hostname })
-            .with_param("myip", ip.to_string())
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         let status_code = response.status_code;
@@ -92,6 +100,11 @@ impl DnsClient for DesecClient {
     fn provider_name(&self) -> &str {
         "deSEC"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
 }
 
 mod base64 {
@@ -101,3 +114,29 @@ mod base64 {
         general_purpose::STANDARD.encode(data.as_bytes())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desec_preview_update() {
+        let config = Config {
+            protocol: Some("desec".to_string()),
+            password: Some("token123456".to_string()),
+            zone: Some("example.dedyn.io".to_string()),
+            ..Default::default()
+        };
+        let client = DesecClient::new(&config).unwrap();
+        let ip = "203.0.113.1".parse().unwrap();
+
+        let requests = client.preview_update("home.example.dedyn.io", ip).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://update.dedyn.io/update?hostname=home.example.dedyn.io&myip=203.0.113.1");
+        assert!(!requests[0].to_string().contains("token123456"));
+        assert_eq!(
+            client.preview_update("example.dedyn.io", ip).unwrap()[0].url,
+            "https://update.dedyn.io/update?hostname=example.dedyn.io&myip=203.0.113.1"
+        );
+    }
+}
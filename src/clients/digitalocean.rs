@@ -1,13 +1,46 @@
-use crate::clients::DnsClient;
+use crate::clients::{zone_for, DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
+use std::fmt;
 use std::net::IpAddr;
 use serde_json::json;
 
+/// Errors from the DigitalOcean records API that callers act on
+#[derive(Debug)]
+pub enum DigitalOceanError {
+    /// No record with the requested name and type exists (or the API answered 404)
+    RecordNotFound,
+    /// The API token was rejected (HTTP 401)
+    Unauthorized,
+}
+
+impl fmt::Display for DigitalOceanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::RecordNotFound => write!(f, "DNS record not found"),
+            Self::Unauthorized => write!(f, "DigitalOcean rejected the API token (HTTP 401)"),
+        }
+    }
+}
+
+impl Error for DigitalOceanError {}
+
+/// Split a hostname into its guessed zone and the record name to the left of it, "@" for
+/// the zone apex
+fn extract_zone_and_name(hostname: &str) -> (String, String) {
+    let hostname = hostname.trim_end_matches('.');
+    let zone = zone_for(None, hostname);
+    let name = hostname.strip_suffix(&format!(".{}", zone)).unwrap_or("@").to_string();
+    (zone, name)
+}
+
 /// DigitalOcean client - https://docs.digitalocean.com/reference/api/api-reference/#tag/Domain-Records
 pub struct DigitalOceanClient {
     token: String,
     server: String,
+    ttl: u32,
+    http: http::HttpClient,
 }
 
 impl DigitalOceanClient {
@@ -20,19 +53,16 @@ impl DigitalOceanClient {
             .cloned()
             .unwrap_or_else(|| "https://api.digitalocean.com".to_string());
 
-        Ok(DigitalOceanClient { token, server })
+        Ok(DigitalOceanClient {
+            token,
+            server,
+            ttl: config.ttl.unwrap_or(1800),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
     }
 
-    fn parse_hostname(&self, hostname: &str) -> (String, String) {
-        // Split hostname into record name and domain
-        let parts: Vec<&str> = hostname.rsplitn(3, '.').collect();
-        if parts.len() >= 3 {
-            let domain = format!("{}.{}", parts[1], parts[0]);
-            let name = parts[2];
-            (name.to_string(), domain)
-        } else {
-            ("@".to_string(), hostname.to_string())
-        }
+    fn records_url(&self, domain: &str) -> String {
+        format!("{}/v2/domains/{}/records", self.server, domain)
     }
 
     fn get_record_id(&self, domain: &str, name: &str, record_type: &str) -> Result<u64, Box<dyn Error>> {
@@ -40,18 +70,22 @@ impl DigitalOceanClient {
         let per_page = 200; // DigitalOcean max per page
         
         loop {
-            let url = format!("{}/v2/domains/{}/records?page={}&per_page={}", 
-                            self.server, domain, page, per_page);
+            let url = format!("{}?page={}&per_page={}", self.records_url(domain), page, per_page);
 
-            let response = minreq::get(&url)
+            let response = self.http.prepare(minreq::get(&url))
                 .with_header("Authorization", format!("Bearer {}", self.token))
                 .with_header("Content-Type", "application/json")
                 .send()?;
             
-            if response.status_code != 200 {
-                return Err(format!("Failed to fetch records: HTTP {} - {}", 
-                                 response.status_code, 
-                                 response.as_str().unwrap_or("unknown error")).into());
+            match response.status_code {
+                200 => {}
+                401 => return Err(DigitalOceanError::Unauthorized.into()),
+                404 => return Err(DigitalOceanError::RecordNotFound.into()),
+                status => {
+                    return Err(format!("Failed to fetch records: HTTP {} - {}",
+                                     status,
+                                     response.as_str().unwrap_or("unknown error")).into());
+                }
             }
 
             let json: serde_json::Value = response.json()?;
@@ -79,13 +113,44 @@ impl DigitalOceanClient {
             break;
         }
 
-        Err(format!("No {} record found for {}.{}", record_type, name, domain).into())
+        log::debug!("No {} record found for {}.{}", record_type, name, domain);
+        Err(DigitalOceanError::RecordNotFound.into())
+    }
+
+    /// Create a new record, returning its ID
+    fn create_record(&self, domain: &str, name: &str, record_type: &str, ip: IpAddr, ttl: u32) -> Result<u64, Box<dyn Error>> {
+        let url = self.records_url(domain);
+
+        let body = json!({
+            "type": record_type,
+            "name": name,
+            "data": ip.to_string(),
+            "ttl": ttl,
+        });
+
+        let response = self.http.prepare(minreq::post(&url))
+            .with_header("Authorization", format!("Bearer {}", self.token))
+            .with_json(&body)?
+            .send()?;
+
+        match response.status_code {
+            201 => {
+                let json: serde_json::Value = response.json()?;
+                json["domain_record"]["id"].as_u64()
+                    .ok_or_else(|| "No record ID in DigitalOcean response".into())
+            }
+            401 => Err(DigitalOceanError::Unauthorized.into()),
+            status => {
+                let body = response.as_str().unwrap_or("unknown error");
+                Err(format!("DigitalOcean API error ({}): {}", status, body).into())
+            }
+        }
     }
 }
 
 impl DnsClient for DigitalOceanClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let (name, domain) = self.parse_hostname(hostname);
+        let (domain, name) = extract_zone_and_name(hostname);
         
         // Determine record type based on IP version
         let record_type = match ip {
@@ -95,18 +160,23 @@ impl DnsClient for DigitalOceanClient {
         
         log::info!("Updating {} with DigitalOcean ({})", hostname, record_type);
         
-        let record_id = self.get_record_id(&domain, &name, record_type)?;
-        
-        let url = format!("{}/v2/domains/{}/records/{}", self.server, domain, record_id);
+        let record_id = match self.get_record_id(&domain, &name, record_type) {
+            Ok(id) => id,
+            Err(e) if matches!(e.downcast_ref(), Some(DigitalOceanError::RecordNotFound)) => {
+                log::info!("No {} record for {}, creating it", record_type, hostname);
+                let id = self.create_record(&domain, &name, record_type, ip, self.ttl)?;
+                log::info!("Created {} record {} for {} with {}", record_type, id, hostname, ip);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
 
-        let body = json!({
-            "data": ip.to_string()
-        });
+        let url = format!("{}/{}", self.records_url(&domain), record_id);
 
-        let response = minreq::put(&url)
+        let response = self.http.prepare(minreq::put(&url))
             .with_header("Authorization", format!("Bearer {}", self.token))
             .with_header("Content-Type", "application/json")
-            .with_json(&body)?
+            .with_json(&json!({"data": ip.to_string()}))?
             .send()?;
 
         let status_code = response.status_code;
@@ -130,4 +200,134 @@ impl DnsClient for DigitalOceanClient {
     fn provider_name(&self) -> &str {
         "DigitalOcean"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let (domain, _) = extract_zone_and_name(hostname);
+        let request = |method, url| {
+            self.http.preview(method, url)
+                .with_header("Authorization", format!("Bearer {}", self.token))
+                .with_header("Content-Type", "application/json")
+        };
+        Ok(vec![
+            request("GET", format!("{}?page=1&per_page=200", self.records_url(&domain))),
+            request("PUT", format!("{}/{{record_id}}", self.records_url(&domain)))
+                .with_body(json!({"data": ip.to_string()}).to_string()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_client(server: &mockito::Server) -> DigitalOceanClient {
+        let config = Config {
+            protocol: Some("digitalocean".to_string()),
+            password: Some("do-token".to_string()),
+            server: Some(server.url()),
+            ..Default::default()
+        };
+        DigitalOceanClient::new(&config).unwrap()
+    }
+
+    fn mock_records(server: &mut mockito::Server, status: usize, body: &str) -> mockito::Mock {
+        server.mock("GET", "/v2/domains/example.com/records")
+            .match_query(Matcher::Any)
+            .match_header("authorization", "Bearer do-token")
+            .with_status(status)
+            .with_body(body)
+            .create()
+    }
+
+    #[test]
+    fn test_extract_zone_and_name() {
+        let split = extract_zone_and_name;
+        assert_eq!(split("example.com"), ("example.com".to_string(), "@".to_string()));
+        assert_eq!(split("www.example.com"), ("example.com".to_string(), "www".to_string()));
+        assert_eq!(split("api.v2.example.com"), ("example.com".to_string(), "api.v2".to_string()));
+        assert_eq!(split("a.b.c.example.com."), ("example.com".to_string(), "a.b.c".to_string()));
+    }
+
+    #[test]
+    fn test_digitalocean_updates_existing_record() {
+        let mut server = mockito::Server::new();
+        let _records = mock_records(&mut server, 200, r#"{"domain_records":[
+            {"id":11,"type":"AAAA","name":"home","data":"2001:db8::5"},
+            {"id":12,"type":"A","name":"home","data":"198.51.100.1"}
+        ],"links":{}}"#);
+        let update = server.mock("PUT", "/v2/domains/example.com/records/12")
+            .match_body(Matcher::Json(json!({"data": "203.0.113.1"})))
+            .with_status(200)
+            .create();
+
+        let client = test_client(&server);
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        update.assert();
+    }
+
+    #[test]
+    fn test_digitalocean_creates_missing_record() {
+        let mut server = mockito::Server::new();
+        let _records = mock_records(&mut server, 200, r#"{"domain_records":[
+            {"id":12,"type":"A","name":"home","data":"198.51.100.1"}
+        ],"links":{}}"#);
+        let create = server.mock("POST", "/v2/domains/example.com/records")
+            .match_header("authorization", "Bearer do-token")
+            .match_body(Matcher::Json(json!({
+                "type": "AAAA",
+                "name": "home",
+                "data": "2001:db8::1",
+                "ttl": 1800,
+            })))
+            .with_status(201)
+            .with_body(r#"{"domain_record":{"id":13,"type":"AAAA","name":"home","data":"2001:db8::1"}}"#)
+            .create();
+
+        let client = test_client(&server);
+        assert!(client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).is_ok());
+        create.assert();
+    }
+
+    #[test]
+    fn test_digitalocean_404_creates_record() {
+        let mut server = mockito::Server::new();
+        let _records = mock_records(&mut server, 404, r#"{"id":"not_found","message":"The resource you were accessing could not be found."}"#);
+        let create = server.mock("POST", "/v2/domains/example.com/records")
+            .with_status(201)
+            .with_body(r#"{"domain_record":{"id":14}}"#)
+            .create();
+
+        let client = test_client(&server);
+        assert_eq!(client.create_record("example.com", "home", "A", "203.0.113.1".parse().unwrap(), 60).unwrap(), 14);
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        create.expect(2).assert();
+    }
+
+    #[test]
+    fn test_digitalocean_unauthorized_does_not_create() {
+        let mut server = mockito::Server::new();
+        let _records = mock_records(&mut server, 401, r#"{"id":"Unauthorized","message":"Unable to authenticate you."}"#);
+        let create = server.mock("POST", "/v2/domains/example.com/records")
+            .expect(0)
+            .create();
+
+        let client = test_client(&server);
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(DigitalOceanError::Unauthorized)));
+        create.assert();
+    }
+
+    #[test]
+    fn test_digitalocean_preview_update() {
+        let server = mockito::Server::new();
+        let requests = test_client(&server).preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        let urls: Vec<_> = requests.iter().map(|r| (r.method, r.url.clone())).collect();
+        assert_eq!(urls, vec![
+            ("GET", format!("{}/v2/domains/example.com/records?page=1&per_page=200", server.url())),
+            ("PUT", format!("{}/v2/domains/example.com/records/{{record_id}}", server.url())),
+        ]);
+        assert_eq!(requests[1].body.as_deref(), Some(r#"{"data":"203.0.113.1"}"#));
+        assert!(!requests[1].to_string().contains("do-token"));
+    }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -9,6 +10,7 @@ pub struct DinahostingClient {
     server: String,
     username: String,
     password: String,
+    http: http::HttpClient,
 }
 
 impl DinahostingClient {
@@ -28,6 +30,7 @@ impl DinahostingClient {
             server,
             username,
             password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
 
@@ -51,31 +54,32 @@ impl DinahostingClient {
             parts[1..].join(".")
         }
     }
-}
 
-impl DnsClient for DinahostingClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let domain = self.get_domain_from_hostname(hostname);
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
         let record_type = match ip {
             IpAddr::V4(_) => "A",
             IpAddr::V6(_) => "AAAA",
         };
-
-        let url = format!(
+        format!(
             "{}/special/api.php?command=Domain_Zone_UpdateDynDNS&domain={}&zone={}&type={}&ip={}",
-            self.server, domain, hostname, record_type, ip
-        );
-
-        log::info!("Updating {} with Dinahosting", hostname);
+            self.server, self.get_domain_from_hostname(hostname), hostname, record_type, ip
+        )
+    }
 
-        // Use HTTP Basic Auth header instead of URL parameters for security
-        let auth = format!("{}:{}", self.username, self.password);
+    /// HTTP Basic Auth header, used instead of URL parameters for security
+    fn auth_header(&self) -> String {
         use base64::{Engine as _, engine::general_purpose};
-        let encoded_auth = general_purpose::STANDARD.encode(auth.as_bytes());
+        let auth = format!("{}:{}", self.username, self.password);
+        format!("Basic {}", general_purpose::STANDARD.encode(auth.as_bytes()))
+    }
+}
+
+impl DnsClient for DinahostingClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with Dinahosting", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", encoded_auth))
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         let status_code = response.status_code;
@@ -115,6 +119,11 @@ impl DnsClient for DinahostingClient {
     fn provider_name(&self) -> &str {
         "Dinahosting"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
 }
 
 #[cfg(test)]
@@ -126,6 +135,7 @@ mod tests {
             server: "https://dinahosting.com".to_string(),
             username: "testuser".to_string(),
             password: "testpass".to_string(),
+            http: http::HttpClientBuilder::new(&Config::default()).build(),
         }
     }
 
@@ -170,4 +180,16 @@ mod tests {
         assert_eq!(client.get_domain_from_hostname("DDNS.EXAMPLE.COM"), "example.com");
         assert_eq!(client.get_domain_from_hostname("WwW.ExAmPlE.OrG"), "example.org");
     }
+
+    #[test]
+    fn test_dinahosting_preview_update() {
+        let requests = create_test_client().preview_update("ddns.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://dinahosting.com/special/api.php?command=Domain_Zone_UpdateDynDNS&domain=example.com&zone=ddns.example.com&type=AAAA&ip=2001:db8::1"
+        );
+        // base64("testuser:testpass")
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dGVzdHVzZXI6dGVzdHBhc3M=".to_string())));
+    }
 }
@@ -1,11 +1,13 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
 pub struct DirectnicClient {
     urlv4: Option<String>,
     urlv6: Option<String>,
+    http: http::HttpClient,
 }
 
 impl DirectnicClient {
@@ -22,8 +24,18 @@ impl DirectnicClient {
         Ok(DirectnicClient {
             urlv4,
             urlv6,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// The configured update URL for the address family of `ip`
+    fn url_for(&self, ip: IpAddr) -> Result<&str, Box<dyn Error>> {
+        let url = match ip {
+            IpAddr::V4(_) => self.urlv4.as_ref().ok_or("urlv4 not configured for IPv4 address")?,
+            IpAddr::V6(_) => self.urlv6.as_ref().ok_or("urlv6 not configured for IPv6 address")?,
+        };
+        Ok(url)
+    }
 }
 
 impl DnsClient for DirectnicClient {
@@ -31,18 +43,10 @@ impl DnsClient for DirectnicClient {
         log::info!("Updating Directnic record for {} to {}", hostname, ip);
 
         // Select the appropriate URL based on IP address type
-        let url = match ip {
-            IpAddr::V4(_) => {
-                self.urlv4.as_ref().ok_or("urlv4 not configured for IPv4 address")?
-            }
-            IpAddr::V6(_) => {
-                self.urlv6.as_ref().ok_or("urlv6 not configured for IPv6 address")?
-            }
-        };
+        let url = self.url_for(ip)?;
 
         // Directnic uses a simple GET request to the provided URL
-        let response = minreq::get(url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(url))
             .send()?;
 
         if response.status_code == 200 {
@@ -81,4 +85,33 @@ impl DnsClient for DirectnicClient {
     fn provider_name(&self) -> &'static str {
         "Directnic"
     }
+
+    fn preview_update(&self, _hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        // The path of the gateway URL carries the update token
+        let url = self.url_for(ip)?;
+        let token = url.splitn(4, '/').nth(3).unwrap_or_default();
+        Ok(vec![self.http.preview("GET", url).with_secret(token)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directnic_preview_update() {
+        let config = Config {
+            protocol: Some("directnic".to_string()),
+            server: Some("https://directnic.com/dns/gateway/token123456/".to_string()),
+            ..Default::default()
+        };
+        let client = DirectnicClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://directnic.com/dns/gateway/token123456/");
+        assert!(!requests[0].to_string().contains("token123456"));
+        // No IPv6 URL configured
+        assert!(client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).is_err());
+    }
 }
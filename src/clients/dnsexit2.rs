@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{zone_for, DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -9,6 +10,7 @@ pub struct Dnsexit2Client {
     path: String,
     ttl: u32,
     zone: String,
+    http: http::HttpClient,
 }
 
 impl Dnsexit2Client {
@@ -20,7 +22,7 @@ impl Dnsexit2Client {
         let path = "/dns/";
         let ttl = config.ttl.unwrap_or(5);
         
-        // Zone from zone_id, will be set from hostname if not specified
+        // Zone from zone_id, derived from the hostname if not specified
         let zone = config.zone.clone().unwrap_or_default();
 
         Ok(Dnsexit2Client {
@@ -29,39 +31,43 @@ impl Dnsexit2Client {
             path: path.to_string(),
             ttl,
             zone,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
 
-impl DnsClient for Dnsexit2Client {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        log::info!("Updating DNSExit2 record for {} to {}", hostname, ip);
+    /// Zone and record name for `hostname` ("" names the zone apex)
+    fn zone_and_name<'a>(&self, hostname: &'a str) -> (String, &'a str) {
+        let configured = Some(self.zone.as_str()).filter(|zone| !zone.is_empty());
+        let zone = zone_for(configured, hostname);
+        let name = hostname.strip_suffix(&format!(".{}", zone)).unwrap_or("");
+        (zone, name)
+    }
 
-        // Zone defaults to hostname if not configured
-        let zone = if self.zone.is_empty() {
-            hostname
-        } else {
-            &self.zone
-        };
+    fn update_url(&self) -> String {
+        format!("https://{}{}", self.server, self.path)
+    }
 
-        // Determine record type and extract hostname from host
-        let (record_type, name) = match ip {
-            IpAddr::V4(_) => ("A", hostname.strip_suffix(&format!(".{}", zone)).unwrap_or("")),
-            IpAddr::V6(_) => ("AAAA", hostname.strip_suffix(&format!(".{}", zone)).unwrap_or("")),
+    /// JSON payload updating the record for `hostname`; carries the API key
+    fn update_body(&self, hostname: &str, ip: IpAddr) -> String {
+        let (zone, name) = self.zone_and_name(hostname);
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
         };
-
-        // Build JSON payload
-        let json_payload = format!(
+        format!(
             r#"{{"apikey":"{}","domain":"{}","update":[{{"type":"{}","name":"{}","content":"{}","ttl":{}}}]}}"#,
             self.api_key, zone, record_type, name, ip, self.ttl
-        );
+        )
+    }
+}
 
-        let url = format!("https://{}{}", self.server, self.path);
+impl DnsClient for Dnsexit2Client {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating DNSExit2 record for {} to {}", hostname, ip);
 
-        let response = minreq::post(&url)
+        let response = self.http.prepare(minreq::post(self.update_url()))
             .with_header("Content-Type", "application/json")
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_body(json_payload)
+            .with_body(self.update_body(hostname, ip))
             .send()?;
 
         if response.status_code != 200 {
@@ -95,4 +101,52 @@ impl DnsClient for Dnsexit2Client {
     fn provider_name(&self) -> &'static str {
         "DNSExit2"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("POST", self.update_url())
+            .with_header("Content-Type", "application/json")
+            .with_body(self.update_body(hostname, ip))
+            .with_secret(&self.api_key)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client(zone: Option<&str>) -> Dnsexit2Client {
+        let config = Config {
+            protocol: Some("dnsexit2".to_string()),
+            password: Some("apikey".to_string()),
+            zone: zone.map(str::to_string),
+            ..Default::default()
+        };
+        Dnsexit2Client::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_dnsexit2_zone_and_name() {
+        let client = test_client(None);
+        assert_eq!(client.zone_and_name("example.com"), ("example.com".to_string(), ""));
+        assert_eq!(client.zone_and_name("home.example.com"), ("example.com".to_string(), "home"));
+        assert_eq!(client.zone_and_name("a.home.example.com"), ("example.com".to_string(), "a.home"));
+
+        let client = test_client(Some("home.example.com"));
+        assert_eq!(client.zone_and_name("a.home.example.com"), ("home.example.com".to_string(), "a"));
+    }
+
+    #[test]
+    fn test_dnsexit2_preview_update() {
+        let mut client = test_client(None);
+        client.api_key = "dnsexit-key-1234".to_string();
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].url, "https://api.dnsexit.com/dns/");
+        assert_eq!(
+            requests[0].body.as_deref(),
+            Some(r#"{"apikey":"dnsexit-key-1234","domain":"example.com","update":[{"type":"A","name":"home","content":"203.0.113.1","ttl":5}]}"#)
+        );
+        assert!(requests[0].to_string().contains(r#"{"apikey":"****1234","domain":"example.com""#));
+    }
 }
@@ -0,0 +1,281 @@
+use crate::clients::{zone_for, DnsClient, RequestPreview};
+use crate::config::Config;
+use crate::http;
+use serde_json::json;
+use std::error::Error;
+use std::net::IpAddr;
+
+/// DNSimple client
+/// API documentation: https://developer.dnsimple.com/v2/zones/records/
+///
+/// Configuration:
+/// - login: account ID
+/// - password: API access token
+/// - zone: DNS zone name (optional, defaults to the last two labels of the hostname)
+/// - ttl: record TTL in seconds (default 3600)
+pub struct DnsimpleClient {
+    account_id: String,
+    access_token: String,
+    server: String,
+    zone: Option<String>,
+    ttl: u32,
+    http: http::HttpClient,
+}
+
+impl DnsimpleClient {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let account_id = config.login.as_ref()
+            .ok_or("login (account ID) is required for DNSimple")?
+            .clone();
+        let access_token = config.password.as_ref()
+            .ok_or("password (API access token) is required for DNSimple")?
+            .clone();
+        let server = config.server.clone()
+            .unwrap_or_else(|| "https://api.dnsimple.com".to_string());
+
+        Ok(DnsimpleClient {
+            account_id,
+            access_token,
+            server,
+            zone: config.zone.clone(),
+            ttl: config.ttl.unwrap_or(3600),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
+    }
+
+    /// Record name relative to the zone (DNSimple uses "" for the apex)
+    fn record_name<'a>(hostname: &'a str, zone: &str) -> &'a str {
+        if hostname == zone {
+            ""
+        } else {
+            hostname.strip_suffix(&format!(".{}", zone)).unwrap_or(hostname)
+        }
+    }
+
+    fn records_url(&self, zone: &str) -> String {
+        format!("{}/v2/{}/zones/{}/records", self.server, self.account_id, zone)
+    }
+
+    fn auth_header(&self) -> String {
+        format!("Bearer {}", self.access_token)
+    }
+
+    fn find_record_url(&self, zone: &str, name: &str, record_type: &str) -> String {
+        format!("{}?name={}&type={}", self.records_url(zone), urlencoding::encode(name), record_type)
+    }
+
+    /// Body updating an existing record's address
+    fn update_body(&self, ip: IpAddr) -> serde_json::Value {
+        json!({
+            "content": ip.to_string(),
+            "ttl": self.ttl,
+        })
+    }
+
+    /// ID of the existing record of `record_type` named `name`, if any
+    fn find_record(&self, zone: &str, name: &str, record_type: &str) -> Result<Option<u64>, Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(self.find_record_url(zone, name, record_type)))
+            .with_header("Authorization", self.auth_header())
+            .with_header("Accept", "application/json")
+            .send()?;
+
+        if response.status_code != 200 {
+            return Err(format!("DNSimple API error (HTTP {}): {}",
+                response.status_code, response.as_str().unwrap_or("")).into());
+        }
+
+        let json: serde_json::Value = response.json()?;
+        let id = json["data"].as_array()
+            .into_iter()
+            .flatten()
+            .find(|record| record["type"] == record_type && record["name"] == name)
+            .and_then(|record| record["id"].as_u64());
+        Ok(id)
+    }
+
+    fn send(&self, request: minreq::Request, body: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+        let response = self.http.prepare(request)
+            .with_header("Authorization", self.auth_header())
+            .with_header("Accept", "application/json")
+            .with_json(body)?
+            .send()?;
+
+        match response.status_code {
+            200 | 201 => Ok(()),
+            status => Err(format!("DNSimple API error (HTTP {}): {}", status, response.as_str().unwrap_or("")).into()),
+        }
+    }
+}
+
+impl DnsClient for DnsimpleClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+
+        log::info!("Updating {} {} record with DNSimple", hostname, record_type);
+
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let name = Self::record_name(hostname, &zone);
+
+        match self.find_record(&zone, name, record_type)? {
+            Some(record_id) => {
+                log::debug!("Found record ID {} for {}", record_id, hostname);
+                let url = format!("{}/{}", self.records_url(&zone), record_id);
+                self.send(minreq::patch(url), &self.update_body(ip))?;
+            }
+            None => {
+                log::info!("No {} record for {} in zone {}, creating it", record_type, hostname, zone);
+                self.send(minreq::post(self.records_url(&zone)), &json!({
+                    "name": name,
+                    "type": record_type,
+                    "content": ip.to_string(),
+                    "ttl": self.ttl,
+                }))?;
+            }
+        }
+
+        log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+        Ok(())
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn Error>> {
+        if self.account_id.is_empty() {
+            return Err("login (account ID) is required for DNSimple".into());
+        }
+        if self.access_token.is_empty() {
+            return Err("password (API access token) is required for DNSimple".into());
+        }
+        Ok(())
+    }
+
+    fn provider_name(&self) -> &str {
+        "DNSimple"
+    }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let request = |method, url| {
+            self.http.preview(method, url)
+                .with_header("Authorization", self.auth_header())
+                .with_header("Accept", "application/json")
+        };
+        Ok(vec![
+            request("GET", self.find_record_url(&zone, Self::record_name(hostname, &zone), record_type)),
+            request("PATCH", format!("{}/{{record_id}}", self.records_url(&zone)))
+                .with_header("Content-Type", "application/json")
+                .with_body(self.update_body(ip).to_string()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_config(server: Option<String>) -> Config {
+        Config {
+            protocol: Some("dnsimple".to_string()),
+            login: Some("1010".to_string()),
+            password: Some("dnsimple-token".to_string()),
+            server,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_dnsimple_client_creation() {
+        let client = DnsimpleClient::new(&test_config(None)).unwrap();
+        assert_eq!(client.server, "https://api.dnsimple.com");
+        assert_eq!(client.ttl, 3600);
+        assert_eq!(client.provider_name(), "DNSimple");
+        assert!(client.validate_config().is_ok());
+        assert!(DnsimpleClient::new(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_dnsimple_urls_and_auth() {
+        let client = DnsimpleClient::new(&test_config(None)).unwrap();
+        assert_eq!(client.records_url("example.com"), "https://api.dnsimple.com/v2/1010/zones/example.com/records");
+        assert_eq!(client.auth_header(), "Bearer dnsimple-token");
+        assert_eq!(zone_for(client.zone.as_deref(), "home.example.com"), "example.com");
+        assert_eq!(DnsimpleClient::record_name("home.example.com", "example.com"), "home");
+        assert_eq!(DnsimpleClient::record_name("example.com", "example.com"), "");
+    }
+
+    #[test]
+    fn test_dnsimple_update_existing_record() {
+        let mut server = mockito::Server::new();
+        let list = server.mock("GET", "/v2/1010/zones/example.com/records")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("name".to_string(), "home".to_string()),
+                Matcher::UrlEncoded("type".to_string(), "A".to_string()),
+            ]))
+            .match_header("authorization", "Bearer dnsimple-token")
+            .with_body(r#"{"data":[{"id":5,"zone_id":"example.com","name":"home","type":"A","content":"198.51.100.1","ttl":3600}]}"#)
+            .create();
+        let update = server.mock("PATCH", "/v2/1010/zones/example.com/records/5")
+            .match_header("authorization", "Bearer dnsimple-token")
+            .match_body(Matcher::Json(json!({"content": "203.0.113.1", "ttl": 3600})))
+            .with_body(r#"{"data":{"id":5}}"#)
+            .create();
+
+        let client = DnsimpleClient::new(&test_config(Some(server.url()))).unwrap();
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        list.assert();
+        update.assert();
+    }
+
+    #[test]
+    fn test_dnsimple_creates_missing_record() {
+        let mut server = mockito::Server::new();
+        let _list = server.mock("GET", "/v2/1010/zones/example.com/records")
+            .match_query(Matcher::Any)
+            .with_body(r#"{"data":[]}"#)
+            .create();
+        let create = server.mock("POST", "/v2/1010/zones/example.com/records")
+            .match_body(Matcher::Json(json!({
+                "name": "",
+                "type": "AAAA",
+                "content": "2001:db8::1",
+                "ttl": 3600,
+            })))
+            .with_status(201)
+            .with_body(r#"{"data":{"id":6}}"#)
+            .create();
+
+        let client = DnsimpleClient::new(&test_config(Some(server.url()))).unwrap();
+        assert!(client.update_record("example.com", "2001:db8::1".parse().unwrap()).is_ok());
+        create.assert();
+    }
+
+    #[test]
+    fn test_dnsimple_unauthorized() {
+        let mut server = mockito::Server::new();
+        let _list = server.mock("GET", "/v2/1010/zones/example.com/records")
+            .match_query(Matcher::Any)
+            .with_status(401)
+            .with_body(r#"{"message":"Authentication failed"}"#)
+            .create();
+
+        let client = DnsimpleClient::new(&test_config(Some(server.url()))).unwrap();
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("HTTP 401"));
+    }
+
+    #[test]
+    fn test_dnsimple_preview_update() {
+        let client = DnsimpleClient::new(&test_config(None)).unwrap();
+        let requests = client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        let urls: Vec<_> = requests.iter().map(|r| (r.method, r.url.as_str())).collect();
+        assert_eq!(urls, vec![
+            ("GET", "https://api.dnsimple.com/v2/1010/zones/example.com/records?name=home&type=AAAA"),
+            ("PATCH", "https://api.dnsimple.com/v2/1010/zones/example.com/records/{record_id}"),
+        ]);
+        assert_eq!(requests[1].body.as_deref(), Some(r#"{"content":"2001:db8::1","ttl":3600}"#));
+        assert!(!requests[1].to_string().contains("dnsimple-token"));
+    }
+}
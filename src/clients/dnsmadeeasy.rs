@@ -1,7 +1,13 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
+use std::thread;
+use std::time::Duration;
+
+/// Retries after an HTTP 503 (maintenance) when `retries=` isn't set
+const DEFAULT_MAX_RETRIES: u8 = 3;
 
 /// DNS Made Easy client
 /// Uses simplified API (full REST API with HMAC would be more complex)
@@ -9,6 +15,10 @@ pub struct DnsMadeEasyClient {
     server: String,
     username: String,
     password: String,
+    max_retries: u8,
+    /// Delay before the first retry, doubled after each one
+    retry_delay: Duration,
+    http: http::HttpClient,
 }
 
 impl DnsMadeEasyClient {
@@ -28,23 +38,51 @@ impl DnsMadeEasyClient {
             server,
             username,
             password,
+            max_retries: config.retries.unwrap_or(DEFAULT_MAX_RETRIES),
+            retry_delay: Duration::from_secs(1),
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// DNS Made Easy dynamic DNS endpoint
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!(
+            "{}/servlet/updateip?username={}&password={}&id={}&ip={}",
+            self.server, self.username, self.password, hostname, ip
+        )
+    }
+
+    /// Send the update, retrying with exponential backoff while the API returns 503
+    fn send_with_retry(&self, url: &str) -> Result<minreq::Response, Box<dyn Error>> {
+        let mut delay = self.retry_delay;
+        let mut retries = 0;
+
+        loop {
+            let response = self.http.prepare(minreq::get(url))
+                .send()?;
+
+            if response.status_code != 503 || retries >= self.max_retries {
+                return Ok(response);
+            }
+
+            retries += 1;
+            log::warn!(
+                "DNS Made Easy unavailable (HTTP 503), retry {}/{} in {:?}",
+                retries, self.max_retries, delay
+            );
+            thread::sleep(delay);
+            delay *= 2;
+        }
+    }
 }
 
 impl DnsClient for DnsMadeEasyClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        // DNS Made Easy dynamic DNS endpoint
-        let url = format!(
-            "{}/servlet/updateip?username={}&password={}&id={}&ip={}",
-            self.server, self.username, self.password, hostname, ip
-        );
+        let url = self.update_url(hostname, ip);
 
         log::info!("Updating {} with DNS Made Easy", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .send()?;
+        let response = self.send_with_retry(&url)?;
 
         let status_code = response.status_code;
         let body = response.as_str()?.trim();
@@ -79,4 +117,81 @@ impl DnsClient for DnsMadeEasyClient {
     fn provider_name(&self) -> &str {
         "DNS Made Easy"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client(server: &str, retries: Option<u8>) -> DnsMadeEasyClient {
+        let config = Config {
+            protocol: Some("dnsmadeeasy".to_string()),
+            login: Some("user".to_string()),
+            password: Some("secret".to_string()),
+            server: Some(server.to_string()),
+            retries,
+            ..Default::default()
+        };
+        let mut client = DnsMadeEasyClient::new(&config).unwrap();
+        client.retry_delay = Duration::from_millis(1);
+        client
+    }
+
+    #[test]
+    fn test_dnsmadeeasy_default_retries() {
+        let client = test_client("https://cp.dnsmadeeasy.com", None);
+        assert_eq!(client.max_retries, 3);
+        assert_eq!(test_client("https://cp.dnsmadeeasy.com", Some(5)).max_retries, 5);
+    }
+
+    #[test]
+    fn test_dnsmadeeasy_retries_on_503() {
+        let mut server = mockito::Server::new();
+        let unavailable = server.mock("GET", "/servlet/updateip")
+            .match_query(mockito::Matcher::Any)
+            .with_status(503)
+            .expect(2)
+            .create();
+        let ok = server.mock("GET", "/servlet/updateip")
+            .match_query(mockito::Matcher::Any)
+            .with_body("success")
+            .expect(1)
+            .create();
+
+        let client = test_client(&server.url(), None);
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        unavailable.assert();
+        ok.assert();
+    }
+
+    #[test]
+    fn test_dnsmadeeasy_gives_up_after_retries() {
+        let mut server = mockito::Server::new();
+        let unavailable = server.mock("GET", "/servlet/updateip")
+            .match_query(mockito::Matcher::Any)
+            .with_status(503)
+            .expect(3)
+            .create();
+
+        let client = test_client(&server.url(), Some(2));
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "HTTP error: 503");
+        unavailable.assert();
+    }
+
+    #[test]
+    fn test_dnsmadeeasy_preview_update() {
+        let client = test_client("https://cp.dnsmadeeasy.com", None);
+        let requests = client.preview_update("12345", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://cp.dnsmadeeasy.com/servlet/updateip?username=user&password=secret&id=12345&ip=203.0.113.1"
+        );
+        assert!(requests[0].to_string().contains("password=[REDACTED]"));
+    }
 }
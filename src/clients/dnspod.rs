@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -8,6 +9,7 @@ use std::net::IpAddr;
 pub struct DnspodClient {
     server: String,
     token: String,
+    http: http::HttpClient,
 }
 
 impl DnspodClient {
@@ -22,12 +24,16 @@ impl DnspodClient {
         Ok(DnspodClient {
             server,
             token,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
 
-impl DnsClient for DnspodClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+    fn update_url(&self) -> String {
+        format!("{}/Record.Ddns", self.server)
+    }
+
+    /// Form body for `Record.Ddns`; carries the login token
+    fn update_body(&self, hostname: &str, ip: IpAddr) -> Result<String, Box<dyn Error>> {
         let record_type = match ip {
             IpAddr::V4(_) => "A",
             IpAddr::V6(_) => "AAAA",
@@ -45,22 +51,26 @@ impl DnsClient for DnspodClient {
             "@"
         };
 
-        log::info!("Updating {} with DNSPod", hostname);
+        Ok(format!(
+            "login_token={}&format=json&domain={}&sub_domain={}&record_type={}&value={}",
+            self.token,
+            domain,
+            subdomain,
+            record_type,
+            ip
+        ))
+    }
+}
 
-        // DNSPod API endpoint
-        let url = format!("{}/Record.Ddns", self.server);
+impl DnsClient for DnspodClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let body = self.update_body(hostname, ip)?;
 
-        let response = minreq::post(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        log::info!("Updating {} with DNSPod", hostname);
+
+        let response = self.http.prepare(minreq::post(self.update_url()))
             .with_header("Content-Type", "application/x-www-form-urlencoded")
-            .with_body(format!(
-                "login_token={}&format=json&domain={}&sub_domain={}&record_type={}&value={}",
-                self.token,
-                domain,
-                subdomain,
-                record_type,
-                ip
-            ))
+            .with_body(body)
             .send()?;
 
         let status_code = response.status_code;
@@ -94,4 +104,36 @@ impl DnsClient for DnspodClient {
     fn provider_name(&self) -> &str {
         "DNSPod"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("POST", self.update_url())
+            .with_header("Content-Type", "application/x-www-form-urlencoded")
+            .with_body(self.update_body(hostname, ip)?)
+            .with_secret(&self.token)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dnspod_preview_update() {
+        let config = Config {
+            protocol: Some("dnspod".to_string()),
+            password: Some("12345,token123456".to_string()),
+            ..Default::default()
+        };
+        let client = DnspodClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://dnsapi.cn/Record.Ddns");
+        assert_eq!(
+            requests[0].body.as_deref(),
+            Some("login_token=12345,token123456&format=json&domain=example.com&sub_domain=home&record_type=A&value=203.0.113.1")
+        );
+        assert!(!requests[0].to_string().contains("token123456"));
+        assert!(client.preview_update("localhost", "203.0.113.1".parse().unwrap()).is_err());
+    }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use base64::{Engine as _, engine::general_purpose};
 use std::error::Error;
 use std::net::IpAddr;
@@ -8,6 +9,7 @@ pub struct DomeneshopClient {
     username: String,
     password: String,
     server: String,
+    http: http::HttpClient,
 }
 
 impl DomeneshopClient {
@@ -25,22 +27,26 @@ impl DomeneshopClient {
             username,
             password,
             server,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/v0/dyndns/update?hostname={}&myip={}", self.server, hostname, ip)
+    }
+
+    fn auth_header(&self) -> String {
+        let auth = general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password));
+        format!("Basic {}", auth)
+    }
 }
 
 impl DnsClient for DomeneshopClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let auth = general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password));
-        
-        let url = format!("{}/v0/dyndns/update?hostname={}&myip={}", 
-            self.server, hostname, ip);
-        
         log::info!("Updating {} to {}", hostname, ip);
         
-        let response = minreq::get(&url)
-            .with_header("Authorization", format!("Basic {}", auth))
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         if response.status_code != 200 && response.status_code != 204 {
@@ -75,4 +81,31 @@ impl DnsClient for DomeneshopClient {
     fn provider_name(&self) -> &str {
         "Domeneshop"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domeneshop_preview_update() {
+        let config = Config {
+            protocol: Some("domeneshop".to_string()),
+            login: Some("token".to_string()),
+            password: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let client = DomeneshopClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://api.domeneshop.no/v0/dyndns/update?hostname=home.example.com&myip=203.0.113.1");
+        // base64("token:secret")
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dG9rZW46c2VjcmV0".to_string())));
+    }
 }
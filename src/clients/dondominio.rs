@@ -1,14 +1,18 @@
-use crate::clients::DnsClient;
+use crate::clients::{same_result, DnsClient, HostResults, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use serde_json::json;
 use std::error::Error;
 use std::net::IpAddr;
 
 /// DonDominio DNS client
-/// Uses DonDominio's dondns API with API key authentication
+/// Uses DonDominio's dondns API with API key authentication.
+/// Several hostnames are sent in one request through the `/batch` endpoint.
 pub struct DonDominioClient {
     server: String,
     api_key: String,
     username: String,
+    http: http::HttpClient,
 }
 
 impl DonDominioClient {
@@ -28,35 +32,57 @@ impl DonDominioClient {
             server,
             api_key,
             username,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
 
-impl DnsClient for DonDominioClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+    /// Record fields for one hostname: the first label is the name, the rest the domain
+    fn record(hostname: &str, ip: IpAddr) -> serde_json::Value {
+        let (name, domain) = hostname.split_once('.').unwrap_or((hostname, ""));
         let record_type = match ip {
             IpAddr::V4(_) => "A",
             IpAddr::V6(_) => "AAAA",
         };
+        json!({
+            "domain": domain,
+            "name": name,
+            "type": record_type,
+            "value": ip.to_string(),
+        })
+    }
 
-        let url = format!("{}/update", self.server);
-        
-        let json_body = format!(
-            r#"{{"apiuser":"{}","apipasswd":"{}","domain":"{}","name":"{}","type":"{}","value":"{}"}}"#,
-            self.username,
-            self.api_key,
-            hostname.split('.').skip(1).collect::<Vec<_>>().join("."),
-            hostname.split('.').next().unwrap_or(""),
-            record_type,
-            ip
-        );
+    /// Body for `/update`: the record fields plus the credentials
+    fn update_body(&self, hostname: &str, ip: IpAddr) -> serde_json::Value {
+        let mut body = Self::record(hostname, ip);
+        body["apiuser"] = json!(self.username);
+        body["apipasswd"] = json!(self.api_key);
+        body
+    }
 
-        log::info!("Updating {} with DonDominio", hostname);
+    fn batch_body(&self, updates: &[(String, IpAddr)]) -> serde_json::Value {
+        json!({
+            "apiuser": self.username,
+            "apipasswd": self.api_key,
+            "records": updates.iter()
+                .map(|(hostname, ip)| Self::record(hostname, *ip))
+                .collect::<Vec<_>>(),
+        })
+    }
 
-        let response = minreq::post(&url)
-            .with_header("Content-Type", "application/json")
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_body(json_body)
+    /// Update several records in one API call
+    pub fn batch_update(&self, updates: &[(String, IpAddr)]) -> Result<(), Box<dyn Error>> {
+        let hostnames: Vec<&str> = updates.iter().map(|(hostname, _)| hostname.as_str()).collect();
+        log::info!("Updating {} with DonDominio", hostnames.join(", "));
+
+        self.post("/batch", &self.batch_body(updates))?;
+
+        log::info!("Successfully updated {}", hostnames.join(", "));
+        Ok(())
+    }
+
+    fn post(&self, path: &str, body: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+        let response = self.http.prepare(minreq::post(format!("{}{}", self.server, path)))
+            .with_json(body)?
             .send()?;
 
         let status_code = response.status_code;
@@ -67,19 +93,44 @@ impl DnsClient for DonDominioClient {
         if status_code != 200 {
             return Err(format!("HTTP error: {}", status_code).into());
         }
+        Self::parse_response(body)
+    }
+
+    /// Check a JSON response for `"success": true`
+    fn parse_response(body: &str) -> Result<(), Box<dyn Error>> {
+        let json: serde_json::Value = serde_json::from_str(body)
+            .map_err(|_| format!("Unexpected response: {}", body))?;
+
+        if json["success"] == true || json["success"] == "true" {
+            return Ok(());
+        }
 
-        // Parse JSON response
-        if body.contains(r#""success":true"#) || body.contains(r#""success":"true""#) {
-            log::info!("Successfully updated {} to {}", hostname, ip);
-            Ok(())
-        } else if body.contains("authentication") || body.contains("credentials") {
+        let message = json["errorCodeMsg"].as_str()
+            .or_else(|| json["error"].as_str())
+            .or_else(|| json["message"].as_str())
+            .unwrap_or(body);
+        if message.contains("authentication") || message.contains("credentials") {
             Err("Authentication failed - check username and API key".into())
-        } else if body.contains("error") {
-            Err(format!("DonDominio error: {}", body).into())
         } else {
-            Err(format!("Unexpected response: {}", body).into())
+            Err(format!("DonDominio error: {}", message).into())
         }
     }
+}
+
+impl DnsClient for DonDominioClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with DonDominio", hostname);
+
+        self.post("/update", &self.update_body(hostname, ip))?;
+
+        log::info!("Successfully updated {} to {}", hostname, ip);
+        Ok(())
+    }
+
+    fn update_records(&self, hostnames: &[String], ip: IpAddr) -> Option<HostResults> {
+        let updates: Vec<(String, IpAddr)> = hostnames.iter().map(|hostname| (hostname.clone(), ip)).collect();
+        Some(same_result(hostnames, self.batch_update(&updates)))
+    }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
         if self.username.is_empty() {
@@ -94,4 +145,108 @@ impl DnsClient for DonDominioClient {
     fn provider_name(&self) -> &str {
         "DonDominio"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("POST", format!("{}/update", self.server))
+            .with_header("Content-Type", "application/json")
+            .with_body(self.update_body(hostname, ip).to_string())
+            .with_secret(&self.api_key)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_client(server: Option<String>) -> DonDominioClient {
+        let config = Config {
+            protocol: Some("dondominio".to_string()),
+            login: Some("apiuser".to_string()),
+            password: Some("apikey".to_string()),
+            server,
+            ..Default::default()
+        };
+        DonDominioClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_dondominio_batch_body() {
+        let client = test_client(None);
+        let updates = vec![
+            ("home.example.com".to_string(), "203.0.113.1".parse().unwrap()),
+            ("vpn.example.org".to_string(), "2001:db8::1".parse().unwrap()),
+        ];
+        assert_eq!(client.batch_body(&updates), json!({
+            "apiuser": "apiuser",
+            "apipasswd": "apikey",
+            "records": [
+                {"domain": "example.com", "name": "home", "type": "A", "value": "203.0.113.1"},
+                {"domain": "example.org", "name": "vpn", "type": "AAAA", "value": "2001:db8::1"},
+            ],
+        }));
+    }
+
+    #[test]
+    fn test_dondominio_batch_update() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/batch")
+            .match_body(Matcher::Json(json!({
+                "apiuser": "apiuser",
+                "apipasswd": "apikey",
+                "records": [
+                    {"domain": "example.com", "name": "a", "type": "A", "value": "203.0.113.1"},
+                    {"domain": "example.com", "name": "b", "type": "A", "value": "203.0.113.1"},
+                ],
+            })))
+            .with_body(r#"{"success":true}"#)
+            .expect(1)
+            .create();
+
+        let client = test_client(Some(server.url()));
+        let hostnames = vec!["a.example.com".to_string(), "b.example.com".to_string()];
+        let result = client.update_records(&hostnames, "203.0.113.1".parse().unwrap());
+        assert!(result.unwrap().iter().all(|(_, result)| result.is_ok()));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_dondominio_parse_response() {
+        assert!(DonDominioClient::parse_response(r#"{"success":true}"#).is_ok());
+        assert!(DonDominioClient::parse_response(r#"{"success":"true"}"#).is_ok());
+        assert_eq!(
+            DonDominioClient::parse_response(r#"{"success":false,"errorCodeMsg":"invalid credentials"}"#).unwrap_err().to_string(),
+            "Authentication failed - check username and API key"
+        );
+        assert_eq!(
+            DonDominioClient::parse_response(r#"{"success":false,"errorCodeMsg":"Domain not found"}"#).unwrap_err().to_string(),
+            "DonDominio error: Domain not found"
+        );
+        assert!(DonDominioClient::parse_response("<html>").is_err());
+    }
+
+    #[test]
+    fn test_dondominio_validate_config() {
+        assert!(test_client(None).validate_config().is_ok());
+        let config = Config {
+            login: Some(String::new()),
+            password: Some("apikey".to_string()),
+            ..Default::default()
+        };
+        assert!(DonDominioClient::new(&config).unwrap().validate_config().is_err());
+    }
+
+    #[test]
+    fn test_dondominio_preview_update() {
+        let mut client = test_client(None);
+        client.api_key = "dondominio-key".to_string();
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://dondns.dondominio.com/update");
+        assert_eq!(
+            requests[0].body.as_deref(),
+            Some(r#"{"apipasswd":"dondominio-key","apiuser":"apiuser","domain":"example.com","name":"home","type":"A","value":"203.0.113.1"}"#)
+        );
+        assert!(!requests[0].to_string().contains("dondominio-key"));
+    }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -9,6 +10,7 @@ pub struct Dslreports1Client {
     server: String,
     username: String,
     password: String,
+    http: http::HttpClient,
 }
 
 impl Dslreports1Client {
@@ -28,23 +30,24 @@ impl Dslreports1Client {
             server,
             username,
             password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// DSLReports legacy update endpoint
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!(
+            "{}/updateip?username={}&password={}&hostname={}&ip={}",
+            self.server, self.username, self.password, hostname, ip
+        )
+    }
 }
 
 impl DnsClient for Dslreports1Client {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with DSLReports", hostname);
 
-        // DSLReports legacy update endpoint
-        let url = format!("{}/updateip", self.server);
-
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_param("username", &self.username)
-            .with_param("password", &self.password)
-            .with_param("hostname", hostname)
-            .with_param("ip", ip.to_string())
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
             .send()?;
 
         let status_code = response.status_code;
@@ -82,4 +85,32 @@ impl DnsClient for Dslreports1Client {
     fn provider_name(&self) -> &str {
         "DSLReports"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dslreports1_preview_update() {
+        let config = Config {
+            protocol: Some("dslreports1".to_string()),
+            login: Some("user".to_string()),
+            password: Some("secret-password".to_string()),
+            ..Default::default()
+        };
+        let client = Dslreports1Client::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://www.dslreports.com/updateip?username=user&password=secret-password&hostname=home.example.com&ip=203.0.113.1"
+        );
+        assert!(!requests[0].to_string().contains("secret-password"));
+    }
 }
@@ -1,13 +1,25 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use crate::utils::mask_credential;
 use std::error::Error;
-use std::net::IpAddr;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 /// DuckDNS client - https://www.duckdns.org/
-#[derive(Debug)]
 pub struct DuckDnsClient {
     token: String,
     server: String,
+    http: http::HttpClient,
+}
+
+impl fmt::Debug for DuckDnsClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DuckDnsClient")
+            .field("token", &mask_credential(&self.token))
+            .field("server", &self.server)
+            .finish()
+    }
 }
 
 impl DuckDnsClient {
@@ -21,30 +33,52 @@ impl DuckDnsClient {
             .cloned()
             .unwrap_or_else(|| "https://www.duckdns.org".to_string());
 
-        Ok(DuckDnsClient { token, server })
+        Ok(DuckDnsClient {
+            token,
+            server,
+            http: http::HttpClientBuilder::new(config).build(),
+        })
     }
-}
 
-impl DnsClient for DuckDnsClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+    /// Build the update URL; IPv4 goes in `ip=`, IPv6 in `ip6=`
+    fn build_url(&self, hostname: &str, ipv4: Option<Ipv4Addr>, ipv6: Option<Ipv6Addr>) -> String {
         // DuckDNS hostname is typically without the .duckdns.org suffix
         let domain = hostname.trim_end_matches(".duckdns.org");
-        
-        let url = format!(
-            "{}/update?domains={}&token={}&ip={}",
-            self.server, domain, self.token, ip
-        );
 
-        log::info!("Updating {} with DuckDNS", hostname);
+        let mut url = format!("{}/update?domains={}&token={}", self.server, domain, self.token);
+        if let Some(ipv4) = ipv4 {
+            url.push_str(&format!("&ip={}", ipv4));
+        }
+        if let Some(ipv6) = ipv6 {
+            url.push_str(&format!("&ip6={}", ipv6));
+        }
+        url
+    }
+
+    /// Update URL for a single address
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        match ip {
+            IpAddr::V4(ipv4) => self.build_url(hostname, Some(ipv4), None),
+            IpAddr::V6(ipv6) => self.build_url(hostname, None, Some(ipv6)),
+        }
+    }
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+    /// Update both the A and AAAA records of a dual-stack host in one request
+    #[allow(dead_code)] // not used by the single-IP update loop yet
+    pub fn update_both(&self, hostname: &str, ipv4: Ipv4Addr, ipv6: Ipv6Addr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with DuckDNS (dual-stack)", hostname);
+        self.send(&self.build_url(hostname, Some(ipv4), Some(ipv6)))?;
+        log::info!("DNS records for {} successfully updated to {} and {}", hostname, ipv4, ipv6);
+        Ok(())
+    }
+
+    fn send(&self, url: &str) -> Result<(), Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(url))
             .send()?;
 
         let body = response.as_str()?.trim();
 
         if body == "OK" {
-            log::info!("DNS record for {} successfully updated to {}", hostname, ip);
             Ok(())
         } else if body == "KO" {
             Err("DuckDNS update failed - check your token and domain".into())
@@ -52,6 +86,16 @@ impl DnsClient for DuckDnsClient {
             Err(format!("Unexpected response: {}", body).into())
         }
     }
+}
+
+impl DnsClient for DuckDnsClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with DuckDNS", hostname);
+
+        self.send(&self.update_url(hostname, ip))?;
+        log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+        Ok(())
+    }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
         if self.token.is_empty() {
@@ -63,6 +107,10 @@ impl DnsClient for DuckDnsClient {
     fn provider_name(&self) -> &str {
         "DuckDNS"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))])
+    }
 }
 
 #[cfg(test)]
@@ -150,16 +198,65 @@ mod tests {
     fn test_duckdns_url_construction() {
         let config = create_test_config();
         let client = DuckDnsClient::new(&config).unwrap();
-        
-        let hostname = "myhost.duckdns.org";
-        let ip = "203.0.113.1";
-        let domain = hostname.trim_end_matches(".duckdns.org");
-        
-        let expected_url = format!(
-            "{}/update?domains={}&token={}&ip={}",
-            client.server, domain, client.token, ip
+
+        assert_eq!(
+            client.build_url("myhost.duckdns.org", Some("203.0.113.1".parse().unwrap()), None),
+            "https://www.duckdns.org/update?domains=myhost&token=test-token-12345&ip=203.0.113.1"
         );
-        
-        assert_eq!(expected_url, "https://www.duckdns.org/update?domains=myhost&token=test-token-12345&ip=203.0.113.1");
+    }
+
+    #[test]
+    fn test_duckdns_url_construction_ipv6() {
+        let config = create_test_config();
+        let client = DuckDnsClient::new(&config).unwrap();
+
+        assert_eq!(
+            client.build_url("myhost.duckdns.org", None, Some("2001:db8::1".parse().unwrap())),
+            "https://www.duckdns.org/update?domains=myhost&token=test-token-12345&ip6=2001:db8::1"
+        );
+    }
+
+    #[test]
+    fn test_duckdns_url_construction_dual_stack() {
+        let config = create_test_config();
+        let client = DuckDnsClient::new(&config).unwrap();
+
+        assert_eq!(
+            client.build_url(
+                "myhost.duckdns.org",
+                Some("203.0.113.1".parse().unwrap()),
+                Some("2001:db8::1".parse().unwrap()),
+            ),
+            "https://www.duckdns.org/update?domains=myhost&token=test-token-12345&ip=203.0.113.1&ip6=2001:db8::1"
+        );
+    }
+
+    #[test]
+    fn test_duckdns_debug_masks_token() {
+        let client = DuckDnsClient::new(&create_test_config()).unwrap();
+        let debug = format!("{:?}", client);
+        assert!(debug.contains("****2345"));
+        assert!(!debug.contains("test-token"));
+    }
+
+    #[test]
+    fn test_duckdns_update_both() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/update")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("domains".into(), "myhost".into()),
+                mockito::Matcher::UrlEncoded("ip".into(), "203.0.113.1".into()),
+                mockito::Matcher::UrlEncoded("ip6".into(), "2001:db8::1".into()),
+            ]))
+            .with_body("OK")
+            .create();
+
+        let config = Config {
+            server: Some(server.url()),
+            ..create_test_config()
+        };
+        let client = DuckDnsClient::new(&config).unwrap();
+        client.update_both("myhost.duckdns.org", "203.0.113.1".parse().unwrap(), "2001:db8::1".parse().unwrap()).unwrap();
+        mock.assert();
     }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 use base64::{Engine as _, engine::general_purpose};
@@ -11,6 +12,7 @@ pub struct Dyndns1Client {
     username: String,
     password: String,
     static_ip: bool,
+    http: http::HttpClient,
 }
 
 impl Dyndns1Client {
@@ -34,34 +36,29 @@ impl Dyndns1Client {
             username,
             password,
             static_ip,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// DynDNS v1 update URL; `system` marks static IPs (DynDNS v1 specific)
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        let system = if self.static_ip { "statdns" } else { "dyndns" };
+        format!("{}/nic/update?hostname={}&myip={}&system={}", self.server, hostname, ip, system)
+    }
+
+    fn auth_header(&self) -> String {
+        let auth = format!("{}:{}", self.username, self.password);
+        format!("Basic {}", general_purpose::STANDARD.encode(auth.as_bytes()))
+    }
 }
 
 impl DnsClient for Dyndns1Client {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with DynDNS v1", hostname);
 
-        // DynDNS v1 update endpoint
-        let url = format!("{}/nic/update", self.server);
-
-        let auth = format!("{}:{}", self.username, self.password);
-        let encoded_auth = format!("Basic {}", general_purpose::STANDARD.encode(auth.as_bytes()));
-
-        let mut request = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", &encoded_auth)
-            .with_param("hostname", hostname)
-            .with_param("myip", ip.to_string());
-
-        // Add system parameter for static IPs (DynDNS v1 specific)
-        if self.static_ip {
-            request = request.with_param("system", "statdns");
-        } else {
-            request = request.with_param("system", "dyndns");
-        }
-
-        let response = request.send()?;
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
+            .send()?;
 
         let status_code = response.status_code;
         let body = response.as_str()?.trim();
@@ -106,4 +103,34 @@ impl DnsClient for Dyndns1Client {
     fn provider_name(&self) -> &str {
         "DynDNS v1"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dyndns1_preview_update() {
+        let config = Config {
+            protocol: Some("dyndns1".to_string()),
+            login: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            ..Default::default()
+        };
+        let client = Dyndns1Client::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://members.dyndns.org/nic/update?hostname=home.example.com&myip=203.0.113.1&system=dyndns"
+        );
+        // base64("user:pass")
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string())));
+    }
 }
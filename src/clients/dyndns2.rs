@@ -1,6 +1,9 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use crate::utils::mask_credential;
 use std::error::Error;
+use std::fmt;
 use std::net::IpAddr;
 use base64::{Engine as _, engine::general_purpose};
 
@@ -11,12 +14,26 @@ use base64::{Engine as _, engine::general_purpose};
 /// - DNSdynamic
 /// - DuckDNS
 /// - Many others
-#[derive(Debug)]
 pub struct DynDns2Client {
     server: String,
     username: String,
     password: String,
     script: String,
+    /// Send `offline=YES` so the provider parks the host
+    offline: bool,
+    http: http::HttpClient,
+}
+
+impl fmt::Debug for DynDns2Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DynDns2Client")
+            .field("server", &self.server)
+            .field("username", &self.username)
+            .field("password", &mask_credential(&self.password))
+            .field("script", &self.script)
+            .field("offline", &self.offline)
+            .finish()
+    }
 }
 
 impl DynDns2Client {
@@ -34,42 +51,41 @@ impl DynDns2Client {
             .unwrap_or_else(|| "https://members.dyndns.org".to_string());
         let script = "/nic/update".to_string();
 
-        Ok(DynDns2Client {
+        let mut client = DynDns2Client {
             server,
             username,
             password,
             script,
-        })
+            offline: false,
+            http: http::HttpClientBuilder::new(config).build(),
+        };
+        client.set_offline_mode(config.offline);
+        Ok(client)
     }
-}
-
-impl DnsClient for DynDns2Client {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
-            "{}{}?hostname={}&myip={}",
-            self.server, self.script, hostname, ip
-        );
-
-        log::info!("Updating {} with DynDNS2 protocol", hostname);
-
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", 
-                general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password))))
-            .send()?;
 
-        let status_code = response.status_code;
-        let body = response.as_str()?.trim();
-
-        log::debug!("Response status: {}, body: {}", status_code, body);
+    /// Mark hosts offline with each update (or back online)
+    pub fn set_offline_mode(&mut self, offline: bool) {
+        self.offline = offline;
+    }
 
-        if status_code != 200 {
-            return Err(format!("HTTP error: {}", status_code).into());
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        let mut url = format!("{}{}?hostname={}&myip={}", self.server, self.script, hostname, ip);
+        if self.offline {
+            url.push_str("&offline=YES");
         }
+        url
+    }
+
+    fn authorization(&self) -> String {
+        format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password)))
+    }
 
+    /// Map the DynDNS2 status word at the start of a response body to a result
+    fn check_response(hostname: &str, ip: IpAddr, body: &str) -> Result<(), Box<dyn Error>> {
         // Parse DynDNS2 response
         // Format: "status [ip]" where status is one of:
         // - good: Update successful
+        // - offline: Host marked offline
         // - nochg: No change needed (IP is the same)
         // - badauth: Bad authorization (username/password)
         // - notfqdn: Not a fully-qualified domain name
@@ -85,6 +101,10 @@ impl DnsClient for DynDns2Client {
                 log::info!("DNS record for {} successfully updated to {}", hostname, ip);
                 Ok(())
             }
+            "offline" => {
+                log::info!("{} marked offline", hostname);
+                Ok(())
+            }
             "nochg" => {
                 log::info!("DNS record for {} already set to {} (no change)", hostname, ip);
                 Ok(())
@@ -100,6 +120,29 @@ impl DnsClient for DynDns2Client {
             _ => Err(format!("Unknown response: {}", body).into()),
         }
     }
+}
+
+impl DnsClient for DynDns2Client {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let url = self.update_url(hostname, ip);
+
+        log::info!("Updating {} with DynDNS2 protocol", hostname);
+
+        let response = self.http.prepare(minreq::get(&url))
+            .with_header("Authorization", self.authorization())
+            .send()?;
+
+        let status_code = response.status_code;
+        let body = response.as_str()?.trim();
+
+        log::debug!("Response status: {}, body: {}", status_code, body);
+
+        if status_code != 200 {
+            return Err(format!("HTTP error: {}", status_code).into());
+        }
+
+        Self::check_response(hostname, ip, body)
+    }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
         if self.username.is_empty() {
@@ -114,6 +157,11 @@ impl DnsClient for DynDns2Client {
     fn provider_name(&self) -> &str {
         "DynDNS2"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.authorization())])
+    }
 }
 
 #[cfg(test)]
@@ -202,6 +250,8 @@ mod tests {
             username: String::new(),
             password: String::new(),
             script: "/update".to_string(),
+            offline: false,
+            http: http::HttpClientBuilder::new(&Config::default()).build(),
         };
         
         let result = client.validate_config();
@@ -232,6 +282,26 @@ mod tests {
         assert_eq!(expected_url, "https://members.dyndns.org/nic/update?hostname=ddns.example.com&myip=203.0.113.1");
     }
 
+    #[test]
+    fn test_dyndns2_offline_url() {
+        let mut client = DynDns2Client::new(&create_test_config()).unwrap();
+        let ip = "203.0.113.1".parse().unwrap();
+        client.set_offline_mode(true);
+        assert_eq!(client.update_url("ddns.example.com", ip),
+            "https://members.dyndns.org/nic/update?hostname=ddns.example.com&myip=203.0.113.1&offline=YES");
+
+        let config = Config { offline: true, ..create_test_config() };
+        assert!(DynDns2Client::new(&config).unwrap().update_url("ddns.example.com", ip).ends_with("&offline=YES"));
+    }
+
+    #[test]
+    fn test_dyndns2_offline_response() {
+        let ip = "203.0.113.1".parse().unwrap();
+        assert!(DynDns2Client::check_response("ddns.example.com", ip, "offline").is_ok());
+        assert!(DynDns2Client::check_response("ddns.example.com", ip, "good 203.0.113.1").is_ok());
+        assert!(DynDns2Client::check_response("ddns.example.com", ip, "badauth").is_err());
+    }
+
     #[test]
     fn test_dyndns2_auth_header() {
         let config = create_test_config();
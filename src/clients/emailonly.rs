@@ -1,16 +1,30 @@
 use crate::clients::DnsClient;
 use crate::config::Config;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
 use std::error::Error;
 use std::net::IpAddr;
 use std::process::{Command, Stdio};
 use std::io::Write;
+use std::time::Duration;
+
+/// SMTP submission port used when `smtp_port=` isn't set
+const DEFAULT_SMTP_PORT: u16 = 587;
 
 /// Email-only notification client
 /// Does NOT update any DNS records, only sends email notifications when IP changes
-/// Requires system sendmail to be configured
+/// Sends through the `smtp_server=` relay (STARTTLS) when set, otherwise via system sendmail
 pub struct EmailonlyClient {
     email: String,
     hostname: String,
+    smtp_server: Option<String>,
+    smtp_port: u16,
+    smtp_username: Option<String>,
+    smtp_password: Option<String>,
+    /// Require STARTTLS on the SMTP connection
+    smtp_starttls: bool,
+    timeout_secs: u64,
 }
 
 impl EmailonlyClient {
@@ -27,27 +41,79 @@ impl EmailonlyClient {
         Ok(EmailonlyClient {
             email,
             hostname,
+            smtp_server: config.smtp_server.clone(),
+            smtp_port: config.smtp_port.unwrap_or(DEFAULT_SMTP_PORT),
+            smtp_username: config.smtp_username.clone(),
+            smtp_password: config.smtp_password.clone(),
+            smtp_starttls: true,
+            timeout_secs: config.timeout_secs,
         })
     }
 
-    fn send_email(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        // Construct email body
-        let body = format!(
+    fn subject(&self) -> String {
+        format!("status report from {}@{}", env!("CARGO_PKG_NAME"), self.hostname)
+    }
+
+    fn body(&self, hostname: &str, ip: IpAddr) -> String {
+        format!(
             "Host IP addresses:\n{:>30}  {}\n\n-- \n   {}@{} (version {})",
             hostname,
             ip,
             env!("CARGO_PKG_NAME"),
             self.hostname,
             env!("CARGO_PKG_VERSION")
-        );
+        )
+    }
+
+    fn send_email(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        match &self.smtp_server {
+            Some(server) => self.send_smtp(server, hostname, ip),
+            None => self.send_sendmail(hostname, ip),
+        }
+    }
+
+    /// Send through the configured SMTP relay
+    fn send_smtp(&self, server: &str, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let to: Mailbox = self.email.parse()
+            .map_err(|e| format!("Invalid email address {}: {}", self.email, e))?;
+        // Send from the SMTP login when it is an address, otherwise from the recipient
+        let from = self.smtp_username.as_deref()
+            .and_then(|user| user.parse::<Mailbox>().ok())
+            .unwrap_or_else(|| to.clone());
 
+        let message = Message::builder()
+            .from(from)
+            .to(to)
+            .subject(self.subject())
+            .body(self.body(hostname, ip))?;
+
+        let mut builder = if self.smtp_starttls {
+            SmtpTransport::starttls_relay(server)?
+        } else {
+            SmtpTransport::builder_dangerous(server)
+        };
+        builder = builder
+            .port(self.smtp_port)
+            .timeout(Some(Duration::from_secs(self.timeout_secs)));
+        if let (Some(username), Some(password)) = (&self.smtp_username, &self.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        builder.build().send(&message)
+            .map_err(|e| format!("SMTP delivery via {}:{} failed: {}", server, self.smtp_port, e))?;
+
+        log::info!("Email notification sent to {} via {} for host {}", self.email, server, hostname);
+        Ok(())
+    }
+
+    /// Pipe the message to the system sendmail
+    fn send_sendmail(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         // Construct email headers and body
         let email_content = format!(
-            "To: {}\nSubject: status report from {}@{}\n\r\n{}\n",
+            "To: {}\nSubject: {}\n\r\n{}\n",
             self.email,
-            env!("CARGO_PKG_NAME"),
-            self.hostname,
-            body
+            self.subject(),
+            self.body(hostname, ip)
         );
 
         // Spawn sendmail process
@@ -102,6 +168,16 @@ impl DnsClient for EmailonlyClient {
         if self.email.is_empty() {
             return Err("email address is required for emailonly provider".into());
         }
+
+        if let Some(server) = &self.smtp_server {
+            if server.is_empty() {
+                return Err("smtp_server cannot be empty".into());
+            }
+            if self.smtp_username.is_some() != self.smtp_password.is_some() {
+                return Err("smtp_user and smtp_pass must be set together".into());
+            }
+            return Ok(());
+        }
         
         // Check if sendmail is available
         match Command::new("sendmail").arg("-h").output() {
@@ -114,3 +190,116 @@ impl DnsClient for EmailonlyClient {
         "EmailOnly"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Minimal SMTP server that accepts one message and returns the session transcript
+    fn mock_smtp_server() -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut transcript = String::new();
+            let mut in_data = false;
+
+            writer.write_all(b"220 localhost ESMTP mock\r\n").unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                transcript.push_str(&line);
+                let reply: &[u8] = if in_data {
+                    if line == ".\r\n" {
+                        in_data = false;
+                        b"250 queued\r\n"
+                    } else {
+                        b""
+                    }
+                } else if line.starts_with("EHLO") {
+                    b"250-localhost\r\n250 AUTH PLAIN LOGIN\r\n"
+                } else if line.starts_with("AUTH") {
+                    b"235 authenticated\r\n"
+                } else if line.starts_with("DATA") {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line.starts_with("QUIT") {
+                    writer.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).unwrap();
+                line.clear();
+            }
+            transcript
+        });
+
+        (port, handle)
+    }
+
+    fn smtp_client(port: u16, credentials: bool) -> EmailonlyClient {
+        let config = Config {
+            protocol: Some("emailonly".to_string()),
+            email: Some("admin@example.com".to_string()),
+            smtp_server: Some("127.0.0.1".to_string()),
+            smtp_port: Some(port),
+            smtp_username: credentials.then(|| "mailer@example.com".to_string()),
+            smtp_password: credentials.then(|| "secret".to_string()),
+            ..Default::default()
+        };
+        let mut client = EmailonlyClient::new(&config).unwrap();
+        // The mock server speaks plain SMTP
+        client.smtp_starttls = false;
+        client
+    }
+
+    #[test]
+    fn test_emailonly_smtp_delivery() {
+        let (port, server) = mock_smtp_server();
+        let client = smtp_client(port, true);
+
+        client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+
+        let transcript = server.join().unwrap();
+        // base64("\0mailer@example.com\0secret")
+        assert!(transcript.contains("AUTH PLAIN AG1haWxlckBleGFtcGxlLmNvbQBzZWNyZXQ=\r\n"));
+        assert!(transcript.contains("MAIL FROM:<mailer@example.com>"));
+        assert!(transcript.contains("RCPT TO:<admin@example.com>"));
+        assert!(transcript.contains("home.example.com  203.0.113.1"));
+    }
+
+    #[test]
+    fn test_emailonly_smtp_without_credentials() {
+        let (port, server) = mock_smtp_server();
+        let client = smtp_client(port, false);
+
+        client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+
+        let transcript = server.join().unwrap();
+        assert!(!transcript.contains("AUTH"));
+        assert!(transcript.contains("MAIL FROM:<admin@example.com>"));
+        assert!(transcript.contains("2001:db8::1"));
+    }
+
+    #[test]
+    fn test_emailonly_smtp_defaults_and_validation() {
+        let mut config = Config {
+            email: Some("admin@example.com".to_string()),
+            smtp_server: Some("smtp.example.com".to_string()),
+            ..Default::default()
+        };
+        let client = EmailonlyClient::new(&config).unwrap();
+        assert_eq!(client.smtp_port, 587);
+        assert!(client.smtp_starttls);
+        assert!(client.validate_config().is_ok());
+
+        config.smtp_username = Some("mailer".to_string());
+        assert!(EmailonlyClient::new(&config).unwrap().validate_config().is_err());
+    }
+}
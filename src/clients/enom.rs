@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -8,6 +9,7 @@ use std::net::IpAddr;
 pub struct EnomClient {
     server: String,
     password: String,
+    http: http::HttpClient,
 }
 
 impl EnomClient {
@@ -23,25 +25,27 @@ impl EnomClient {
         Ok(EnomClient {
             server,
             password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
 
-impl DnsClient for EnomClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!(
             "{}/interface.asp?command=SetDNSHost&HostName={}&Zone={}&Address={}&DomainPassword={}",
             self.server,
             hostname.split('.').next().unwrap_or(""),
             hostname.split('.').skip(1).collect::<Vec<_>>().join("."),
             ip,
             self.password
-        );
+        )
+    }
+}
 
+impl DnsClient for EnomClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with Enom", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
             .send()?;
 
         let status_code = response.status_code;
@@ -74,4 +78,31 @@ impl DnsClient for EnomClient {
     fn provider_name(&self) -> &str {
         "Enom"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip)).with_secret(&self.password)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enom_preview_update() {
+        let config = Config {
+            protocol: Some("enom".to_string()),
+            password: Some("enom-domain-password".to_string()),
+            ..Default::default()
+        };
+        let client = EnomClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://dynamic.name-services.com/interface.asp?command=SetDNSHost&HostName=home&Zone=example.com&Address=203.0.113.1&DomainPassword=enom-domain-password"
+        );
+        assert!(!requests[0].to_string().contains("enom-domain-password"));
+    }
 }
@@ -1,18 +1,35 @@
-use crate::clients::DnsClient;
+use crate::clients::afraid;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use std::collections::HashMap;
 use std::error::Error;
 use std::net::IpAddr;
+use std::sync::Mutex;
+
+/// How the Freedns credentials are used (`freedns_auth=`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FreednsAuth {
+    /// `password=` is the per-host update token (the part after `update.php?`)
+    Token,
+    /// `login=`/`password=` are account credentials; each host's update URL is looked up
+    /// via the afraid.org v2 API and cached for the life of the client
+    Account,
+}
 
 /// Freedns (afraid.org) client - https://freedns.afraid.org/
 pub struct FreednsClient {
+    auth: FreednsAuth,
+    login: Option<String>,
     token: String,
     server: String,
+    update_url_cache: Mutex<HashMap<(String, bool), String>>,
+    http: http::HttpClient,
 }
 
 impl FreednsClient {
     pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
         let token = config.password.as_ref()
-            .or(config.password.as_ref())
             .ok_or("token (password or api_token) is required for Freedns")?
             .clone();
         
@@ -20,19 +37,58 @@ impl FreednsClient {
             .cloned()
             .unwrap_or_else(|| "https://freedns.afraid.org/dynamic".to_string());
 
-        Ok(FreednsClient { token, server })
+        let auth = match config.freedns_auth.as_deref() {
+            None | Some("token") => FreednsAuth::Token,
+            Some("account") => FreednsAuth::Account,
+            Some(other) => return Err(format!("Invalid freedns_auth '{}': expected token or account", other).into()),
+        };
+
+        Ok(FreednsClient {
+            auth,
+            login: config.login.clone(),
+            token,
+            server,
+            update_url_cache: Mutex::new(HashMap::new()),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
+    }
+
+    /// Base URL of the account API (the server without its `/dynamic` path)
+    fn api_base(&self) -> &str {
+        self.server.strip_suffix("/dynamic").unwrap_or(&self.server)
+    }
+
+    /// Update URL built from the token
+    fn token_url(&self) -> String {
+        format!("{}/update.php?{}", self.server, self.token)
+    }
+
+    /// Update URL for `hostname`: built from the token, or looked up once per record type
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> Result<String, Box<dyn Error>> {
+        if self.auth == FreednsAuth::Token {
+            return Ok(self.token_url());
+        }
+
+        let key = (hostname.to_string(), ip.is_ipv6());
+        if let Some(url) = self.update_url_cache.lock().unwrap().get(&key) {
+            return Ok(url.clone());
+        }
+
+        let login = self.login.as_deref().unwrap_or_default();
+        let records = afraid::get_record_list(&self.http, self.api_base(), login, &self.token)?;
+        let (_, _, url) = afraid::find_record(&records, hostname, ip)?;
+        self.update_url_cache.lock().unwrap().insert(key, url.clone());
+        Ok(url.clone())
     }
 }
 
 impl DnsClient for FreednsClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        // Freedns uses a unique token per host
-        let url = format!("{}/update.php?{}&address={}", self.server, self.token, ip);
+        let url = afraid::address_url(&self.update_url(hostname, ip)?, ip);
 
         log::info!("Updating {} with Freedns", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(&url))
             .send()?;
 
         let body = response.as_str()?;
@@ -51,10 +107,148 @@ impl DnsClient for FreednsClient {
         if self.token.is_empty() {
             return Err("token is required for Freedns".into());
         }
+        if self.auth == FreednsAuth::Account && self.login.as_deref().is_none_or(str::is_empty) {
+            return Err("login is required for Freedns with freedns_auth=account".into());
+        }
         Ok(())
     }
 
     fn provider_name(&self) -> &str {
         "Freedns"
     }
+
+    fn preview_update(&self, _hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        if self.auth == FreednsAuth::Token {
+            return Ok(vec![self.http.preview("GET", afraid::address_url(&self.token_url(), ip)).with_secret(&self.token)]);
+        }
+
+        let hash = afraid::credentials_hash(self.login.as_deref().unwrap_or_default(), &self.token);
+        Ok(vec![
+            self.http.preview("GET", afraid::record_list_url(self.api_base(), &hash)).with_secret(&hash),
+            self.http.preview("GET", afraid::address_url("{update_url}", ip)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+    use sha1::{Digest, Sha1};
+
+    fn test_config(server: &str, login: Option<&str>, password: &str, auth: Option<&str>) -> Config {
+        Config {
+            protocol: Some("freedns".to_string()),
+            login: login.map(str::to_string),
+            password: Some(password.to_string()),
+            server: Some(server.to_string()),
+            freedns_auth: auth.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn test_client(server: &str, login: Option<&str>, password: &str, auth: Option<&str>) -> FreednsClient {
+        FreednsClient::new(&test_config(server, login, password, auth)).unwrap()
+    }
+
+    #[test]
+    fn test_freedns_direct_url() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/dynamic/update.php")
+            .match_query(Matcher::Exact("abc123&address=203.0.113.1".to_string()))
+            .with_body("Updated home.example.com to 203.0.113.1")
+            .create();
+
+        let client = test_client(&format!("{}/dynamic", server.url()), None, "abc123", None);
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_freedns_login_without_account_mode_uses_token() {
+        let mut server = mockito::Server::new();
+        let list = server.mock("GET", "/api/").match_query(Matcher::Any).expect(0).create();
+        let update = server.mock("GET", "/dynamic/update.php")
+            .match_query(Matcher::Exact("abc123&address=203.0.113.1".to_string()))
+            .with_body("Updated home.example.com to 203.0.113.1")
+            .create();
+
+        let client = test_client(&format!("{}/dynamic", server.url()), Some("user"), "abc123", None);
+        assert!(client.validate_config().is_ok());
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        list.assert();
+        update.assert();
+    }
+
+    #[test]
+    fn test_freedns_account_then_update() {
+        let mut server = mockito::Server::new();
+        // sha1("user|pass")
+        let sha = hex::encode(Sha1::digest(b"user|pass"));
+        let list = server.mock("GET", "/api/")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("action".into(), "getdyndns".into()),
+                Matcher::UrlEncoded("v".into(), "2".into()),
+                Matcher::UrlEncoded("sha".into(), sha),
+            ]))
+            .with_body(format!(
+                "other.example.com|192.0.2.1|{0}/dynamic/update.php?other\nhome.example.com|2001:db8::1|{0}/dynamic/update.php?home6\nhome.example.com|192.0.2.2|{0}/dynamic/update.php?hometoken\n",
+                server.url()
+            ))
+            .expect(1)
+            .create();
+        let update = server.mock("GET", "/dynamic/update.php")
+            .match_query(Matcher::Exact("hometoken&address=203.0.113.1".to_string()))
+            .with_body("Updated home.example.com to 203.0.113.1")
+            .expect(2)
+            .create();
+
+        let client = test_client(&format!("{}/dynamic", server.url()), Some("user"), "pass", Some("account"));
+        let ip = "203.0.113.1".parse().unwrap();
+        assert!(client.update_record("home.example.com", ip).is_ok());
+        // Second update reuses the cached URL
+        assert!(client.update_record("home.example.com", ip).is_ok());
+        list.assert();
+        update.assert();
+    }
+
+    #[test]
+    fn test_freedns_account_unknown_host() {
+        let mut server = mockito::Server::new();
+        let _list = server.mock("GET", "/api/")
+            .match_query(Matcher::Any)
+            .with_body("other.example.com|192.0.2.1|https://freedns.afraid.org/dynamic/update.php?other\n")
+            .create();
+
+        let client = test_client(&server.url(), Some("user"), "pass", Some("account"));
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "No matching A record found for home.example.com");
+    }
+
+    #[test]
+    fn test_freedns_auth_option() {
+        let client = test_client("https://freedns.afraid.org/dynamic", None, "pass", Some("account"));
+        assert!(client.validate_config().is_err());
+
+        let err = FreednsClient::new(&test_config("https://freedns.afraid.org/dynamic", None, "pass", Some("sha1")))
+            .err().unwrap();
+        assert!(err.to_string().contains("Invalid freedns_auth"));
+    }
+
+    #[test]
+    fn test_freedns_preview_update() {
+        let ip = "203.0.113.1".parse().unwrap();
+        let client = test_client("https://freedns.afraid.org/dynamic", None, "abc123456789", None);
+        let requests = client.preview_update("home.example.com", ip).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://freedns.afraid.org/dynamic/update.php?abc123456789&address=203.0.113.1");
+        assert!(!requests[0].to_string().contains("abc123456789"));
+
+        let client = test_client("https://freedns.afraid.org/dynamic", Some("user"), "pass", Some("account"));
+        let requests = client.preview_update("home.example.com", ip).unwrap();
+        let sha = hex::encode(Sha1::digest(b"user|pass"));
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, format!("https://freedns.afraid.org/api/?action=getdyndns&v=2&sha={}", sha));
+        assert_eq!(requests[1].url, "{update_url}?address=203.0.113.1");
+    }
 }
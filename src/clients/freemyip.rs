@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -8,6 +9,7 @@ use std::net::IpAddr;
 pub struct FreemyipClient {
     server: String,
     token: String,
+    http: http::HttpClient,
 }
 
 impl FreemyipClient {
@@ -23,21 +25,32 @@ impl FreemyipClient {
         Ok(FreemyipClient {
             server,
             token,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// Update URL; `myip` carries the IPv4 or IPv6 address
+    fn build_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/update?token={}&domain={}&myip={}", self.server, self.token, hostname, ip)
+    }
+
+    /// Freemyip answers "OK" on success; anything else is an error message
+    fn parse_response(body: &str) -> Result<(), Box<dyn Error>> {
+        match body.trim() {
+            "OK" => Ok(()),
+            "" => Err("Freemyip returned an empty response".into()),
+            other => Err(format!("Freemyip error: {}", other).into()),
+        }
+    }
 }
 
 impl DnsClient for FreemyipClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
-            "{}/update?token={}&domain={}",
-            self.server, self.token, hostname
-        );
+        let url = self.build_url(hostname, ip);
 
         log::info!("Updating {} with Freemyip", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(&url))
             .send()?;
 
         let status_code = response.status_code;
@@ -49,15 +62,9 @@ impl DnsClient for FreemyipClient {
             return Err(format!("HTTP error: {}", status_code).into());
         }
 
-        // Parse Freemyip response
-        if body.contains("SUCCESS") || body.contains("UPDATED") || body == "OK" {
-            log::info!("Successfully updated {} to {}", hostname, ip);
-            Ok(())
-        } else if body.contains("ERROR") {
-            Err(format!("Freemyip error: {}", body).into())
-        } else {
-            Err(format!("Unexpected response: {}", body).into())
-        }
+        Self::parse_response(body)?;
+        log::info!("Successfully updated {} to {}", hostname, ip);
+        Ok(())
     }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
@@ -70,4 +77,67 @@ impl DnsClient for FreemyipClient {
     fn provider_name(&self) -> &str {
         "Freemyip"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.build_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client(token: &str) -> FreemyipClient {
+        let config = Config {
+            protocol: Some("freemyip".to_string()),
+            password: Some(token.to_string()),
+            ..Default::default()
+        };
+        FreemyipClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_freemyip_url_ipv4() {
+        let client = test_client("abc123");
+        assert_eq!(
+            client.build_url("home.freemyip.com", "203.0.113.1".parse().unwrap()),
+            "https://freemyip.com/update?token=abc123&domain=home.freemyip.com&myip=203.0.113.1"
+        );
+    }
+
+    #[test]
+    fn test_freemyip_url_ipv6() {
+        let client = test_client("abc123");
+        assert_eq!(
+            client.build_url("home.freemyip.com", "2001:db8::1".parse().unwrap()),
+            "https://freemyip.com/update?token=abc123&domain=home.freemyip.com&myip=2001:db8::1"
+        );
+    }
+
+    #[test]
+    fn test_freemyip_missing_token() {
+        assert!(test_client("").validate_config().is_err());
+        assert!(test_client("abc123").validate_config().is_ok());
+        assert!(FreemyipClient::new(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_freemyip_parse_response() {
+        assert!(FreemyipClient::parse_response("OK\n").is_ok());
+        assert_eq!(
+            FreemyipClient::parse_response("ERROR: invalid token").unwrap_err().to_string(),
+            "Freemyip error: ERROR: invalid token"
+        );
+        assert!(FreemyipClient::parse_response("UPDATED").is_err());
+        assert!(FreemyipClient::parse_response("").is_err());
+    }
+
+    #[test]
+    fn test_freemyip_preview_update() {
+        let client = test_client("freemyip-token");
+        let requests = client.preview_update("home.freemyip.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://freemyip.com/update?token=freemyip-token&domain=home.freemyip.com&myip=203.0.113.1");
+        assert!(!requests[0].to_string().contains("freemyip-token"));
+    }
 }
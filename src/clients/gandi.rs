@@ -1,19 +1,22 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 use serde_json::json;
 
-/// Gandi client - https://api.gandi.net/docs/livedns/
+/// Gandi LiveDNS v5 client - https://api.gandi.net/docs/livedns/
 pub struct GandiClient {
     api_key: String,
     server: String,
+    zone: Option<String>,
+    ttl: u32,
+    http: http::HttpClient,
 }
 
 impl GandiClient {
     pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
         let api_key = config.password.as_ref()
-            .or(config.password.as_ref())
             .ok_or("api_token or password is required for Gandi")?
             .clone();
         
@@ -21,10 +24,25 @@ impl GandiClient {
             .cloned()
             .unwrap_or_else(|| "https://api.gandi.net".to_string());
 
-        Ok(GandiClient { api_key, server })
+        Ok(GandiClient {
+            api_key,
+            server,
+            zone: config.zone.clone(),
+            ttl: config.ttl.unwrap_or(300),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
     }
 
+    /// Split a hostname into (record name, zone), using the configured zone when set
     fn parse_hostname(&self, hostname: &str) -> (String, String) {
+        if let Some(zone) = &self.zone {
+            let name = if hostname == zone {
+                "@"
+            } else {
+                hostname.strip_suffix(&format!(".{}", zone)).unwrap_or(hostname)
+            };
+            return (name.to_string(), zone.clone());
+        }
         let parts: Vec<&str> = hostname.rsplitn(3, '.').collect();
         if parts.len() >= 3 {
             let domain = format!("{}.{}", parts[1], parts[0]);
@@ -34,38 +52,60 @@ impl GandiClient {
             ("@".to_string(), hostname.to_string())
         }
     }
-}
 
-impl DnsClient for GandiClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+    fn record_url(&self, hostname: &str, record_type: &str) -> String {
         let (name, domain) = self.parse_hostname(hostname);
-        
-        let url = format!(
-            "{}/v5/livedns/domains/{}/records/{}/A",
-            self.server, domain, name
-        );
+        format!("{}/v5/livedns/domains/{}/records/{}/{}", self.server, domain, name, record_type)
+    }
 
-        let body = json!({
+    fn record_body(&self, ip: IpAddr) -> serde_json::Value {
+        json!({
             "rrset_values": [ip.to_string()],
-            "rrset_ttl": 300
-        });
+            "rrset_ttl": self.ttl
+        })
+    }
 
-        log::info!("Updating {} with Gandi", hostname);
+    fn auth_header(&self) -> String {
+        format!("Apikey {}", self.api_key)
+    }
 
-        let response = minreq::put(&url)
-            .with_header("Authorization", format!("Apikey {}", self.api_key))
-            .with_header("Content-Type", "application/json")
-            .with_json(&body)?
-            .send()?;
+    fn send(&self, request: minreq::Request, body: &serde_json::Value) -> Result<minreq::Response, Box<dyn Error>> {
+        Ok(self.http.prepare(request)
+            .with_header("Authorization", self.auth_header())
+            .with_json(body)?
+            .send()?)
+    }
+}
 
-        let status_code = response.status_code;
+impl DnsClient for GandiClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
 
-        if status_code == 200 || status_code == 201 {
-            log::info!("DNS record for {} successfully updated to {}", hostname, ip);
-            Ok(())
-        } else {
-            let body = response.as_str().unwrap_or("unknown error");
-            Err(format!("Gandi API error ({}): {}", status_code, body).into())
+        let url = self.record_url(hostname, record_type);
+        let body = self.record_body(ip);
+
+        log::info!("Updating {} {} record with Gandi", hostname, record_type);
+
+        let mut response = self.send(minreq::patch(url.as_str()), &body)?;
+        if response.status_code == 404 {
+            // PATCH only changes existing records; PUT creates the rrset
+            log::info!("No {} record for {}, creating it", record_type, hostname);
+            response = self.send(minreq::put(url.as_str()), &body)?;
+        }
+
+        match response.status_code {
+            200 | 201 => {
+                log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+                Ok(())
+            }
+            401 | 403 => Err(format!("Gandi rejected the API key (HTTP {})", response.status_code).into()),
+            status_code => {
+                let body = response.as_str().unwrap_or("unknown error");
+                Err(format!("Gandi API error ({}): {}", status_code, body).into())
+            }
         }
     }
 
@@ -79,4 +119,139 @@ impl DnsClient for GandiClient {
     fn provider_name(&self) -> &str {
         "Gandi"
     }
+
+    /// The PATCH; a PUT with the same body follows only when the record doesn't exist yet
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+        Ok(vec![self.http.preview("PATCH", self.record_url(hostname, record_type))
+            .with_header("Authorization", self.auth_header())
+            .with_header("Content-Type", "application/json")
+            .with_body(self.record_body(ip).to_string())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_client(server: Option<String>) -> GandiClient {
+        let config = Config {
+            protocol: Some("gandi".to_string()),
+            password: Some("gandi-key".to_string()),
+            server,
+            ..Default::default()
+        };
+        GandiClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_gandi_record_url() {
+        let client = test_client(None);
+        assert_eq!(
+            client.record_url("home.example.com", "A"),
+            "https://api.gandi.net/v5/livedns/domains/example.com/records/home/A"
+        );
+        assert_eq!(
+            client.record_url("example.com", "AAAA"),
+            "https://api.gandi.net/v5/livedns/domains/example.com/records/@/AAAA"
+        );
+
+        let config = Config {
+            password: Some("gandi-key".to_string()),
+            zone: Some("example.co.uk".to_string()),
+            ..Default::default()
+        };
+        let client = GandiClient::new(&config).unwrap();
+        assert_eq!(
+            client.record_url("a.b.example.co.uk", "A"),
+            "https://api.gandi.net/v5/livedns/domains/example.co.uk/records/a.b/A"
+        );
+    }
+
+    #[test]
+    fn test_gandi_record_body() {
+        let client = test_client(None);
+        assert_eq!(
+            client.record_body("203.0.113.1".parse().unwrap()),
+            json!({"rrset_values": ["203.0.113.1"], "rrset_ttl": 300})
+        );
+        assert_eq!(
+            client.record_body("2001:db8::1".parse().unwrap()),
+            json!({"rrset_values": ["2001:db8::1"], "rrset_ttl": 300})
+        );
+    }
+
+    #[test]
+    fn test_gandi_patch_existing_record() {
+        let mut server = mockito::Server::new();
+        let patch = server.mock("PATCH", "/v5/livedns/domains/example.com/records/home/AAAA")
+            .match_header("authorization", "Apikey gandi-key")
+            .match_body(Matcher::Json(json!({"rrset_values": ["2001:db8::1"], "rrset_ttl": 300})))
+            .with_status(201)
+            .with_body(r#"{"message":"DNS Record Created"}"#)
+            .create();
+
+        let client = test_client(Some(server.url()));
+        assert!(client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).is_ok());
+        patch.assert();
+    }
+
+    #[test]
+    fn test_gandi_creates_missing_record() {
+        let mut server = mockito::Server::new();
+        let patch = server.mock("PATCH", "/v5/livedns/domains/example.com/records/home/A")
+            .with_status(404)
+            .with_body(r#"{"code":404,"message":"Can't find the DNS record","object":"HTTPNotFound","cause":"Not Found"}"#)
+            .create();
+        let put = server.mock("PUT", "/v5/livedns/domains/example.com/records/home/A")
+            .match_body(Matcher::Json(json!({"rrset_values": ["203.0.113.1"], "rrset_ttl": 300})))
+            .with_status(201)
+            .create();
+
+        let client = test_client(Some(server.url()));
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        patch.assert();
+        put.assert();
+    }
+
+    #[test]
+    fn test_gandi_errors() {
+        let mut server = mockito::Server::new();
+        let _patch = server.mock("PATCH", "/v5/livedns/domains/example.com/records/home/A")
+            .with_status(401)
+            .create();
+        let put = server.mock("PUT", "/v5/livedns/domains/example.com/records/home/A").expect(0).create();
+
+        let client = test_client(Some(server.url()));
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "Gandi rejected the API key (HTTP 401)");
+        put.assert();
+
+        // A 404 from the create request (e.g. unknown domain) is reported as is
+        let mut server = mockito::Server::new();
+        let _patch = server.mock("PATCH", "/v5/livedns/domains/example.com/records/home/A").with_status(404).create();
+        let _put = server.mock("PUT", "/v5/livedns/domains/example.com/records/home/A")
+            .with_status(404)
+            .with_body("domain not found")
+            .create();
+
+        let client = test_client(Some(server.url()));
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "Gandi API error (404): domain not found");
+    }
+
+    #[test]
+    fn test_gandi_preview_update() {
+        let client = test_client(None);
+        let requests = client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "PATCH");
+        assert_eq!(requests[0].url, "https://api.gandi.net/v5/livedns/domains/example.com/records/home/AAAA");
+        assert_eq!(requests[0].body.as_deref(), Some(r#"{"rrset_ttl":300,"rrset_values":["2001:db8::1"]}"#));
+        assert!(!requests[0].to_string().contains("gandi-key"));
+    }
 }
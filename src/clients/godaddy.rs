@@ -1,14 +1,21 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 use serde_json::json;
 
+const PRODUCTION_SERVER: &str = "https://api.godaddy.com";
+/// GoDaddy's OTE (Operational Test Environment) sandbox
+const OTE_SERVER: &str = "https://api.ote-godaddy.com";
+
 /// GoDaddy client - https://developer.godaddy.com/doc/endpoint/domains
 pub struct GoDaddyClient {
     api_key: String,
     api_secret: String,
     server: String,
+    ote: bool,
+    http: http::HttpClient,
 }
 
 impl GoDaddyClient {
@@ -20,17 +27,32 @@ impl GoDaddyClient {
             .ok_or("password (API secret) is required for GoDaddy")?
             .clone();
         
-        let server = config.server.as_ref()
-            .cloned()
-            .unwrap_or_else(|| "https://api.godaddy.com".to_string());
+        // `server=ote` is a shorthand for the sandbox, like --provider-test
+        let ote = config.provider_test
+            || config.server.as_deref().is_some_and(|server| server.eq_ignore_ascii_case("ote"));
+        let server = Self::resolve_server(config.server.as_deref().unwrap_or(PRODUCTION_SERVER), ote);
+        if ote {
+            log::info!("Using GoDaddy OTE sandbox at {}", server);
+        }
 
         Ok(GoDaddyClient {
             api_key,
             api_secret,
             server,
+            ote,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
 
+    /// API base URL: the OTE sandbox when `ote` is set, otherwise `server`
+    fn resolve_server(server: &str, ote: bool) -> String {
+        if ote {
+            OTE_SERVER.to_string()
+        } else {
+            server.to_string()
+        }
+    }
+
     fn parse_hostname(&self, hostname: &str) -> (String, String) {
         // Split hostname into domain and record name
         // e.g., "www.example.com" -> ("www", "example.com")
@@ -44,34 +66,37 @@ impl GoDaddyClient {
             ("@".to_string(), hostname.to_string())
         }
     }
-}
 
-impl DnsClient for GoDaddyClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+    fn record_url(&self, hostname: &str, ip: IpAddr) -> String {
         let (name, domain) = self.parse_hostname(hostname);
-        
         // Determine record type based on IP version
         let record_type = match ip {
             IpAddr::V4(_) => "A",
             IpAddr::V6(_) => "AAAA",
         };
-        
-        let url = format!(
-            "{}/v1/domains/{}/records/{}/{}",
-            self.server, domain, record_type, name
-        );
+        format!("{}/v1/domains/{}/records/{}/{}", self.server, domain, record_type, name)
+    }
 
-        let body = json!([{
+    fn record_body(ip: IpAddr) -> serde_json::Value {
+        json!([{
             "data": ip.to_string(),
             "ttl": 600
-        }]);
+        }])
+    }
 
+    fn auth_header(&self) -> String {
+        format!("sso-key {}:{}", self.api_key, self.api_secret)
+    }
+}
+
+impl DnsClient for GoDaddyClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with GoDaddy", hostname);
 
-        let response = minreq::put(&url)
-            .with_header("Authorization", format!("sso-key {}:{}", self.api_key, self.api_secret))
+        let response = self.http.prepare(minreq::put(self.record_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .with_header("Content-Type", "application/json")
-            .with_json(&body)?
+            .with_json(&Self::record_body(ip))?
             .send()?;
 
         let status_code = response.status_code;
@@ -96,6 +121,68 @@ impl DnsClient for GoDaddyClient {
     }
 
     fn provider_name(&self) -> &str {
-        "GoDaddy"
+        if self.ote {
+            "GoDaddy (OTE)"
+        } else {
+            "GoDaddy"
+        }
+    }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("PUT", self.record_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())
+            .with_header("Content-Type", "application/json")
+            .with_body(Self::record_body(ip).to_string())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(server: Option<&str>) -> Config {
+        Config {
+            protocol: Some("godaddy".to_string()),
+            login: Some("key".to_string()),
+            password: Some("secret".to_string()),
+            server: server.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_server() {
+        assert_eq!(GoDaddyClient::resolve_server(PRODUCTION_SERVER, true), "https://api.ote-godaddy.com");
+        assert_eq!(GoDaddyClient::resolve_server(PRODUCTION_SERVER, false), "https://api.godaddy.com");
+    }
+
+    #[test]
+    fn test_godaddy_server_selection() {
+        let client = GoDaddyClient::new(&test_config(None)).unwrap();
+        assert!(!client.ote);
+        assert_eq!(client.server, PRODUCTION_SERVER);
+
+        let client = GoDaddyClient::new(&test_config(Some("ote"))).unwrap();
+        assert!(client.ote);
+        assert_eq!(client.server, OTE_SERVER);
+        assert_eq!(client.provider_name(), "GoDaddy (OTE)");
+
+        let mut config = test_config(None);
+        config.provider_test = true;
+        assert_eq!(GoDaddyClient::new(&config).unwrap().server, OTE_SERVER);
+
+        let client = GoDaddyClient::new(&test_config(Some("http://localhost:8080"))).unwrap();
+        assert_eq!(client.server, "http://localhost:8080");
+    }
+
+    #[test]
+    fn test_godaddy_preview_update() {
+        let client = GoDaddyClient::new(&test_config(None)).unwrap();
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].url, "https://api.godaddy.com/v1/domains/example.com/records/A/home");
+        assert_eq!(requests[0].body.as_deref(), Some(r#"[{"data":"203.0.113.1","ttl":600}]"#));
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "sso-key key:secret".to_string())));
     }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 use base64::{Engine as _, engine::general_purpose};
@@ -10,6 +11,7 @@ pub struct GoogleDomainsClient {
     server: String,
     username: String,
     password: String,
+    http: http::HttpClient,
 }
 
 impl GoogleDomainsClient {
@@ -29,23 +31,25 @@ impl GoogleDomainsClient {
             server,
             username,
             password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/nic/update?hostname={}&myip={}", self.server, hostname, ip)
+    }
+
+    fn auth_header(&self) -> String {
+        format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password)))
+    }
 }
 
 impl DnsClient for GoogleDomainsClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
-            "{}/nic/update?hostname={}&myip={}",
-            self.server, hostname, ip
-        );
-
         log::info!("Updating {} with Google Domains", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", 
-                general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password))))
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         let status_code = response.status_code;
@@ -89,4 +93,31 @@ impl DnsClient for GoogleDomainsClient {
     fn provider_name(&self) -> &str {
         "Google Domains"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_googledomains_preview_update() {
+        let config = Config {
+            protocol: Some("googledomains".to_string()),
+            login: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            ..Default::default()
+        };
+        let client = GoogleDomainsClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://domains.google.com/nic/update?hostname=home.example.com&myip=203.0.113.1");
+        // base64("user:pass")
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string())));
+    }
 }
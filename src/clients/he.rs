@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{same_result, DnsClient, HostResults, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -7,6 +8,7 @@ use std::net::IpAddr;
 pub struct HurricaneElectricClient {
     password: String,
     server: String,
+    http: http::HttpClient,
 }
 
 impl HurricaneElectricClient {
@@ -19,21 +21,29 @@ impl HurricaneElectricClient {
             .cloned()
             .unwrap_or_else(|| "https://dyn.dns.he.net/nic/update".to_string());
 
-        Ok(HurricaneElectricClient { password, server })
+        Ok(HurricaneElectricClient {
+            password,
+            server,
+            http: http::HttpClientBuilder::new(config).build(),
+        })
     }
-}
 
-impl DnsClient for HurricaneElectricClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
-            "{}?hostname={}&password={}&myip={}",
-            self.server, hostname, self.password, ip
-        );
+    /// Update URL; `hostname` may be a comma-separated list
+    fn build_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}?hostname={}&password={}&myip={}", self.server, hostname, self.password, ip)
+    }
+
+    /// Update several hostnames sharing the same password in one request
+    pub fn batch_update(&self, hostnames: &[String], ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        self.send(&hostnames.join(","), ip)
+    }
+
+    fn send(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let url = self.build_url(hostname, ip);
 
         log::info!("Updating {} with Hurricane Electric", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(&url))
             .send()?;
 
         let body = response.as_str()?.trim();
@@ -50,6 +60,16 @@ impl DnsClient for HurricaneElectricClient {
             Err(format!("Unexpected response: {}", body).into())
         }
     }
+}
+
+impl DnsClient for HurricaneElectricClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        self.send(hostname, ip)
+    }
+
+    fn update_records(&self, hostnames: &[String], ip: IpAddr) -> Option<HostResults> {
+        Some(same_result(hostnames, self.batch_update(hostnames, ip)))
+    }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
         if self.password.is_empty() {
@@ -61,4 +81,75 @@ impl DnsClient for HurricaneElectricClient {
     fn provider_name(&self) -> &str {
         "Hurricane Electric"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.build_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client(server: Option<String>) -> HurricaneElectricClient {
+        let config = Config {
+            protocol: Some("he".to_string()),
+            password: Some("ddns-key".to_string()),
+            server,
+            ..Default::default()
+        };
+        HurricaneElectricClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_he_url() {
+        let client = test_client(None);
+        assert_eq!(
+            client.build_url("home.example.com", "203.0.113.1".parse().unwrap()),
+            "https://dyn.dns.he.net/nic/update?hostname=home.example.com&password=ddns-key&myip=203.0.113.1"
+        );
+    }
+
+    #[test]
+    fn test_he_batch_update_joins_hostnames() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("hostname".to_string(), "a.example.com,b.example.com".to_string()),
+                mockito::Matcher::UrlEncoded("myip".to_string(), "2001:db8::1".to_string()),
+            ]))
+            .with_body("good 2001:db8::1")
+            .expect(1)
+            .create();
+
+        let client = test_client(Some(format!("{}/", server.url())));
+        let hostnames = vec!["a.example.com".to_string(), "b.example.com".to_string()];
+        let result = client.update_records(&hostnames, "2001:db8::1".parse().unwrap());
+
+        assert!(result.unwrap().iter().all(|(_, result)| result.is_ok()));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_he_batch_update_error() {
+        let mut server = mockito::Server::new();
+        let _mock = server.mock("GET", "/")
+            .match_query(mockito::Matcher::Any)
+            .with_body("badauth")
+            .create();
+
+        let client = test_client(Some(format!("{}/", server.url())));
+        let hostnames = vec!["a.example.com".to_string(), "b.example.com".to_string()];
+        let err = client.batch_update(&hostnames, "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Bad authentication"));
+    }
+
+    #[test]
+    fn test_he_preview_update() {
+        let client = test_client(None);
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, client.build_url("home.example.com", "203.0.113.1".parse().unwrap()));
+        assert!(!requests[0].to_string().contains("ddns-key"));
+    }
 }
@@ -1,12 +1,18 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
+use std::sync::Mutex;
 
 pub struct HetznerClient {
     api_token: String,
+    /// Zone ID, or the zone's domain name (anything with a dot), resolved on first use
     zone_id: String,
+    resolved_zone_id: Mutex<Option<String>>,
     server: String,
+    ttl: u32,
+    http: http::HttpClient,
 }
 
 impl HetznerClient {
@@ -23,7 +29,10 @@ impl HetznerClient {
         Ok(Self {
             api_token,
             zone_id,
+            resolved_zone_id: Mutex::new(None),
             server,
+            ttl: config.ttl.unwrap_or(60),
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
 
@@ -42,15 +51,57 @@ impl HetznerClient {
         }
     }
 
-    fn get_record_id(&self, hostname: &str, record_type: &str) -> Result<String, Box<dyn Error>> {
-        let url = format!("{}/records?zone_id={}", self.server, self.zone_id);
-        
-        let response = minreq::get(&url)
+    /// Hetzner zone ID; a configured domain name is looked up via `GET /zones?name=`
+    fn resolve_zone_id(&self) -> Result<String, Box<dyn Error>> {
+        if !self.zone_id.contains('.') {
+            return Ok(self.zone_id.clone());
+        }
+        let mut resolved = self.resolved_zone_id.lock().unwrap();
+        if let Some(id) = resolved.as_ref() {
+            return Ok(id.clone());
+        }
+
+        log::debug!("Looking up Hetzner zone ID for {}", self.zone_id);
+        let response = self.http.prepare(minreq::get(self.zone_lookup_url()))
             .with_header("Auth-API-Token", &self.api_token)
             .send()?;
-
         if response.status_code != 200 {
-            return Err(format!("Failed to fetch records: HTTP {}", response.status_code).into());
+            return Err(format!("Failed to look up Hetzner zone {}: HTTP {}", self.zone_id, response.status_code).into());
+        }
+
+        let json: serde_json::Value = response.json()?;
+        let id = json["zones"].as_array()
+            .and_then(|zones| zones.iter().find(|zone| zone["name"].as_str() == Some(self.zone_id.as_str())))
+            .and_then(|zone| zone["id"].as_str())
+            .ok_or_else(|| format!("Hetzner zone {} not found", self.zone_id))?
+            .to_string();
+
+        log::debug!("Hetzner zone {} has ID {}", self.zone_id, id);
+        *resolved = Some(id.clone());
+        Ok(id)
+    }
+
+    fn zone_lookup_url(&self) -> String {
+        format!("{}/zones?name={}", self.server, self.zone_id)
+    }
+
+    fn records_url(&self, zone_id: &str) -> String {
+        format!("{}/records?zone_id={}", self.server, zone_id)
+    }
+
+    /// ID of the existing record, or `None` if the zone has no matching record
+    fn get_record_id(&self, hostname: &str, record_type: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(self.records_url(&self.resolve_zone_id()?)))
+            .with_header("Auth-API-Token", &self.api_token)
+            .send()?;
+
+        match response.status_code {
+            200 => {}
+            404 => {
+                log::debug!("Hetzner returned 404 for records of zone {}", self.zone_id);
+                return Ok(None);
+            }
+            status => return Err(format!("Failed to fetch records: HTTP {}", status).into()),
         }
 
         let json: serde_json::Value = response.json()?;
@@ -65,13 +116,44 @@ impl HetznerClient {
                     && record["type"].as_str() == Some(record_type) {
                     if let Some(id) = record["id"].as_str() {
                         log::debug!("Found record ID {} for {} (subdomain: {})", id, hostname, subdomain);
-                        return Ok(id.to_string());
+                        return Ok(Some(id.to_string()));
                     }
                 }
             }
         }
 
-        Err(format!("Record {} (subdomain: {}) not found", hostname, subdomain).into())
+        log::debug!("Record {} (subdomain: {}) not found", hostname, subdomain);
+        Ok(None)
+    }
+
+    fn record_payload(&self, name: &str, record_type: &str, ip: IpAddr, zone_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "value": ip.to_string(),
+            "ttl": self.ttl,
+            "type": record_type,
+            "name": name,
+            "zone_id": zone_id
+        })
+    }
+
+    /// Create a new record, returning its ID
+    fn create_record(&self, name: &str, record_type: &str, ip: IpAddr) -> Result<String, Box<dyn Error>> {
+        let url = format!("{}/records", self.server);
+
+        let response = self.http.prepare(minreq::post(&url))
+            .with_header("Auth-API-Token", &self.api_token)
+            .with_json(&self.record_payload(name, record_type, ip, &self.resolve_zone_id()?))?
+            .send()?;
+
+        match response.status_code {
+            200 | 201 => {
+                let json: serde_json::Value = response.json()?;
+                json["record"]["id"].as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| "No record ID in Hetzner response".into())
+            }
+            status => Err(format!("Failed to create record: HTTP {}", status).into()),
+        }
     }
 }
 
@@ -83,25 +165,23 @@ impl DnsClient for HetznerClient {
             IpAddr::V6(_) => "AAAA",
         };
         
+        // Use subdomain (not FQDN) in API calls
+        let subdomain = self.extract_subdomain(hostname);
+
         log::info!("Fetching {} record ID for {}", record_type, hostname);
-        let record_id = self.get_record_id(hostname, record_type)?;
-        
+        let Some(record_id) = self.get_record_id(hostname, record_type)? else {
+            log::info!("No {} record for {}, creating it", record_type, hostname);
+            let id = self.create_record(&subdomain, record_type, ip)?;
+            log::info!("Created record {} for {} with {}", id, hostname, ip);
+            return Ok(());
+        };
+
         let url = format!("{}/records/{}", self.server, record_id);
-        
-        // Use subdomain (not FQDN) in API call
-        let subdomain = self.extract_subdomain(hostname);
-        
-        let payload = serde_json::json!({
-            "value": ip.to_string(),
-            "ttl": 60,
-            "type": record_type,
-            "name": subdomain,
-            "zone_id": self.zone_id
-        });
+        let payload = self.record_payload(&subdomain, record_type, ip, &self.resolve_zone_id()?);
 
         log::info!("Updating {} to {}", hostname, ip);
         
-        let response = minreq::put(&url)
+        let response = self.http.prepare(minreq::put(&url))
             .with_header("Auth-API-Token", &self.api_token)
             .with_header("Content-Type", "application/json")
             .with_json(&payload)?
@@ -128,4 +208,195 @@ impl DnsClient for HetznerClient {
     fn provider_name(&self) -> &str {
         "Hetzner"
     }
+
+    /// A missing record is created with a POST to `/records` instead of the PUT
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        let mut requests = Vec::new();
+        let zone_id = if self.zone_id.contains('.') {
+            requests.push(self.http.preview("GET", self.zone_lookup_url())
+                .with_header("Auth-API-Token", &self.api_token));
+            "{zone_id}"
+        } else {
+            self.zone_id.as_str()
+        };
+        requests.push(self.http.preview("GET", self.records_url(zone_id))
+            .with_header("Auth-API-Token", &self.api_token));
+        let payload = self.record_payload(&self.extract_subdomain(hostname), record_type, ip, zone_id);
+        requests.push(self.http.preview("PUT", format!("{}/records/{{record_id}}", self.server))
+            .with_header("Auth-API-Token", &self.api_token)
+            .with_header("Content-Type", "application/json")
+            .with_body(payload.to_string()));
+        Ok(requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn test_client(server: &mockito::Server) -> HetznerClient {
+        let config = Config {
+            protocol: Some("hetzner".to_string()),
+            password: Some("hz-token".to_string()),
+            zone: Some("example.com".to_string()),
+            server: Some(server.url()),
+            ..Default::default()
+        };
+        HetznerClient::new(&config).unwrap()
+    }
+
+    fn mock_zones(server: &mut mockito::Server) -> mockito::Mock {
+        server.mock("GET", "/zones")
+            .match_query(Matcher::UrlEncoded("name".to_string(), "example.com".to_string()))
+            .match_header("auth-api-token", "hz-token")
+            .with_body(r#"{"zones":[{"id":"zX1","name":"example.com"}]}"#)
+            .create()
+    }
+
+    fn mock_records(server: &mut mockito::Server, status: usize, body: &str) -> mockito::Mock {
+        mock_zones(server);
+        server.mock("GET", "/records")
+            .match_query(Matcher::UrlEncoded("zone_id".to_string(), "zX1".to_string()))
+            .match_header("auth-api-token", "hz-token")
+            .with_status(status)
+            .with_body(body)
+            .create()
+    }
+
+    #[test]
+    fn test_hetzner_updates_existing_record() {
+        let mut server = mockito::Server::new();
+        let _records = mock_records(&mut server, 200, r#"{"records":[{"id":"r1","type":"A","name":"home","value":"198.51.100.1"}]}"#);
+        let update = server.mock("PUT", "/records/r1")
+            .match_body(Matcher::Json(json!({
+                "value": "203.0.113.1", "ttl": 60, "type": "A", "name": "home", "zone_id": "zX1"
+            })))
+            .with_status(200)
+            .create();
+
+        let client = test_client(&server);
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        update.assert();
+    }
+
+    #[test]
+    fn test_hetzner_creates_missing_record() {
+        let mut server = mockito::Server::new();
+        let _records = mock_records(&mut server, 200, r#"{"records":[{"id":"r1","type":"A","name":"home","value":"198.51.100.1"}]}"#);
+        let create = server.mock("POST", "/records")
+            .match_header("auth-api-token", "hz-token")
+            .match_body(Matcher::Json(json!({
+                "value": "2001:db8::1", "ttl": 60, "type": "AAAA", "name": "home", "zone_id": "zX1"
+            })))
+            .with_status(200)
+            .with_body(r#"{"record":{"id":"r2","type":"AAAA","name":"home","value":"2001:db8::1"}}"#)
+            .create();
+
+        let client = test_client(&server);
+        assert!(client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).is_ok());
+        create.assert();
+    }
+
+    #[test]
+    fn test_hetzner_404_creates_record() {
+        let mut server = mockito::Server::new();
+        let _records = mock_records(&mut server, 404, r#"{"error":{"message":"records not found","code":404}}"#);
+        let create = server.mock("POST", "/records")
+            .with_status(200)
+            .with_body(r#"{"record":{"id":"r3"}}"#)
+            .create();
+
+        let client = test_client(&server);
+        assert_eq!(client.create_record("@", "A", "203.0.113.1".parse().unwrap()).unwrap(), "r3");
+        assert!(client.update_record("example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        create.expect(2).assert();
+    }
+
+    #[test]
+    fn test_hetzner_other_errors_do_not_create() {
+        let mut server = mockito::Server::new();
+        let _records = mock_records(&mut server, 401, r#"{"message":"Invalid authentication credentials"}"#);
+        let create = server.mock("POST", "/records").expect(0).create();
+
+        let client = test_client(&server);
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("HTTP 401"));
+        create.assert();
+    }
+
+    #[test]
+    fn test_hetzner_zone_name_resolved_once() {
+        let mut server = mockito::Server::new();
+        let zones = mock_zones(&mut server).expect(1);
+        let _records = server.mock("GET", "/records")
+            .match_query(Matcher::UrlEncoded("zone_id".to_string(), "zX1".to_string()))
+            .with_body(r#"{"records":[{"id":"r1","type":"A","name":"home","value":"198.51.100.1"}]}"#)
+            .create();
+        let update = server.mock("PUT", "/records/r1")
+            .match_body(Matcher::PartialJson(json!({"zone_id": "zX1"})))
+            .expect(2)
+            .create();
+
+        let client = test_client(&server);
+        client.validate_config().unwrap();
+        let ip = "203.0.113.1".parse().unwrap();
+        client.update_record("home.example.com", ip).unwrap();
+        client.update_record("home.example.com", ip).unwrap();
+        zones.assert();
+        update.assert();
+    }
+
+    #[test]
+    fn test_hetzner_zone_lookup_failure() {
+        let mut server = mockito::Server::new();
+        let _zones = server.mock("GET", "/zones").match_query(Matcher::Any).with_status(401).create();
+
+        let client = test_client(&server);
+        assert_eq!(client.resolve_zone_id().unwrap_err().to_string(), "Failed to look up Hetzner zone example.com: HTTP 401");
+    }
+
+    #[test]
+    fn test_hetzner_validate_config_is_offline() {
+        let mut server = mockito::Server::new();
+        let zones = server.mock("GET", "/zones").match_query(Matcher::Any).expect(0).create();
+
+        let client = test_client(&server);
+        client.validate_config().unwrap();
+        zones.assert();
+    }
+
+    #[test]
+    fn test_hetzner_zone_id_used_directly() {
+        let config = Config {
+            password: Some("hz-token".to_string()),
+            zone: Some("zX1".to_string()),
+            server: Some("http://127.0.0.1:1".to_string()),
+            ..Default::default()
+        };
+        let client = HetznerClient::new(&config).unwrap();
+        assert_eq!(client.resolve_zone_id().unwrap(), "zX1");
+    }
+
+    #[test]
+    fn test_hetzner_preview_update() {
+        let mut server = mockito::Server::new();
+        let lookups = server.mock("GET", Matcher::Any).expect(0).create();
+
+        let client = test_client(&server);
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].url, format!("{}/zones?name=example.com", server.url()));
+        assert_eq!(requests[1].url, format!("{}/records?zone_id={{zone_id}}", server.url()));
+        assert_eq!(requests[2].method, "PUT");
+        assert_eq!(requests[2].url, format!("{}/records/{{record_id}}", server.url()));
+        assert_eq!(
+            requests[2].body.as_deref(),
+            Some(r#"{"name":"home","ttl":60,"type":"A","value":"203.0.113.1","zone_id":"{zone_id}"}"#)
+        );
+        assert!(!requests[2].to_string().contains("hz-token"));
+        lookups.assert();
+    }
 }
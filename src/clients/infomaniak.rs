@@ -1,15 +1,19 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 use base64::{Engine as _, engine::general_purpose};
 
 /// Infomaniak DNS client
-/// Uses Infomaniak's API with basic authentication
+/// Uses Infomaniak's DynDNS endpoint (`/nic/update`) with basic authentication.
+/// The endpoint answers either with DynDNS2 return codes or with a JSON result
+/// object: `{"result":"success"}` / `{"result":"error","error":{"code":..,"description":".."}}`
 pub struct InfomaniakClient {
     server: String,
     username: String,
     password: String,
+    http: http::HttpClient,
 }
 
 impl InfomaniakClient {
@@ -29,29 +33,23 @@ impl InfomaniakClient {
             server,
             username,
             password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
-
-impl DnsClient for InfomaniakClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
-            "{}/nic/update?hostname={}&myip={}",
-            self.server, hostname, ip
-        );
 
-        log::info!("Updating {} with Infomaniak", hostname);
-
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", 
-                general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password))))
-            .send()?;
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/nic/update?hostname={}&myip={}", self.server, hostname, ip)
+    }
 
-        let status_code = response.status_code;
-        let body = response.as_str()?.trim();
+    fn auth_header(&self) -> String {
+        format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password)))
+    }
 
-        log::debug!("Response status: {}, body: {}", status_code, body);
+    /// Interpret an update response, JSON or DynDNS2 text
+    fn parse_response(status_code: u16, body: &str) -> Result<(), Box<dyn Error>> {
+        if let Ok(json @ serde_json::Value::Object(_)) = serde_json::from_str(body) {
+            return Self::parse_json_response(&json);
+        }
 
         if status_code != 200 {
             return Err(format!("HTTP error: {}", status_code).into());
@@ -59,7 +57,6 @@ impl DnsClient for InfomaniakClient {
 
         // Parse DynDNS2-style response
         if body.starts_with("good") || body.starts_with("nochg") {
-            log::info!("Successfully updated {} to {}", hostname, ip);
             Ok(())
         } else if body.starts_with("badauth") {
             Err("Authentication failed - check username and password".into())
@@ -76,6 +73,46 @@ impl DnsClient for InfomaniakClient {
         }
     }
 
+    fn parse_json_response(json: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+        match json["result"].as_str() {
+            Some("success") => Ok(()),
+            Some("error") => {
+                let error = &json["error"];
+                let description = error["description"].as_str().unwrap_or("unknown error");
+                let code = error["code"].as_u64()
+                    .or_else(|| error["code"].as_str().and_then(|code| code.parse().ok()));
+                let message = match code {
+                    Some(400) | Some(401) => "Invalid credentials",
+                    Some(404) => "Host not found",
+                    Some(429) => "Too many requests - try again later",
+                    _ => return Err(format!("Infomaniak error: {}", description).into()),
+                };
+                Err(format!("{} ({})", message, description).into())
+            }
+            _ => Err(format!("Unexpected response: {}", json).into()),
+        }
+    }
+}
+
+impl DnsClient for InfomaniakClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with Infomaniak", hostname);
+
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
+            .send()?;
+
+        let status_code = response.status_code;
+        let body = response.as_str()?.trim();
+
+        log::debug!("Response status: {}, body: {}", status_code, body);
+
+        Self::parse_response(status_code, body)?;
+
+        log::info!("Successfully updated {} to {}", hostname, ip);
+        Ok(())
+    }
+
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
         if self.username.is_empty() {
             return Err("username is required for Infomaniak".into());
@@ -89,4 +126,79 @@ impl DnsClient for InfomaniakClient {
     fn provider_name(&self) -> &str {
         "Infomaniak"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infomaniak_json_success() {
+        assert!(InfomaniakClient::parse_response(200, r#"{"result":"success","data":"nochg 203.0.113.1"}"#).is_ok());
+    }
+
+    #[test]
+    fn test_infomaniak_json_auth_error() {
+        let body = r#"{"result":"error","error":{"code":400,"description":"invalid username or password"}}"#;
+        let err = InfomaniakClient::parse_response(401, body).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid credentials (invalid username or password)");
+    }
+
+    #[test]
+    fn test_infomaniak_json_host_not_found() {
+        let body = r#"{"result":"error","error":{"code":404,"description":"no such host"}}"#;
+        let err = InfomaniakClient::parse_response(200, body).unwrap_err();
+        assert_eq!(err.to_string(), "Host not found (no such host)");
+
+        let body = r#"{"result":"error","error":{"code":500,"description":"internal"}}"#;
+        let err = InfomaniakClient::parse_response(200, body).unwrap_err();
+        assert_eq!(err.to_string(), "Infomaniak error: internal");
+    }
+
+    #[test]
+    fn test_infomaniak_text_response() {
+        assert!(InfomaniakClient::parse_response(200, "good 203.0.113.1").is_ok());
+        assert!(InfomaniakClient::parse_response(200, "nohost").is_err());
+        assert_eq!(InfomaniakClient::parse_response(200, "911").unwrap_err().to_string(), "Server error - try again later");
+        assert_eq!(InfomaniakClient::parse_response(502, "Bad Gateway").unwrap_err().to_string(), "HTTP error: 502");
+    }
+
+    #[test]
+    fn test_infomaniak_validate_config() {
+        let config = Config {
+            login: Some("user".to_string()),
+            password: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(InfomaniakClient::new(&config).unwrap().validate_config().is_err());
+
+        let config = Config {
+            login: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            ..Default::default()
+        };
+        assert!(InfomaniakClient::new(&config).unwrap().validate_config().is_ok());
+    }
+
+    #[test]
+    fn test_infomaniak_preview_update() {
+        let config = Config {
+            protocol: Some("infomaniak".to_string()),
+            login: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            ..Default::default()
+        };
+        let client = InfomaniakClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://infomaniak.com/nic/update?hostname=home.example.com&myip=203.0.113.1");
+        // base64("user:pass")
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string())));
+    }
 }
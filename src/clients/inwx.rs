@@ -1,13 +1,21 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use base64::{Engine as _, engine::general_purpose};
 use std::error::Error;
 use std::net::IpAddr;
+use totp_rs::{Builder, Secret};
+
+/// Digits in the INWX two-factor code
+const TOTP_DIGITS: u8 = 6;
 
 pub struct InwxClient {
     username: String,
     password: String,
     server: String,
+    /// Base32 TOTP secret for accounts with 2FA (`inwx_totp=`); sent as `tan=`
+    totp_secret: Option<String>,
+    http: http::HttpClient,
 }
 
 impl InwxClient {
@@ -25,22 +33,44 @@ impl InwxClient {
             username,
             password,
             server,
+            totp_secret: config.inwx_totp.clone(),
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn build_url(&self, hostname: &str, ip: IpAddr, time: u64) -> Result<String, Box<dyn Error>> {
+        let mut url = format!("{}/nic/update?hostname={}&myip={}", self.server, hostname, ip);
+        if let Some(secret) = &self.totp_secret {
+            url.push_str(&format!("&tan={}", totp_code(secret, TOTP_DIGITS, time)?));
+        }
+        Ok(url)
+    }
+
+    fn auth_header(&self) -> String {
+        format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password)))
+    }
+}
+
+/// RFC 6238 TOTP code (HMAC-SHA1, 30 second steps) for a Base32 secret at Unix time `time`
+fn totp_code(secret: &str, digits: u8, time: u64) -> Result<String, Box<dyn Error>> {
+    let secret = Secret::try_from_base32(secret.trim().replace(' ', "").to_uppercase())
+        .map_err(|e| format!("Invalid INWX TOTP secret (expected Base32): {:?}", e))?;
+    let totp = Builder::new()
+        .with_digits(digits)
+        .with_secret(secret)
+        .build()
+        .map_err(|e| format!("Invalid INWX TOTP secret: {}", e))?;
+    Ok(totp.generate(time).to_string())
 }
 
 impl DnsClient for InwxClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let auth = general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password));
-        
-        let url = format!("{}/nic/update?hostname={}&myip={}", 
-            self.server, hostname, ip);
+        let url = self.build_url(hostname, ip, crate::state::current_timestamp())?;
         
         log::info!("Updating {} to {}", hostname, ip);
         
-        let response = minreq::get(&url)
-            .with_header("Authorization", format!("Basic {}", auth))
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(&url))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         if response.status_code != 200 {
@@ -75,10 +105,93 @@ impl DnsClient for InwxClient {
         if self.password.is_empty() {
             return Err("INWX password cannot be empty".into())
         }
+        if let Some(secret) = &self.totp_secret {
+            totp_code(secret, TOTP_DIGITS, 0)?;
+        }
         Ok(())
     }
 
     fn provider_name(&self) -> &str {
         "INWX"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let url = self.build_url(hostname, ip, crate::state::current_timestamp())?;
+        Ok(vec![self.http.preview("GET", url).with_header("Authorization", self.auth_header())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 6238 appendix B seed "12345678901234567890" in Base32
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    fn test_client(totp: Option<&str>) -> InwxClient {
+        let config = Config {
+            protocol: Some("inwx".to_string()),
+            login: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            inwx_totp: totp.map(str::to_string),
+            ..Default::default()
+        };
+        InwxClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        let vectors = [
+            (59, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+            (20000000000, "65353130"),
+        ];
+        for (time, expected) in vectors {
+            assert_eq!(totp_code(RFC_SECRET, 8, time).unwrap(), expected);
+        }
+        assert_eq!(totp_code(RFC_SECRET, 6, 59).unwrap(), "287082");
+        assert_eq!(totp_code("gezd gnbv gy3t qojq gezd gnbv gy3t qojq", 6, 59).unwrap(), "287082");
+    }
+
+    #[test]
+    fn test_inwx_url_with_tan() {
+        let client = test_client(Some(RFC_SECRET));
+        assert_eq!(
+            client.build_url("home.example.com", "203.0.113.1".parse().unwrap(), 1111111109).unwrap(),
+            "https://dyndns.inwx.com/nic/update?hostname=home.example.com&myip=203.0.113.1&tan=081804"
+        );
+    }
+
+    #[test]
+    fn test_inwx_url_without_tan() {
+        let client = test_client(None);
+        let url = client.build_url("home.example.com", "203.0.113.1".parse().unwrap(), 59).unwrap();
+        assert_eq!(url, "https://dyndns.inwx.com/nic/update?hostname=home.example.com&myip=203.0.113.1");
+        assert!(client.validate_config().is_ok());
+    }
+
+    #[test]
+    fn test_inwx_invalid_totp_secret() {
+        assert!(test_client(Some("not base32!")).validate_config().is_err());
+        // Shorter than the 128 bits RFC 4226 requires
+        assert!(test_client(Some("GEZDGNBV")).validate_config().is_err());
+    }
+
+    #[test]
+    fn test_inwx_preview_update() {
+        let client = test_client(None);
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://dyndns.inwx.com/nic/update?hostname=home.example.com&myip=203.0.113.1");
+        // base64("user:pass")
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string())));
+
+        let client = test_client(Some(RFC_SECRET));
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert!(requests[0].url.contains("&tan="));
+        assert!(test_client(Some("not base32!")).preview_update("home.example.com", "203.0.113.1".parse().unwrap()).is_err());
+    }
 }
@@ -1,43 +1,83 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use base64::{Engine as _, engine::general_purpose};
 use std::error::Error;
 use std::net::IpAddr;
 
-/// Key-Systems (RRPproxy) DNS client
-/// Uses Key-Systems dynamic DNS API
+/// Key-Systems (RRPproxy / CentralNic Reseller) DNS client
+/// Uses the DynDNS endpoint `https://www.rrpproxy.net/CNIC/update/dyndns.html`
+/// with HTTP Basic authentication (login = DynDNS user, password = DynDNS password)
 pub struct KeysystemsClient {
     server: String,
-    token: String,
+    login: String,
+    password: String,
+    http: http::HttpClient,
 }
 
 impl KeysystemsClient {
     pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
-        let token = config.password.as_ref()
-            .ok_or("password (token) is required for Key-Systems")?
+        let login = config.login.as_ref()
+            .ok_or("login is required for Key-Systems")?
+            .clone();
+        let password = config.password.as_ref()
+            .ok_or("password is required for Key-Systems")?
             .clone();
         
         let server = config.server.clone()
-            .unwrap_or_else(|| "https://dynamicdns.key-systems.net".to_string());
+            .unwrap_or_else(|| "https://www.rrpproxy.net".to_string());
 
         Ok(KeysystemsClient {
             server,
-            token,
+            login,
+            password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn build_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/CNIC/update/dyndns.html?hostname={}&myip={}", self.server, hostname, ip)
+    }
+
+    fn auth_header(&self) -> String {
+        let credentials = general_purpose::STANDARD.encode(format!("{}:{}", self.login, self.password));
+        format!("Basic {}", credentials)
+    }
+
+    /// Interpret the response body: `success` on success, otherwise an error keyword
+    fn parse_response(status_code: u16, body: &str) -> Result<(), Box<dyn Error>> {
+        let lower = body.to_lowercase();
+        if status_code == 200 && lower.contains("success") {
+            return Ok(());
+        }
+
+        let message = if status_code == 401 || lower.contains("badauth") || lower.contains("authentication failed") {
+            "Authentication failed - check login and password"
+        } else if lower.contains("notfqdn") {
+            "Invalid hostname format"
+        } else if lower.contains("nohost") || lower.contains("not found") {
+            "Hostname not found in your account"
+        } else if lower.contains("abuse") {
+            "Account blocked for abuse"
+        } else if lower.contains("badagent") {
+            "User agent blocked"
+        } else if lower.contains("911") || status_code >= 500 {
+            "Server error - try again later"
+        } else if status_code != 200 {
+            return Err(format!("HTTP error: {}", status_code).into());
+        } else {
+            return Err(format!("Update failed: {}", body).into());
+        };
+        Err(format!("Key-Systems error: {} ({})", message, body).into())
+    }
 }
 
 impl DnsClient for KeysystemsClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with Key-Systems", hostname);
 
-        // Key-Systems dynamic DNS endpoint
-        let url = format!("{}/nic/update", self.server);
-
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_param("token", &self.token)
-            .with_param("hostname", hostname)
-            .with_param("myip", ip.to_string())
+        let response = self.http.prepare(minreq::get(self.build_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         let status_code = response.status_code;
@@ -45,32 +85,18 @@ impl DnsClient for KeysystemsClient {
 
         log::debug!("Response status: {}, body: {}", status_code, body);
 
-        if status_code != 200 {
-            return Err(format!("HTTP error: {}", status_code).into());
-        }
+        Self::parse_response(status_code, body)?;
 
-        // Key-Systems uses DynDNS-like response codes
-        if body.starts_with("good") || body.starts_with("nochg") {
-            log::info!("Successfully updated {} to {}", hostname, ip);
-            Ok(())
-        } else if body.starts_with("badauth") {
-            Err("Authentication failed - invalid token".into())
-        } else if body.starts_with("notfqdn") {
-            Err("Invalid hostname format".into())
-        } else if body.starts_with("nohost") {
-            Err("Hostname not found in your account".into())
-        } else if body.starts_with("abuse") {
-            Err("Account blocked for abuse".into())
-        } else if body.starts_with("badagent") {
-            Err("User agent blocked".into())
-        } else {
-            Err(format!("Update failed: {}", body).into())
-        }
+        log::info!("Successfully updated {} to {}", hostname, ip);
+        Ok(())
     }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
-        if self.token.is_empty() {
-            return Err("password (token) is required for Key-Systems".into());
+        if self.login.is_empty() {
+            return Err("login is required for Key-Systems".into());
+        }
+        if self.password.is_empty() {
+            return Err("password is required for Key-Systems".into());
         }
         Ok(())
     }
@@ -78,4 +104,68 @@ impl DnsClient for KeysystemsClient {
     fn provider_name(&self) -> &str {
         "Key-Systems"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.build_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client(login: &str, password: &str) -> KeysystemsClient {
+        let config = Config {
+            protocol: Some("keysystems".to_string()),
+            login: Some(login.to_string()),
+            password: Some(password.to_string()),
+            ..Default::default()
+        };
+        KeysystemsClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_keysystems_url_and_auth() {
+        let client = test_client("user", "pass");
+        assert_eq!(
+            client.build_url("home.example.com", "203.0.113.1".parse().unwrap()),
+            "https://www.rrpproxy.net/CNIC/update/dyndns.html?hostname=home.example.com&myip=203.0.113.1"
+        );
+        // base64("user:pass")
+        assert_eq!(client.auth_header(), "Basic dXNlcjpwYXNz");
+    }
+
+    #[test]
+    fn test_keysystems_success() {
+        assert!(KeysystemsClient::parse_response(200, "success").is_ok());
+        assert!(KeysystemsClient::parse_response(200, "Update success: 203.0.113.1").is_ok());
+    }
+
+    #[test]
+    fn test_keysystems_errors() {
+        let message = |status, body| KeysystemsClient::parse_response(status, body).unwrap_err().to_string();
+        assert_eq!(message(200, "badauth"), "Key-Systems error: Authentication failed - check login and password (badauth)");
+        assert_eq!(message(200, "nohost"), "Key-Systems error: Hostname not found in your account (nohost)");
+        assert_eq!(message(200, "notfqdn"), "Key-Systems error: Invalid hostname format (notfqdn)");
+        assert_eq!(message(200, "abuse"), "Key-Systems error: Account blocked for abuse (abuse)");
+        assert_eq!(message(503, "Service Unavailable"), "Key-Systems error: Server error - try again later (Service Unavailable)");
+        assert_eq!(message(200, "something else"), "Update failed: something else");
+    }
+
+    #[test]
+    fn test_keysystems_validate_config() {
+        assert!(test_client("user", "pass").validate_config().is_ok());
+        assert!(test_client("", "pass").validate_config().is_err());
+        assert!(test_client("user", "").validate_config().is_err());
+    }
+
+    #[test]
+    fn test_keysystems_preview_update() {
+        let client = test_client("user", "pass");
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://www.rrpproxy.net/CNIC/update/dyndns.html?hostname=home.example.com&myip=203.0.113.1");
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), client.auth_header())));
+    }
 }
@@ -1,15 +1,26 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use serde_json::json;
 use std::error::Error;
 use std::net::IpAddr;
 
+/// Most pages fetched from one list endpoint, in case `pages` never stops growing
+const MAX_PAGES: u64 = 50;
+
 /// Linode DNS client
-/// Uses Linode API v4
+/// Uses Linode API v4 with a personal access token (`Authorization: Bearer`)
+///
+/// Configuration:
+/// - password: API token with Domains read/write access
+/// - zone: domain name (or its numeric Linode domain ID)
+/// - ttl: record TTL in seconds (default 300)
 pub struct LinodeClient {
     server: String,
     token: String,
-    domain_id: String,
-    record_id: String,
+    zone: String,
+    ttl: u32,
+    http: http::HttpClient,
 }
 
 impl LinodeClient {
@@ -17,25 +28,115 @@ impl LinodeClient {
         let token = config.password.as_ref()
             .ok_or("api_token is required for Linode")?
             .clone();
-        
-        let domain_id = config.zone.as_ref()
-            .ok_or("zone_id (domain ID) is required for Linode")?
-            .clone();
-        
-        let record_id = config.host.as_ref()
-            .ok_or("dns_record (record ID) is required for Linode")?
+
+        let zone = config.zone.as_ref()
+            .ok_or("zone (domain name) is required for Linode")?
             .clone();
-        
+
         let server = config.server.clone()
             .unwrap_or_else(|| "https://api.linode.com".to_string());
 
         Ok(LinodeClient {
             server,
             token,
-            domain_id,
-            record_id,
+            zone,
+            ttl: config.ttl.unwrap_or(300),
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn auth_header(&self) -> String {
+        format!("Bearer {}", self.token)
+    }
+
+    fn page_url(&self, path: &str, page: u64) -> String {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        format!("{}/v4{}{}page={}", self.server, path, separator, page)
+    }
+
+    fn records_path(domain_id: impl std::fmt::Display, record_type: &str) -> String {
+        format!("/domains/{}/records?type={}", domain_id, record_type)
+    }
+
+    fn record_url(&self, domain_id: impl std::fmt::Display, record_id: impl std::fmt::Display) -> String {
+        format!("{}/v4/domains/{}/records/{}", self.server, domain_id, record_id)
+    }
+
+    fn record_body(&self, ip: IpAddr) -> serde_json::Value {
+        json!({
+            "target": ip.to_string(),
+            "ttl_sec": self.ttl,
+        })
+    }
+
+    fn get(&self, url: &str) -> Result<serde_json::Value, Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(url))
+            .with_header("Authorization", self.auth_header())
+            .send()?;
+
+        let body = response.as_str()?;
+        if response.status_code != 200 {
+            return Err(Self::api_error(response.status_code, body));
+        }
+        Ok(serde_json::from_str(body)?)
+    }
+
+    fn api_error(status_code: u16, body: &str) -> Box<dyn Error> {
+        let reason = serde_json::from_str::<serde_json::Value>(body).ok()
+            .and_then(|json| json["errors"][0]["reason"].as_str().map(str::to_string));
+        match reason {
+            Some(reason) => format!("Linode API error (HTTP {}): {}", status_code, reason).into(),
+            None => format!("HTTP error: {}", status_code).into(),
+        }
+    }
+
+    /// Every `data` item of a paginated list endpoint, fetching pages until `page == pages`
+    fn get_all_pages(&self, path: &str) -> Result<Vec<serde_json::Value>, Box<dyn Error>> {
+        let mut items = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let json = self.get(&self.page_url(path, page))?;
+            let data = json["data"].as_array().ok_or("Linode API response has no data list")?;
+            items.extend(data.iter().cloned());
+
+            let pages = json["pages"].as_u64().unwrap_or(1);
+            if page >= pages {
+                return Ok(items);
+            }
+            if page >= MAX_PAGES {
+                return Err(format!("Linode API listed more than {} pages for {}", MAX_PAGES, path).into());
+            }
+            page += 1;
+        }
+    }
+
+    /// Every domain in the account, across all pages
+    fn list_all_domains(&self) -> Result<Vec<serde_json::Value>, Box<dyn Error>> {
+        self.get_all_pages("/domains")
+    }
+
+    /// ID of the configured domain; a numeric zone is used as the ID directly
+    fn domain_id(&self) -> Result<u64, Box<dyn Error>> {
+        if let Ok(id) = self.zone.parse() {
+            return Ok(id);
+        }
+
+        self.list_all_domains()?
+            .iter()
+            .find(|domain| domain["domain"].as_str().is_some_and(|name| name.eq_ignore_ascii_case(&self.zone)))
+            .and_then(|domain| domain["id"].as_u64())
+            .ok_or_else(|| format!("Domain {} not found in Linode account", self.zone).into())
+    }
+
+    /// Record name relative to the domain ("" for the apex)
+    fn record_name<'a>(hostname: &'a str, zone: &str) -> &'a str {
+        if hostname.eq_ignore_ascii_case(zone) {
+            ""
+        } else {
+            hostname.strip_suffix(&format!(".{}", zone)).unwrap_or(hostname)
+        }
+    }
 }
 
 impl DnsClient for LinodeClient {
@@ -47,21 +148,22 @@ impl DnsClient for LinodeClient {
 
         log::info!("Updating {} with Linode", hostname);
 
-        // Linode API v4 endpoint
-        let url = format!("{}/v4/domains/{}/records/{}", 
-            self.server, self.domain_id, self.record_id);
+        let domain_id = self.domain_id()?;
+        let name = Self::record_name(hostname, &self.zone);
+        let records = self.get_all_pages(&Self::records_path(domain_id, record_type))?;
+        let record = records.iter()
+            .find(|record| record["type"] == record_type && record["name"] == name)
+            .ok_or_else(|| format!("No {} record found for {} in Linode domain {}", record_type, hostname, domain_id))?;
+        let record_id = record["id"].as_u64().ok_or("Linode record has no id")?;
 
-        let body = format!(
-            r#"{{"type":"{}","target":"{}"}}"#,
-            record_type,
-            ip
-        );
+        if record["target"] == ip.to_string().as_str() {
+            log::info!("Record {} already set to {}, no update needed", hostname, ip);
+            return Ok(());
+        }
 
-        let response = minreq::put(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Bearer {}", self.token))
-            .with_header("Content-Type", "application/json")
-            .with_body(body)
+        let response = self.http.prepare(minreq::put(self.record_url(domain_id, record_id)))
+            .with_header("Authorization", self.auth_header())
+            .with_json(&self.record_body(ip))?
             .send()?;
 
         let status_code = response.status_code;
@@ -72,10 +174,8 @@ impl DnsClient for LinodeClient {
         if status_code == 200 {
             log::info!("Successfully updated {} to {}", hostname, ip);
             Ok(())
-        } else if response_body.contains("errors") {
-            Err(format!("Linode API error: {}", response_body).into())
         } else {
-            Err(format!("HTTP error: {}", status_code).into())
+            Err(Self::api_error(status_code, response_body))
         }
     }
 
@@ -83,11 +183,8 @@ impl DnsClient for LinodeClient {
         if self.token.is_empty() {
             return Err("api_token is required for Linode".into());
         }
-        if self.domain_id.is_empty() {
-            return Err("zone_id (domain ID) is required for Linode".into());
-        }
-        if self.record_id.is_empty() {
-            return Err("dns_record (record ID) is required for Linode".into());
+        if self.zone.is_empty() {
+            return Err("zone (domain name) is required for Linode".into());
         }
         Ok(())
     }
@@ -95,4 +192,162 @@ impl DnsClient for LinodeClient {
     fn provider_name(&self) -> &str {
         "Linode"
     }
+
+    /// Only the first page of each list is shown; the PUT is skipped when the record is already current
+    fn preview_update(&self, _hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        let mut requests = Vec::new();
+        let domain_id = match self.zone.parse::<u64>() {
+            Ok(id) => id.to_string(),
+            Err(_) => {
+                requests.push(self.http.preview("GET", self.page_url("/domains", 1))
+                    .with_header("Authorization", self.auth_header()));
+                "{domain_id}".to_string()
+            }
+        };
+        requests.push(self.http.preview("GET", self.page_url(&Self::records_path(&domain_id, record_type), 1))
+            .with_header("Authorization", self.auth_header()));
+        requests.push(self.http.preview("PUT", self.record_url(&domain_id, "{record_id}"))
+            .with_header("Authorization", self.auth_header())
+            .with_header("Content-Type", "application/json")
+            .with_body(self.record_body(ip).to_string()));
+        Ok(requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_client(server: &str, zone: &str) -> LinodeClient {
+        let config = Config {
+            protocol: Some("linode".to_string()),
+            password: Some("token".to_string()),
+            zone: Some(zone.to_string()),
+            server: Some(server.to_string()),
+            ..Default::default()
+        };
+        LinodeClient::new(&config).unwrap()
+    }
+
+    fn domains_page(server: &mut mockito::Server, page: u32, pages: u32, domains: &[(u64, &str)]) -> mockito::Mock {
+        let data: Vec<_> = domains.iter().map(|(id, domain)| json!({"id": id, "domain": domain})).collect();
+        server.mock("GET", "/v4/domains")
+            .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+            .match_header("authorization", "Bearer token")
+            .with_body(json!({"data": data, "page": page, "pages": pages, "results": 5}).to_string())
+            .expect(1)
+            .create()
+    }
+
+    #[test]
+    fn test_linode_domain_id_across_pages() {
+        let mut server = mockito::Server::new();
+        let page1 = domains_page(&mut server, 1, 3, &[(1, "one.example"), (2, "two.example")]);
+        let page2 = domains_page(&mut server, 2, 3, &[(3, "three.example"), (4, "four.example")]);
+        let page3 = domains_page(&mut server, 3, 3, &[(5, "example.com")]);
+
+        let client = test_client(&server.url(), "example.com");
+        assert_eq!(client.domain_id().unwrap(), 5);
+        page1.assert();
+        page2.assert();
+        page3.assert();
+    }
+
+    #[test]
+    fn test_linode_list_all_domains() {
+        let mut server = mockito::Server::new();
+        let page1 = domains_page(&mut server, 1, 3, &[(1, "one.example"), (2, "two.example")]);
+        let page2 = domains_page(&mut server, 2, 3, &[(3, "three.example")]);
+        let page3 = domains_page(&mut server, 3, 3, &[(4, "four.example"), (5, "example.com")]);
+
+        let client = test_client(&server.url(), "example.com");
+        let ids: Vec<u64> = client.list_all_domains().unwrap().iter().filter_map(|d| d["id"].as_u64()).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        page1.assert();
+        page2.assert();
+        page3.assert();
+    }
+
+    #[test]
+    fn test_linode_page_cap() {
+        let mut server = mockito::Server::new();
+        let pages = server.mock("GET", "/v4/domains")
+            .match_query(Matcher::Any)
+            .with_body(json!({"data": [{"id": 1, "domain": "other.example"}], "pages": 1000}).to_string())
+            .expect(MAX_PAGES as usize)
+            .create();
+
+        let client = test_client(&server.url(), "example.com");
+        assert_eq!(
+            client.list_all_domains().unwrap_err().to_string(),
+            "Linode API listed more than 50 pages for /domains"
+        );
+        pages.assert();
+    }
+
+    #[test]
+    fn test_linode_domain_not_found() {
+        let mut server = mockito::Server::new();
+        let _page1 = domains_page(&mut server, 1, 1, &[(1, "other.example")]);
+
+        let client = test_client(&server.url(), "example.com");
+        assert_eq!(client.domain_id().unwrap_err().to_string(), "Domain example.com not found in Linode account");
+    }
+
+    #[test]
+    fn test_linode_numeric_zone_and_record_name() {
+        let client = test_client("http://127.0.0.1:1", "1234");
+        assert_eq!(client.domain_id().unwrap(), 1234);
+        assert_eq!(LinodeClient::record_name("home.example.com", "example.com"), "home");
+        assert_eq!(LinodeClient::record_name("example.com", "example.com"), "");
+    }
+
+    #[test]
+    fn test_linode_update_record() {
+        let mut server = mockito::Server::new();
+        let _domains = domains_page(&mut server, 1, 1, &[(5, "example.com")]);
+        let records = server.mock("GET", "/v4/domains/5/records")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("type".into(), "A".into()),
+                Matcher::UrlEncoded("page".into(), "1".into()),
+            ]))
+            .with_body(json!({
+                "data": [
+                    {"id": 10, "type": "A", "name": "www", "target": "192.0.2.1"},
+                    {"id": 11, "type": "A", "name": "home", "target": "192.0.2.1"},
+                ],
+                "page": 1,
+                "pages": 1,
+            }).to_string())
+            .create();
+        let update = server.mock("PUT", "/v4/domains/5/records/11")
+            .match_body(Matcher::Json(json!({"target": "203.0.113.1", "ttl_sec": 300})))
+            .with_body(r#"{"id": 11}"#)
+            .create();
+
+        let client = test_client(&server.url(), "example.com");
+        client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        records.assert();
+        update.assert();
+    }
+
+    #[test]
+    fn test_linode_preview_update() {
+        let client = test_client("https://api.linode.com", "example.com");
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].url, "https://api.linode.com/v4/domains?page=1");
+        assert_eq!(requests[1].url, "https://api.linode.com/v4/domains/{domain_id}/records?type=A&page=1");
+        assert_eq!(requests[2].method, "PUT");
+        assert_eq!(requests[2].url, "https://api.linode.com/v4/domains/{domain_id}/records/{record_id}");
+        assert_eq!(requests[2].body.as_deref(), Some(r#"{"target":"203.0.113.1","ttl_sec":300}"#));
+
+        // A numeric zone is the domain ID, so there's no domain lookup
+        let client = test_client("https://api.linode.com", "1234");
+        let requests = client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, "https://api.linode.com/v4/domains/1234/records?type=AAAA&page=1");
+    }
 }
@@ -1,15 +1,274 @@
-use crate::clients::DnsClient;
+use crate::clients::{zone_for, DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
+use std::fmt;
 use std::net::IpAddr;
-use base64::{Engine as _, engine::general_purpose};
+use std::thread;
+use std::time::Duration;
+
+/// Fault code Loopia returns when requests are rate limited
+const FAULT_RATE_LIMITED: i64 = 504;
+
+/// Attempts made while the API keeps rate limiting
+const MAX_ATTEMPTS: u32 = 3;
 
 /// Loopia DNS client
-/// Uses DynDNS2 protocol with Loopia's server
+/// Uses Loopia's XML-RPC API (`https://api.loopia.se/RPCSERV`):
+/// `getZoneRecords` finds the A/AAAA record, `updateZoneRecord` sets its rdata.
+///
+/// Configuration:
+/// - login: API user (e.g. `user@loopiaapi`)
+/// - password: API user password
+/// - zone: domain name (optional, defaults to the last two labels of the hostname)
+/// - ttl: record TTL in seconds (default 3600)
 pub struct LoopiaClient {
     server: String,
     username: String,
     password: String,
+    zone: Option<String>,
+    ttl: u32,
+    /// Delay before retrying a rate-limited call
+    retry_delay: Duration,
+    http: http::HttpClient,
+}
+
+/// XML-RPC value (the subset Loopia uses)
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i64),
+    Bool(bool),
+    String(String),
+    Struct(Vec<(String, Value)>),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn str(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+
+    fn member(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(members) => members.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    fn write_xml(&self, out: &mut String) {
+        out.push_str("<value>");
+        match self {
+            Value::Int(i) => out.push_str(&format!("<int>{}</int>", i)),
+            Value::Bool(b) => out.push_str(&format!("<boolean>{}</boolean>", u8::from(*b))),
+            Value::String(s) => out.push_str(&format!("<string>{}</string>", escape(s))),
+            Value::Struct(members) => {
+                out.push_str("<struct>");
+                for (name, value) in members {
+                    out.push_str(&format!("<member><name>{}</name>", escape(name)));
+                    value.write_xml(out);
+                    out.push_str("</member>");
+                }
+                out.push_str("</struct>");
+            }
+            Value::Array(values) => {
+                out.push_str("<array><data>");
+                for value in values {
+                    value.write_xml(out);
+                }
+                out.push_str("</data></array>");
+            }
+        }
+        out.push_str("</value>");
+    }
+}
+
+/// A failed XML-RPC call
+#[derive(Debug)]
+enum RpcError {
+    Fault { code: i64, message: String },
+    Malformed(String),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcError::Fault { code, message } => write!(f, "Loopia API fault {}: {}", code, message),
+            RpcError::Malformed(reason) => write!(f, "Malformed Loopia API response: {}", reason),
+        }
+    }
+}
+
+impl Error for RpcError {}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"")
+        .replace("&apos;", "'").replace("&amp;", "&")
+}
+
+/// XML body of a `methodCall`
+fn method_call(method: &str, params: &[Value]) -> String {
+    let mut out = format!("<?xml version=\"1.0\"?><methodCall><methodName>{}</methodName><params>", method);
+    for param in params {
+        out.push_str("<param>");
+        param.write_xml(&mut out);
+        out.push_str("</param>");
+    }
+    out.push_str("</params></methodCall>");
+    out
+}
+
+/// Minimal pull parser over the tags of an XML-RPC response
+struct Parser<'a> {
+    input: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        let mut parser = Parser { input };
+        parser.skip_ws();
+        if parser.input.starts_with("<?") {
+            let end = parser.input.find("?>").map_or(parser.input.len(), |i| i + 2);
+            parser.input = &parser.input[end..];
+        }
+        parser
+    }
+
+    fn skip_ws(&mut self) {
+        self.input = self.input.trim_start();
+    }
+
+    /// Name of the next tag (`/name` for closing tags) without consuming it
+    fn peek_tag(&mut self) -> Option<&'a str> {
+        self.skip_ws();
+        let rest = self.input.strip_prefix('<')?;
+        let end = rest.find('>')?;
+        Some(rest[..end].trim_end_matches('/').trim())
+    }
+
+    fn expect(&mut self, tag: &str) -> Result<(), RpcError> {
+        match self.peek_tag() {
+            Some(found) if found == tag => {
+                let end = self.input.find('>').unwrap_or(0);
+                self.input = &self.input[end + 1..];
+                Ok(())
+            }
+            found => Err(RpcError::Malformed(format!("expected <{}>, found {:?}", tag, found))),
+        }
+    }
+
+    /// Consume `<tag/>` if that is next, returning whether it was
+    fn empty_tag(&mut self, tag: &str) -> bool {
+        self.skip_ws();
+        for form in [format!("<{}/>", tag), format!("<{} />", tag)] {
+            if let Some(rest) = self.input.strip_prefix(form.as_str()) {
+                self.input = rest;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Text up to the next tag
+    fn text(&mut self) -> String {
+        let end = self.input.find('<').unwrap_or(self.input.len());
+        let text = unescape(&self.input[..end]);
+        self.input = &self.input[end..];
+        text
+    }
+
+    /// `<tag>text</tag>`
+    fn text_element(&mut self, tag: &str) -> Result<String, RpcError> {
+        if self.empty_tag(tag) {
+            return Ok(String::new());
+        }
+        self.expect(tag)?;
+        let text = self.text();
+        self.expect(&format!("/{}", tag))?;
+        Ok(text)
+    }
+
+    fn value(&mut self) -> Result<Value, RpcError> {
+        self.expect("value")?;
+        let value = match self.peek_tag() {
+            Some("/value") => Value::String(String::new()),
+            Some(tag @ ("int" | "i4" | "i8")) => {
+                let text = self.text_element(tag)?;
+                Value::Int(text.trim().parse().map_err(|_| RpcError::Malformed(format!("bad integer {:?}", text)))?)
+            }
+            Some("boolean") => Value::Bool(self.text_element("boolean")?.trim() == "1"),
+            Some("string") => Value::String(self.text_element("string")?),
+            Some("double") => Value::String(self.text_element("double")?),
+            Some("struct") => {
+                self.expect("struct")?;
+                let mut members = Vec::new();
+                while self.peek_tag() == Some("member") {
+                    self.expect("member")?;
+                    let name = self.text_element("name")?;
+                    let value = self.value()?;
+                    self.expect("/member")?;
+                    members.push((name, value));
+                }
+                self.expect("/struct")?;
+                Value::Struct(members)
+            }
+            Some("array") => {
+                self.expect("array")?;
+                let mut values = Vec::new();
+                if !self.empty_tag("data") {
+                    self.expect("data")?;
+                    while self.peek_tag() == Some("value") {
+                        values.push(self.value()?);
+                    }
+                    self.expect("/data")?;
+                }
+                self.expect("/array")?;
+                Value::Array(values)
+            }
+            Some(tag) => return Err(RpcError::Malformed(format!("unsupported type <{}>", tag))),
+            None => Value::String(self.text()),
+        };
+        self.expect("/value")?;
+        Ok(value)
+    }
+}
+
+/// Parse a `methodResponse`, turning `<fault>` into `RpcError::Fault`
+fn parse_response(body: &str) -> Result<Value, RpcError> {
+    let mut parser = Parser::new(body);
+    parser.expect("methodResponse")?;
+    match parser.peek_tag() {
+        Some("fault") => {
+            parser.expect("fault")?;
+            let fault = parser.value()?;
+            Err(RpcError::Fault {
+                code: fault.member("faultCode").and_then(Value::as_int).unwrap_or(0),
+                message: fault.member("faultString").and_then(Value::as_str).unwrap_or("").to_string(),
+            })
+        }
+        _ => {
+            parser.expect("params")?;
+            parser.expect("param")?;
+            parser.value()
+        }
+    }
 }
 
 impl LoopiaClient {
@@ -20,60 +279,124 @@ impl LoopiaClient {
         let password = config.password.as_ref()
             .ok_or("password is required for Loopia")?
             .clone();
-        
+
         let server = config.server.as_ref()
             .cloned()
-            .unwrap_or_else(|| "https://dns.loopia.se".to_string());
+            .unwrap_or_else(|| "https://api.loopia.se/RPCSERV".to_string());
 
         Ok(LoopiaClient {
             server,
             username,
             password,
+            zone: config.zone.clone(),
+            ttl: config.ttl.unwrap_or(3600),
+            retry_delay: Duration::from_secs(1),
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// Hostname relative to the zone ("@" for the apex)
+    fn subdomain<'a>(hostname: &'a str, zone: &str) -> &'a str {
+        if hostname == zone {
+            "@"
+        } else {
+            hostname.strip_suffix(&format!(".{}", zone)).unwrap_or(hostname)
+        }
+    }
+
+    /// Request body for `method` with the credentials prepended
+    fn call_body(&self, method: &str, params: Vec<Value>) -> String {
+        let mut all_params = vec![Value::str(&self.username), Value::str(&self.password)];
+        all_params.extend(params);
+        method_call(method, &all_params)
+    }
+
+    /// Call `method`, retrying while rate limited
+    fn call(&self, method: &str, params: Vec<Value>) -> Result<Value, Box<dyn Error>> {
+        let body = self.call_body(method, params);
+
+        let mut attempt = 1;
+        loop {
+            let response = self.http.prepare(minreq::post(&self.server))
+                .with_header("Content-Type", "text/xml")
+                .with_body(body.as_str())
+                .send()?;
+
+            if response.status_code != 200 {
+                return Err(format!("HTTP error: {}", response.status_code).into());
+            }
+
+            let result = parse_response(response.as_str()?);
+            let rate_limited = match &result {
+                Err(RpcError::Fault { code, .. }) => *code == FAULT_RATE_LIMITED,
+                Ok(value) => value.as_str() == Some("RATE_LIMITED"),
+                Err(_) => false,
+            };
+            if !rate_limited || attempt >= MAX_ATTEMPTS {
+                return Ok(result?);
+            }
+
+            log::warn!("Loopia API rate limited, retrying {} in {:?}", method, self.retry_delay);
+            thread::sleep(self.retry_delay);
+            attempt += 1;
+        }
+    }
+
+    /// Map Loopia's status strings to a result
+    fn check_status(value: &Value) -> Result<(), Box<dyn Error>> {
+        match value.as_str() {
+            Some("OK") => Ok(()),
+            Some("AUTH_ERROR") => Err("Authentication failed - check username and password".into()),
+            Some("RATE_LIMITED") => Err("Rate limited by Loopia API - try again later".into()),
+            Some("BAD_INDATA") => Err("Loopia rejected the record data".into()),
+            Some(status) => Err(format!("Loopia API error: {}", status).into()),
+            None => Err(format!("Unexpected Loopia response: {:?}", value).into()),
+        }
+    }
 }
 
 impl DnsClient for LoopiaClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
-            "{}/XDynDNSServer/XDynDNS.php?hostname={}&myip={}",
-            self.server, hostname, ip
-        );
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let subdomain = Self::subdomain(hostname, &zone);
 
-        log::info!("Updating {} with Loopia", hostname);
-
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", 
-                general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password))))
-            .send()?;
-
-        let status_code = response.status_code;
-        let body = response.as_str()?.trim();
-
-        log::debug!("Response status: {}, body: {}", status_code, body);
-
-        if status_code != 200 {
-            return Err(format!("HTTP error: {}", status_code).into());
-        }
-
-        // Parse DynDNS2-style response
-        if body.starts_with("good") || body.starts_with("nochg") {
-            log::info!("Successfully updated {} to {}", hostname, ip);
-            Ok(())
-        } else if body.starts_with("badauth") {
-            Err("Authentication failed - check username and password".into())
-        } else if body.starts_with("notfqdn") {
-            Err("Invalid hostname format".into())
-        } else if body.starts_with("nohost") {
-            Err("Hostname does not exist".into())
-        } else if body.starts_with("abuse") {
-            Err("Account blocked for abuse".into())
-        } else if body.starts_with("911") {
-            Err("Server error - try again later".into())
-        } else {
-            Err(format!("Unexpected response: {}", body).into())
+        log::info!("Updating {} {} record with Loopia", hostname, record_type);
+
+        let records = self.call("getZoneRecords", vec![Value::str(&zone), Value::str(subdomain)])?;
+        let records = match records {
+            Value::Array(records) => records,
+            other => return Self::check_status(&other).and(Err("Unexpected getZoneRecords response".into())),
+        };
+
+        let record = records.iter()
+            .find(|record| record.member("type").and_then(Value::as_str) == Some(record_type))
+            .ok_or_else(|| format!("No {} record found for {}", record_type, hostname))?;
+
+        if record.member("rdata").and_then(Value::as_str) == Some(ip.to_string().as_str()) {
+            log::info!("Record {} already set to {}, no update needed", hostname, ip);
+            return Ok(());
         }
+
+        let record_id = record.member("record_id").and_then(Value::as_int)
+            .ok_or("Loopia record has no record_id")?;
+        let priority = record.member("priority").and_then(Value::as_int).unwrap_or(0);
+
+        let updated = Value::Struct(vec![
+            ("type".to_string(), Value::str(record_type)),
+            ("ttl".to_string(), Value::Int(self.ttl.into())),
+            ("priority".to_string(), Value::Int(priority)),
+            ("rdata".to_string(), Value::String(ip.to_string())),
+            ("record_id".to_string(), Value::Int(record_id)),
+        ]);
+        let status = self.call("updateZoneRecord", vec![Value::str(&zone), Value::str(subdomain), updated])?;
+        Self::check_status(&status)?;
+
+        log::info!("Successfully updated {} to {}", hostname, ip);
+        Ok(())
     }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
@@ -89,4 +412,168 @@ impl DnsClient for LoopiaClient {
     fn provider_name(&self) -> &str {
         "Loopia"
     }
+
+    /// `updateZoneRecord` is skipped when the record is already current
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let subdomain = Self::subdomain(hostname, &zone);
+        let updated = Value::Struct(vec![
+            ("type".to_string(), Value::str(record_type)),
+            ("ttl".to_string(), Value::Int(self.ttl.into())),
+            ("priority".to_string(), Value::str("{priority}")),
+            ("rdata".to_string(), Value::String(ip.to_string())),
+            ("record_id".to_string(), Value::str("{record_id}")),
+        ]);
+        Ok([
+            self.call_body("getZoneRecords", vec![Value::str(&zone), Value::str(subdomain)]),
+            self.call_body("updateZoneRecord", vec![Value::str(&zone), Value::str(subdomain), updated]),
+        ]
+        .into_iter()
+        .map(|body| self.http.preview("POST", self.server.as_str())
+            .with_header("Content-Type", "text/xml")
+            .with_body(body)
+            .with_secret(&escape(&self.password)))
+        .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    const UPDATE_REQUEST: &str = include_str!("../../tests/fixtures/loopia/update_zone_record_request.xml");
+    const ZONE_RECORDS: &str = include_str!("../../tests/fixtures/loopia/get_zone_records_response.xml");
+    const OK_RESPONSE: &str = include_str!("../../tests/fixtures/loopia/ok_response.xml");
+    const RATE_LIMIT_FAULT: &str = include_str!("../../tests/fixtures/loopia/rate_limit_fault.xml");
+
+    /// Drop the indentation between tags of a pretty-printed fixture
+    fn compact(xml: &str) -> String {
+        xml.lines().map(str::trim).collect()
+    }
+
+    fn test_client(server: &str) -> LoopiaClient {
+        let config = Config {
+            protocol: Some("loopia".to_string()),
+            login: Some("user@loopiaapi".to_string()),
+            password: Some("secret".to_string()),
+            server: Some(server.to_string()),
+            ttl: Some(300),
+            ..Default::default()
+        };
+        let mut client = LoopiaClient::new(&config).unwrap();
+        client.retry_delay = Duration::from_millis(1);
+        client
+    }
+
+    #[test]
+    fn test_loopia_method_call_fixture() {
+        let record = Value::Struct(vec![
+            ("type".to_string(), Value::str("A")),
+            ("ttl".to_string(), Value::Int(300)),
+            ("priority".to_string(), Value::Int(0)),
+            ("rdata".to_string(), Value::str("203.0.113.1")),
+            ("record_id".to_string(), Value::Int(12345)),
+        ]);
+        let body = method_call("updateZoneRecord", &[
+            Value::str("user@loopiaapi"),
+            Value::str("p&ss<word>"),
+            Value::str("example.com"),
+            Value::str("home"),
+            record,
+        ]);
+        assert_eq!(body, compact(UPDATE_REQUEST));
+    }
+
+    #[test]
+    fn test_loopia_parse_responses() {
+        assert_eq!(parse_response(OK_RESPONSE).unwrap(), Value::str("OK"));
+
+        let records = parse_response(ZONE_RECORDS).unwrap();
+        let Value::Array(records) = records else { panic!("expected array") };
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].member("type"), Some(&Value::str("A")));
+        assert_eq!(records[1].member("ttl"), Some(&Value::Int(3600)));
+        assert_eq!(records[1].member("rdata"), Some(&Value::str("192.0.2.1")));
+        assert_eq!(records[1].member("record_id"), Some(&Value::Int(12345)));
+
+        match parse_response(RATE_LIMIT_FAULT) {
+            Err(RpcError::Fault { code, message }) => {
+                assert_eq!(code, 504);
+                assert_eq!(message, "Too many requests");
+            }
+            other => panic!("expected fault, got {:?}", other),
+        }
+
+        assert!(matches!(parse_response("<html>Bad Gateway</html>"), Err(RpcError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_loopia_check_status() {
+        assert!(LoopiaClient::check_status(&Value::str("OK")).is_ok());
+        assert_eq!(
+            LoopiaClient::check_status(&Value::str("AUTH_ERROR")).unwrap_err().to_string(),
+            "Authentication failed - check username and password"
+        );
+        assert!(LoopiaClient::check_status(&Value::str("UNKNOWN_ERROR")).is_err());
+    }
+
+    #[test]
+    fn test_loopia_update_record() {
+        let mut server = mockito::Server::new();
+        let get = server.mock("POST", "/RPCSERV")
+            .match_body(Matcher::Regex("<methodName>getZoneRecords</methodName>".to_string()))
+            .with_body(ZONE_RECORDS)
+            .create();
+        let update = server.mock("POST", "/RPCSERV")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex("<methodName>updateZoneRecord</methodName>".to_string()),
+                Matcher::Regex("<name>rdata</name><value><string>203.0.113.1</string>".to_string()),
+                Matcher::Regex("<name>record_id</name><value><int>12345</int>".to_string()),
+            ]))
+            .with_body(OK_RESPONSE)
+            .create();
+
+        let client = test_client(&format!("{}/RPCSERV", server.url()));
+        client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        get.assert();
+        update.assert();
+    }
+
+    #[test]
+    fn test_loopia_retries_rate_limit_fault() {
+        let mut server = mockito::Server::new();
+        let limited = server.mock("POST", "/RPCSERV")
+            .with_body(RATE_LIMIT_FAULT)
+            .expect(1)
+            .create();
+        let records = server.mock("POST", "/RPCSERV")
+            .with_body(ZONE_RECORDS)
+            .expect(1)
+            .create();
+
+        let client = test_client(&format!("{}/RPCSERV", server.url()));
+        // The A record already holds this address, so only getZoneRecords is needed
+        client.update_record("home.example.com", "192.0.2.1".parse().unwrap()).unwrap();
+        limited.assert();
+        records.assert();
+    }
+
+    #[test]
+    fn test_loopia_preview_update() {
+        let mut client = test_client("https://api.loopia.se/RPCSERV");
+        client.password = "loopia-password".to_string();
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|request| request.method == "POST" && request.url == "https://api.loopia.se/RPCSERV"));
+        let lookup = requests[0].body.as_deref().unwrap();
+        assert!(lookup.contains("<methodName>getZoneRecords</methodName>"));
+        assert!(lookup.contains("<string>example.com</string></value></param><param><value><string>home</string>"));
+        let update = requests[1].body.as_deref().unwrap();
+        assert!(update.contains("<methodName>updateZoneRecord</methodName>"));
+        assert!(update.contains("<string>203.0.113.1</string>"));
+        assert!(update.contains("{record_id}"));
+        assert!(!requests[1].to_string().contains("loopia-password"));
+    }
 }
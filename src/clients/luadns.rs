@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -11,6 +12,7 @@ pub struct LuadnsClient {
     token: String,
     zone_id: String,
     record_id: String,
+    http: http::HttpClient,
 }
 
 impl LuadnsClient {
@@ -40,8 +42,22 @@ impl LuadnsClient {
             token,
             zone_id,
             record_id,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// LuaDNS API endpoint
+    fn record_url(&self) -> String {
+        format!("{}/v1/zones/{}/records/{}", self.server, self.zone_id, self.record_id)
+    }
+
+    fn record_body(record_type: &str, ip: IpAddr) -> String {
+        format!(r#"{{"content":"{}","type":"{}"}}"#, ip, record_type)
+    }
+
+    fn auth_header(&self) -> String {
+        format!("Basic {}", base64::encode(&format!("{}:{}", self.email, self.token)))
+    }
 }
 
 impl DnsClient for LuadnsClient {
@@ -53,25 +69,11 @@ impl DnsClient for LuadnsClient {
 
         log::info!("Updating {} with LuaDNS", hostname);
 
-        // LuaDNS API endpoint
-        let url = format!("{}/v1/zones/{}/records/{}", 
-            self.server, self.zone_id, self.record_id);
-
-        let body = format!(
-            r#"{{"content":"{}","type":"{}"}}"#,
-            ip,
-            record_type
-        );
-
-        let auth = format!("{}:{}", self.email, self.token);
-        let encoded_auth = format!("Basic {}", base64::encode(&auth));
-
-        let response = minreq::put(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", &encoded_auth)
+        let response = self.http.prepare(minreq::put(self.record_url()))
+            .with_header("Authorization", self.auth_header())
             .with_header("Content-Type", "application/json")
             .with_header("Accept", "application/json")
-            .with_body(body)
+            .with_body(Self::record_body(record_type, ip))
             .send()?;
 
         let status_code = response.status_code;
@@ -108,6 +110,15 @@ impl DnsClient for LuadnsClient {
     fn provider_name(&self) -> &str {
         "LuaDNS"
     }
+
+    fn preview_update(&self, _hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        Ok(vec![self.http.preview("PUT", self.record_url())
+            .with_header("Authorization", self.auth_header())
+            .with_header("Content-Type", "application/json")
+            .with_header("Accept", "application/json")
+            .with_body(Self::record_body(record_type, ip))])
+    }
 }
 
 mod base64 {
@@ -117,3 +128,28 @@ mod base64 {
         general_purpose::STANDARD.encode(data.as_bytes())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luadns_preview_update() {
+        let config = Config {
+            protocol: Some("luadns".to_string()),
+            login: Some("user@example.com".to_string()),
+            password: Some("luadns-token".to_string()),
+            zone: Some("42".to_string()),
+            host: Some("1001".to_string()),
+            ..Default::default()
+        };
+        let client = LuadnsClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].url, "https://api.luadns.com/v1/zones/42/records/1001");
+        assert_eq!(requests[0].body.as_deref(), Some(r#"{"content":"2001:db8::1","type":"AAAA"}"#));
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), client.auth_header())));
+    }
+}
@@ -1,7 +1,11 @@
+use crate::utils::mask_credential;
 use std::error::Error;
+use std::fmt;
 use std::net::IpAddr;
 
 pub mod afraid;
+pub mod azure;
+pub mod bunny;
 pub mod changeip;
 pub mod cloudflare;
 pub mod cloudns;
@@ -12,6 +16,7 @@ pub mod digitalocean;
 pub mod dinahosting;
 pub mod directnic;
 pub mod dnsexit2;
+pub mod dnsimple;
 pub mod dnsmadeeasy;
 pub mod dnspod;
 pub mod domeneshop;
@@ -27,6 +32,7 @@ pub mod enom;
 pub mod freedns;
 pub mod freemyip;
 pub mod gandi;
+pub mod gcloud;
 pub mod godaddy;
 pub mod googledomains;
 pub mod he;
@@ -39,9 +45,11 @@ pub mod loopia;
 pub mod luadns;
 pub mod mythicbeasts;
 pub mod namecheap;
+pub mod netlify;
 pub mod nfsn;
 pub mod njalla;
 pub mod noip;
+pub mod ns1;
 pub mod nsupdate;
 pub mod one984;
 pub mod ovh;
@@ -49,11 +57,81 @@ pub mod porkbun;
 pub mod regfish;
 pub mod selfhost;
 pub mod sitelutions;
+pub mod vultr;
 pub mod woima;
 pub mod yandex;
 pub mod zoneedit;
 pub mod zoneedit1;
 
+/// Supported provider names, aliases separated by '/'
+pub const PROVIDERS: &[&str] = &[
+    "1984/one984",
+    "afraid",
+    "azure/azure-dns",
+    "bunny/bunny-dns",
+    "changeip",
+    "cloudflare",
+    "cloudns",
+    "ddnsfm/ddns.fm",
+    "ddnss",
+    "desec",
+    "digitalocean",
+    "dinahosting",
+    "directnic",
+    "dnsexit/dnsexit2",
+    "dnsimple",
+    "dnsmadeeasy/dns-made-easy",
+    "dnspod",
+    "domeneshop",
+    "dondominio",
+    "dslreports/dslreports1",
+    "duckdns",
+    "dyndns1",
+    "dyndns/dyndns2",
+    "dynu",
+    "easydns",
+    "emailonly",
+    "enom",
+    "freedns",
+    "freemyip",
+    "gandi",
+    "gcloud/google-cloud-dns",
+    "godaddy",
+    "googledomains/google-domains",
+    "he/hurricane/hurricaneelectric",
+    "hetzner",
+    "infomaniak",
+    "inwx",
+    "keysystems/key-systems",
+    "linode",
+    "loopia",
+    "luadns",
+    "mythicbeasts/mythic-beasts/mythicdyn",
+    "namecheap",
+    "netlify",
+    "nfsn",
+    "njalla",
+    "noip/no-ip",
+    "ns1",
+    "nsupdate",
+    "ovh",
+    "porkbun",
+    "regfish",
+    "selfhost",
+    "sitelutions",
+    "vultr",
+    "woima",
+    "yandex",
+    "zoneedit",
+    "zoneedit1",
+];
+
+/// True when `provider` (or one of its aliases) is in `PROVIDERS`
+pub fn is_supported(provider: &str) -> bool {
+    let normalized = provider.to_ascii_lowercase();
+    PROVIDERS.iter().any(|names| names.split('/').any(|name| name == normalized))
+}
+
 /// Common trait that all DNS client implementations must implement
 pub trait DnsClient {
     /// Update DNS record with the provided IP address
@@ -65,6 +143,166 @@ pub trait DnsClient {
 
     /// Get the provider name for logging purposes
     fn provider_name(&self) -> &str;
+
+    /// Update several hostnames to the same IP in one request, with a result per hostname.
+    /// Returns None for providers without a batch API; their hosts are updated one by one.
+    fn update_records(&self, _hostnames: &[String], _ip: IpAddr) -> Option<HostResults> {
+        None
+    }
+
+    /// The requests `update_record` would send, for --dry-run.
+    /// Values only known from a provider response are shown as placeholders.
+    /// Empty for providers that do not update over HTTP; an error when the update would fail
+    /// before sending anything (e.g. a hostname outside the zone).
+    fn preview_update(&self, _hostname: &str, _ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Outcome of a batch update for each hostname
+pub type HostResults = Vec<(String, Result<(), Box<dyn Error>>)>;
+
+/// The outcome of a request covering every hostname at once, repeated per hostname
+pub fn same_result(hostnames: &[String], result: Result<(), Box<dyn Error>>) -> HostResults {
+    let message = result.err().map(|e| e.to_string());
+    hostnames.iter()
+        .map(|hostname| (hostname.clone(), match &message {
+            None => Ok(()),
+            Some(message) => Err(message.as_str().into()),
+        }))
+        .collect()
+}
+
+/// Zone a hostname belongs to: the configured zone, or a guess at its last two labels.
+/// The guess is wrong for zones such as co.uk, so it warns and points at the `zone` option.
+pub fn zone_for(configured: Option<&str>, hostname: &str) -> String {
+    if let Some(zone) = configured {
+        return zone.to_string();
+    }
+    let hostname = hostname.trim_end_matches('.');
+    let labels: Vec<&str> = hostname.rsplitn(3, '.').collect();
+    let zone = if labels.len() >= 2 {
+        format!("{}.{}", labels[1], labels[0])
+    } else {
+        hostname.to_string()
+    };
+    log::warn!("No zone configured for {}, guessing {} (set zone= if that is wrong)", hostname, zone);
+    zone
+}
+
+/// One HTTP request a client would send; built with `HttpClient::preview`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestPreview {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// Credentials masked wherever they appear in the URL or body
+    pub secrets: Vec<String>,
+}
+
+impl RequestPreview {
+    pub fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Mask `secret` when printed, for credentials sent in a URL path or a body
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        if !secret.is_empty() {
+            self.secrets.push(secret.to_string());
+        }
+        self
+    }
+
+    /// `text` with every registered secret masked
+    fn mask_secrets(&self, text: &str) -> String {
+        self.secrets.iter()
+            .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), &mask_credential(secret)))
+    }
+}
+
+/// Headers whose values are safe to print unmasked
+const PLAIN_HEADERS: &[&str] = &["user-agent", "content-type", "accept"];
+
+/// Query parameters carrying credentials
+const SECRET_PARAMS: &[&str] = &["token", "password", "pass", "pw", "key", "k", "apikey", "api_key", "secret"];
+
+impl fmt::Display for RequestPreview {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "  {} {}", self.method, self.mask_secrets(&mask_url(&self.url)))?;
+        for (name, value) in &self.headers {
+            if PLAIN_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                writeln!(f, "    {}: {}", name, value)?;
+            } else {
+                writeln!(f, "    {}: {}", name, mask_credential(value))?;
+            }
+        }
+        if let Some(body) = &self.body {
+            writeln!(f, "    body: {}", self.mask_secrets(body))?;
+        }
+        Ok(())
+    }
+}
+
+/// `url` with the values of credential query parameters masked
+fn mask_url(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let params: Vec<String> = query.split('&')
+        .map(|param| match param.split_once('=') {
+            Some((name, value)) if SECRET_PARAMS.contains(&name.to_ascii_lowercase().as_str()) => {
+                format!("{}={}", name, mask_credential(value))
+            }
+            _ => param.to_string(),
+        })
+        .collect();
+    format!("{}?{}", base, params.join("&"))
+}
+
+/// Wraps a client for --dry-run: updates print the requests instead of sending them
+pub struct DryRunClient {
+    inner: Box<dyn DnsClient>,
+}
+
+impl DryRunClient {
+    pub fn new(inner: Box<dyn DnsClient>) -> Self {
+        Self { inner }
+    }
+
+    /// What `update_record` prints; an error when the real update would fail before sending anything
+    fn describe(&self, hostname: &str, ip: IpAddr) -> Result<String, Box<dyn Error>> {
+        let mut out = format!("DRY RUN: {} update of {} to {}\n", self.inner.provider_name(), hostname, ip);
+        let requests = self.inner.preview_update(hostname, ip)?;
+        if requests.is_empty() {
+            out.push_str("  (request details not available for this provider)\n");
+        }
+        for request in requests {
+            out.push_str(&request.to_string());
+        }
+        Ok(out)
+    }
+}
+
+impl DnsClient for DryRunClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        print!("{}", self.describe(hostname, ip)?);
+        Ok(())
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn Error>> {
+        self.inner.validate_config()
+    }
+
+    fn provider_name(&self) -> &str {
+        self.inner.provider_name()
+    }
 }
 
 /// Factory function to create the appropriate DNS client based on provider type
@@ -74,6 +312,8 @@ pub fn create_client(provider: &str, config: &crate::config::Config) -> Result<B
     match normalized.as_str() {
         "1984" | "one984" => Ok(Box::new(one984::One984Client::new(config)?)),
         "afraid" => Ok(Box::new(afraid::AfraidClient::new(config)?)),
+        "azure" | "azure-dns" => Ok(Box::new(azure::AzureDnsClient::new(config)?)),
+        "bunny" | "bunny-dns" => Ok(Box::new(bunny::BunnyDnsClient::new(config)?)),
         "changeip" => Ok(Box::new(changeip::ChangeipClient::new(config)?)),
         "cloudflare" => Ok(Box::new(cloudflare::CloudflareClient::new(config)?)),
         "cloudns" => Ok(Box::new(cloudns::CloudnsClient::new(config)?)),
@@ -85,6 +325,7 @@ pub fn create_client(provider: &str, config: &crate::config::Config) -> Result<B
         "dinahosting" => Ok(Box::new(dinahosting::DinahostingClient::new(config)?)),
         "directnic" => Ok(Box::new(directnic::DirectnicClient::new(config)?)),
         "dnsexit" | "dnsexit2" => Ok(Box::new(dnsexit2::Dnsexit2Client::new(config)?)),
+        "dnsimple" => Ok(Box::new(dnsimple::DnsimpleClient::new(config)?)),
         "dnsmadeeasy" | "dns-made-easy" => Ok(Box::new(dnsmadeeasy::DnsMadeEasyClient::new(config)?)),
         "dnspod" => Ok(Box::new(dnspod::DnspodClient::new(config)?)),
         "domeneshop" => Ok(Box::new(domeneshop::DomeneshopClient::new(config)?)),
@@ -100,6 +341,7 @@ pub fn create_client(provider: &str, config: &crate::config::Config) -> Result<B
         "freedns" => Ok(Box::new(freedns::FreednsClient::new(config)?)),
         "freemyip" => Ok(Box::new(freemyip::FreemyipClient::new(config)?)),
         "gandi" => Ok(Box::new(gandi::GandiClient::new(config)?)),
+        "gcloud" | "google-cloud-dns" => Ok(Box::new(gcloud::GCloudDnsClient::new(config)?)),
         "godaddy" => Ok(Box::new(godaddy::GoDaddyClient::new(config)?)),
         "googledomains" | "google-domains" => Ok(Box::new(googledomains::GoogleDomainsClient::new(config)?)),
         "he" | "hurricane" | "hurricaneelectric" => Ok(Box::new(he::HurricaneElectricClient::new(config)?)),
@@ -112,19 +354,116 @@ pub fn create_client(provider: &str, config: &crate::config::Config) -> Result<B
         "luadns" => Ok(Box::new(luadns::LuadnsClient::new(config)?)),
         "mythicbeasts" | "mythic-beasts" | "mythicdyn" => Ok(Box::new(mythicbeasts::MythicbeastsClient::new(config)?)),
         "namecheap" => Ok(Box::new(namecheap::NamecheapClient::new(config)?)),
+        "netlify" => Ok(Box::new(netlify::NetlifyClient::new(config)?)),
         "nfsn" => Ok(Box::new(nfsn::NfsnClient::new(config)?)),
         "njalla" => Ok(Box::new(njalla::NjallaClient::new(config)?)),
         "noip" | "no-ip" => Ok(Box::new(noip::NoIpClient::new(config)?)),
+        "ns1" => Ok(Box::new(ns1::Ns1Client::new(config)?)),
         "nsupdate" => Ok(Box::new(nsupdate::NsupdateClient::new(config)?)),
         "ovh" => Ok(Box::new(ovh::OvhClient::new(config)?)),
         "porkbun" => Ok(Box::new(porkbun::PorkbunClient::new(config)?)),
         "regfish" => Ok(Box::new(regfish::RegfishClient::new(config)?)),
         "selfhost" => Ok(Box::new(selfhost::SelfhostClient::new(config)?)),
         "sitelutions" => Ok(Box::new(sitelutions::SitelutionsClient::new(config)?)),
+        "vultr" => Ok(Box::new(vultr::VultrClient::new(config)?)),
         "woima" => Ok(Box::new(woima::WoimaClient::new(config)?)),
         "yandex" => Ok(Box::new(yandex::YandexClient::new(config)?)),
         "zoneedit" => Ok(Box::new(zoneedit::ZoneeditClient::new(config)?)),
         "zoneedit1" => Ok(Box::new(zoneedit1::Zoneedit1Client::new(config)?)),
-        _ => Err(format!("Unsupported provider: {}. Supported providers: 1984/one984, afraid, changeip, cloudflare, cloudns, ddnsfm/ddns.fm, ddnss, desec, digitalocean, dinahosting, directnic, dnsexit/dnsexit2, dnsmadeeasy/dns-made-easy, dnspod, domeneshop, dondominio, dslreports/dslreports1, duckdns, dyndns1, dyndns/dyndns2, dynu, easydns, emailonly, enom, freedns, freemyip, gandi, godaddy, googledomains/google-domains, he/hurricane/hurricaneelectric, hetzner, infomaniak, inwx, keysystems/key-systems, linode, loopia, luadns, mythicbeasts/mythic-beasts/mythicdyn, namecheap, nfsn, njalla, noip/no-ip, nsupdate, ovh, porkbun, regfish, selfhost, sitelutions, woima, yandex, zoneedit, zoneedit1", provider).into()),
+        _ => Err(format!("Unsupported provider: {}. Supported providers: {}", provider, PROVIDERS.join(", ")).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_mask_url() {
+        assert_eq!(
+            mask_url("https://www.duckdns.org/update?domains=home&token=abcdef123456&ip=192.0.2.1"),
+            "https://www.duckdns.org/update?domains=home&token=****3456&ip=192.0.2.1"
+        );
+        assert_eq!(mask_url("https://example.com/update"), "https://example.com/update");
+    }
+
+    #[test]
+    fn test_request_preview_masks_auth_headers() {
+        let preview = RequestPreview {
+            method: "PUT",
+            url: "https://api.example.com/records/1".to_string(),
+            headers: vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("Authorization".to_string(), "Bearer secret-token-1234".to_string()),
+            ],
+            body: Some(r#"{"content":"192.0.2.1"}"#.to_string()),
+            secrets: Vec::new(),
+        };
+        assert_eq!(
+            preview.to_string(),
+            "  PUT https://api.example.com/records/1\n    Content-Type: application/json\n    Authorization: ****1234\n    body: {\"content\":\"192.0.2.1\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_request_preview_masks_secrets() {
+        let http = crate::http::HttpClientBuilder::new(&Config::default()).build();
+        let preview = http.preview("POST", "https://api.example.com/update/abcdef123456")
+            .with_body(r#"{"secretapikey":"sk1-9876543210"}"#)
+            .with_secret("abcdef123456")
+            .with_secret("sk1-9876543210");
+        let text = preview.to_string();
+        assert!(text.contains("POST https://api.example.com/update/****3456\n"));
+        assert!(text.contains(r#"body: {"secretapikey":"****3210"}"#));
+    }
+
+    #[test]
+    fn test_dry_run_client_describes_update() {
+        let config = Config {
+            password: Some("abcdef123456".to_string()),
+            ..Default::default()
+        };
+        let client = DryRunClient::new(create_client("duckdns", &config).unwrap());
+        assert_eq!(client.provider_name(), "DuckDNS");
+        let description = client.describe("home.duckdns.org", "192.0.2.1".parse().unwrap()).unwrap();
+        assert!(description.starts_with("DRY RUN: DuckDNS update of home.duckdns.org to 192.0.2.1\n"));
+        assert!(description.contains("GET https://www.duckdns.org/update?domains=home&token=****3456&ip=192.0.2.1"));
+        assert!(!description.contains("abcdef123456"));
+    }
+
+    #[test]
+    fn test_dry_run_client_without_preview() {
+        // nsupdate speaks DNS, not HTTP
+        let config = Config {
+            login: Some("example.com".to_string()),
+            password: Some("key".to_string()),
+            ..Default::default()
+        };
+        let client = DryRunClient::new(create_client("nsupdate", &config).unwrap());
+        assert!(client.describe("home.example.com", "192.0.2.1".parse().unwrap()).unwrap()
+            .contains("request details not available"));
+    }
+
+    #[test]
+    fn test_dry_run_client_reports_preview_errors() {
+        let config = Config {
+            login: Some("user".to_string()),
+            password: Some("token".to_string()),
+            zone: Some("example.com".to_string()),
+            ..Default::default()
+        };
+        let client = DryRunClient::new(create_client("easydns", &config).unwrap());
+        let err = client.update_record("home.example.org", "192.0.2.1".parse().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("outside zone"), "{}", err);
+    }
+
+    #[test]
+    fn test_zone_for() {
+        assert_eq!(zone_for(None, "home.example.com"), "example.com");
+        assert_eq!(zone_for(None, "a.home.example.com."), "example.com");
+        assert_eq!(zone_for(None, "example.com"), "example.com");
+        assert_eq!(zone_for(None, "localhost"), "localhost");
+        assert_eq!(zone_for(Some("example.co.uk"), "home.example.co.uk"), "example.co.uk");
     }
 }
@@ -1,13 +1,34 @@
-use crate::clients::DnsClient;
+use crate::clients::{zone_for, DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use base64::{Engine as _, engine::general_purpose};
+use serde_json::json;
 use std::error::Error;
 use std::net::IpAddr;
-use base64::{Engine as _, engine::general_purpose};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+const DEFAULT_AUTH_URL: &str = "https://auth.mythic-beasts.com/login";
+
+/// Mythic Beasts DNS API client
+/// Authenticates with an API key (OAuth2 client credentials) and replaces the
+/// host's A/AAAA records via `PUT /beta/dns/zones/{zone}/records/{host}/{type}`
+///
+/// Configuration:
+/// - login: API key ID
+/// - password: API key secret
+/// - zone: DNS zone (optional, defaults to the last two labels of the hostname)
+/// - ttl: record TTL in seconds (default 300)
 pub struct MythicbeastsClient {
     username: String,
     password: String,
     server: String,
+    auth_url: String,
+    zone: Option<String>,
+    ttl: u32,
+    /// Cached access token and its expiry (Unix epoch seconds)
+    token: Mutex<Option<(String, u64)>>,
+    http: http::HttpClient,
 }
 
 impl MythicbeastsClient {
@@ -16,40 +37,106 @@ impl MythicbeastsClient {
             .ok_or("username is required for Mythic Beasts")?;
         let password = config.password.as_ref()
             .ok_or("password is required for Mythic Beasts")?;
-        let server = config.server.as_deref()
-            .unwrap_or("api.mythic-beasts.com");
+        let server = match config.server.as_deref() {
+            Some(server) if server.contains("://") => server.to_string(),
+            Some(server) => format!("https://{}", server),
+            None => "https://api.mythic-beasts.com".to_string(),
+        };
 
         Ok(MythicbeastsClient {
             username: username.to_string(),
             password: password.to_string(),
-            server: server.to_string(),
+            server,
+            auth_url: DEFAULT_AUTH_URL.to_string(),
+            zone: config.zone.clone(),
+            ttl: config.ttl.unwrap_or(300),
+            token: Mutex::new(None),
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn login_auth_header(&self) -> String {
+        format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password)))
+    }
+
+    fn record_url(&self, zone: &str, host: &str, record_type: &str) -> String {
+        format!("{}/beta/dns/zones/{}/records/{}/{}", self.server, zone, host, record_type)
+    }
+
+    fn record_body(&self, host: &str, record_type: &str, ip: IpAddr) -> serde_json::Value {
+        json!({
+            "records": [{
+                "host": host,
+                "type": record_type,
+                "ttl": self.ttl,
+                "data": ip.to_string(),
+            }]
+        })
+    }
+
+    /// Get an access token, logging in again when the cached one has expired
+    fn access_token(&self) -> Result<String, Box<dyn Error>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let mut cached = self.token.lock().map_err(|_| "token cache poisoned")?;
+
+        if let Some((token, expiry)) = cached.as_ref() {
+            if now + 60 < *expiry {
+                return Ok(token.clone());
+            }
+        }
+
+        log::debug!("Requesting Mythic Beasts access token");
+
+        let response = self.http.prepare(minreq::post(&self.auth_url))
+            .with_header("Authorization", self.login_auth_header())
+            .with_header("Content-Type", "application/x-www-form-urlencoded")
+            .with_body("grant_type=client_credentials")
+            .send()?;
+
+        if response.status_code != 200 {
+            return Err(format!("Mythic Beasts login failed: HTTP {} {}",
+                response.status_code, response.as_str().unwrap_or("")).into());
+        }
+
+        let json: serde_json::Value = response.json()?;
+        let token = json["access_token"].as_str()
+            .ok_or("No access_token in Mythic Beasts login response")?
+            .to_string();
+        let expires_in = json["expires_in"].as_u64().unwrap_or(300);
+
+        *cached = Some((token.clone(), now + expires_in));
+        Ok(token)
+    }
+
+    /// Hostname relative to the zone ("@" for the apex)
+    fn host<'a>(hostname: &'a str, zone: &str) -> &'a str {
+        if hostname == zone {
+            "@"
+        } else {
+            hostname.strip_suffix(&format!(".{}", zone)).unwrap_or(hostname)
+        }
+    }
 }
 
 impl DnsClient for MythicbeastsClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        log::info!("Updating Mythic Beasts record for {} to {}", hostname, ip);
-
-        // Mythic Beasts uses separate IPv4 and IPv6 endpoints
-        let (ipv, subdomain) = match ip {
-            IpAddr::V4(_) => ("4", "ipv4"),
-            IpAddr::V6(_) => ("6", "ipv6"),
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
         };
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let host = Self::host(hostname, &zone);
 
-        let url = format!(
-            "https://{}.{}/dns/v2/dynamic/{}",
-            subdomain, self.server, hostname
-        );
+        log::info!("Updating Mythic Beasts {} record for {} to {}", record_type, hostname, ip);
 
-        let auth = general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password));
-        let response = minreq::post(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", auth))
+        let token = self.access_token()?;
+        let response = self.http.prepare(minreq::put(self.record_url(&zone, host, record_type)))
+            .with_header("Authorization", format!("Bearer {}", token))
+            .with_json(&self.record_body(host, record_type, ip))?
             .send()?;
 
         if response.status_code == 200 {
-            log::info!("Successfully updated IPv{} record for {} to {}", ipv, hostname, ip);
+            log::info!("Successfully updated {} record for {} to {}", record_type, hostname, ip);
             Ok(())
         } else {
             let body = response.as_str().unwrap_or("No response body");
@@ -74,4 +161,128 @@ impl DnsClient for MythicbeastsClient {
     fn provider_name(&self) -> &'static str {
         "Mythic Beasts"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let host = Self::host(hostname, &zone);
+        Ok(vec![
+            self.http.preview("POST", self.auth_url.as_str())
+                .with_header("Authorization", self.login_auth_header())
+                .with_header("Content-Type", "application/x-www-form-urlencoded")
+                .with_body("grant_type=client_credentials"),
+            self.http.preview("PUT", self.record_url(&zone, host, record_type))
+                .with_header("Authorization", "Bearer {access_token}")
+                .with_header("Content-Type", "application/json")
+                .with_body(self.record_body(host, record_type, ip).to_string()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_client(server: &mockito::Server) -> MythicbeastsClient {
+        let config = Config {
+            protocol: Some("mythicbeasts".to_string()),
+            login: Some("keyid".to_string()),
+            password: Some("secret".to_string()),
+            server: Some(server.url()),
+            ..Default::default()
+        };
+        let mut client = MythicbeastsClient::new(&config).unwrap();
+        client.auth_url = format!("{}/login", server.url());
+        client
+    }
+
+    fn login_mock(server: &mut mockito::Server, token: &str, hits: usize) -> mockito::Mock {
+        server.mock("POST", "/login")
+            // base64("keyid:secret")
+            .match_header("authorization", "Basic a2V5aWQ6c2VjcmV0")
+            .match_body("grant_type=client_credentials")
+            .with_body(json!({"access_token": token, "expires_in": 300, "token_type": "bearer"}).to_string())
+            .expect(hits)
+            .create()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn test_mythicbeasts_token_acquired_and_cached() {
+        let mut server = mockito::Server::new();
+        let login = login_mock(&mut server, "token-1", 1);
+
+        let client = test_client(&server);
+        assert_eq!(client.access_token().unwrap(), "token-1");
+        assert_eq!(client.access_token().unwrap(), "token-1");
+        login.assert();
+
+        let (_, expiry) = client.token.lock().unwrap().clone().unwrap();
+        assert!(expiry > now() + 200);
+    }
+
+    #[test]
+    fn test_mythicbeasts_token_refreshed_after_expiry() {
+        let mut server = mockito::Server::new();
+        let login = login_mock(&mut server, "fresh", 1);
+
+        let client = test_client(&server);
+        *client.token.lock().unwrap() = Some(("valid".to_string(), now() + 3600));
+        assert_eq!(client.access_token().unwrap(), "valid");
+
+        *client.token.lock().unwrap() = Some(("expired".to_string(), now() - 1));
+        assert_eq!(client.access_token().unwrap(), "fresh");
+        login.assert();
+    }
+
+    #[test]
+    fn test_mythicbeasts_login_failure() {
+        let mut server = mockito::Server::new();
+        let _login = server.mock("POST", "/login").with_status(401).with_body("bad credentials").create();
+
+        let client = test_client(&server);
+        assert_eq!(
+            client.access_token().unwrap_err().to_string(),
+            "Mythic Beasts login failed: HTTP 401 bad credentials"
+        );
+    }
+
+    #[test]
+    fn test_mythicbeasts_update_record() {
+        let mut server = mockito::Server::new();
+        let _login = login_mock(&mut server, "token-1", 1);
+        let update = server.mock("PUT", "/beta/dns/zones/example.com/records/home/AAAA")
+            .match_header("authorization", "Bearer token-1")
+            .match_body(Matcher::Json(json!({
+                "records": [{"host": "home", "type": "AAAA", "ttl": 300, "data": "2001:db8::1"}]
+            })))
+            .with_body(r#"{"records_added": 1, "records_removed": 1}"#)
+            .create();
+
+        let client = test_client(&server);
+        client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        update.assert();
+    }
+
+    #[test]
+    fn test_mythicbeasts_preview_update() {
+        let mut server = mockito::Server::new();
+        let calls = server.mock("POST", mockito::Matcher::Any).expect(0).create();
+
+        let client = test_client(&server);
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, format!("{}/login", server.url()));
+        assert_eq!(requests[1].method, "PUT");
+        assert_eq!(requests[1].url, format!("{}/beta/dns/zones/example.com/records/home/A", server.url()));
+        assert_eq!(
+            requests[1].body.as_deref(),
+            Some(r#"{"records":[{"data":"203.0.113.1","host":"home","ttl":300,"type":"A"}]}"#)
+        );
+        calls.assert();
+    }
 }
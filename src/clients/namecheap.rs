@@ -1,13 +1,15 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 /// Namecheap Dynamic DNS client
 pub struct NamecheapClient {
     server: String,
     domain: String,
     password: String,
+    http: http::HttpClient,
 }
 
 impl NamecheapClient {
@@ -28,31 +30,76 @@ impl NamecheapClient {
             server,
             domain,
             password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
 
-impl DnsClient for NamecheapClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        // Extract subdomain from hostname
-        // e.g., "www.example.com" with domain "example.com" -> "www"
-        let host = if hostname.ends_with(&format!(".{}", self.domain)) {
-            hostname.trim_end_matches(&format!(".{}", self.domain))
-        } else if hostname == self.domain {
+    /// Extract the host part Namecheap expects from a hostname
+    /// e.g., "www.example.com" with domain "example.com" -> "www", "example.com" -> "@"
+    fn host_part<'a>(&self, hostname: &'a str) -> &'a str {
+        if hostname == self.domain {
             "@"
         } else {
             hostname
-        };
+                .strip_suffix(&format!(".{}", self.domain))
+                .unwrap_or(hostname)
+        }
+    }
 
-        let url = format!(
-            "https://{}/update?host={}&domain={}&password={}&ip={}",
-            self.server, host, self.domain, self.password, ip
+    /// Build the update URL; IPv4 goes in `ip=`, IPv6 in `ipv6=`, both for dual-stack
+    fn build_url(&self, hostname: &str, ipv4: Option<Ipv4Addr>, ipv6: Option<Ipv6Addr>) -> String {
+        let mut url = format!(
+            "https://{}/update?host={}&domain={}&password={}",
+            self.server, self.host_part(hostname), self.domain, self.password
         );
+        if let Some(ipv4) = ipv4 {
+            url.push_str(&format!("&ip={}", ipv4));
+        }
+        if let Some(ipv6) = ipv6 {
+            url.push_str(&format!("&ipv6={}", ipv6));
+        }
+        url
+    }
+
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        match ip {
+            IpAddr::V4(ipv4) => self.build_url(hostname, Some(ipv4), None),
+            IpAddr::V6(ipv6) => self.build_url(hostname, None, Some(ipv6)),
+        }
+    }
+
+    /// Check the XML response: `<ErrCount>0</ErrCount>` on success
+    fn parse_response(body: &str) -> Result<(), Box<dyn Error>> {
+        if body.contains("<ErrCount>0") {
+            return Ok(());
+        }
+        let errors = extract_namecheap_errors(body);
+        if errors.is_empty() {
+            Err(format!("Update failed: {}", body).into())
+        } else {
+            Err(format!("Namecheap error: {}", errors.join("; ")).into())
+        }
+    }
+}
+
+/// Text of every `<Err1>` .. `<Err5>` element in a Namecheap response
+fn extract_namecheap_errors(xml: &str) -> Vec<String> {
+    (1..=5)
+        .filter_map(|n| {
+            let (open, close) = (format!("<Err{}>", n), format!("</Err{}>", n));
+            let start = xml.find(&open)? + open.len();
+            let end = xml[start..].find(&close)? + start;
+            Some(xml[start..end].trim().to_string())
+        })
+        .filter(|error| !error.is_empty())
+        .collect()
+}
 
+impl DnsClient for NamecheapClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with Namecheap", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
             .send()?;
 
         let status_code = response.status_code;
@@ -64,22 +111,10 @@ impl DnsClient for NamecheapClient {
             return Err(format!("HTTP error: {}", status_code).into());
         }
 
-        // Namecheap returns XML response
-        // Success: <ErrCount>0</ErrCount>
-        // Failure: <ErrCount>1</ErrCount> (or higher)
-        if body.contains("<ErrCount>0") {
-            log::info!("DNS record for {} successfully updated to {}", hostname, ip);
-            Ok(())
-        } else {
-            // Try to extract error message
-            if let Some(start) = body.find("<Err1>") {
-                if let Some(end) = body[start..].find("</Err1>") {
-                    let error_msg = &body[start + 6..start + end];
-                    return Err(format!("Namecheap error: {}", error_msg).into());
-                }
-            }
-            Err(format!("Update failed: {}", body).into())
-        }
+        Self::parse_response(body)?;
+
+        log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+        Ok(())
     }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
@@ -95,4 +130,100 @@ impl DnsClient for NamecheapClient {
     fn provider_name(&self) -> &str {
         "Namecheap"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client() -> NamecheapClient {
+        let config = Config {
+            protocol: Some("namecheap".to_string()),
+            login: Some("example.com".to_string()),
+            password: Some("ddns-password".to_string()),
+            ..Default::default()
+        };
+        NamecheapClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_namecheap_host_part() {
+        let client = test_client();
+        assert_eq!(client.host_part("www.example.com"), "www");
+        assert_eq!(client.host_part("a.b.example.com"), "a.b");
+        assert_eq!(client.host_part("example.com"), "@");
+        assert_eq!(client.host_part("other.org"), "other.org");
+    }
+
+    #[test]
+    fn test_namecheap_url_ipv4() {
+        let client = test_client();
+        assert_eq!(
+            client.build_url("www.example.com", Some("203.0.113.1".parse().unwrap()), None),
+            "https://dynamicdns.park-your-domain.com/update?host=www&domain=example.com&password=ddns-password&ip=203.0.113.1"
+        );
+    }
+
+    #[test]
+    fn test_namecheap_url_ipv6_root() {
+        let client = test_client();
+        assert_eq!(
+            client.build_url("example.com", None, Some("2001:db8::1".parse().unwrap())),
+            "https://dynamicdns.park-your-domain.com/update?host=@&domain=example.com&password=ddns-password&ipv6=2001:db8::1"
+        );
+    }
+
+    #[test]
+    fn test_namecheap_url_dual_stack() {
+        let client = test_client();
+        assert_eq!(
+            client.build_url(
+                "example.com",
+                Some("203.0.113.1".parse().unwrap()),
+                Some("2001:db8::1".parse().unwrap()),
+            ),
+            "https://dynamicdns.park-your-domain.com/update?host=@&domain=example.com&password=ddns-password&ip=203.0.113.1&ipv6=2001:db8::1"
+        );
+    }
+
+    #[test]
+    fn test_namecheap_multiple_errors() {
+        let xml = "<?xml version=\"1.0\"?><interface-response><Command>SETDNSHOST</Command>\
+            <ErrCount>2</ErrCount><errors><Err1>Passwords do not match</Err1>\
+            <Err2>Domain name not active</Err2></errors><Done>true</Done></interface-response>";
+        assert_eq!(extract_namecheap_errors(xml), vec!["Passwords do not match", "Domain name not active"]);
+
+        let err = NamecheapClient::parse_response(xml).unwrap_err().to_string();
+        assert_eq!(err, "Namecheap error: Passwords do not match; Domain name not active");
+    }
+
+    #[test]
+    fn test_namecheap_single_error() {
+        let xml = "<interface-response><ErrCount>1</ErrCount><errors><Err1>No Records updated. A record not Found;</Err1></errors></interface-response>";
+        assert_eq!(extract_namecheap_errors(xml), vec!["No Records updated. A record not Found;"]);
+        assert!(NamecheapClient::parse_response(xml).unwrap_err().to_string().contains("A record not Found"));
+    }
+
+    #[test]
+    fn test_namecheap_success_response() {
+        let xml = "<interface-response><Command>SETDNSHOST</Command><IP>203.0.113.1</IP><ErrCount>0</ErrCount><Done>true</Done></interface-response>";
+        assert!(extract_namecheap_errors(xml).is_empty());
+        assert!(NamecheapClient::parse_response(xml).is_ok());
+    }
+
+    #[test]
+    fn test_namecheap_preview_update() {
+        let client = test_client();
+        let requests = client.preview_update("www.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://dynamicdns.park-your-domain.com/update?host=www&domain=example.com&password=ddns-password&ipv6=2001:db8::1"
+        );
+        assert!(!requests[0].to_string().contains("ddns-password"));
+    }
 }
@@ -0,0 +1,270 @@
+use crate::clients::{zone_for, DnsClient, RequestPreview};
+use crate::config::Config;
+use crate::http;
+use serde_json::json;
+use std::error::Error;
+use std::net::IpAddr;
+
+/// Netlify DNS client
+/// API documentation: https://open-api.netlify.com/#tag/dnsZone
+///
+/// Configuration:
+/// - password: Netlify personal access token
+/// - zone: DNS zone name (optional, defaults to the last two labels of the hostname)
+/// - ttl: TTL for created records in seconds (default 3600)
+pub struct NetlifyClient {
+    access_token: String,
+    server: String,
+    zone: Option<String>,
+    ttl: u32,
+    http: http::HttpClient,
+}
+
+impl NetlifyClient {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let access_token = config.password.as_ref()
+            .ok_or("password (personal access token) is required for Netlify")?
+            .clone();
+        let server = config.server.clone()
+            .unwrap_or_else(|| "https://api.netlify.com/api/v1".to_string());
+
+        Ok(NetlifyClient {
+            access_token,
+            server,
+            zone: config.zone.clone(),
+            ttl: config.ttl.unwrap_or(3600),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
+    }
+
+    fn auth_header(&self) -> String {
+        format!("Bearer {}", self.access_token)
+    }
+
+    fn zone_lookup_url(&self, zone: &str) -> String {
+        format!("{}/dns_zones?name={}", self.server, urlencoding::encode(zone))
+    }
+
+    fn records_url(&self, zone_id: &str) -> String {
+        format!("{}/dns_zones/{}/dns_records", self.server, zone_id)
+    }
+
+    fn get(&self, url: &str) -> Result<serde_json::Value, Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(url))
+            .with_header("Authorization", self.auth_header())
+            .send()?;
+
+        if response.status_code != 200 {
+            return Err(format!("Netlify API error (HTTP {}): {}",
+                response.status_code, response.as_str().unwrap_or("")).into());
+        }
+        Ok(response.json()?)
+    }
+
+    fn send(&self, request: minreq::Request, body: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+        let response = self.http.prepare(request)
+            .with_header("Authorization", self.auth_header())
+            .with_json(body)?
+            .send()?;
+
+        match response.status_code {
+            200 | 201 => Ok(()),
+            status => Err(format!("Netlify API error (HTTP {}): {}", status, response.as_str().unwrap_or("")).into()),
+        }
+    }
+
+    fn find_zone_id(&self, zone: &str) -> Result<String, Box<dyn Error>> {
+        let json = self.get(&self.zone_lookup_url(zone))?;
+
+        json.as_array()
+            .into_iter()
+            .flatten()
+            .find(|item| item["name"].as_str().is_some_and(|name| name.eq_ignore_ascii_case(zone)))
+            .and_then(|item| item["id"].as_str())
+            .map(str::to_string)
+            .ok_or_else(|| format!("Netlify DNS zone {} not found", zone).into())
+    }
+
+    /// ID of the existing `record_type` record for `hostname`, if any
+    fn find_record(&self, zone_id: &str, hostname: &str, record_type: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let json = self.get(&self.records_url(zone_id))?;
+
+        let id = json.as_array()
+            .into_iter()
+            .flatten()
+            .find(|record| {
+                record["type"] == record_type
+                    && record["hostname"].as_str().is_some_and(|name| name.eq_ignore_ascii_case(hostname))
+            })
+            .and_then(|record| record["id"].as_str())
+            .map(str::to_string);
+        Ok(id)
+    }
+}
+
+impl DnsClient for NetlifyClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+
+        log::info!("Updating {} {} record with Netlify", hostname, record_type);
+
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let zone_id = self.find_zone_id(&zone)?;
+        let records_url = self.records_url(&zone_id);
+
+        match self.find_record(&zone_id, hostname, record_type)? {
+            Some(record_id) => {
+                log::debug!("Found record ID {} for {}", record_id, hostname);
+                let url = format!("{}/{}", records_url, record_id);
+                self.send(minreq::patch(url), &json!({"value": ip.to_string()}))?;
+            }
+            None => {
+                log::info!("No {} record for {} in zone {}, creating it", record_type, hostname, zone);
+                self.send(minreq::post(records_url), &json!({
+                    "type": record_type,
+                    "hostname": hostname,
+                    "value": ip.to_string(),
+                    "ttl": self.ttl,
+                }))?;
+            }
+        }
+
+        log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+        Ok(())
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn Error>> {
+        if self.access_token.is_empty() {
+            return Err("password (personal access token) is required for Netlify".into());
+        }
+        Ok(())
+    }
+
+    fn provider_name(&self) -> &str {
+        "Netlify"
+    }
+
+    /// A missing record is created with a POST to the records URL instead of the PATCH
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let records_url = self.records_url("{zone_id}");
+        Ok(vec![
+            self.http.preview("GET", self.zone_lookup_url(&zone_for(self.zone.as_deref(), hostname)))
+                .with_header("Authorization", self.auth_header()),
+            self.http.preview("GET", records_url.as_str())
+                .with_header("Authorization", self.auth_header()),
+            self.http.preview("PATCH", format!("{}/{{record_id}}", records_url))
+                .with_header("Authorization", self.auth_header())
+                .with_header("Content-Type", "application/json")
+                .with_body(json!({"value": ip.to_string()}).to_string()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_client(server: Option<String>) -> NetlifyClient {
+        let config = Config {
+            protocol: Some("netlify".to_string()),
+            password: Some("netlify-token".to_string()),
+            server,
+            ..Default::default()
+        };
+        NetlifyClient::new(&config).unwrap()
+    }
+
+    fn mock_zone(server: &mut mockito::Server) -> mockito::Mock {
+        server.mock("GET", "/dns_zones")
+            .match_query(Matcher::UrlEncoded("name".to_string(), "example.com".to_string()))
+            .match_header("authorization", "Bearer netlify-token")
+            .with_body(r#"[{"id":"zone-1","name":"example.com"}]"#)
+            .create()
+    }
+
+    #[test]
+    fn test_netlify_client_creation() {
+        let client = test_client(None);
+        assert_eq!(client.server, "https://api.netlify.com/api/v1");
+        assert_eq!(zone_for(client.zone.as_deref(), "home.example.com"), "example.com");
+        assert_eq!(client.provider_name(), "Netlify");
+        assert!(NetlifyClient::new(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_netlify_update_existing_record() {
+        let mut server = mockito::Server::new();
+        let zone = mock_zone(&mut server);
+        let records = server.mock("GET", "/dns_zones/zone-1/dns_records")
+            .match_header("authorization", "Bearer netlify-token")
+            .with_body(r#"[
+                {"id":"rec-1","hostname":"home.example.com","type":"AAAA","value":"2001:db8::5"},
+                {"id":"rec-2","hostname":"home.example.com","type":"A","value":"198.51.100.1"}
+            ]"#)
+            .create();
+        let update = server.mock("PATCH", "/dns_zones/zone-1/dns_records/rec-2")
+            .match_header("authorization", "Bearer netlify-token")
+            .match_body(Matcher::Json(json!({"value": "203.0.113.1"})))
+            .with_body(r#"{"id":"rec-2"}"#)
+            .create();
+
+        let client = test_client(Some(server.url()));
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        zone.assert();
+        records.assert();
+        update.assert();
+    }
+
+    #[test]
+    fn test_netlify_creates_missing_record() {
+        let mut server = mockito::Server::new();
+        let _zone = mock_zone(&mut server);
+        let _records = server.mock("GET", "/dns_zones/zone-1/dns_records")
+            .with_body(r#"[{"id":"rec-2","hostname":"home.example.com","type":"A","value":"198.51.100.1"}]"#)
+            .create();
+        let create = server.mock("POST", "/dns_zones/zone-1/dns_records")
+            .match_body(Matcher::Json(json!({
+                "type": "AAAA",
+                "hostname": "home.example.com",
+                "value": "2001:db8::1",
+                "ttl": 3600,
+            })))
+            .with_status(201)
+            .with_body(r#"{"id":"rec-3"}"#)
+            .create();
+
+        let client = test_client(Some(server.url()));
+        assert!(client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).is_ok());
+        create.assert();
+    }
+
+    #[test]
+    fn test_netlify_zone_not_found() {
+        let mut server = mockito::Server::new();
+        let _zone = server.mock("GET", "/dns_zones")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .create();
+
+        let client = test_client(Some(server.url()));
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "Netlify DNS zone example.com not found");
+    }
+
+    #[test]
+    fn test_netlify_preview_update() {
+        let client = test_client(None);
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].url, "https://api.netlify.com/api/v1/dns_zones?name=example.com");
+        assert_eq!(requests[1].url, "https://api.netlify.com/api/v1/dns_zones/{zone_id}/dns_records");
+        assert_eq!(requests[2].method, "PATCH");
+        assert_eq!(requests[2].url, "https://api.netlify.com/api/v1/dns_zones/{zone_id}/dns_records/{record_id}");
+        assert_eq!(requests[2].body.as_deref(), Some(r#"{"value":"203.0.113.1"}"#));
+        assert!(!requests[2].to_string().contains("netlify-token"));
+    }
+}
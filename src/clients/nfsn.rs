@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use sha1::{Digest, Sha1};
 use std::error::Error;
 use std::net::IpAddr;
@@ -14,6 +15,7 @@ pub struct NfsnClient {
     api_key: String,
     zone: String,
     ttl: u32,
+    http: http::HttpClient,
 }
 
 impl NfsnClient {
@@ -41,6 +43,7 @@ impl NfsnClient {
             api_key,
             zone,
             ttl,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
 
@@ -48,6 +51,8 @@ impl NfsnClient {
     /// Format: login;timestamp;salt;hash
     /// hash = SHA1(login;timestamp;salt;api-key;request-uri;body-hash)
     fn gen_auth_header(&self, path: &str, body: &str) -> String {
+        use rand::rand_core::UnwrapErr;
+        use rand::rngs::SysRng;
         use rand::RngExt;
         
         let timestamp = SystemTime::now()
@@ -55,9 +60,9 @@ impl NfsnClient {
             .unwrap()
             .as_secs();
         
-        // Generate cryptographically secure 16-character random salt
+        // 16-character salt drawn straight from the OS CSPRNG
         const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
-        let mut rng = rand::rng();
+        let mut rng = UnwrapErr(SysRng);
         let salt: String = (0..16)
             .map(|_| {
                 let idx = rng.random_range(0..CHARSET.len());
@@ -85,17 +90,15 @@ impl NfsnClient {
         let auth_header = self.gen_auth_header(path, body);
         
         let request = match method {
-            "GET" => minreq::get(&url),
-            "POST" => minreq::post(&url)
+            "GET" => self.http.prepare(minreq::get(&url)),
+            "POST" => self.http.prepare(minreq::post(&url))
                 .with_header("Content-Type", "application/x-www-form-urlencoded")
                 .with_body(body),
             _ => return Err(format!("Unsupported HTTP method: {}", method).into()),
         };
         
         let response = request
-            .with_header("User-Agent", crate::USER_AGENT)
             .with_header("X-NFSN-Authentication", auth_header)
-            .with_timeout(30)  // 30 second timeout to prevent indefinite hangs
             .send()
             .map_err(|e| format!("NFSN API request failed: {}", e))?;
         
@@ -115,6 +118,35 @@ impl NfsnClient {
         }
     }
 
+    /// Form body for `listRRs`
+    fn list_body(name: &str, record_type: &str) -> String {
+        format!("name={}&type={}", urlencoding::encode(name), record_type)
+    }
+
+    /// Form body for `removeRR`; `data` is already URL-encoded
+    fn remove_body(name: &str, record_type: &str, data: &str) -> String {
+        format!("name={}&type={}&data={}", urlencoding::encode(name), record_type, data)
+    }
+
+    /// Form body for `addRR`
+    fn add_body(&self, name: &str, record_type: &str, ip: IpAddr) -> String {
+        format!(
+            "name={}&type={}&data={}&ttl={}",
+            urlencoding::encode(name),
+            record_type,
+            urlencoding::encode(&ip.to_string()),
+            self.ttl
+        )
+    }
+
+    /// Verify hostname is in the zone
+    fn check_zone(&self, hostname: &str) -> Result<(), Box<dyn Error>> {
+        if hostname != self.zone && !hostname.ends_with(&format!(".{}", self.zone)) {
+            return Err(format!("{} is outside zone {}", hostname, self.zone).into());
+        }
+        Ok(())
+    }
+
     /// Extract subdomain name from hostname (strip zone suffix)
     fn extract_name(&self, hostname: &str) -> String {
         if hostname == self.zone {
@@ -131,10 +163,7 @@ impl DnsClient for NfsnClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with NFSN", hostname);
 
-        // Verify hostname is in the zone
-        if hostname != self.zone && !hostname.ends_with(&format!(".{}", self.zone)) {
-            return Err(format!("{} is outside zone {}", hostname, self.zone).into());
-        }
+        self.check_zone(hostname)?;
 
         let name = self.extract_name(hostname);
         
@@ -146,8 +175,7 @@ impl DnsClient for NfsnClient {
         
         // Step 1: List existing records for this name
         let list_path = format!("/dns/{}/listRRs", self.zone);
-        let list_body = format!("name={}&type={}", urlencoding::encode(&name), record_type);
-        let list_resp = self.make_request(&list_path, "POST", &list_body)?;
+        let list_resp = self.make_request(&list_path, "POST", &Self::list_body(&name, record_type))?;
         
         log::debug!("List response: {}", list_resp);
         
@@ -159,27 +187,14 @@ impl DnsClient for NfsnClient {
             if let Some(old_ip) = record.get("data").and_then(|d| d.as_str()) {
                 log::info!("Removing old {} record: {} -> {}", record_type, name, old_ip);
                 let rm_path = format!("/dns/{}/removeRR", self.zone);
-                let rm_body = format!(
-                    "name={}&type={}&data={}",
-                    urlencoding::encode(&name),
-                    record_type,
-                    urlencoding::encode(old_ip)
-                );
-                self.make_request(&rm_path, "POST", &rm_body)?;
+                self.make_request(&rm_path, "POST", &Self::remove_body(&name, record_type, &urlencoding::encode(old_ip)))?;
             }
         }
         
         // Step 3: Add new record
         log::info!("Adding new {} record: {} -> {}", record_type, name, ip);
         let add_path = format!("/dns/{}/addRR", self.zone);
-        let add_body = format!(
-            "name={}&type={}&data={}&ttl={}",
-            urlencoding::encode(&name),
-            record_type,
-            urlencoding::encode(&ip.to_string()),
-            self.ttl
-        );
-        self.make_request(&add_path, "POST", &add_body)?;
+        self.make_request(&add_path, "POST", &self.add_body(&name, record_type, ip))?;
         
         log::info!("Successfully updated {} to {} ({})", hostname, ip, record_type);
         Ok(())
@@ -201,4 +216,88 @@ impl DnsClient for NfsnClient {
     fn provider_name(&self) -> &str {
         "NFSN"
     }
+
+    /// `removeRR` is only sent when a record exists; each request carries a fresh auth header
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        self.check_zone(hostname)?;
+        let name = self.extract_name(hostname);
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        Ok([
+            ("listRRs", Self::list_body(&name, record_type)),
+            ("removeRR", Self::remove_body(&name, record_type, "{old_ip}")),
+            ("addRR", self.add_body(&name, record_type, ip)),
+        ]
+        .into_iter()
+        .map(|(method, body)| {
+            let path = format!("/dns/{}/{}", self.zone, method);
+            self.http.preview("POST", format!("{}{}", self.server, path))
+                .with_header("Content-Type", "application/x-www-form-urlencoded")
+                .with_header("X-NFSN-Authentication", self.gen_auth_header(&path, &body))
+                .with_body(body)
+        })
+        .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client() -> NfsnClient {
+        let config = Config {
+            protocol: Some("nfsn".to_string()),
+            login: Some("testuser".to_string()),
+            password: Some("test-api-key".to_string()),
+            zone: Some("example.com".to_string()),
+            ..Default::default()
+        };
+        NfsnClient::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_nfsn_auth_header_format() {
+        let client = test_client();
+        let header = client.gen_auth_header("/dns/example.com/listRRs", "name=home");
+        let parts: Vec<&str> = header.split(';').collect();
+
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], "testuser");
+        assert!(parts[1].parse::<u64>().is_ok());
+
+        let body_hash = hex::encode(Sha1::digest(b"name=home"));
+        let expected = format!("testuser;{};{};test-api-key;/dns/example.com/listRRs;{}", parts[1], parts[2], body_hash);
+        assert_eq!(parts[3], hex::encode(Sha1::digest(expected.as_bytes())));
+    }
+
+    #[test]
+    fn test_nfsn_salt_properties() {
+        let client = test_client();
+        let mut previous = String::new();
+
+        for _ in 0..10_000 {
+            let header = client.gen_auth_header("/dns/example.com/listRRs", "");
+            let salt = header.split(';').nth(2).unwrap().to_string();
+
+            assert_eq!(salt.len(), 16);
+            assert!(salt.chars().all(|c| c.is_ascii_alphanumeric()), "{}", salt);
+            assert_ne!(salt, previous);
+            previous = salt;
+        }
+    }
+
+    #[test]
+    fn test_nfsn_preview_update() {
+        let client = test_client();
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].url, "https://api.nearlyfreespeech.net/dns/example.com/listRRs");
+        assert_eq!(requests[0].body.as_deref(), Some("name=home&type=A"));
+        assert_eq!(requests[1].url, "https://api.nearlyfreespeech.net/dns/example.com/removeRR");
+        assert_eq!(requests[1].body.as_deref(), Some("name=home&type=A&data={old_ip}"));
+        assert_eq!(requests[2].url, "https://api.nearlyfreespeech.net/dns/example.com/addRR");
+        assert_eq!(requests[2].body.as_deref(), Some("name=home&type=A&data=203.0.113.1&ttl=3600"));
+        assert!(!requests[2].to_string().contains("test-api-key"));
+
+        assert!(client.preview_update("home.example.org", "203.0.113.1".parse().unwrap()).is_err());
+    }
 }
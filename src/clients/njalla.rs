@@ -1,11 +1,17 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
+/// Njalla dynamic DNS client (HTTP endpoint)
+///
+/// Responses are JSON: `{"result":"Good"}` on success, or a JSON-RPC error object
+/// `{"jsonrpc":"2.0","error":{"code":..,"message":".."}}`.
 pub struct NjallaClient {
     api_key: String,
     server: String,
+    http: http::HttpClient,
 }
 
 impl NjallaClient {
@@ -19,33 +25,66 @@ impl NjallaClient {
         Ok(Self {
             api_key,
             server,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/update?h={}&k={}&a={}", self.server, hostname, self.api_key, ip)
+    }
+
+    /// Interpret an update response body
+    fn parse_response(status_code: u16, body: &str) -> Result<(), Box<dyn Error>> {
+        let json: serde_json::Value = match serde_json::from_str(body.trim()) {
+            Ok(json) => json,
+            // Older deployments answer with an empty body
+            Err(_) if status_code == 200 && body.trim().is_empty() => return Ok(()),
+            Err(_) if status_code != 200 => return Err(format!("HTTP error: {}", status_code).into()),
+            Err(_) => return Err(format!("Unexpected Njalla response: {}", body.trim()).into()),
+        };
+
+        if let Some(error) = json.get("error") {
+            let code = error["code"].as_i64().unwrap_or(0);
+            let message = error["message"].as_str().unwrap_or("unknown error");
+            return Err(match Self::describe_error(code) {
+                Some(description) => format!("Njalla error {}: {} ({})", code, description, message),
+                None => format!("Njalla error {}: {}", code, message),
+            }.into());
+        }
+
+        match json["result"].as_str() {
+            Some(result) if result.eq_ignore_ascii_case("good") => Ok(()),
+            Some(result) => Err(format!("Njalla update failed: {}", result).into()),
+            None => Err(format!("Unexpected Njalla response: {}", json).into()),
+        }
+    }
+
+    /// Friendly description for known Njalla / JSON-RPC error codes
+    fn describe_error(code: i64) -> Option<&'static str> {
+        match code {
+            401 | 403 => Some("invalid API token"),
+            404 => Some("hostname not found"),
+            429 => Some("too many requests"),
+            -32600 => Some("invalid request"),
+            -32601 => Some("unknown method"),
+            -32602 => Some("invalid parameters"),
+            -32603 | -32000 => Some("Njalla internal error"),
+            _ => None,
+        }
+    }
 }
 
 impl DnsClient for NjallaClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!("{}/update?h={}&k={}&a={}", 
-            self.server, hostname, self.api_key, ip);
-        
         log::info!("Updating {} to {}", hostname, ip);
-        
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
             .send()?;
 
-        // Njalla returns status 200 on success
-        if response.status_code == 200 {
-            let body = response.as_str()?;
-            // Empty response or contains success indicators
-            if body.is_empty() || !body.to_lowercase().contains("error") {
-                log::info!("Successfully updated {} to {}", hostname, ip);
-                return Ok(());
-            }
-            return Err(format!("Update failed: {}", body).into());
-        }
+        Self::parse_response(response.status_code, response.as_str()?)?;
 
-        Err(format!("HTTP error: {}", response.status_code).into())
+        log::info!("Successfully updated {} to {}", hostname, ip);
+        Ok(())
     }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
@@ -58,4 +97,69 @@ impl DnsClient for NjallaClient {
     fn provider_name(&self) -> &str {
         "Njalla"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_njalla_success() {
+        assert!(NjallaClient::parse_response(200, r#"{"result":"Good"}"#).is_ok());
+        assert!(NjallaClient::parse_response(200, "").is_ok());
+    }
+
+    #[test]
+    fn test_njalla_invalid_token() {
+        let body = r#"{"jsonrpc":"2.0","error":{"code":403,"message":"Invalid key"}}"#;
+        assert_eq!(
+            NjallaClient::parse_response(200, body).unwrap_err().to_string(),
+            "Njalla error 403: invalid API token (Invalid key)"
+        );
+    }
+
+    #[test]
+    fn test_njalla_hostname_not_found() {
+        let body = r#"{"jsonrpc":"2.0","error":{"code":404,"message":"No such record"}}"#;
+        assert_eq!(
+            NjallaClient::parse_response(404, body).unwrap_err().to_string(),
+            "Njalla error 404: hostname not found (No such record)"
+        );
+    }
+
+    #[test]
+    fn test_njalla_other_responses() {
+        let body = r#"{"jsonrpc":"2.0","error":{"code":7,"message":"Something odd"}}"#;
+        assert_eq!(NjallaClient::parse_response(200, body).unwrap_err().to_string(), "Njalla error 7: Something odd");
+        assert!(NjallaClient::parse_response(200, r#"{"result":"Bad"}"#).is_err());
+        assert_eq!(NjallaClient::parse_response(502, "Bad Gateway").unwrap_err().to_string(), "HTTP error: 502");
+    }
+
+    #[test]
+    fn test_njalla_validate_config() {
+        let config = Config {
+            password: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(NjallaClient::new(&config).unwrap().validate_config().is_err());
+    }
+
+    #[test]
+    fn test_njalla_preview_update() {
+        let config = Config {
+            protocol: Some("njalla".to_string()),
+            password: Some("njalla-key".to_string()),
+            ..Default::default()
+        };
+        let client = NjallaClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://njal.la/update?h=home.example.com&k=njalla-key&a=203.0.113.1");
+        assert!(!requests[0].to_string().contains("njalla-key"));
+    }
 }
@@ -1,15 +1,28 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, HostResults, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use crate::utils::mask_credential;
 use std::error::Error;
+use std::fmt;
 use std::net::IpAddr;
 use base64::{Engine as _, engine::general_purpose};
 
 /// No-IP client - compatible with DynDNS2 but with No-IP specifics
-#[derive(Debug)]
 pub struct NoIpClient {
     username: String,
     password: String,
     server: String,
+    http: http::HttpClient,
+}
+
+impl fmt::Debug for NoIpClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NoIpClient")
+            .field("username", &self.username)
+            .field("password", &mask_credential(&self.password))
+            .field("server", &self.server)
+            .finish()
+    }
 }
 
 impl NoIpClient {
@@ -29,42 +42,77 @@ impl NoIpClient {
             username,
             password,
             server,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
 
-impl DnsClient for NoIpClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
-            "{}/nic/update?hostname={}&myip={}",
-            self.server, hostname, ip
-        );
+    /// Update URL; several hostnames are joined with commas
+    fn update_url(&self, hostnames: &[String], ip: IpAddr) -> String {
+        format!("{}/nic/update?hostname={}&myip={}", self.server, hostnames.join(","), ip)
+    }
 
-        log::info!("Updating {} with No-IP", hostname);
+    fn auth_header(&self) -> String {
+        format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password)))
+    }
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", 
-                general_purpose::STANDARD.encode(format!("{}:{}", self.username, self.password))))
+    /// Send one update request and return the response body
+    fn send(&self, hostnames: &[String], ip: IpAddr) -> Result<String, Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(self.update_url(hostnames, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
+        Ok(response.as_str()?.trim().to_string())
+    }
 
-        let body = response.as_str()?.trim();
-        let status = body.split_whitespace().next().unwrap_or("");
-
-        match status {
-            "good" | "nochg" => {
-                log::info!("DNS record for {} successfully updated to {}", hostname, ip);
-                Ok(())
-            }
+    /// Map one response line ("good 1.2.3.4", "nohost", ...) to a result
+    fn parse_status(line: &str) -> Result<(), Box<dyn Error>> {
+        match line.split_whitespace().next().unwrap_or("") {
+            "good" | "nochg" => Ok(()),
             "badauth" => Err("Bad authentication".into()),
             "nohost" => Err("Hostname doesn't exist".into()),
             "badagent" => Err("Client disabled - contact No-IP".into()),
             "abuse" => Err("Username blocked for abuse".into()),
             "911" => Err("Server error - try again later".into()),
-            _ => Err(format!("Unknown response: {}", body).into()),
+            _ => Err(format!("Unknown response: {}", line).into()),
         }
     }
 
+    /// Update several hostnames in one request. No-IP answers with one status line per
+    /// hostname, in request order; a single line (e.g. badauth) applies to all of them.
+    pub fn batch_update(&self, hostnames: &[String], ip: IpAddr) -> Result<HostResults, Box<dyn Error>> {
+        log::info!("Updating {} with No-IP", hostnames.join(", "));
+        Ok(Self::parse_batch_response(hostnames, &self.send(hostnames, ip)?))
+    }
+
+    fn parse_batch_response(hostnames: &[String], body: &str) -> HostResults {
+        let lines: Vec<&str> = body.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        hostnames.iter()
+            .enumerate()
+            .map(|(i, hostname)| {
+                let result = match (lines.get(i), lines.as_slice()) {
+                    (Some(line), _) => Self::parse_status(line),
+                    (None, [line]) => Self::parse_status(line),
+                    (None, _) => Err(format!("No response for {}", hostname).into()),
+                };
+                (hostname.clone(), result)
+            })
+            .collect()
+    }
+}
+
+impl DnsClient for NoIpClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with No-IP", hostname);
+
+        let body = self.send(&[hostname.to_string()], ip)?;
+        Self::parse_status(&body)?;
+        log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+        Ok(())
+    }
+
+    fn update_records(&self, hostnames: &[String], ip: IpAddr) -> Option<HostResults> {
+        Some(self.batch_update(hostnames, ip).unwrap_or_else(|e| crate::clients::same_result(hostnames, Err(e))))
+    }
+
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
         if self.username.is_empty() {
             return Err("username is required for No-IP".into());
@@ -78,6 +126,11 @@ impl DnsClient for NoIpClient {
     fn provider_name(&self) -> &str {
         "No-IP"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(&[hostname.to_string()], ip))
+            .with_header("Authorization", self.auth_header())])
+    }
 }
 
 #[cfg(test)]
@@ -179,4 +232,66 @@ mod tests {
         
         assert_eq!(expected_url, "https://dynupdate.no-ip.com/nic/update?hostname=myhost.no-ip.com&myip=203.0.113.1");
     }
+
+    #[test]
+    fn test_noip_batch_url_joins_hostnames() {
+        let client = NoIpClient::new(&create_test_config()).unwrap();
+        let hostnames = vec!["a.no-ip.com".to_string(), "b.no-ip.com".to_string()];
+        assert_eq!(
+            client.update_url(&hostnames, "203.0.113.1".parse().unwrap()),
+            "https://dynupdate.no-ip.com/nic/update?hostname=a.no-ip.com,b.no-ip.com&myip=203.0.113.1"
+        );
+    }
+
+    #[test]
+    fn test_noip_parse_batch_response() {
+        let hostnames = vec!["a.no-ip.com".to_string(), "b.no-ip.com".to_string(), "c.no-ip.com".to_string()];
+
+        let results = NoIpClient::parse_batch_response(&hostnames, "good 203.0.113.1\nnohost\nnochg 203.0.113.1\n");
+        let outcome: Vec<(&str, Option<String>)> = results.iter()
+            .map(|(hostname, result)| (hostname.as_str(), result.as_ref().err().map(|e| e.to_string())))
+            .collect();
+        assert_eq!(outcome, vec![
+            ("a.no-ip.com", None),
+            ("b.no-ip.com", Some("Hostname doesn't exist".to_string())),
+            ("c.no-ip.com", None),
+        ]);
+
+        // One line answers for every host; otherwise missing lines are errors
+        let results = NoIpClient::parse_batch_response(&hostnames, "badauth");
+        assert!(results.iter().all(|(_, result)| result.as_ref().unwrap_err().to_string() == "Bad authentication"));
+        let results = NoIpClient::parse_batch_response(&hostnames, "good 203.0.113.1\ngood 203.0.113.1");
+        assert_eq!(results[2].1.as_ref().unwrap_err().to_string(), "No response for c.no-ip.com");
+    }
+
+    #[test]
+    fn test_noip_batch_update() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/nic/update")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("hostname".to_string(), "a.no-ip.com,b.no-ip.com".to_string()),
+                mockito::Matcher::UrlEncoded("myip".to_string(), "203.0.113.1".to_string()),
+            ]))
+            .with_body("good 203.0.113.1\nabuse")
+            .expect(1)
+            .create();
+
+        let config = Config { server: Some(server.url()), ..create_test_config() };
+        let client = NoIpClient::new(&config).unwrap();
+        let hostnames = vec!["a.no-ip.com".to_string(), "b.no-ip.com".to_string()];
+        let results = client.update_records(&hostnames, "203.0.113.1".parse().unwrap()).unwrap();
+
+        assert!(results[0].1.is_ok());
+        assert!(results[1].1.is_err());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_noip_preview_update() {
+        let client = NoIpClient::new(&create_test_config()).unwrap();
+        let requests = client.preview_update("myhost.no-ip.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://dynupdate.no-ip.com/nic/update?hostname=myhost.no-ip.com&myip=203.0.113.1");
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), client.auth_header())));
+    }
 }
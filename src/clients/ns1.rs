@@ -0,0 +1,204 @@
+use crate::clients::{zone_for, DnsClient, RequestPreview};
+use crate::config::Config;
+use crate::http;
+use serde_json::json;
+use std::error::Error;
+use std::net::IpAddr;
+
+/// NS1 client
+/// API documentation: https://developer.ibm.com/apis/catalog/ns1--ibm-ns1-connect-api/api/API--ns1--ibm-ns1-connect-api
+///
+/// Configuration:
+/// - password: NS1 API key
+/// - zone: DNS zone name (optional, defaults to the last two labels of the hostname)
+pub struct Ns1Client {
+    api_key: String,
+    server: String,
+    zone: Option<String>,
+    http: http::HttpClient,
+}
+
+impl Ns1Client {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let api_key = config.password.as_ref()
+            .ok_or("password (API key) is required for NS1")?
+            .clone();
+        let server = config.server.clone()
+            .unwrap_or_else(|| "https://api.nsone.net/v1".to_string());
+
+        Ok(Ns1Client {
+            api_key,
+            server,
+            zone: config.zone.clone(),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
+    }
+
+    /// NS1 addresses records by zone, fully qualified name and type
+    fn record_url(&self, zone: &str, hostname: &str, record_type: &str) -> String {
+        format!("{}/zones/{}/{}/{}", self.server, zone, hostname, record_type)
+    }
+
+    fn record_body(ip: IpAddr) -> serde_json::Value {
+        json!({
+            "answers": [{"answer": [ip.to_string()]}]
+        })
+    }
+
+    /// Map an unsuccessful response to a descriptive error
+    fn error(status: u16, body: &str, zone: &str, hostname: &str, record_type: &str) -> Box<dyn Error> {
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|json| json["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| body.to_string());
+        match status {
+            401 | 403 => format!("NS1 rejected the API key (HTTP {}): {}", status, message).into(),
+            404 => format!("NS1 {} record for {} not found in zone {}: {}", record_type, hostname, zone, message).into(),
+            _ => format!("NS1 API error (HTTP {}): {}", status, message).into(),
+        }
+    }
+}
+
+impl DnsClient for Ns1Client {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+
+        log::info!("Updating {} {} record with NS1", hostname, record_type);
+
+        let zone = zone_for(self.zone.as_deref(), hostname);
+        let url = self.record_url(&zone, hostname, record_type);
+
+        // Make sure the record exists first; POST to a missing record fails with a less useful error
+        let response = self.http.prepare(minreq::get(&url))
+            .with_header("X-NSONE-Key", &self.api_key)
+            .send()?;
+        if response.status_code != 200 {
+            return Err(Self::error(response.status_code, response.as_str().unwrap_or(""), &zone, hostname, record_type));
+        }
+
+        let response = self.http.prepare(minreq::post(&url))
+            .with_header("X-NSONE-Key", &self.api_key)
+            .with_json(&Self::record_body(ip))?
+            .send()?;
+
+        if response.status_code == 200 {
+            log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+            Ok(())
+        } else {
+            Err(Self::error(response.status_code, response.as_str().unwrap_or(""), &zone, hostname, record_type))
+        }
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn Error>> {
+        if self.api_key.is_empty() {
+            return Err("password (API key) is required for NS1".into());
+        }
+        Ok(())
+    }
+
+    fn provider_name(&self) -> &str {
+        "NS1"
+    }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        let url = self.record_url(&zone_for(self.zone.as_deref(), hostname), hostname, record_type);
+        Ok(vec![
+            self.http.preview("GET", url.as_str())
+                .with_header("X-NSONE-Key", &self.api_key),
+            self.http.preview("POST", url)
+                .with_header("X-NSONE-Key", &self.api_key)
+                .with_header("Content-Type", "application/json")
+                .with_body(Self::record_body(ip).to_string()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_client(server: Option<String>) -> Ns1Client {
+        let config = Config {
+            protocol: Some("ns1".to_string()),
+            password: Some("ns1-key".to_string()),
+            server,
+            ..Default::default()
+        };
+        Ns1Client::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_ns1_client_creation() {
+        let client = test_client(None);
+        assert_eq!(client.server, "https://api.nsone.net/v1");
+        assert_eq!(client.provider_name(), "NS1");
+        assert_eq!(
+            client.record_url("example.com", "home.example.com", "AAAA"),
+            "https://api.nsone.net/v1/zones/example.com/home.example.com/AAAA"
+        );
+        assert!(Ns1Client::new(&Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_ns1_update_record() {
+        let mut server = mockito::Server::new();
+        let get = server.mock("GET", "/zones/example.com/home.example.com/A")
+            .match_header("x-nsone-key", "ns1-key")
+            .with_body(r#"{"zone":"example.com","domain":"home.example.com","type":"A","answers":[{"answer":["198.51.100.1"]}]}"#)
+            .create();
+        let post = server.mock("POST", "/zones/example.com/home.example.com/A")
+            .match_header("x-nsone-key", "ns1-key")
+            .match_body(mockito::Matcher::Json(json!({"answers": [{"answer": ["203.0.113.1"]}]})))
+            .with_body(r#"{"domain":"home.example.com","type":"A","answers":[{"answer":["203.0.113.1"]}]}"#)
+            .create();
+
+        let client = test_client(Some(server.url()));
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        get.assert();
+        post.assert();
+    }
+
+    #[test]
+    fn test_ns1_record_not_found() {
+        let mut server = mockito::Server::new();
+        let _get = server.mock("GET", "/zones/example.com/home.example.com/AAAA")
+            .with_status(404)
+            .with_body(r#"{"message":"record not found"}"#)
+            .create();
+        let post = server.mock("POST", "/zones/example.com/home.example.com/AAAA").expect(0).create();
+
+        let client = test_client(Some(server.url()));
+        let err = client.update_record("home.example.com", "2001:db8::1".parse().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "NS1 AAAA record for home.example.com not found in zone example.com: record not found");
+        post.assert();
+    }
+
+    #[test]
+    fn test_ns1_auth_error() {
+        let mut server = mockito::Server::new();
+        let _get = server.mock("GET", "/zones/example.com/home.example.com/A")
+            .with_status(401)
+            .with_body(r#"{"message":"Unauthorized"}"#)
+            .create();
+
+        let client = test_client(Some(server.url()));
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "NS1 rejected the API key (HTTP 401): Unauthorized");
+    }
+
+    #[test]
+    fn test_ns1_preview_update() {
+        let client = test_client(None);
+        let requests = client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[1].url, "https://api.nsone.net/v1/zones/example.com/home.example.com/AAAA");
+        assert_eq!(requests[1].body.as_deref(), Some(r#"{"answers":[{"answer":["2001:db8::1"]}]}"#));
+        assert!(!requests[1].to_string().contains("ns1-key"));
+    }
+}
@@ -1,55 +1,224 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
+use sha1::{Digest, Sha1};
 use std::error::Error;
 use std::net::IpAddr;
 
-/// OVH DynHost client
-/// Uses OVH's DynDNS2-compatible DynHost service
-/// API documentation: https://docs.ovh.com/gb/en/domains/hosting_dynhost/
+/// Expand an API endpoint preset (`ovh-eu`, `ovh-ca`, `ovh-us`, `soyoustart-eu`,
+/// `soyoustart-ca`) to its base URL; anything else is returned unchanged
+fn resolve_ovh_endpoint(server: &str) -> &str {
+    match server {
+        "ovh-eu" => "https://eu.api.ovh.com/1.0",
+        "ovh-ca" => "https://ca.api.ovh.com/1.0",
+        "ovh-us" => "https://api.us.ovhcloud.com/1.0",
+        "soyoustart-eu" => "https://eu.api.soyoustart.com/1.0",
+        "soyoustart-ca" => "https://ca.api.soyoustart.com/1.0",
+        _ => server,
+    }
+}
+
+/// OVH client
+///
+/// Supports two modes:
+/// - DynHost (default): DynDNS2-compatible service using the DynHost login/password
+/// - API: signed requests against the OVH REST API, enabled when `consumer_key` is set.
+///   `login` is the application key and `password` the application secret.
+///
+/// API documentation:
+/// - https://docs.ovh.com/gb/en/domains/hosting_dynhost/
+/// - https://help.ovhcloud.com/csm/en-gb-api-getting-started-ovhcloud-api
 pub struct OvhClient {
     server: String,
     login: String,
     password: String,
+    consumer_key: Option<String>,
+    zone: Option<String>,
+    ttl: u32,
+    http: http::HttpClient,
 }
 
 impl OvhClient {
     pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
         let login = config.login.as_ref()
-            .ok_or("login (DynHost username) is required for OVH")?
+            .ok_or("login (DynHost username or application key) is required for OVH")?
             .clone();
-        
+
         let password = config.password.as_ref()
-            .ok_or("password is required for OVH")?
+            .ok_or("password (DynHost password or application secret) is required for OVH")?
             .clone();
-        
-        let server = config.server.clone()
-            .unwrap_or_else(|| "www.ovh.com".to_string());
+
+        let consumer_key = config.consumer_key.clone();
+
+        // The signed API and DynHost live on different hosts
+        let default_server = if consumer_key.is_some() {
+            "https://eu.api.ovh.com/1.0"
+        } else {
+            "www.ovh.com"
+        };
+        let server = config.server.as_deref()
+            .map_or(default_server, resolve_ovh_endpoint)
+            .to_string();
 
         Ok(OvhClient {
             server,
             login,
             password,
+            consumer_key,
+            zone: config.zone.clone(),
+            ttl: config.ttl.unwrap_or(3600),
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
-
-impl DnsClient for OvhClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        log::info!("Updating {} with OVH DynHost", hostname);
 
-        // OVH uses DynDNS2-compatible protocol
-        let url = format!(
-            "https://{}/nic/update?system=dyndns&hostname={}&myip={}",
-            self.server, hostname, ip
+    /// Compute the OVH API request signature
+    /// Format: "$1$" + SHA1_HEX(AS+"+"+CK+"+"+METHOD+"+"+URL+"+"+BODY+"+"+TIMESTAMP)
+    fn sign(&self, consumer_key: &str, method: &str, url: &str, body: &str, timestamp: u64) -> String {
+        let to_sign = format!(
+            "{}+{}+{}+{}+{}+{}",
+            self.password, consumer_key, method, url, body, timestamp
         );
+        format!("$1${}", hex::encode(Sha1::digest(to_sign.as_bytes())))
+    }
 
-        let auth = format!("{}:{}", self.login, self.password);
+    /// Build the authentication headers for a signed API request
+    fn auth_headers(&self, method: &str, url: &str, body: &str, timestamp: u64) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let consumer_key = self.consumer_key.as_deref()
+            .ok_or("consumer_key is required for the OVH API")?;
+
+        Ok(vec![
+            ("X-Ovh-Application".to_string(), self.login.clone()),
+            ("X-Ovh-Timestamp".to_string(), timestamp.to_string()),
+            ("X-Ovh-Consumer".to_string(), consumer_key.to_string()),
+            ("X-Ovh-Signature".to_string(), self.sign(consumer_key, method, url, body, timestamp)),
+        ])
+    }
+
+    /// Fetch the OVH server time, used as the signature timestamp
+    fn server_time(&self) -> Result<u64, Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(format!("{}/auth/time", self.server)))
+            .send()?;
+
+        if response.status_code != 200 {
+            return Err(format!("Failed to get OVH server time: HTTP {}", response.status_code).into());
+        }
+
+        let time = response.as_str()?.trim().parse::<u64>()
+            .map_err(|e| format!("Invalid OVH server time: {}", e))?;
+        Ok(time)
+    }
+
+    /// Send a request to the OVH API, signed with the server `timestamp`, and return the parsed JSON response
+    fn api_request(&self, timestamp: u64, method: minreq::Method, path: &str, body: Option<&serde_json::Value>) -> Result<serde_json::Value, Box<dyn Error>> {
+        let url = format!("{}{}", self.server, path);
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let method_name = method.to_string();
+
+        let mut request = self.http.prepare(minreq::Request::new(method, url.as_str()))
+            .with_header("Content-Type", "application/json")
+            .with_headers(self.auth_headers(&method_name, &url, &body, timestamp)?);
+        if !body.is_empty() {
+            request = request.with_body(body);
+        }
+
+        let response = request.send()?;
+        let status_code = response.status_code;
+        let text = response.as_str()?;
+
+        log::debug!("OVH API {} {} -> HTTP {}: {}", method_name, path, status_code, text);
+
+        if !(200..300).contains(&status_code) {
+            return Err(format!("OVH API error (HTTP {}): {}", status_code, text).into());
+        }
+
+        if text.trim().is_empty() {
+            Ok(serde_json::Value::Null)
+        } else {
+            Ok(serde_json::from_str(text)?)
+        }
+    }
+
+    /// Zone and subdomain ("" for the apex) of `hostname` for the OVH API
+    fn api_zone<'a>(&'a self, hostname: &'a str) -> Result<(&'a str, &'a str), Box<dyn Error>> {
+        let zone = self.zone.as_deref()
+            .ok_or("zone is required for the OVH API")?;
+        let subdomain = if hostname == zone {
+            ""
+        } else {
+            hostname.strip_suffix(&format!(".{}", zone))
+                .ok_or_else(|| format!("{} is outside zone {}", hostname, zone))?
+        };
+        Ok((zone, subdomain))
+    }
+
+    fn lookup_path(zone: &str, record_type: &str, subdomain: &str) -> String {
+        format!("/domain/zone/{}/record?fieldType={}&subDomain={}", zone, record_type, subdomain)
+    }
+
+    fn update_body(&self, ip: IpAddr) -> serde_json::Value {
+        serde_json::json!({
+            "target": ip.to_string(),
+            "ttl": self.ttl,
+        })
+    }
+
+    /// Update a record through the signed OVH API
+    fn update_via_api(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let (zone, subdomain) = self.api_zone(hostname)?;
+
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+
+        log::info!("Updating {} {} record with OVH API", hostname, record_type);
+
+        // One server time signs every call of this update
+        let timestamp = self.server_time()?;
+        let ids = self.api_request(timestamp, minreq::Method::Get, &Self::lookup_path(zone, record_type, subdomain), None)?;
+
+        match ids.as_array().and_then(|ids| ids.first()).and_then(|id| id.as_u64()) {
+            Some(record_id) => {
+                let path = format!("/domain/zone/{}/record/{}", zone, record_id);
+                self.api_request(timestamp, minreq::Method::Put, &path, Some(&self.update_body(ip)))?;
+            }
+            None => {
+                log::info!("No {} record found for {}, creating it", record_type, hostname);
+                let body = serde_json::json!({
+                    "fieldType": record_type,
+                    "subDomain": subdomain,
+                    "target": ip.to_string(),
+                    "ttl": self.ttl,
+                });
+                let path = format!("/domain/zone/{}/record", zone);
+                self.api_request(timestamp, minreq::Method::Post, &path, Some(&body))?;
+            }
+        }
+
+        // Changes are only published once the zone is refreshed
+        self.api_request(timestamp, minreq::Method::Post, &format!("/domain/zone/{}/refresh", zone), None)?;
+
+        log::info!("Successfully updated {} to {}", hostname, ip);
+        Ok(())
+    }
+
+    /// OVH uses DynDNS2-compatible protocol
+    fn dynhost_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("https://{}/nic/update?system=dyndns&hostname={}&myip={}", self.server, hostname, ip)
+    }
+
+    fn dynhost_auth_header(&self) -> String {
         use base64::{Engine as _, engine::general_purpose};
-        let encoded_auth = general_purpose::STANDARD.encode(auth.as_bytes());
+        let auth = format!("{}:{}", self.login, self.password);
+        format!("Basic {}", general_purpose::STANDARD.encode(auth.as_bytes()))
+    }
+
+    /// Update a record through the DynHost service
+    fn update_via_dynhost(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        log::info!("Updating {} with OVH DynHost", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", format!("Basic {}", encoded_auth))
+        let response = self.http.prepare(minreq::get(self.dynhost_url(hostname, ip)))
+            .with_header("Authorization", self.dynhost_auth_header())
             .send()?;
 
         let status_code = response.status_code;
@@ -72,13 +241,31 @@ impl DnsClient for OvhClient {
             Err(format!("OVH DynHost error: {}", body).into())
         }
     }
+}
+
+impl DnsClient for OvhClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        if self.consumer_key.is_some() {
+            self.update_via_api(hostname, ip)
+        } else {
+            self.update_via_dynhost(hostname, ip)
+        }
+    }
 
     fn validate_config(&self) -> Result<(), Box<dyn Error>> {
         if self.login.is_empty() {
-            return Err("login (DynHost username) is required for OVH".into());
+            return Err("login (DynHost username or application key) is required for OVH".into());
         }
         if self.password.is_empty() {
-            return Err("password is required for OVH".into());
+            return Err("password (DynHost password or application secret) is required for OVH".into());
+        }
+        if let Some(consumer_key) = &self.consumer_key {
+            if consumer_key.is_empty() {
+                return Err("consumer_key cannot be empty for the OVH API".into());
+            }
+            if self.zone.as_deref().unwrap_or("").is_empty() {
+                return Err("zone is required for the OVH API".into());
+            }
         }
         Ok(())
     }
@@ -86,4 +273,207 @@ impl DnsClient for OvhClient {
     fn provider_name(&self) -> &str {
         "OVH"
     }
+
+    /// In API mode the signature depends on the server time, so it is shown as a placeholder.
+    /// A missing record is created with a POST to `/record` instead of the PUT.
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let Some(consumer_key) = &self.consumer_key else {
+            return Ok(vec![self.http.preview("GET", self.dynhost_url(hostname, ip))
+                .with_header("Authorization", self.dynhost_auth_header())]);
+        };
+        let (zone, subdomain) = self.api_zone(hostname)?;
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+
+        let signed = |method: &'static str, path: String| {
+            self.http.preview(method, format!("{}{}", self.server, path))
+                .with_header("Content-Type", "application/json")
+                .with_header("X-Ovh-Application", self.login.as_str())
+                .with_header("X-Ovh-Timestamp", "{server_time}")
+                .with_header("X-Ovh-Consumer", consumer_key.as_str())
+                .with_header("X-Ovh-Signature", "{signature}")
+        };
+        Ok(vec![
+            self.http.preview("GET", format!("{}/auth/time", self.server)),
+            signed("GET", Self::lookup_path(zone, record_type, subdomain)),
+            signed("PUT", format!("/domain/zone/{}/record/{{record_id}}", zone))
+                .with_body(self.update_body(ip).to_string()),
+            signed("POST", format!("/domain/zone/{}/refresh", zone)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_api_config(server: &str) -> Config {
+        Config {
+            protocol: Some("ovh".to_string()),
+            login: Some("7kbG7Bk7S9Nt7ZSV".to_string()),
+            password: Some("EXEEWd9VarCHCbn2XqHPPHiVUoUlTOQk".to_string()),
+            consumer_key: Some("MtSwSrPpNjqfVSmJhLbPyr2i45lSDRQd".to_string()),
+            zone: Some("example.com".to_string()),
+            server: Some(server.to_string()),
+            ttl: Some(300),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ovh_default_servers() {
+        let dynhost = OvhClient::new(&Config {
+            login: Some("example.com-home".to_string()),
+            password: Some("secret".to_string()),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(dynhost.server, "www.ovh.com");
+        assert!(dynhost.consumer_key.is_none());
+
+        let mut config = create_api_config("unused");
+        config.server = None;
+        let api = OvhClient::new(&config).unwrap();
+        assert_eq!(api.server, "https://eu.api.ovh.com/1.0");
+    }
+
+    #[test]
+    fn test_ovh_endpoint_presets() {
+        assert_eq!(resolve_ovh_endpoint("ovh-eu"), "https://eu.api.ovh.com/1.0");
+        assert_eq!(resolve_ovh_endpoint("ovh-ca"), "https://ca.api.ovh.com/1.0");
+        assert_eq!(resolve_ovh_endpoint("ovh-us"), "https://api.us.ovhcloud.com/1.0");
+        assert_eq!(resolve_ovh_endpoint("soyoustart-eu"), "https://eu.api.soyoustart.com/1.0");
+        assert_eq!(resolve_ovh_endpoint("soyoustart-ca"), "https://ca.api.soyoustart.com/1.0");
+        assert_eq!(resolve_ovh_endpoint("https://api.example.test/1.0"), "https://api.example.test/1.0");
+
+        let api = OvhClient::new(&create_api_config("ovh-ca")).unwrap();
+        assert_eq!(api.server, "https://ca.api.ovh.com/1.0");
+    }
+
+    /// Keys used by the published signature vectors in python-ovh's test suite (tests/test_client.py)
+    fn published_vector_config() -> Config {
+        Config {
+            protocol: Some("ovh".to_string()),
+            login: Some("TDPKJdwZwAQPwKX2".to_string()),
+            password: Some("9ufkBmLaTQ9nz5yMUlg79taH0GNnzDjk".to_string()),
+            consumer_key: Some("5mBuy6SUQcRw2ZUxg0cG68BoDKpED4KY".to_string()),
+            zone: Some("example.com".to_string()),
+            server: Some("ovh-eu".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ovh_signature() {
+        let client = OvhClient::new(&published_vector_config()).unwrap();
+
+        let signature = client.sign(
+            "5mBuy6SUQcRw2ZUxg0cG68BoDKpED4KY",
+            "GET",
+            "https://eu.api.ovh.com/1.0/auth",
+            "",
+            1457018875,
+        );
+        assert_eq!(signature, "$1$e9556054b6309771395efa467c22e627407461ad");
+
+        let signature = client.sign(
+            "5mBuy6SUQcRw2ZUxg0cG68BoDKpED4KY",
+            "POST",
+            "https://eu.api.ovh.com/1.0/auth",
+            r#"{"a":"b","c":"d"}"#,
+            1457018875,
+        );
+        assert_eq!(signature, "$1$ec2fb5c7a81f64723c77d2e5b609ae6f58a84fc1");
+    }
+
+    #[test]
+    fn test_ovh_auth_headers() {
+        let client = OvhClient::new(&published_vector_config()).unwrap();
+        let headers = client.auth_headers("GET", "https://eu.api.ovh.com/1.0/auth", "", 1457018875).unwrap();
+
+        assert_eq!(headers, vec![
+            ("X-Ovh-Application".to_string(), "TDPKJdwZwAQPwKX2".to_string()),
+            ("X-Ovh-Timestamp".to_string(), "1457018875".to_string()),
+            ("X-Ovh-Consumer".to_string(), "5mBuy6SUQcRw2ZUxg0cG68BoDKpED4KY".to_string()),
+            ("X-Ovh-Signature".to_string(), "$1$e9556054b6309771395efa467c22e627407461ad".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_ovh_validate_api_config_requires_zone() {
+        let mut config = create_api_config("https://eu.api.ovh.com/1.0");
+        config.zone = None;
+        let client = OvhClient::new(&config).unwrap();
+
+        let result = client.validate_config();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("zone is required"));
+    }
+
+    #[test]
+    fn test_ovh_api_update_signed_requests() {
+        let mut server = mockito::Server::new();
+        let mut config = create_api_config(&server.url());
+        config.user_agent = Some("ovh-test/1.0".to_string());
+        let client = OvhClient::new(&config).unwrap();
+
+        // Fetched once per update, not before every call
+        let time_mock = server.mock("GET", "/auth/time")
+            .with_body("1457018875")
+            .expect(1)
+            .create();
+        let lookup_mock = server.mock("GET", "/domain/zone/example.com/record")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("fieldType".into(), "A".into()),
+                mockito::Matcher::UrlEncoded("subDomain".into(), "home".into()),
+            ]))
+            .match_header("X-Ovh-Application", "7kbG7Bk7S9Nt7ZSV")
+            .match_header("X-Ovh-Consumer", "MtSwSrPpNjqfVSmJhLbPyr2i45lSDRQd")
+            .match_header("X-Ovh-Timestamp", "1457018875")
+            .match_header("User-Agent", "ovh-test/1.0")
+            .with_body("[12345]")
+            .create();
+        let expected_body = r#"{"target":"203.0.113.1","ttl":300}"#;
+        let update_url = format!("{}/domain/zone/example.com/record/12345", server.url());
+        let update_mock = server.mock("PUT", "/domain/zone/example.com/record/12345")
+            .match_header("X-Ovh-Signature", client.sign(
+                "MtSwSrPpNjqfVSmJhLbPyr2i45lSDRQd", "PUT", &update_url, expected_body, 1457018875,
+            ).as_str())
+            .match_body(expected_body)
+            .with_body("null")
+            .create();
+        let refresh_mock = server.mock("POST", "/domain/zone/example.com/refresh")
+            .create();
+
+        let result = client.update_record("home.example.com", "203.0.113.1".parse().unwrap());
+        assert!(result.is_ok(), "{:?}", result);
+
+        time_mock.assert();
+        lookup_mock.assert();
+        update_mock.assert();
+        refresh_mock.assert();
+    }
+
+    #[test]
+    fn test_ovh_preview_update() {
+        let client = OvhClient::new(&create_api_config("https://eu.api.ovh.com/1.0")).unwrap();
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        let lines: Vec<_> = requests.iter().map(|request| format!("{} {}", request.method, request.url)).collect();
+        assert_eq!(lines, [
+            "GET https://eu.api.ovh.com/1.0/auth/time",
+            "GET https://eu.api.ovh.com/1.0/domain/zone/example.com/record?fieldType=A&subDomain=home",
+            "PUT https://eu.api.ovh.com/1.0/domain/zone/example.com/record/{record_id}",
+            "POST https://eu.api.ovh.com/1.0/domain/zone/example.com/refresh",
+        ]);
+        assert_eq!(requests[2].body.as_deref(), Some(r#"{"target":"203.0.113.1","ttl":300}"#));
+        assert!(!requests[2].to_string().contains("MtSwSrPpNjqfVSmJhLbPyr2i45lSDRQd"));
+        assert!(client.preview_update("home.example.org", "203.0.113.1".parse().unwrap()).is_err());
+
+        let dynhost = OvhClient::new(&Config {
+            login: Some("example.com-home".to_string()),
+            password: Some("secret".to_string()),
+            ..Default::default()
+        }).unwrap();
+        let requests = dynhost.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://www.ovh.com/nic/update?system=dyndns&hostname=home.example.com&myip=203.0.113.1");
+    }
 }
@@ -1,14 +1,20 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 use serde_json::json;
 
+/// Porkbun rejects TTLs below 600 seconds
+const MIN_TTL: u32 = 600;
+
 /// Porkbun client - https://porkbun.com/api/json/v3/documentation
 pub struct PorkbunClient {
     api_key: String,
     secret_key: String,
     server: String,
+    ttl: u32,
+    http: http::HttpClient,
 }
 
 impl PorkbunClient {
@@ -24,10 +30,21 @@ impl PorkbunClient {
             .cloned()
             .unwrap_or_else(|| "https://porkbun.com/api/json/v3".to_string());
 
+        let ttl = match config.ttl {
+            Some(ttl) if ttl < MIN_TTL => {
+                log::warn!("Porkbun minimum TTL is {}s; using {} instead of {}", MIN_TTL, MIN_TTL, ttl);
+                MIN_TTL
+            }
+            Some(ttl) => ttl,
+            None => MIN_TTL,
+        };
+
         Ok(PorkbunClient {
             api_key,
             secret_key,
             server,
+            ttl,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
 
@@ -41,36 +58,40 @@ impl PorkbunClient {
             ("".to_string(), hostname.to_string())
         }
     }
-}
-
-impl DnsClient for PorkbunClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let (name, domain) = self.parse_hostname(hostname);
-        
-        // Determine record type based on IP version
-        let record_type = match ip {
-            IpAddr::V4(_) => "A",
-            IpAddr::V6(_) => "AAAA",
-        };
-        
-        let url = format!("{}/dns/editByNameType/{}/{}", self.server, domain, record_type);
-        
-        let subdomain = if name.is_empty() { None } else { Some(name.as_str()) };
 
+    fn request_body(&self, name: &str, ip: IpAddr) -> serde_json::Value {
         let mut body = json!({
             "apikey": self.api_key,
             "secretapikey": self.secret_key,
             "content": ip.to_string(),
-            "ttl": "600"
+            "ttl": self.ttl.to_string()
         });
 
-        if let Some(sub) = subdomain {
-            body["name"] = json!(sub);
+        if !name.is_empty() {
+            body["name"] = json!(name);
         }
+        body
+    }
+
+    fn update_url(&self, domain: &str, ip: IpAddr) -> String {
+        // Determine record type based on IP version
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+        format!("{}/dns/editByNameType/{}/{}", self.server, domain, record_type)
+    }
+}
+
+impl DnsClient for PorkbunClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let (name, domain) = self.parse_hostname(hostname);
+        let url = self.update_url(&domain, ip);
+        let body = self.request_body(&name, ip);
 
         log::info!("Updating {} with Porkbun", hostname);
 
-        let response = minreq::post(&url)
+        let response = self.http.prepare(minreq::post(&url))
             .with_header("Content-Type", "application/json")
             .with_json(&body)?
             .send()?;
@@ -93,10 +114,95 @@ impl DnsClient for PorkbunClient {
         if self.secret_key.is_empty() {
             return Err("Secret key (password) is required for Porkbun".into());
         }
+        if self.ttl < MIN_TTL {
+            return Err(format!("Porkbun TTL must be at least {} seconds", MIN_TTL).into());
+        }
         Ok(())
     }
 
     fn provider_name(&self) -> &str {
         "Porkbun"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let (name, domain) = self.parse_hostname(hostname);
+        Ok(vec![self.http.preview("POST", self.update_url(&domain, ip))
+            .with_header("Content-Type", "application/json")
+            .with_body(self.request_body(&name, ip).to_string())
+            .with_secret(&self.api_key)
+            .with_secret(&self.secret_key)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(ttl: Option<u32>) -> Config {
+        Config {
+            protocol: Some("porkbun".to_string()),
+            login: Some("pk1_key".to_string()),
+            password: Some("sk1_secret".to_string()),
+            ttl,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_porkbun_ttl_in_request_body() {
+        let client = PorkbunClient::new(&test_config(Some(3600))).unwrap();
+        assert_eq!(
+            client.request_body("home", "203.0.113.1".parse().unwrap()),
+            json!({
+                "apikey": "pk1_key",
+                "secretapikey": "sk1_secret",
+                "content": "203.0.113.1",
+                "ttl": "3600",
+                "name": "home",
+            })
+        );
+
+        let body = client.request_body("", "203.0.113.1".parse().unwrap());
+        assert!(body.get("name").is_none());
+    }
+
+    #[test]
+    fn test_porkbun_ttl_default_and_minimum() {
+        let client = PorkbunClient::new(&test_config(None)).unwrap();
+        assert_eq!(client.request_body("", "203.0.113.1".parse().unwrap())["ttl"], "600");
+
+        // Values below Porkbun's minimum are clamped
+        let client = PorkbunClient::new(&test_config(Some(60))).unwrap();
+        assert_eq!(client.ttl, 600);
+        assert!(client.validate_config().is_ok());
+    }
+
+    #[test]
+    fn test_porkbun_update_sends_ttl() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/dns/editByNameType/example.com/A")
+            .match_body(mockito::Matcher::PartialJson(json!({"ttl": "1200", "name": "home"})))
+            .with_body(r#"{"status":"SUCCESS"}"#)
+            .create();
+
+        let mut config = test_config(Some(1200));
+        config.server = Some(server.url());
+        let client = PorkbunClient::new(&config).unwrap();
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_porkbun_preview_update() {
+        let client = PorkbunClient::new(&test_config(None)).unwrap();
+        let requests = client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://porkbun.com/api/json/v3/dns/editByNameType/example.com/AAAA");
+        assert_eq!(
+            requests[0].body.as_deref(),
+            Some(r#"{"apikey":"pk1_key","content":"2001:db8::1","name":"home","secretapikey":"sk1_secret","ttl":"600"}"#)
+        );
+        let shown = requests[0].to_string();
+        assert!(!shown.contains("pk1_key") && !shown.contains("sk1_secret"));
+    }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -8,6 +9,7 @@ use std::net::IpAddr;
 pub struct RegfishClient {
     server: String,
     token: String,
+    http: http::HttpClient,
 }
 
 impl RegfishClient {
@@ -23,22 +25,23 @@ impl RegfishClient {
         Ok(RegfishClient {
             server,
             token,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
 
-impl DnsClient for RegfishClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
-        let url = format!(
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!(
             "{}/?fqdn={}&myip={}&forcehost=1&authtype=secure&token={}",
             self.server, hostname, ip, self.token
-        );
+        )
+    }
+}
 
+impl DnsClient for RegfishClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with Regfish", hostname);
 
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_timeout(30)
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
             .send()?;
 
         let status_code = response.status_code;
@@ -79,4 +82,31 @@ impl DnsClient for RegfishClient {
     fn provider_name(&self) -> &str {
         "Regfish"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regfish_preview_update() {
+        let config = Config {
+            protocol: Some("regfish".to_string()),
+            password: Some("regfish-token".to_string()),
+            ..Default::default()
+        };
+        let client = RegfishClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://dyndns.regfish.de/?fqdn=home.example.com&myip=203.0.113.1&forcehost=1&authtype=secure&token=regfish-token"
+        );
+        assert!(!requests[0].to_string().contains("regfish-token"));
+    }
 }
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -9,6 +10,7 @@ pub struct SelfhostClient {
     server: String,
     username: String,
     password: String,
+    http: http::HttpClient,
 }
 
 impl SelfhostClient {
@@ -28,25 +30,26 @@ impl SelfhostClient {
             server,
             username,
             password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// Selfhost.de DynDNS2 compatible endpoint
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/nic/update?hostname={}&myip={}", self.server, hostname, ip)
+    }
+
+    fn auth_header(&self) -> String {
+        format!("Basic {}", base64::encode(&format!("{}:{}", self.username, self.password)))
+    }
 }
 
 impl DnsClient for SelfhostClient {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with Selfhost.de", hostname);
 
-        // Selfhost.de DynDNS2 compatible endpoint
-        let url = format!("{}/nic/update", self.server);
-
-        let auth = format!("{}:{}", self.username, self.password);
-        let encoded_auth = format!("Basic {}", base64::encode(&auth));
-
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", &encoded_auth)
-            .with_param("hostname", hostname)
-            .with_param("myip", ip.to_string())
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         let status_code = response.status_code;
@@ -88,6 +91,11 @@ impl DnsClient for SelfhostClient {
     fn provider_name(&self) -> &str {
         "Selfhost.de"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
 }
 
 mod base64 {
@@ -97,3 +105,25 @@ mod base64 {
         general_purpose::STANDARD.encode(data.as_bytes())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selfhost_preview_update() {
+        let config = Config {
+            protocol: Some("selfhost".to_string()),
+            login: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            ..Default::default()
+        };
+        let client = SelfhostClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://carol.selfhost.de/nic/update?hostname=home.example.com&myip=203.0.113.1");
+        // base64("user:pass")
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string())));
+    }
+}
@@ -0,0 +1,376 @@
+use crate::clients::{DnsClient, RequestPreview};
+use crate::config::Config;
+use crate::http;
+use serde_json::json;
+use std::error::Error;
+use std::net::IpAddr;
+
+/// Vultr DNS client
+/// API documentation: https://www.vultr.com/api/#tag/dns
+///
+/// Configuration:
+/// - password: Vultr API key
+/// - ttl: record TTL in seconds (default 300)
+///
+/// The domain is found by matching the hostname against the domains on the account;
+/// missing A/AAAA records are created.
+pub struct VultrClient {
+    api_key: String,
+    server: String,
+    ttl: u32,
+    http: http::HttpClient,
+}
+
+impl VultrClient {
+    pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let api_key = config.password.as_ref()
+            .ok_or("password (API key) is required for Vultr")?
+            .clone();
+        let server = config.server.clone()
+            .unwrap_or_else(|| "https://api.vultr.com".to_string());
+
+        Ok(VultrClient {
+            api_key,
+            server,
+            ttl: config.ttl.unwrap_or(300),
+            http: http::HttpClientBuilder::new(config).build(),
+        })
+    }
+
+    fn auth_header(&self) -> String {
+        format!("Bearer {}", self.api_key)
+    }
+
+    fn records_url(&self, domain: &str) -> String {
+        format!("{}/v2/domains/{}/records", self.server, domain)
+    }
+
+    fn get(&self, url: &str) -> Result<serde_json::Value, Box<dyn Error>> {
+        let response = self.http.prepare(minreq::get(url))
+            .with_header("Authorization", self.auth_header())
+            .send()?;
+
+        if response.status_code != 200 {
+            return Err(format!("Vultr API error (HTTP {}): {}",
+                response.status_code, response.as_str().unwrap_or("")).into());
+        }
+        Ok(response.json()?)
+    }
+
+    /// Pick the longest account domain that `hostname` is equal to or a subdomain of
+    fn match_domain(hostname: &str, domains: &[String]) -> Option<String> {
+        domains
+            .iter()
+            .filter(|domain| hostname == *domain || hostname.ends_with(&format!(".{}", domain)))
+            .max_by_key(|domain| domain.len())
+            .cloned()
+    }
+
+    /// List the domains on the account, following the pagination cursor
+    fn list_domains(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut domains = Vec::new();
+        let mut cursor = String::new();
+
+        loop {
+            let mut url = format!("{}/v2/domains?per_page=500", self.server);
+            if !cursor.is_empty() {
+                url.push_str(&format!("&cursor={}", urlencoding::encode(&cursor)));
+            }
+            let json = self.get(&url)?;
+
+            if let Some(entries) = json["domains"].as_array() {
+                domains.extend(entries.iter().filter_map(|d| d["domain"].as_str().map(str::to_string)));
+            }
+
+            match json["meta"]["links"]["next"].as_str() {
+                Some(next) if !next.is_empty() => cursor = next.to_string(),
+                _ => break,
+            }
+        }
+
+        Ok(domains)
+    }
+
+    fn find_domain(&self, hostname: &str) -> Result<String, Box<dyn Error>> {
+        let domains = self.list_domains()?;
+        Self::match_domain(hostname, &domains)
+            .ok_or_else(|| format!("No Vultr domain found for {}", hostname).into())
+    }
+
+    /// Record name relative to the domain (Vultr uses "" for the apex)
+    fn record_name<'a>(hostname: &'a str, domain: &str) -> &'a str {
+        if hostname == domain {
+            ""
+        } else {
+            hostname.strip_suffix(&format!(".{}", domain)).unwrap_or(hostname)
+        }
+    }
+
+    /// Find the ID of the existing record of `record_type` named `name`
+    fn find_record(&self, domain: &str, name: &str, record_type: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let url = format!("{}?type={}&per_page=500", self.records_url(domain), record_type);
+        let json = self.get(&url)?;
+
+        let id = json["records"].as_array()
+            .into_iter()
+            .flatten()
+            .find(|record| record["type"] == record_type && record["name"] == name)
+            .and_then(|record| record["id"].as_str())
+            .map(str::to_string);
+        Ok(id)
+    }
+
+    fn update_body(&self, ip: IpAddr) -> serde_json::Value {
+        json!({
+            "data": ip.to_string(),
+            "ttl": self.ttl,
+        })
+    }
+
+    fn update_existing(&self, domain: &str, id: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let url = format!("{}/{}", self.records_url(domain), id);
+        let response = self.http.prepare(minreq::patch(&url))
+            .with_header("Authorization", self.auth_header())
+            .with_json(&self.update_body(ip))?
+            .send()?;
+
+        match response.status_code {
+            200 | 204 => Ok(()),
+            status => Err(format!("Vultr API error (HTTP {}): {}", status, response.as_str().unwrap_or("")).into()),
+        }
+    }
+
+    fn create(&self, domain: &str, name: &str, record_type: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let response = self.http.prepare(minreq::post(self.records_url(domain)))
+            .with_header("Authorization", self.auth_header())
+            .with_json(&json!({
+                "name": name,
+                "type": record_type,
+                "data": ip.to_string(),
+                "ttl": self.ttl,
+            }))?
+            .send()?;
+
+        match response.status_code {
+            200 | 201 => Ok(()),
+            status => Err(format!("Vultr API error (HTTP {}): {}", status, response.as_str().unwrap_or("")).into()),
+        }
+    }
+}
+
+impl DnsClient for VultrClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let record_type = match ip {
+            IpAddr::V4(_) => "A",
+            IpAddr::V6(_) => "AAAA",
+        };
+
+        log::info!("Updating {} {} record with Vultr", hostname, record_type);
+
+        let domain = self.find_domain(hostname)?;
+        let name = Self::record_name(hostname, &domain);
+
+        match self.find_record(&domain, name, record_type)? {
+            Some(id) => {
+                log::debug!("Found record ID {} for {}", id, hostname);
+                self.update_existing(&domain, &id, ip)?;
+            }
+            None => {
+                log::info!("No {} record for {}, creating it", record_type, hostname);
+                self.create(&domain, name, record_type, ip)?;
+            }
+        }
+
+        log::info!("DNS record for {} successfully updated to {}", hostname, ip);
+        Ok(())
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn Error>> {
+        if self.api_key.is_empty() {
+            return Err("password (API key) is required for Vultr".into());
+        }
+        Ok(())
+    }
+
+    fn provider_name(&self) -> &str {
+        "Vultr"
+    }
+
+    /// Only the first page of domains is shown; a missing record is created with a POST
+    /// to the records URL instead of the PATCH
+    fn preview_update(&self, _hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+        let records_url = self.records_url("{domain}");
+        Ok(vec![
+            self.http.preview("GET", format!("{}/v2/domains?per_page=500", self.server))
+                .with_header("Authorization", self.auth_header()),
+            self.http.preview("GET", format!("{}?type={}&per_page=500", records_url, record_type))
+                .with_header("Authorization", self.auth_header()),
+            self.http.preview("PATCH", format!("{}/{{record_id}}", records_url))
+                .with_header("Authorization", self.auth_header())
+                .with_header("Content-Type", "application/json")
+                .with_body(self.update_body(ip).to_string()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(server: Option<String>) -> Config {
+        Config {
+            protocol: Some("vultr".to_string()),
+            password: Some("vultr-api-key".to_string()),
+            server,
+            ..Default::default()
+        }
+    }
+
+    fn mock_domains(server: &mut mockito::Server) -> mockito::Mock {
+        server.mock("GET", "/v2/domains")
+            .match_query(mockito::Matcher::Any)
+            .match_header("authorization", "Bearer vultr-api-key")
+            .with_body(r#"{"domains":[{"domain":"example.com"},{"domain":"lab.example.com"}],
+                           "meta":{"total":2,"links":{"next":"","prev":""}}}"#)
+            .create()
+    }
+
+    #[test]
+    fn test_vultr_client_creation() {
+        let client = VultrClient::new(&test_config(None)).unwrap();
+        assert_eq!(client.api_key, "vultr-api-key");
+        assert_eq!(client.server, "https://api.vultr.com");
+        assert_eq!(client.ttl, 300);
+        assert_eq!(client.provider_name(), "Vultr");
+        assert!(client.validate_config().is_ok());
+    }
+
+    #[test]
+    fn test_vultr_missing_api_key() {
+        let result = VultrClient::new(&Config::default());
+        assert!(result.is_err());
+        assert!(result.err().unwrap().to_string().contains("API key"));
+    }
+
+    #[test]
+    fn test_vultr_match_domain() {
+        let domains = vec!["example.com".to_string(), "lab.example.com".to_string()];
+        assert_eq!(VultrClient::match_domain("www.example.com", &domains), Some("example.com".to_string()));
+        assert_eq!(VultrClient::match_domain("host.lab.example.com", &domains), Some("lab.example.com".to_string()));
+        assert_eq!(VultrClient::match_domain("example.com", &domains), Some("example.com".to_string()));
+        assert_eq!(VultrClient::match_domain("notexample.com", &domains), None);
+    }
+
+    #[test]
+    fn test_vultr_record_name() {
+        assert_eq!(VultrClient::record_name("www.example.com", "example.com"), "www");
+        assert_eq!(VultrClient::record_name("a.b.example.com", "example.com"), "a.b");
+        assert_eq!(VultrClient::record_name("example.com", "example.com"), "");
+    }
+
+    #[test]
+    fn test_vultr_list_domains_follows_cursor() {
+        let mut server = mockito::Server::new();
+        let page2 = server.mock("GET", "/v2/domains")
+            .match_query(mockito::Matcher::UrlEncoded("cursor".to_string(), "bmV4dA==".to_string()))
+            .with_body(r#"{"domains":[{"domain":"example.org"}],"meta":{"links":{"next":"","prev":""}}}"#)
+            .create();
+        let page1 = server.mock("GET", "/v2/domains")
+            .match_query(mockito::Matcher::Exact("per_page=500".to_string()))
+            .with_body(r#"{"domains":[{"domain":"example.com"}],"meta":{"links":{"next":"bmV4dA==","prev":""}}}"#)
+            .create();
+
+        let client = VultrClient::new(&test_config(Some(server.url()))).unwrap();
+        assert_eq!(client.list_domains().unwrap(), vec!["example.com", "example.org"]);
+        page1.assert();
+        page2.assert();
+    }
+
+    #[test]
+    fn test_vultr_update_existing_record() {
+        let mut server = mockito::Server::new();
+        let domains = mock_domains(&mut server);
+        let records = server.mock("GET", "/v2/domains/example.com/records")
+            .match_query(mockito::Matcher::UrlEncoded("type".to_string(), "A".to_string()))
+            .with_body(r#"{"records":[
+                {"id":"mx-1","type":"MX","name":"home","data":"mail.example.com","ttl":300},
+                {"id":"a-1","type":"A","name":"home","data":"198.51.100.1","ttl":300}
+            ]}"#)
+            .create();
+        let patch = server.mock("PATCH", "/v2/domains/example.com/records/a-1")
+            .match_header("authorization", "Bearer vultr-api-key")
+            .match_body(mockito::Matcher::Json(json!({"data": "203.0.113.1", "ttl": 300})))
+            .with_status(204)
+            .create();
+
+        let client = VultrClient::new(&test_config(Some(server.url()))).unwrap();
+        assert!(client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).is_ok());
+        domains.assert();
+        records.assert();
+        patch.assert();
+    }
+
+    #[test]
+    fn test_vultr_creates_missing_record() {
+        let mut server = mockito::Server::new();
+        let _domains = mock_domains(&mut server);
+        let _records = server.mock("GET", "/v2/domains/lab.example.com/records")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"records":[]}"#)
+            .create();
+        let create = server.mock("POST", "/v2/domains/lab.example.com/records")
+            .match_body(mockito::Matcher::Json(json!({
+                "name": "",
+                "type": "AAAA",
+                "data": "2001:db8::1",
+                "ttl": 120,
+            })))
+            .with_status(201)
+            .with_body(r#"{"record":{"id":"new-1"}}"#)
+            .create();
+
+        let mut config = test_config(Some(server.url()));
+        config.ttl = Some(120);
+        let client = VultrClient::new(&config).unwrap();
+        assert!(client.update_record("lab.example.com", "2001:db8::1".parse().unwrap()).is_ok());
+        create.assert();
+    }
+
+    #[test]
+    fn test_vultr_unknown_domain() {
+        let mut server = mockito::Server::new();
+        let _domains = mock_domains(&mut server);
+
+        let client = VultrClient::new(&test_config(Some(server.url()))).unwrap();
+        let err = client.update_record("home.example.net", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("No Vultr domain"));
+    }
+
+    #[test]
+    fn test_vultr_api_error() {
+        let mut server = mockito::Server::new();
+        let _domains = server.mock("GET", "/v2/domains")
+            .match_query(mockito::Matcher::Any)
+            .with_status(401)
+            .with_body(r#"{"error":"Invalid API token.","status":401}"#)
+            .create();
+
+        let client = VultrClient::new(&test_config(Some(server.url()))).unwrap();
+        let err = client.update_record("home.example.com", "203.0.113.1".parse().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("HTTP 401"));
+    }
+
+    #[test]
+    fn test_vultr_preview_update() {
+        let client = VultrClient::new(&test_config(None)).unwrap();
+        let requests = client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].url, "https://api.vultr.com/v2/domains?per_page=500");
+        assert_eq!(requests[1].url, "https://api.vultr.com/v2/domains/{domain}/records?type=AAAA&per_page=500");
+        assert_eq!(requests[2].method, "PATCH");
+        assert_eq!(requests[2].url, "https://api.vultr.com/v2/domains/{domain}/records/{record_id}");
+        assert_eq!(requests[2].body.as_deref(), Some(r#"{"data":"2001:db8::1","ttl":300}"#));
+        assert!(!requests[2].to_string().contains("vultr-api-key"));
+    }
+}
@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 
@@ -9,6 +10,7 @@ pub struct YandexClient {
     server: String,
     token: String,
     domain: String,
+    http: http::HttpClient,
 }
 
 impl YandexClient {
@@ -28,12 +30,11 @@ impl YandexClient {
             server,
             token,
             domain,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
-}
 
-impl DnsClient for YandexClient {
-    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
         let record_type = match ip {
             IpAddr::V4(_) => "A",
             IpAddr::V6(_) => "AAAA",
@@ -46,15 +47,18 @@ impl DnsClient for YandexClient {
             hostname
         };
 
-        let url = format!(
+        format!(
             "{}/api2/admin/dns/edit?domain={}&subdomain={}&record_id=0&type={}&content={}",
             self.server, self.domain, subdomain, record_type, ip
-        );
+        )
+    }
+}
 
+impl DnsClient for YandexClient {
+    fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with Yandex", hostname);
 
-        let response = minreq::post(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
+        let response = self.http.prepare(minreq::post(self.update_url(hostname, ip)))
             .with_header("PddToken", &self.token)
             .send()?;
 
@@ -91,4 +95,34 @@ impl DnsClient for YandexClient {
     fn provider_name(&self) -> &str {
         "Yandex"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("POST", self.update_url(hostname, ip))
+            .with_header("PddToken", &self.token)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yandex_preview_update() {
+        let config = Config {
+            protocol: Some("yandex".to_string()),
+            password: Some("pddtoken".to_string()),
+            zone: Some("example.com".to_string()),
+            ..Default::default()
+        };
+        let client = YandexClient::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(
+            requests[0].url,
+            "https://pddimp.yandex.ru/api2/admin/dns/edit?domain=example.com&subdomain=home&record_id=0&type=AAAA&content=2001:db8::1"
+        );
+        assert!(requests[0].headers.contains(&("PddToken".to_string(), "pddtoken".to_string())));
+    }
 }
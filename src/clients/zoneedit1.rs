@@ -1,5 +1,6 @@
-use crate::clients::DnsClient;
+use crate::clients::{DnsClient, RequestPreview};
 use crate::config::Config;
+use crate::http;
 use std::error::Error;
 use std::net::IpAddr;
 use base64::{Engine as _, engine::general_purpose};
@@ -10,6 +11,7 @@ pub struct Zoneedit1Client {
     server: String,
     username: String,
     password: String,
+    http: http::HttpClient,
 }
 
 impl Zoneedit1Client {
@@ -29,25 +31,27 @@ impl Zoneedit1Client {
             server,
             username,
             password,
+            http: http::HttpClientBuilder::new(config).build(),
         })
     }
+
+    /// ZoneEdit v1 update endpoint
+    fn update_url(&self, hostname: &str, ip: IpAddr) -> String {
+        format!("{}/auth/dynamic.html?host={}&dnsto={}", self.server, hostname, ip)
+    }
+
+    fn auth_header(&self) -> String {
+        let auth = format!("{}:{}", self.username, self.password);
+        format!("Basic {}", general_purpose::STANDARD.encode(auth.as_bytes()))
+    }
 }
 
 impl DnsClient for Zoneedit1Client {
     fn update_record(&self, hostname: &str, ip: IpAddr) -> Result<(), Box<dyn Error>> {
         log::info!("Updating {} with ZoneEdit v1", hostname);
 
-        // ZoneEdit v1 update endpoint
-        let url = format!("{}/auth/dynamic.html", self.server);
-
-        let auth = format!("{}:{}", self.username, self.password);
-        let encoded_auth = format!("Basic {}", general_purpose::STANDARD.encode(auth.as_bytes()));
-
-        let response = minreq::get(&url)
-            .with_header("User-Agent", crate::USER_AGENT)
-            .with_header("Authorization", &encoded_auth)
-            .with_param("host", hostname)
-            .with_param("dnsto", ip.to_string())
+        let response = self.http.prepare(minreq::get(self.update_url(hostname, ip)))
+            .with_header("Authorization", self.auth_header())
             .send()?;
 
         let status_code = response.status_code;
@@ -91,4 +95,31 @@ impl DnsClient for Zoneedit1Client {
     fn provider_name(&self) -> &str {
         "ZoneEdit v1"
     }
+
+    fn preview_update(&self, hostname: &str, ip: IpAddr) -> Result<Vec<RequestPreview>, Box<dyn Error>> {
+        Ok(vec![self.http.preview("GET", self.update_url(hostname, ip))
+            .with_header("Authorization", self.auth_header())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zoneedit1_preview_update() {
+        let config = Config {
+            protocol: Some("zoneedit1".to_string()),
+            login: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            ..Default::default()
+        };
+        let client = Zoneedit1Client::new(&config).unwrap();
+
+        let requests = client.preview_update("home.example.com", "203.0.113.1".parse().unwrap()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://dynamic.zoneedit.com/auth/dynamic.html?host=home.example.com&dnsto=203.0.113.1");
+        // base64("user:pass")
+        assert!(requests[0].headers.contains(&("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string())));
+    }
 }
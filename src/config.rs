@@ -3,6 +3,7 @@
 /// This module parses the ddclient configuration format:
 /// - key=value pairs (comma-separated or on separate lines)
 /// - Backslash line continuation
+/// - Single-quoted values, which may contain commas and spaces
 /// - Global defaults that apply to subsequent blocks
 /// - Host blocks terminated by bare hostnames
 ///
@@ -14,13 +15,21 @@
 /// password=key \
 /// host1.example.com,host2.example.com
 /// ```
-use std::collections::HashMap;
+use crate::utils::mask_credential;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// HTTP timeout for provider requests when `timeout=` / `--timeout` isn't set
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// (min_interval, max_interval) in seconds
+pub type Intervals = (Option<u64>, Option<u64>);
 
 // Main Config struct used throughout the codebase
-#[derive(Debug, Clone, Default)]
+#[derive(Clone, PartialEq)]
 pub struct Config {
     pub protocol: Option<String>,
     pub login: Option<String>,
@@ -31,20 +40,163 @@ pub struct Config {
     pub ttl: Option<u32>,
     pub email: Option<String>,
     pub ip: Option<String>,
+    pub consumer_key: Option<String>,
+    pub subscription_id: Option<String>,
+    pub resource_group: Option<String>,
+    pub tenant_id: Option<String>,
+    /// Afraid.org per-record update URL (`afraid_update_url=`), used instead of login/password
+    pub afraid_update_url: Option<String>,
+    /// Freedns authentication mode (`freedns_auth=token|account`), default token
+    pub freedns_auth: Option<String>,
+    /// Base32 TOTP secret for INWX accounts with two-factor authentication (`inwx_totp=`)
+    pub inwx_totp: Option<String>,
+    pub min_interval: Option<u64>,
+    pub max_interval: Option<u64>,
+    /// Additional HTTP headers sent with every provider request (`header.<name>=` / `--header`)
+    pub extra_headers: Vec<(String, String)>,
+    /// Per-host (min_interval, max_interval) from config blocks whose intervals differ from the first block's
+    pub host_intervals: HashMap<String, Intervals>,
+    /// Use the provider's test/sandbox API where one exists (`--provider-test`)
+    pub provider_test: bool,
+    /// Fetch every Cloudflare zone ID when the client starts (`--cf-preload-zones`)
+    pub cf_preload_zones: bool,
+    /// Ask DynDNS2 servers to mark the host offline (`offline=yes` / `--offline`)
+    pub offline: bool,
+    /// Timeout in seconds for each provider HTTP request (`timeout=` / `--timeout`)
+    pub timeout_secs: u64,
+    /// Extra CA certificate (PEM) trusted for provider HTTPS requests (`ca_cert=` / `--ca-cert`)
+    pub ca_cert_path: Option<PathBuf>,
+    /// User-Agent sent with provider requests (`user-agent=` / `--user-agent`); rddclient/<version> when unset
+    pub user_agent: Option<String>,
+    /// Skip IP web services whose response matches this regex (`web-skip=`)
+    pub web_skip: Option<String>,
+    /// Provider-specific retry count for transient errors (`retries=`)
+    pub retries: Option<u8>,
+    /// SMTP relay for the emailonly provider (`smtp_server=`); sendmail is used when unset
+    pub smtp_server: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            protocol: None,
+            login: None,
+            password: None,
+            server: None,
+            zone: None,
+            host: None,
+            ttl: None,
+            email: None,
+            ip: None,
+            consumer_key: None,
+            subscription_id: None,
+            resource_group: None,
+            tenant_id: None,
+            afraid_update_url: None,
+            freedns_auth: None,
+            inwx_totp: None,
+            min_interval: None,
+            max_interval: None,
+            extra_headers: Vec::new(),
+            host_intervals: HashMap::new(),
+            provider_test: false,
+            cf_preload_zones: false,
+            offline: false,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            ca_cert_path: None,
+            user_agent: None,
+            web_skip: None,
+            retries: None,
+            smtp_server: None,
+            smtp_port: None,
+            smtp_username: None,
+            smtp_password: None,
+        }
+    }
+}
+
+/// Secrets are masked so a logged config never exposes them
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let masked = |secret: &Option<String>| secret.as_deref().map(mask_credential);
+        f.debug_struct("Config")
+            .field("protocol", &self.protocol)
+            .field("login", &self.login)
+            .field("password", &masked(&self.password))
+            .field("server", &self.server)
+            .field("zone", &self.zone)
+            .field("host", &self.host)
+            .field("ttl", &self.ttl)
+            .field("email", &self.email)
+            .field("ip", &self.ip)
+            .field("consumer_key", &masked(&self.consumer_key))
+            .field("subscription_id", &self.subscription_id)
+            .field("resource_group", &self.resource_group)
+            .field("tenant_id", &self.tenant_id)
+            .field("afraid_update_url", &masked(&self.afraid_update_url))
+            .field("freedns_auth", &self.freedns_auth)
+            .field("inwx_totp", &masked(&self.inwx_totp))
+            .field("min_interval", &self.min_interval)
+            .field("max_interval", &self.max_interval)
+            .field("extra_headers", &self.extra_headers.iter()
+                .map(|(name, value)| (name, mask_credential(value)))
+                .collect::<Vec<_>>())
+            .field("host_intervals", &self.host_intervals)
+            .field("provider_test", &self.provider_test)
+            .field("cf_preload_zones", &self.cf_preload_zones)
+            .field("offline", &self.offline)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("ca_cert_path", &self.ca_cert_path)
+            .field("user_agent", &self.user_agent)
+            .field("web_skip", &self.web_skip)
+            .field("retries", &self.retries)
+            .field("smtp_server", &self.smtp_server)
+            .field("smtp_port", &self.smtp_port)
+            .field("smtp_username", &self.smtp_username)
+            .field("smtp_password", &masked(&self.smtp_password))
+            .finish()
+    }
 }
 
 impl Config {
     /// Load configuration from file (ddclient format)
     pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
-        let ddclient_config = DdclientConfig::from_file(path)?;
-        
-        // For now, take the first config block
-        // TODO: Support multiple host blocks
-        if let Some(host_config) = ddclient_config.configs.first() {
-            Ok(Config::from(host_config.clone()))
-        } else {
-            Err("No valid configuration found in file".into())
+        Self::from_blocks(&DdclientConfig::from_file(path)?)
+    }
+
+    /// The first block's settings with the hosts of every block that shares them.
+    /// Blocks may differ in their intervals; blocks for another provider or account are
+    /// skipped with a warning, as one run drives a single client.
+    fn from_blocks(ddclient_config: &DdclientConfig) -> Result<Self, Box<dyn Error>> {
+        let first = ddclient_config.configs.first()
+            .ok_or("No valid configuration found in file")?;
+
+        let mut hosts: Vec<String> = Vec::new();
+        let mut host_intervals = HashMap::new();
+        for block in &ddclient_config.configs {
+            let Some(block_hosts) = block.host.as_deref() else { continue };
+            if !block.same_settings(first) {
+                log::warn!("Skipping {}: only hosts with the same provider settings as the first block are updated", block_hosts);
+                continue;
+            }
+            let intervals = (block.min_interval, block.max_interval);
+            for host in split_hosts(block_hosts) {
+                if intervals != (first.min_interval, first.max_interval) {
+                    host_intervals.insert(host.clone(), intervals);
+                }
+                if !hosts.contains(&host) {
+                    hosts.push(host);
+                }
+            }
         }
+
+        let mut config = Config::from(first.clone());
+        config.host = (!hosts.is_empty()).then(|| hosts.join(","));
+        config.host_intervals = host_intervals;
+        Ok(config)
     }
 
     /// Merge configuration from file with CLI arguments
@@ -62,15 +214,47 @@ impl Config {
             ttl: args.ttl.or(base.ttl),
             email: base.email,
             ip: args.ip.clone().or(base.ip),
+            consumer_key: base.consumer_key,
+            subscription_id: base.subscription_id,
+            resource_group: base.resource_group,
+            tenant_id: base.tenant_id,
+            afraid_update_url: base.afraid_update_url,
+            freedns_auth: base.freedns_auth,
+            inwx_totp: base.inwx_totp,
+            min_interval: base.min_interval,
+            max_interval: base.max_interval,
+            extra_headers: base.extra_headers.into_iter().chain(args.header.iter().cloned()).collect(),
+            host_intervals: base.host_intervals,
+            provider_test: args.provider_test || base.provider_test,
+            cf_preload_zones: args.cf_preload_zones || base.cf_preload_zones,
+            offline: args.offline || base.offline,
+            timeout_secs: args.timeout.unwrap_or(base.timeout_secs),
+            ca_cert_path: args.ca_cert.clone().or(base.ca_cert_path),
+            user_agent: args.user_agent.clone().or(base.user_agent),
+            web_skip: base.web_skip,
+            retries: base.retries,
+            smtp_server: base.smtp_server,
+            smtp_port: base.smtp_port,
+            smtp_username: base.smtp_username,
+            smtp_password: base.smtp_password,
         }
     }
 
+    /// Config file path from --file, or the default
+    pub fn file_path(args: &crate::args::Args) -> &str {
+        args.file.as_deref().unwrap_or("rddclient.conf")
+    }
+
     /// Load and merge configuration
     pub fn load(args: &crate::args::Args) -> Result<Self, Box<dyn Error>> {
-        let default_config_path = "rddclient.conf";
-        let config_file = args.file.as_deref().unwrap_or(default_config_path);
+        let config_file = Self::file_path(args);
 
         let file_config = if Path::new(config_file).exists() {
+            if !args.ignore_permissions {
+                if let Some(warning) = permission_warning(Path::new(config_file)) {
+                    log::warn!("{}", warning);
+                }
+            }
             Some(Self::from_file(config_file)?)
         } else {
             None
@@ -93,15 +277,96 @@ impl Config {
 
     /// Get DNS records as a vector
     pub fn dns_records(&self) -> Vec<String> {
-        self.host
-            .as_ref()
-            .map(|h| {
-                h.split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect()
-            })
-            .unwrap_or_default()
+        self.host.as_deref().map(split_hosts).unwrap_or_default()
+    }
+
+    /// Render as a ddclient-format config: one `key=value` line per set option, then the hosts.
+    /// Hosts with their own intervals get blocks of their own, keeping the host order.
+    /// Command-line-only settings (`--ip`, `--provider-test`) have no config key and are left out.
+    pub fn to_ddclient_format(&self) -> String {
+        let mut lines = Vec::new();
+        let mut push = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                lines.push(format!("{}={}", key, quote_value(&value)));
+            }
+        };
+
+        push("protocol", self.protocol.clone());
+        push("login", self.login.clone());
+        push("password", self.password.clone());
+        push("server", self.server.clone());
+        push("zone", self.zone.clone());
+        push("ttl", self.ttl.map(|ttl| ttl.to_string()));
+        push("email", self.email.clone());
+        push("consumer_key", self.consumer_key.clone());
+        push("subscription_id", self.subscription_id.clone());
+        push("resource_group", self.resource_group.clone());
+        push("tenant_id", self.tenant_id.clone());
+        push("afraid_update_url", self.afraid_update_url.clone());
+        push("freedns_auth", self.freedns_auth.clone());
+        push("inwx_totp", self.inwx_totp.clone());
+        push("min-interval", self.min_interval.map(|secs| format!("{}s", secs)));
+        push("max-interval", self.max_interval.map(|secs| format!("{}s", secs)));
+        push("timeout", (self.timeout_secs != DEFAULT_TIMEOUT_SECS).then(|| self.timeout_secs.to_string()));
+        push("ca_cert", self.ca_cert_path.as_ref().map(|path| path.display().to_string()));
+        push("user-agent", self.user_agent.clone());
+        push("web-skip", self.web_skip.clone());
+        push("offline", self.offline.then(|| "yes".to_string()));
+        push("retries", self.retries.map(|retries| retries.to_string()));
+        push("smtp_server", self.smtp_server.clone());
+        push("smtp_port", self.smtp_port.map(|port| port.to_string()));
+        push("smtp_user", self.smtp_username.clone());
+        push("smtp_pass", self.smtp_password.clone());
+        for (name, value) in &self.extra_headers {
+            push(&format!("header.{}", name), Some(value.clone()));
+        }
+
+        // Consecutive hosts with the same intervals share a block
+        let hosts = self.dns_records();
+        let mut blocks: Vec<(Option<&Intervals>, Vec<&str>)> = Vec::new();
+        for host in &hosts {
+            let intervals = self.host_intervals.get(host);
+            match blocks.last_mut() {
+                Some((last, block_hosts)) if *last == intervals => block_hosts.push(host),
+                _ => blocks.push((intervals, vec![host])),
+            }
+        }
+
+        for (index, (intervals, block_hosts)) in blocks.into_iter().enumerate() {
+            if index > 0 {
+                lines.push(String::new());
+            }
+            if let Some((min, max)) = intervals {
+                lines.extend(min.map(|secs| format!("min-interval={}s", secs)));
+                lines.extend(max.map(|secs| format!("max-interval={}s", secs)));
+            }
+            // Quoted, a multi-host list stays one host setting instead of one block per host
+            lines.push(quote_value(&block_hosts.join(",")));
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    /// `to_ddclient_format` with secrets masked as in the `Debug` output, for --print-config
+    pub fn to_ddclient_format_masked(&self) -> String {
+        let masked = |secret: &Option<String>| secret.as_deref().map(mask_credential);
+        Config {
+            password: masked(&self.password),
+            consumer_key: masked(&self.consumer_key),
+            afraid_update_url: masked(&self.afraid_update_url),
+            inwx_totp: masked(&self.inwx_totp),
+            smtp_password: masked(&self.smtp_password),
+            extra_headers: self.extra_headers.iter()
+                .map(|(name, value)| (name.clone(), mask_credential(value)))
+                .collect(),
+            ..self.clone()
+        }.to_ddclient_format()
+    }
+
+    /// Get the (min_interval, max_interval) configured for a specific host
+    /// Hosts that are not in the config file (e.g. from --host) get the first block's intervals
+    pub fn intervals_for(&self, hostname: &str) -> Intervals {
+        self.host_intervals.get(hostname).copied()
+            .unwrap_or((self.min_interval, self.max_interval))
     }
 }
 
@@ -117,6 +382,29 @@ impl From<HostConfig> for Config {
             ttl: hc.ttl,
             email: hc.email,
             ip: None,
+            consumer_key: hc.consumer_key,
+            subscription_id: hc.subscription_id,
+            resource_group: hc.resource_group,
+            tenant_id: hc.tenant_id,
+            afraid_update_url: hc.afraid_update_url,
+            freedns_auth: hc.freedns_auth,
+            inwx_totp: hc.inwx_totp,
+            min_interval: hc.min_interval,
+            max_interval: hc.max_interval,
+            extra_headers: hc.extra_headers,
+            host_intervals: HashMap::new(),
+            provider_test: false,
+            cf_preload_zones: false,
+            offline: hc.offline.unwrap_or(false),
+            timeout_secs: hc.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS),
+            ca_cert_path: hc.ca_cert.map(PathBuf::from),
+            user_agent: hc.user_agent,
+            web_skip: hc.web_skip,
+            retries: hc.retries,
+            smtp_server: hc.smtp_server,
+            smtp_port: hc.smtp_port,
+            smtp_username: hc.smtp_user,
+            smtp_password: hc.smtp_pass,
         }
     }
 }
@@ -127,7 +415,7 @@ struct DdclientConfig {
     configs: Vec<HostConfig>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 struct HostConfig {
     protocol: Option<String>,
     login: Option<String>,
@@ -136,7 +424,26 @@ struct HostConfig {
     zone: Option<String>,
     host: Option<String>,
     ttl: Option<u32>,
+    timeout: Option<u64>,
+    ca_cert: Option<String>,
+    user_agent: Option<String>,
+    web_skip: Option<String>,
+    retries: Option<u8>,
+    smtp_server: Option<String>,
+    smtp_port: Option<u16>,
+    smtp_user: Option<String>,
+    smtp_pass: Option<String>,
     email: Option<String>,
+    consumer_key: Option<String>,
+    subscription_id: Option<String>,
+    resource_group: Option<String>,
+    tenant_id: Option<String>,
+    afraid_update_url: Option<String>,
+    freedns_auth: Option<String>,
+    inwx_totp: Option<String>,
+    min_interval: Option<u64>,
+    max_interval: Option<u64>,
+    extra_headers: Vec<(String, String)>,
     
     // ddclient-specific fields (for future compatibility)
     #[allow(dead_code)]
@@ -145,18 +452,74 @@ struct HostConfig {
     web: Option<String>,
     #[allow(dead_code)]
     ssl: Option<bool>,
+    offline: Option<bool>,
+}
+
+/// Maximum number of files pulled in through `include` directives
+const MAX_INCLUDES: usize = 100;
+
+/// Files already read while following `include` directives
+#[derive(Default)]
+struct IncludeState {
+    visited: HashSet<PathBuf>,
+    count: usize,
 }
 
 impl DdclientConfig {
     /// Parse ddclient configuration file
     pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
-        let content = fs::read_to_string(path)?;
-        Self::parse(&content)
+        Self::parse_file(Path::new(path), &mut IncludeState::default())
+    }
+
+    fn parse_file(path: &Path, includes: &mut IncludeState) -> Result<Self, Box<dyn Error>> {
+        let canonical = fs::canonicalize(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if !includes.visited.insert(canonical.clone()) {
+            return Err(format!("Circular include of {}", path.display()).into());
+        }
+
+        let content = fs::read_to_string(&canonical)?;
+        Self::parse_with_includes(&content, canonical.parent(), includes)
+    }
+
+    /// Parse every file matched by an `include` path or glob
+    /// Relative patterns are resolved against the including file's directory
+    fn include(pattern: &str, base_dir: Option<&Path>, includes: &mut IncludeState) -> Result<Vec<HostConfig>, Box<dyn Error>> {
+        let pattern = match base_dir {
+            Some(dir) if Path::new(pattern).is_relative() => dir.join(pattern),
+            _ => PathBuf::from(pattern),
+        };
+        let pattern = pattern.to_str().ok_or("include path is not valid UTF-8")?;
+
+        let mut paths = glob::glob(pattern)?.collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        // A glob may legitimately match nothing (e.g. an empty conf.d), a plain path may not
+        if paths.is_empty() && !pattern.contains(['*', '?', '[']) {
+            return Err(format!("Included file not found: {}", pattern).into());
+        }
+
+        let mut configs = Vec::new();
+        for path in paths {
+            includes.count += 1;
+            if includes.count > MAX_INCLUDES {
+                return Err(format!("Too many included files (limit is {})", MAX_INCLUDES).into());
+            }
+            configs.extend(Self::parse_file(&path, includes)?.configs);
+        }
+        Ok(configs)
     }
 
     /// Parse ddclient configuration from string
+    /// Relative `include` paths are resolved against the current directory
+    #[cfg(test)]
     pub fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
+        Self::parse_with_includes(content, None, &mut IncludeState::default())
+    }
+
+    fn parse_with_includes(content: &str, base_dir: Option<&Path>, includes: &mut IncludeState) -> Result<Self, Box<dyn Error>> {
         let mut configs = Vec::new();
+        // Host blocks from included files go after this file's own blocks
+        let mut included = Vec::new();
         let mut global_defaults: HashMap<String, String> = HashMap::new();
         let mut current_block: HashMap<String, String> = HashMap::new();
         
@@ -170,11 +533,17 @@ impl DdclientConfig {
             if line.is_empty() || line.starts_with('#') {
                 continue;
             }
+
+            // include <path or glob>
+            if let Some(pattern) = line.strip_prefix("include").filter(|rest| rest.starts_with([' ', '\t'])) {
+                included.extend(Self::include(pattern.trim(), base_dir, includes)?);
+                continue;
+            }
             
             // Check if this line contains bare hostnames (no = sign)
             if !line.contains('=') {
                 // This is a hostname list - create configs for each host
-                let hosts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+                let hosts: Vec<&str> = split_unquoted(line).into_iter().map(|s| unquote(s.trim())).collect();
                 
                 for host in hosts {
                     if !host.is_empty() {
@@ -183,7 +552,7 @@ impl DdclientConfig {
                         config_map.extend(current_block.clone());
                         config_map.insert("host".to_string(), host.to_string());
                         
-                        configs.push(Self::map_to_config(config_map));
+                        configs.push(Self::map_to_config(config_map)?);
                     }
                 }
                 
@@ -194,7 +563,7 @@ impl DdclientConfig {
             
             // Parse key=value pairs (comma-separated)
             // But also handle trailing hostnames after the last comma
-            let parts: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+            let parts: Vec<&str> = split_unquoted(line).into_iter().map(|s| s.trim()).collect();
             let mut found_hostnames = Vec::new();
             
             for part in parts {
@@ -202,34 +571,16 @@ impl DdclientConfig {
                     let key = key.trim();
                     let value = value.trim();
                     
-                    // Check if value contains a space followed by something that's not a comment
-                    if let Some(space_pos) = value.find(' ') {
-                        let (actual_value, rest) = value.split_at(space_pos);
-                        let rest = rest.trim();
-                        
-                        // If what follows is not a comment, treat it as a hostname
-                        if !rest.starts_with('#') && !rest.is_empty() {
-                            if configs.is_empty() && current_block.is_empty() {
-                                global_defaults.insert(key.to_string(), actual_value.trim().to_string());
-                            } else {
-                                current_block.insert(key.to_string(), actual_value.trim().to_string());
-                            }
-                            found_hostnames.push(rest);
-                        } else {
-                            // It's a comment or empty, just use the actual value
-                            if configs.is_empty() && current_block.is_empty() {
-                                global_defaults.insert(key.to_string(), actual_value.trim().to_string());
-                            } else {
-                                current_block.insert(key.to_string(), actual_value.trim().to_string());
-                            }
-                        }
+                    let (actual_value, rest) = split_value(value);
+                    if configs.is_empty() && current_block.is_empty() {
+                        global_defaults.insert(key.to_string(), actual_value.to_string());
                     } else {
-                        // Normal key=value
-                        if configs.is_empty() && current_block.is_empty() {
-                            global_defaults.insert(key.to_string(), value.to_string());
-                        } else {
-                            current_block.insert(key.to_string(), value.to_string());
-                        }
+                        current_block.insert(key.to_string(), actual_value.to_string());
+                    }
+
+                    // If what follows the value is not a comment, treat it as a hostname
+                    if !rest.starts_with('#') && !rest.is_empty() {
+                        found_hostnames.push(rest);
                     }
                 } else if !part.is_empty() && !part.starts_with('#') {
                     // This is a bare hostname (not a comment)
@@ -244,7 +595,7 @@ impl DdclientConfig {
                     let mut config_map = global_defaults.clone();
                     config_map.extend(current_block.clone());
                     config_map.insert("host".to_string(), host.to_string());
-                    configs.push(Self::map_to_config(config_map));
+                    configs.push(Self::map_to_config(config_map)?);
                 }
             }
             
@@ -258,9 +609,10 @@ impl DdclientConfig {
         if !current_block.is_empty() {
             let mut config_map = global_defaults.clone();
             config_map.extend(current_block);
-            configs.push(Self::map_to_config(config_map));
+            configs.push(Self::map_to_config(config_map)?);
         }
-        
+
+        configs.extend(included);
         Ok(DdclientConfig { configs })
     }
     
@@ -294,29 +646,189 @@ impl DdclientConfig {
         result
     }
     
+    /// Collect `header.<name>=<value>` keys, sorted by header name
+    fn extra_headers(map: &HashMap<String, String>) -> Vec<(String, String)> {
+        let mut headers: Vec<(String, String)> = map
+            .iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix("header.")
+                    .filter(|name| !name.is_empty())
+                    .map(|name| (name.to_string(), value.clone()))
+            })
+            .collect();
+        headers.sort();
+        headers
+    }
+
+    /// Parse an interval setting; an invalid value is a config error
+    fn interval(map: &HashMap<String, String>, key: &str) -> Result<Option<u64>, Box<dyn Error>> {
+        map.get(key)
+            .map(|value| parse_interval(value).map_err(|e| format!("Invalid {}={}: {}", key, value, e)))
+            .transpose()
+            .map_err(Into::into)
+    }
+
     /// Convert HashMap to HostConfig
-    fn map_to_config(map: HashMap<String, String>) -> HostConfig {
-        HostConfig {
+    fn map_to_config(map: HashMap<String, String>) -> Result<HostConfig, Box<dyn Error>> {
+        let map = map
+            .into_iter()
+            .map(|(key, value)| {
+                let value = interpolate_env(&value).map_err(|e| format!("{} (in {}=)", e, key))?;
+                Ok((key, value))
+            })
+            .collect::<Result<HashMap<_, _>, Box<dyn Error>>>()?;
+
+        Ok(HostConfig {
             protocol: map.get("protocol").cloned(),
             login: map.get("login").cloned(),
             password: map.get("password").cloned(),
             server: map.get("server").cloned(),
             zone: map.get("zone").cloned(),
             host: map.get("host").cloned(),
-            ttl: map.get("ttl").and_then(|s| s.parse().ok()),
+            ttl: map.get("ttl").and_then(|s| parse_ttl(s).ok()),
+            timeout: map.get("timeout").and_then(|s| s.parse().ok()),
+            ca_cert: map.get("ca_cert").cloned(),
+            user_agent: map.get("user-agent").cloned(),
+            web_skip: map.get("web-skip").cloned(),
+            retries: map.get("retries").and_then(|s| s.parse().ok()),
+            smtp_server: map.get("smtp_server").cloned(),
+            smtp_port: map.get("smtp_port").and_then(|s| s.parse().ok()),
+            smtp_user: map.get("smtp_user").cloned(),
+            smtp_pass: map.get("smtp_pass").cloned(),
             email: map.get("email").cloned(),
+            consumer_key: map.get("consumer_key").cloned(),
+            subscription_id: map.get("subscription_id").cloned(),
+            resource_group: map.get("resource_group").cloned(),
+            tenant_id: map.get("tenant_id").cloned(),
+            afraid_update_url: map.get("afraid_update_url").cloned(),
+            freedns_auth: map.get("freedns_auth").cloned(),
+            inwx_totp: map.get("inwx_totp").cloned(),
+            min_interval: Self::interval(&map, "min-interval")?,
+            max_interval: Self::interval(&map, "max-interval")?,
+            extra_headers: Self::extra_headers(&map),
             use_method: map.get("use").cloned(),
             web: map.get("web").cloned(),
-            ssl: map.get("ssl").and_then(|s| match s.to_lowercase().as_str() {
-                "yes" | "true" | "1" => Some(true),
-                "no" | "false" | "0" => Some(false),
-                _ => None,
-            }),
+            ssl: map.get("ssl").and_then(|s| parse_bool(s)),
+            offline: map.get("offline").and_then(|s| parse_bool(s)),
+        })
+    }
+}
+
+impl HostConfig {
+    /// Whether both blocks use the same settings apart from their hosts and intervals
+    fn same_settings(&self, other: &HostConfig) -> bool {
+        let settings = |hc: &HostConfig| HostConfig { host: None, min_interval: None, max_interval: None, ..hc.clone() };
+        settings(self) == settings(other)
+    }
+}
+
+/// Split a comma-separated host list, dropping empty entries
+fn split_hosts(hosts: &str) -> Vec<String> {
+    hosts.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Replace `${VAR_NAME}` references in a config value with the environment variable's value
+fn interpolate_env(value: &str) -> Result<String, Box<dyn Error>> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find('}').ok_or_else(|| format!("Unterminated ${{ in config value '{}'", value))?;
+        let name = &after[..end];
+        let var = std::env::var(name).map_err(|_| format!("Environment variable {} is not set", name))?;
+        result.push_str(&var);
+        rest = &after[end + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Warning for a config file other users can read; it usually holds provider credentials
+#[cfg(unix)]
+pub fn permission_warning(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mode = fs::metadata(path).ok()?.mode() & 0o777;
+    (mode & 0o044 != 0).then(|| format!(
+        "Config file {} may expose credentials (permissions: {:#o})", path.display(), mode
+    ))
+}
+
+/// File modes are not checked outside Unix
+#[cfg(not(unix))]
+pub fn permission_warning(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Split a config line at the commas that are not inside single quotes
+fn split_unquoted(line: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            ',' if !quoted => {
+                parts.push(&line[start..index]);
+                start = index + 1;
+            }
+            _ => {}
         }
     }
+    parts.push(&line[start..]);
+    parts
+}
+
+/// `value` without its surrounding single quotes, if it has them
+fn unquote(value: &str) -> &str {
+    value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')).unwrap_or(value)
+}
+
+/// Split the text after `key=` into the value and what follows it (a hostname or comment)
+fn split_value(value: &str) -> (&str, &str) {
+    if let Some((quoted, rest)) = value.strip_prefix('\'').and_then(|v| v.split_once('\'')) {
+        return (quoted, rest.trim());
+    }
+    match value.split_once(' ') {
+        Some((value, rest)) => (value, rest.trim()),
+        None => (value, ""),
+    }
+}
+
+/// `value` as written to a config file: quoted when a comma or space would split it
+fn quote_value(value: &str) -> String {
+    if value.contains([',', ' ']) && !value.contains('\'') {
+        format!("'{}'", value)
+    } else {
+        value.to_string()
+    }
 }
 
-/// Parse an interval string (e.g., "30s", "5m", "2h", "25d") into seconds
+/// ddclient-style boolean: yes/true/1 or no/false/0
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "yes" | "true" | "1" => Some(true),
+        "no" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a TTL in seconds; "auto" is 1, Cloudflare's value for automatic TTL
+pub fn parse_ttl(ttl: &str) -> Result<u32, Box<dyn Error>> {
+    let ttl = ttl.trim();
+    if ttl.eq_ignore_ascii_case("auto") {
+        return Ok(1);
+    }
+    ttl.parse().map_err(|_| format!("Invalid TTL: '{}'. Use a number of seconds or auto", ttl).into())
+}
+
+/// Parse an interval string (e.g., "30s", "5m", "2h", "25d", "2w") into seconds
 pub fn parse_interval(interval: &str) -> Result<u64, Box<dyn Error>> {
     let interval = interval.trim();
     if interval.is_empty() {
@@ -337,7 +849,8 @@ pub fn parse_interval(interval: &str) -> Result<u64, Box<dyn Error>> {
         "m" | "min" | "minutes" => 60,
         "h" | "hr" | "hours" => 3600,
         "d" | "day" | "days" => 86400,
-        _ => return Err(format!("Invalid time unit: {}. Use s, m, h, or d", unit).into()),
+        "w" | "week" | "weeks" => 604800,
+        _ => return Err(format!("Invalid time unit: {}. Use s, m, h, d, or w", unit).into()),
     };
 
     num.checked_mul(multiplier)
@@ -347,6 +860,10 @@ pub fn parse_interval(interval: &str) -> Result<u64, Box<dyn Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    /// Serializes tests that modify the process environment
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
 
     #[test]
     fn test_parse_simple_config() {
@@ -434,6 +951,14 @@ host.example.com
         assert_eq!(parsed.configs.len(), 1);
     }
 
+    #[test]
+    fn test_multiple_zones_kept_in_one_value() {
+        let parsed = DdclientConfig::parse("protocol=cloudflare\nzone=example.com;example.org\nhome.example.com\n").unwrap();
+        assert_eq!(parsed.configs.len(), 1);
+        assert_eq!(parsed.configs[0].zone.as_deref(), Some("example.com;example.org"));
+        assert_eq!(parsed.configs[0].host.as_deref(), Some("home.example.com"));
+    }
+
     #[test]
     fn test_ttl_parsing() {
         let config = r#"
@@ -445,6 +970,58 @@ host.example.com
         
         let parsed = DdclientConfig::parse(config).unwrap();
         assert_eq!(parsed.configs[0].ttl, Some(300));
+
+        let parsed = DdclientConfig::parse("protocol=cloudflare\nttl=auto\nhost.example.com\n").unwrap();
+        assert_eq!(parsed.configs[0].ttl, Some(1));
+    }
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl("auto").unwrap(), 1);
+        assert_eq!(parse_ttl("AUTO").unwrap(), 1);
+        assert_eq!(parse_ttl("300").unwrap(), 300);
+        assert!(parse_ttl("").is_err());
+        assert!(parse_ttl("abc").is_err());
+        assert!(parse_ttl("-5").is_err());
+    }
+
+    #[test]
+    fn test_timeout_parsing() {
+        let config = r#"
+protocol=cloudflare
+timeout=10
+host1.example.com
+
+timeout=
+host2.example.com
+"#;
+
+        let parsed = DdclientConfig::parse(config).unwrap();
+        assert_eq!(parsed.configs[0].timeout, Some(10));
+        assert_eq!(Config::from(parsed.configs[0].clone()).timeout_secs, 10);
+        assert_eq!(Config::from(parsed.configs[1].clone()).timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(Config::default().timeout_secs, 30);
+    }
+
+    #[test]
+    fn test_smtp_parsing() {
+        let config = r#"
+protocol=emailonly
+email=admin@example.com
+smtp_server=smtp.example.com
+smtp_port=2525
+smtp_user=mailer@example.com
+smtp_pass=secret
+host.example.com
+"#;
+
+        let parsed = DdclientConfig::parse(config).unwrap();
+        let config = Config::from(parsed.configs[0].clone());
+        assert_eq!(config.smtp_server.as_deref(), Some("smtp.example.com"));
+        assert_eq!(config.smtp_port, Some(2525));
+        assert_eq!(config.smtp_username.as_deref(), Some("mailer@example.com"));
+        assert_eq!(config.smtp_password.as_deref(), Some("secret"));
+        assert!(!format!("{:?}", config).contains("secret"));
     }
 
     #[test]
@@ -463,6 +1040,113 @@ host2.example.com
         assert_eq!(parsed.configs[1].ssl, Some(false));
     }
 
+    #[test]
+    fn test_offline_parsing() {
+        let parsed = DdclientConfig::parse("protocol=dyndns2\noffline=yes\nhost.example.com\n").unwrap();
+        assert!(Config::from(parsed.configs[0].clone()).offline);
+    }
+
+    #[test]
+    fn test_per_host_intervals() {
+        let config = r#"
+protocol=dyndns2
+login=user
+password=pass
+host1.example.com
+
+min-interval=5m
+max-interval=7d
+host2.example.com
+
+min-interval=1h
+host3.example.com
+"#;
+
+        let parsed = DdclientConfig::parse(config).unwrap();
+        assert_eq!(parsed.configs.len(), 3);
+        assert_eq!(parsed.configs[0].min_interval, None);
+        assert_eq!(parsed.configs[1].min_interval, Some(300));
+        assert_eq!(parsed.configs[1].max_interval, Some(7 * 86400));
+        assert_eq!(parsed.configs[2].min_interval, Some(3600));
+        assert_eq!(parsed.configs[2].max_interval, None);
+
+        let config = Config::from_blocks(&parsed).unwrap();
+        assert_eq!(config.dns_records(), vec!["host1.example.com", "host2.example.com", "host3.example.com"]);
+        assert_eq!(config.intervals_for("host1.example.com"), (None, None));
+        assert_eq!(config.intervals_for("host2.example.com"), (Some(300), Some(7 * 86400)));
+        assert_eq!(config.intervals_for("host3.example.com"), (Some(3600), None));
+    }
+
+    #[test]
+    fn test_per_host_intervals_for_host_lists() {
+        let config = r#"
+protocol=dyndns2
+login=user
+password=pass
+min-interval=1h
+c.example.com
+
+min-interval=2m
+'a.example.com,b.example.com'
+"#;
+
+        let config = Config::from_blocks(&DdclientConfig::parse(config).unwrap()).unwrap();
+        assert_eq!(config.dns_records(), vec!["c.example.com", "a.example.com", "b.example.com"]);
+        assert_eq!(config.intervals_for("a.example.com"), (Some(120), None));
+        assert_eq!(config.intervals_for("b.example.com"), (Some(120), None));
+        assert_eq!(config.intervals_for("c.example.com"), (Some(3600), None));
+        // Not in the file, e.g. from --host
+        assert_eq!(config.intervals_for("d.example.com"), (Some(3600), None));
+    }
+
+    #[test]
+    fn test_blocks_for_another_provider_are_skipped() {
+        let config = r#"
+protocol=dyndns2
+login=user
+password=pass
+host1.example.com
+
+protocol=duckdns
+password=token
+host2.duckdns.org
+"#;
+
+        let config = Config::from_blocks(&DdclientConfig::parse(config).unwrap()).unwrap();
+        assert_eq!(config.protocol.as_deref(), Some("dyndns2"));
+        assert_eq!(config.dns_records(), vec!["host1.example.com"]);
+    }
+
+    #[test]
+    fn test_invalid_interval_is_an_error() {
+        let error = DdclientConfig::parse("protocol=dyndns2\nmin-interval=5x\nhost.example.com\n").unwrap_err();
+        assert!(error.to_string().contains("Invalid min-interval=5x"), "{}", error);
+        assert!(DdclientConfig::parse("protocol=dyndns2\nmax-interval=soon\nhost.example.com\n").is_err());
+    }
+
+    #[test]
+    fn test_global_intervals_apply_to_all_hosts() {
+        let config = r#"
+protocol=dyndns2
+min-interval=30s
+max-interval=10d
+host1.example.com
+
+min-interval=2m
+host2.example.com
+"#;
+
+        let config = Config::from_blocks(&DdclientConfig::parse(config).unwrap()).unwrap();
+        assert_eq!(config.intervals_for("host1.example.com"), (Some(30), Some(10 * 86400)));
+        assert_eq!(config.intervals_for("host2.example.com"), (Some(120), Some(10 * 86400)));
+    }
+
+    #[test]
+    fn test_intervals_for_without_overrides() {
+        let config = Config::default();
+        assert_eq!(config.intervals_for("host.example.com"), (None, None));
+    }
+
     #[test]
     fn test_parse_interval() {
         assert_eq!(parse_interval("30s").unwrap(), 30);
@@ -470,6 +1154,8 @@ host2.example.com
         assert_eq!(parse_interval("2h").unwrap(), 7200);
         assert_eq!(parse_interval("1d").unwrap(), 86400);
         assert_eq!(parse_interval("25d").unwrap(), 2160000);
+        assert_eq!(parse_interval("2w").unwrap(), 1209600);
+        assert_eq!(parse_interval("3600").unwrap(), 3600);
         assert!(parse_interval("invalid").is_err());
         assert!(parse_interval("").is_err());
     }
@@ -500,5 +1186,303 @@ host2.example.com
         // Invalid formats
         assert!(parse_interval("1.5h").is_err()); // decimal not supported
         assert!(parse_interval("-5m").is_err()); // negative not supported
+        assert!(parse_interval("1h30m").is_err()); // compound notation not supported
+    }
+
+    #[test]
+    fn test_debug_masks_secrets() {
+        let config = Config {
+            login: Some("user@example.com".to_string()),
+            password: Some("super-secret-token".to_string()),
+            consumer_key: Some("ck-1234567890".to_string()),
+            ..Default::default()
+        };
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("user@example.com"));
+        assert!(debug.contains("****oken"));
+        assert!(debug.contains("****7890"));
+        assert!(!debug.contains("super-secret"));
+    }
+
+    #[test]
+    fn test_to_ddclient_format_masked() {
+        let config = Config {
+            protocol: Some("ovh".to_string()),
+            login: Some("app-key".to_string()),
+            password: Some("super-secret-token".to_string()),
+            consumer_key: Some("ck-1234567890".to_string()),
+            inwx_totp: Some("JBSWY3DPEHPK3PXP".to_string()),
+            smtp_password: Some("hunter2".to_string()),
+            extra_headers: vec![("CF-Access-Client-Secret".to_string(), "access-secret-value".to_string())],
+            host: Some("home.example.com".to_string()),
+            ..Default::default()
+        };
+
+        let rendered = config.to_ddclient_format_masked();
+        assert!(rendered.contains("login=app-key\n"));
+        assert!(rendered.contains("password=****oken\n"));
+        assert!(rendered.contains("consumer_key=****7890\n"));
+        assert!(rendered.contains("inwx_totp=****3PXP\n"));
+        assert!(rendered.contains("smtp_pass=[REDACTED]\n"));
+        assert!(rendered.contains("header.CF-Access-Client-Secret=****alue\n"));
+        for secret in ["super-secret-token", "ck-1234567890", "JBSWY3DPEHPK3PXP", "hunter2", "access-secret-value"] {
+            assert!(!rendered.contains(secret), "{} in {}", secret, rendered);
+        }
+        assert!(config.to_ddclient_format().contains("password=super-secret-token\n"));
+    }
+
+    #[test]
+    fn test_to_ddclient_format_round_trip_host_intervals() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let original = dir.path().join("original.conf");
+        fs::write(&original, "\
+protocol=dyndns2, login=user, password=secret
+max-interval=7d
+home.example.com
+
+min-interval=1h
+a.example.com
+
+min-interval=5m
+b.example.com
+
+min-interval=1h
+c.example.com
+").unwrap();
+
+        let config = Config::from_file(original.to_str().unwrap()).unwrap();
+        let rendered = config.to_ddclient_format();
+        assert!(rendered.ends_with("\
+max-interval=604800s
+home.example.com
+
+min-interval=3600s
+max-interval=604800s
+a.example.com
+
+min-interval=300s
+max-interval=604800s
+b.example.com
+
+min-interval=3600s
+max-interval=604800s
+c.example.com
+"), "{}", rendered);
+
+        let copy = dir.path().join("copy.conf");
+        fs::write(&copy, &rendered).unwrap();
+        let parsed = Config::from_file(copy.to_str().unwrap()).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.intervals_for("c.example.com"), (Some(3600), Some(7 * 86400)));
+    }
+
+    #[test]
+    fn test_to_ddclient_format_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let original = dir.path().join("original.conf");
+        fs::write(&original, "\
+# Cloudflare with a few extras
+protocol=cloudflare, zone=example.com
+login=token
+password=secret-token
+ttl=120
+min-interval=5m
+max-interval=25d
+timeout=15
+retries=3
+smtp_server=smtp.example.com, smtp_port=2525
+header.X-Tenant=home
+home.example.com
+").unwrap();
+
+        let config = Config::from_file(original.to_str().unwrap()).unwrap();
+        let rendered = config.to_ddclient_format();
+        assert!(rendered.contains("min-interval=300s\n"));
+        assert!(rendered.contains("header.X-Tenant=home\n"));
+        assert!(rendered.ends_with("\nhome.example.com\n"));
+        assert!(!rendered.contains("email="));
+
+        let copy = dir.path().join("copy.conf");
+        fs::write(&copy, &rendered).unwrap();
+        assert_eq!(Config::from_file(copy.to_str().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_to_ddclient_format_round_trip_commas() {
+        let config = Config {
+            protocol: Some("cloudflare".to_string()),
+            password: Some("secret".to_string()),
+            zone: Some("example.com,example.org".to_string()),
+            host: Some("home.example.com,www.example.org".to_string()),
+            user_agent: Some("home router/1.0".to_string()),
+            ..Default::default()
+        };
+
+        let rendered = config.to_ddclient_format();
+        assert_eq!(rendered, "\
+protocol=cloudflare
+password=secret
+zone='example.com,example.org'
+user-agent='home router/1.0'
+'home.example.com,www.example.org'
+");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rddclient.conf");
+        fs::write(&path, &rendered).unwrap();
+        let parsed = Config::from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.dns_records(), vec!["home.example.com", "www.example.org"]);
+    }
+
+    #[test]
+    fn test_quoted_values() {
+        let config = "protocol=dyndns2, password='a,b c', login=user host1.example.com\n";
+        let parsed = DdclientConfig::parse(config).unwrap();
+        assert_eq!(parsed.configs.len(), 1);
+        assert_eq!(parsed.configs[0].password, Some("a,b c".to_string()));
+        assert_eq!(parsed.configs[0].login, Some("user".to_string()));
+        assert_eq!(parsed.configs[0].host, Some("host1.example.com".to_string()));
+    }
+
+    #[test]
+    fn test_include_glob() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("conf.d")).unwrap();
+        fs::write(dir.path().join("rddclient.conf"), "\
+protocol=duckdns, password=token
+home.duckdns.org
+include conf.d/*.conf
+").unwrap();
+        fs::write(dir.path().join("conf.d/a.conf"), "protocol=cloudflare, zone=example.com\nwww.example.com\n").unwrap();
+        fs::write(dir.path().join("conf.d/b.conf"), "protocol=dyndns2\nvpn.example.org,mail.example.org\n").unwrap();
+        fs::write(dir.path().join("conf.d/ignored.txt"), "not.a.config\n").unwrap();
+
+        let parsed = DdclientConfig::from_file(dir.path().join("rddclient.conf").to_str().unwrap()).unwrap();
+        let hosts: Vec<_> = parsed.configs.iter().map(|c| c.host.clone().unwrap()).collect();
+        assert_eq!(hosts, vec!["home.duckdns.org", "www.example.com", "vpn.example.org", "mail.example.org"]);
+        assert_eq!(parsed.configs[1].protocol, Some("cloudflare".to_string()));
+        assert_eq!(parsed.configs[2].protocol, Some("dyndns2".to_string()));
+    }
+
+    #[test]
+    fn test_include_nested_and_empty_glob() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("main.conf"), "include sub/one.conf\ninclude missing.d/*.conf\n").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/one.conf"), "include two.conf\nprotocol=duckdns\none.example.com\n").unwrap();
+        fs::write(dir.path().join("sub/two.conf"), "protocol=duckdns\ntwo.example.com\n").unwrap();
+
+        let parsed = DdclientConfig::from_file(dir.path().join("main.conf").to_str().unwrap()).unwrap();
+        let hosts: Vec<_> = parsed.configs.iter().map(|c| c.host.clone().unwrap()).collect();
+        assert_eq!(hosts, vec!["one.example.com", "two.example.com"]);
+    }
+
+    #[test]
+    fn test_include_circular() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("a.conf"), "include b.conf\n").unwrap();
+        fs::write(dir.path().join("b.conf"), "include a.conf\n").unwrap();
+
+        let err = DdclientConfig::from_file(dir.path().join("a.conf").to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Circular include"));
+    }
+
+    #[test]
+    fn test_include_missing_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("main.conf"), "include nope.conf\n").unwrap();
+
+        let err = DdclientConfig::from_file(dir.path().join("main.conf").to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Included file not found"));
+    }
+
+    #[test]
+    fn test_include_limit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("conf.d")).unwrap();
+        for i in 0..=MAX_INCLUDES {
+            fs::write(dir.path().join(format!("conf.d/{:03}.conf", i)), format!("h{}.example.com\n", i)).unwrap();
+        }
+        fs::write(dir.path().join("main.conf"), "include conf.d/*.conf\n").unwrap();
+
+        let err = DdclientConfig::from_file(dir.path().join("main.conf").to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Too many included files"));
+    }
+
+    #[test]
+    fn test_extra_headers() {
+        let config = r#"
+protocol=cloudflare
+header.X-Tenant=home, header.CF-Access-Client-Id=abc.access
+password=secret
+host1.example.com
+"#;
+        let parsed = DdclientConfig::parse(config).unwrap();
+        assert_eq!(
+            parsed.configs[0].extra_headers,
+            vec![
+                ("CF-Access-Client-Id".to_string(), "abc.access".to_string()),
+                ("X-Tenant".to_string(), "home".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_env_interpolation() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("RDDCLIENT_TEST_TOKEN", "s3cret");
+        std::env::set_var("RDDCLIENT_TEST_ZONE", "example.com");
+
+        let config = r#"
+protocol=cloudflare
+zone=${RDDCLIENT_TEST_ZONE}
+password=${RDDCLIENT_TEST_TOKEN}
+login=user-${RDDCLIENT_TEST_TOKEN}-x
+server=https://api.example.com
+host1.example.com
+"#;
+        let parsed = DdclientConfig::parse(config).unwrap();
+        assert_eq!(parsed.configs[0].zone.as_deref(), Some("example.com"));
+        assert_eq!(parsed.configs[0].password.as_deref(), Some("s3cret"));
+        assert_eq!(parsed.configs[0].login.as_deref(), Some("user-s3cret-x"));
+        assert_eq!(parsed.configs[0].server.as_deref(), Some("https://api.example.com"));
+
+        std::env::remove_var("RDDCLIENT_TEST_TOKEN");
+        std::env::remove_var("RDDCLIENT_TEST_ZONE");
+    }
+
+    #[test]
+    fn test_env_interpolation_errors() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("RDDCLIENT_TEST_UNSET");
+
+        let err = DdclientConfig::parse("protocol=duckdns\npassword=${RDDCLIENT_TEST_UNSET}\nhome.example.com\n")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Environment variable RDDCLIENT_TEST_UNSET is not set (in password=)");
+
+        assert!(interpolate_env("${UNTERMINATED").is_err());
+        assert_eq!(interpolate_env("plain $value").unwrap(), "plain $value");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permission_warning() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rddclient.conf");
+        fs::write(&path, "protocol=duckdns\n").unwrap();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(
+            permission_warning(&path).unwrap(),
+            format!("Config file {} may expose credentials (permissions: 0o644)", path.display())
+        );
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        assert!(permission_warning(&path).unwrap().ends_with("(permissions: 0o640)"));
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(permission_warning(&path), None);
     }
 }
@@ -0,0 +1,126 @@
+/// Daemon mode support: PID file handling and graceful shutdown on SIGINT/SIGTERM
+///
+/// A shutdown request never interrupts an update pass; the main loop only checks
+/// for it between passes, while sleeping.
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Granularity at which a sleeping daemon notices a shutdown request
+const SLEEP_STEP: Duration = Duration::from_millis(250);
+
+/// PID file that is removed again when dropped
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write the current process ID to `path`
+    pub fn create(path: &Path) -> Result<Self, Box<dyn Error>> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, format!("{}\n", std::process::id()))
+            .map_err(|e| format!("Failed to write PID file {}: {}", path.display(), e))?;
+
+        log::debug!("Wrote PID file {}", path.display());
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!("Failed to remove PID file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Set once SIGINT or SIGTERM has been received
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    requested: Arc<AtomicBool>,
+}
+
+impl Shutdown {
+    /// Install the SIGINT/SIGTERM handler
+    pub fn install() -> Result<Self, Box<dyn Error>> {
+        let shutdown = Self::default();
+        let requested = shutdown.requested.clone();
+        ctrlc::set_handler(move || {
+            log::info!("Shutdown requested, exiting after the current update");
+            requested.store(true, Ordering::SeqCst);
+        })?;
+        Ok(shutdown)
+    }
+
+    pub fn requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Sleep for up to `duration`, returning early (false) if shutdown is requested
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        loop {
+            if self.requested() {
+                return false;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return true;
+            }
+            thread::sleep(remaining.min(SLEEP_STEP));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file_created_and_removed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("run").join("rddclient.pid");
+
+        let pid_file = PidFile::create(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.trim(), std::process::id().to_string());
+
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_pid_file_unwritable_location() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        // A directory can't be overwritten with the PID
+        assert!(PidFile::create(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_sleep_completes_without_shutdown() {
+        let shutdown = Shutdown::default();
+        assert!(shutdown.sleep(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_sleep_interrupted_by_shutdown() {
+        let shutdown = Shutdown::default();
+        let handle = shutdown.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            handle.requested.store(true, Ordering::SeqCst);
+        });
+
+        let start = Instant::now();
+        assert!(!shutdown.sleep(Duration::from_secs(30)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,89 @@
+/// Error categories and the process exit codes they map to
+use std::error::Error;
+use std::fmt;
+
+/// Errors classified by what went wrong, so main can pick an exit code
+#[derive(Debug)]
+pub enum RddclientError {
+    /// Invalid or incomplete configuration (config file, arguments, provider settings)
+    Config(String),
+    /// The current IP address could not be determined or is unusable
+    IpDetection(String),
+    /// One or more hosts failed to update at the provider
+    Provider(String),
+    /// The state file or database could not be read or written
+    State(String),
+}
+
+impl fmt::Display for RddclientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Config(msg) | Self::IpDetection(msg) | Self::Provider(msg) | Self::State(msg) => {
+                write!(f, "{}", msg)
+            }
+        }
+    }
+}
+
+impl Error for RddclientError {}
+
+/// Process exit codes; a higher value is a more severe failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExitCode {
+    Success = 0,
+    UpdateFailure = 1,
+    ConfigError = 2,
+    IpDetectionError = 3,
+    StateError = 4,
+}
+
+impl ExitCode {
+    /// Exit code for an error; errors that weren't classified count as update failures
+    pub fn for_error(error: &(dyn Error + 'static)) -> Self {
+        match error.downcast_ref::<RddclientError>() {
+            Some(RddclientError::Config(_)) => Self::ConfigError,
+            Some(RddclientError::IpDetection(_)) => Self::IpDetectionError,
+            Some(RddclientError::Provider(_)) => Self::UpdateFailure,
+            Some(RddclientError::State(_)) => Self::StateError,
+            None => Self::UpdateFailure,
+        }
+    }
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        std::process::ExitCode::from(code as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_for_error() {
+        let cases = [
+            (RddclientError::Config("bad".into()), ExitCode::ConfigError),
+            (RddclientError::IpDetection("none".into()), ExitCode::IpDetectionError),
+            (RddclientError::Provider("badauth".into()), ExitCode::UpdateFailure),
+            (RddclientError::State("read-only".into()), ExitCode::StateError),
+        ];
+        for (error, code) in cases {
+            let boxed: Box<dyn Error> = error.into();
+            assert_eq!(ExitCode::for_error(boxed.as_ref()), code);
+        }
+
+        let other: Box<dyn Error> = "something else".into();
+        assert_eq!(ExitCode::for_error(other.as_ref()), ExitCode::UpdateFailure);
+    }
+
+    #[test]
+    fn test_exit_code_values_and_severity() {
+        assert_eq!(ExitCode::Success as u8, 0);
+        assert_eq!(ExitCode::StateError as u8, 4);
+        assert_eq!(
+            [ExitCode::UpdateFailure, ExitCode::StateError, ExitCode::ConfigError].into_iter().max(),
+            Some(ExitCode::StateError)
+        );
+    }
+}
@@ -0,0 +1,96 @@
+/// User commands run before and after each update pass (--pre-update-cmd / --post-update-cmd)
+///
+/// Commands run through the system shell with the update details in `RDDCLIENT_*` variables.
+use std::collections::HashMap;
+use std::error::Error;
+use std::process::Command;
+
+/// Run `cmd` through the shell with `env` added to its environment.
+/// Fails if the command can't be started or exits unsuccessfully.
+pub fn run_hook(cmd: &str, env: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    log::info!("Running hook: {}", cmd);
+
+    let output = shell(cmd)
+        .envs(env)
+        .output()
+        .map_err(|e| format!("Failed to run hook '{}': {}", cmd, e))?;
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        log::debug!("hook: {}", line);
+    }
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Hook '{}' failed with {}: {}", cmd, output.status, stderr.trim()).into());
+    }
+    Ok(())
+}
+
+/// Environment for the pre-update hook
+pub fn pre_update_env(ip: &str, hostnames: &[String]) -> HashMap<String, String> {
+    HashMap::from([
+        ("RDDCLIENT_IP".to_string(), ip.to_string()),
+        ("RDDCLIENT_HOSTNAMES".to_string(), hostnames.join(",")),
+    ])
+}
+
+/// Environment for the post-update hook: the pre-update variables plus the outcome
+pub fn post_update_env(ip: &str, hostnames: &[String], updated: &[&str], failed: &[&str]) -> HashMap<String, String> {
+    let mut env = pre_update_env(ip, hostnames);
+    env.insert("RDDCLIENT_UPDATED".to_string(), updated.join(","));
+    env.insert("RDDCLIENT_FAILED".to_string(), failed.join(","));
+    env
+}
+
+#[cfg(unix)]
+fn shell(cmd: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(cmd);
+    command
+}
+
+#[cfg(windows)]
+fn shell(cmd: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(cmd);
+    command
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_receives_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("hook.out");
+        let hostnames = vec!["a.example.com".to_string(), "b.example.com".to_string()];
+        let env = post_update_env("203.0.113.1", &hostnames, &["a.example.com"], &["b.example.com"]);
+
+        let cmd = format!(
+            "echo \"$RDDCLIENT_IP|$RDDCLIENT_HOSTNAMES|$RDDCLIENT_UPDATED|$RDDCLIENT_FAILED\" > {}",
+            out.display()
+        );
+        run_hook(&cmd, &env).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "203.0.113.1|a.example.com,b.example.com|a.example.com|b.example.com\n"
+        );
+    }
+
+    #[test]
+    fn test_pre_update_env() {
+        let env = pre_update_env("2001:db8::1", &["a.example.com".to_string()]);
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["RDDCLIENT_IP"], "2001:db8::1");
+        assert_eq!(env["RDDCLIENT_HOSTNAMES"], "a.example.com");
+    }
+
+    #[test]
+    fn test_failing_hook() {
+        let err = run_hook("echo oops >&2; exit 3", &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("oops"));
+        assert!(run_hook("echo fine", &HashMap::new()).is_ok());
+    }
+}
@@ -0,0 +1,223 @@
+/// HTTP plumbing shared by the provider clients
+///
+/// Every provider request goes through an `HttpClient`, which applies the user-configured
+/// headers and timeout.
+use crate::clients::RequestPreview;
+use crate::config::Config;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::CertificateDer;
+use std::error::Error;
+use std::path::Path;
+
+/// Per-client HTTP settings applied to every provider request
+#[derive(Clone)]
+pub struct HttpClient {
+    user_agent: String,
+    extra_headers: Vec<(String, String)>,
+    timeout_secs: u64,
+}
+
+impl HttpClient {
+    /// Apply the User-Agent, configured headers and timeout to a provider request.
+    /// A `User-Agent` given as an extra header wins over the configured one.
+    pub fn prepare(&self, req: minreq::Request) -> minreq::Request {
+        let req = req.with_header("User-Agent", &self.user_agent);
+        with_timeout(apply_extra_headers(req, &self.extra_headers), self.timeout_secs)
+    }
+
+    /// The request `prepare` would send, without sending it (for --dry-run)
+    pub fn preview(&self, method: &'static str, url: impl Into<String>) -> RequestPreview {
+        let mut headers = Vec::new();
+        if !self.extra_headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("user-agent")) {
+            headers.push(("User-Agent".to_string(), self.user_agent.clone()));
+        }
+        headers.extend(self.extra_headers.iter().cloned());
+        RequestPreview { method, url: url.into(), headers, body: None, secrets: Vec::new() }
+    }
+}
+
+/// Builds the `HttpClient` for a provider client from its `Config`
+pub struct HttpClientBuilder {
+    user_agent: String,
+    extra_headers: Vec<(String, String)>,
+    timeout_secs: u64,
+}
+
+impl HttpClientBuilder {
+    pub fn new(config: &Config) -> Self {
+        HttpClientBuilder {
+            user_agent: config.user_agent.clone().unwrap_or_else(|| crate::DEFAULT_USER_AGENT.to_string()),
+            extra_headers: config.extra_headers.clone(),
+            timeout_secs: config.timeout_secs,
+        }
+    }
+
+    pub fn build(self) -> HttpClient {
+        HttpClient {
+            user_agent: self.user_agent,
+            extra_headers: self.extra_headers,
+            timeout_secs: self.timeout_secs,
+        }
+    }
+}
+
+/// Check the `ca_cert=` / `--ca-cert` file before any provider request is made.
+/// minreq's rustls backend builds its ClientConfig once from the bundled webpki roots and
+/// can't be handed another one, so even a valid CA file is refused rather than silently
+/// ignored; trusting it needs a TLS backend that accepts the config from `tls_config`.
+pub fn check_ca_cert(path: &Path) -> Result<(), Box<dyn Error>> {
+    tls_config(path)?;
+    Err(format!(
+        "CA certificate {} can't be used: the minreq TLS backend only trusts its built-in root certificates",
+        path.display()
+    ).into())
+}
+
+/// rustls configuration trusting the bundled webpki roots plus the CA certificates in a PEM file
+pub fn tls_config(path: &Path) -> Result<rustls::ClientConfig, Box<dyn Error>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .map_err(|e| format!("Failed to read CA certificate {}: {}", path.display(), e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid CA certificate {}: {}", path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()).into());
+    }
+
+    let mut roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let (added, _) = roots.add_parsable_certificates(certs);
+    if added == 0 {
+        return Err(format!("No usable CA certificates in {}", path.display()).into());
+    }
+
+    Ok(rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+/// Attach the user-configured headers (`header.<name>=` / `--header`) to a request
+pub fn apply_extra_headers(req: minreq::Request, headers: &[(String, String)]) -> minreq::Request {
+    headers
+        .iter()
+        .fold(req, |req, (name, value)| req.with_header(name, value))
+}
+
+/// Apply the configured request timeout (`timeout=` / `--timeout`)
+pub fn with_timeout(req: minreq::Request, secs: u64) -> minreq::Request {
+    req.with_timeout(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    #[test]
+    fn test_apply_extra_headers() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/")
+            .match_header("x-tenant", "home")
+            .match_header("cf-access-client-id", "abc.access")
+            .create();
+
+        let headers = vec![
+            ("X-Tenant".to_string(), "home".to_string()),
+            ("CF-Access-Client-Id".to_string(), "abc.access".to_string()),
+        ];
+        let response = apply_extra_headers(minreq::get(server.url()), &headers).send().unwrap();
+
+        assert_eq!(response.status_code, 200);
+        mock.assert();
+    }
+
+    #[test]
+    fn test_with_timeout() {
+        let req = with_timeout(minreq::get("http://example.com/"), 5);
+        assert_eq!(req, minreq::get("http://example.com/").with_timeout(5));
+        assert!(format!("{:?}", req).contains("timeout: Some(5)"));
+    }
+
+    #[test]
+    fn test_http_client_prepare() {
+        let config = Config {
+            extra_headers: vec![("X-Tenant".to_string(), "home".to_string())],
+            timeout_secs: 7,
+            ..Default::default()
+        };
+        let client = HttpClientBuilder::new(&config).build();
+
+        let req = client.prepare(minreq::get("http://example.com/"));
+        assert_eq!(req, minreq::get("http://example.com/")
+            .with_header("User-Agent", crate::DEFAULT_USER_AGENT)
+            .with_header("X-Tenant", "home")
+            .with_timeout(7));
+    }
+
+    #[test]
+    fn test_http_client_preview() {
+        let config = Config {
+            extra_headers: vec![
+                ("X-Tenant".to_string(), "home".to_string()),
+                ("user-agent".to_string(), "override/2.0".to_string()),
+            ],
+            ..Default::default()
+        };
+        let client = HttpClientBuilder::new(&config).build();
+
+        let preview = client.preview("POST", "http://example.com/update")
+            .with_header("Content-Type", "application/json")
+            .with_body("{}");
+        assert_eq!(preview.method, "POST");
+        assert_eq!(preview.url, "http://example.com/update");
+        assert_eq!(preview.headers, vec![
+            ("X-Tenant".to_string(), "home".to_string()),
+            ("user-agent".to_string(), "override/2.0".to_string()),
+            ("Content-Type".to_string(), "application/json".to_string()),
+        ]);
+        assert_eq!(preview.body.as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn test_http_client_custom_user_agent() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/")
+            .match_header("user-agent", "my-router/1.0")
+            .create();
+
+        let config = Config {
+            user_agent: Some("my-router/1.0".to_string()),
+            ..Default::default()
+        };
+        let client = HttpClientBuilder::new(&config).build();
+        assert_eq!(client.preview("GET", "http://example.com/").headers, vec![
+            ("User-Agent".to_string(), "my-router/1.0".to_string()),
+        ]);
+
+        client.prepare(minreq::get(server.url())).send().unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn test_tls_config_from_self_signed_cert() {
+        let config = tls_config(&fixture("ca.pem")).unwrap();
+        assert!(config.alpn_protocols.is_empty());
+
+        let err = tls_config(&fixture("ddclient.cache")).unwrap_err();
+        assert!(err.to_string().contains("No certificates found"));
+        assert!(tls_config(&fixture("missing.pem")).is_err());
+    }
+
+    #[test]
+    fn test_ca_cert_rejected() {
+        let err = check_ca_cert(&fixture("ca.pem")).unwrap_err();
+        assert!(err.to_string().contains("built-in root certificates"));
+
+        let err = check_ca_cert(&fixture("missing.pem")).unwrap_err();
+        assert!(err.to_string().contains("Failed to read CA certificate"));
+    }
+}
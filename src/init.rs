@@ -0,0 +1,284 @@
+/// `rddclient init` configuration wizard
+///
+/// Asks for the provider, credentials, hostnames and IP detection method, then writes a
+/// ddclient-format config file. On a terminal the prompts use dialoguer; otherwise the
+/// answers are read line by line from stdin, so the wizard can also be scripted.
+use crate::clients::PROVIDERS;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// IP detection methods offered by the wizard (`--use-method` values)
+const IP_METHODS: &[&str] = &["web", "if", "default-if", "cmd"];
+
+/// Everything the wizard asks for
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    pub protocol: String,
+    pub login: String,
+    pub password: String,
+    pub zone: String,
+    pub hosts: String,
+    pub use_method: String,
+    /// Web service URL, interface name or command, depending on `use_method`
+    pub use_value: String,
+}
+
+/// Source of answers for the wizard
+pub trait Prompter {
+    /// Index of the chosen item
+    fn select(&mut self, prompt: &str, items: &[&str]) -> Result<usize, Box<dyn Error>>;
+
+    /// A line of text, empty only when `allow_empty` is set
+    fn input(&mut self, prompt: &str, allow_empty: bool) -> Result<String, Box<dyn Error>>;
+
+    /// Like `input`, but not echoed on a terminal
+    fn secret(&mut self, prompt: &str) -> Result<String, Box<dyn Error>>;
+}
+
+/// Interactive prompts on the terminal
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn select(&mut self, prompt: &str, items: &[&str]) -> Result<usize, Box<dyn Error>> {
+        Ok(dialoguer::Select::new().with_prompt(prompt).items(items).default(0).interact()?)
+    }
+
+    fn input(&mut self, prompt: &str, allow_empty: bool) -> Result<String, Box<dyn Error>> {
+        Ok(dialoguer::Input::<String>::new().with_prompt(prompt).allow_empty(allow_empty).interact_text()?)
+    }
+
+    fn secret(&mut self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        Ok(dialoguer::Password::new().with_prompt(prompt).allow_empty_password(true).interact()?)
+    }
+}
+
+/// Prompts answered one line at a time from a reader (piped stdin, tests)
+pub struct LinePrompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> LinePrompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    fn ask(&mut self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        write!(self.output, "{}: ", prompt)?;
+        self.output.flush()?;
+
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err("Unexpected end of input".into());
+        }
+        Ok(line.trim().to_string())
+    }
+}
+
+impl<R: BufRead, W: Write> Prompter for LinePrompter<R, W> {
+    /// Accepts the item's number or one of its '/'-separated names
+    fn select(&mut self, prompt: &str, items: &[&str]) -> Result<usize, Box<dyn Error>> {
+        for (index, item) in items.iter().enumerate() {
+            writeln!(self.output, "{:>3}) {}", index + 1, item)?;
+        }
+        loop {
+            let answer = self.ask(&format!("{} [1-{}]", prompt, items.len()))?;
+            if let Some(number) = answer.parse::<usize>().ok().filter(|n| (1..=items.len()).contains(n)) {
+                return Ok(number - 1);
+            }
+            if let Some(index) = items.iter().position(|item| item.split('/').any(|name| name == answer)) {
+                return Ok(index);
+            }
+            writeln!(self.output, "Please choose one of the listed options")?;
+        }
+    }
+
+    fn input(&mut self, prompt: &str, allow_empty: bool) -> Result<String, Box<dyn Error>> {
+        loop {
+            let answer = self.ask(prompt)?;
+            if allow_empty || !answer.is_empty() {
+                return Ok(answer);
+            }
+            writeln!(self.output, "A value is required")?;
+        }
+    }
+
+    fn secret(&mut self, prompt: &str) -> Result<String, Box<dyn Error>> {
+        self.input(prompt, true)
+    }
+}
+
+/// Run the prompts
+pub fn ask(prompter: &mut dyn Prompter) -> Result<Answers, Box<dyn Error>> {
+    let provider = PROVIDERS[prompter.select("DNS provider", PROVIDERS)?];
+    let protocol = provider.split('/').next().unwrap_or(provider).to_string();
+    let login = prompter.input("Login (leave empty if not needed)", true)?;
+    let password = prompter.secret("Password or API token")?;
+    let zone = prompter.input("Zone (leave empty if not needed)", true)?;
+    let hosts = prompter.input("Hostnames to update, comma separated", false)?;
+
+    let use_method = IP_METHODS[prompter.select("IP detection method", IP_METHODS)?].to_string();
+    let use_value = match use_method.as_str() {
+        "web" => prompter.input("Web service URL (leave empty for the built-in services)", true)?,
+        "if" => prompter.input("Interface name", false)?,
+        "cmd" => prompter.input("Command that prints the IP", false)?,
+        _ => String::new(),
+    };
+
+    Ok(Answers { protocol, login, password, zone, hosts, use_method, use_value })
+}
+
+/// The config file for `answers`
+pub fn render(answers: &Answers) -> String {
+    let mut lines = vec!["# Generated by rddclient init".to_string()];
+    lines.push(format!("use={}", answers.use_method));
+    match answers.use_method.as_str() {
+        "web" if !answers.use_value.is_empty() => lines.push(format!("web={}", answers.use_value)),
+        "if" => lines.push(format!("if={}", answers.use_value)),
+        // Commands usually contain spaces, which a config line can't hold; see run_command
+        _ => {}
+    }
+    lines.push(format!("protocol={}", answers.protocol));
+    for (key, value) in [("login", &answers.login), ("password", &answers.password), ("zone", &answers.zone)] {
+        if !value.is_empty() {
+            lines.push(format!("{}={}", key, value));
+        }
+    }
+    lines.push(answers.hosts.clone());
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Command line that runs rddclient with the new file and the chosen IP detection method
+pub fn run_command(answers: &Answers, path: &Path) -> String {
+    let mut command = format!("rddclient --file {}", path.display());
+    match answers.use_method.as_str() {
+        "web" if answers.use_value.is_empty() => {}
+        "web" => command.push_str(&format!(" --use-method web --web {}", answers.use_value)),
+        "if" => command.push_str(&format!(" --use-method if --if-name {}", answers.use_value)),
+        "cmd" => command.push_str(&format!(" --use-method cmd --cmd '{}'", answers.use_value)),
+        method => command.push_str(&format!(" --use-method {}", method)),
+    }
+    command
+}
+
+/// Write `content` to `path`, readable by the owner only, keeping an existing file as
+/// `<path>.bak`. Returns the backup path when one was made.
+pub fn write_config(path: &Path, content: &str) -> Result<Option<PathBuf>, Box<dyn Error>> {
+    let backup = if path.exists() {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+        fs::copy(path, &backup)
+            .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+        Some(backup)
+    } else {
+        None
+    };
+
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(backup)
+}
+
+/// `rddclient init`: prompt for the settings and write them to `output`
+pub fn run(output: &Path) -> Result<(), Box<dyn Error>> {
+    let answers = if io::stdin().is_terminal() {
+        ask(&mut TerminalPrompter)?
+    } else {
+        ask(&mut LinePrompter::new(io::stdin().lock(), io::stdout()))?
+    };
+
+    if let Some(backup) = write_config(output, &render(&answers))? {
+        println!("Backed up the existing {} to {}", output.display(), backup.display());
+    }
+    println!("Wrote {}", output.display());
+    println!("Run it with: {}", run_command(&answers, output));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::io::Cursor;
+
+    fn ask_with(input: &str) -> (Answers, String) {
+        let mut output = Vec::new();
+        let answers = ask(&mut LinePrompter::new(Cursor::new(input), &mut output)).unwrap();
+        (answers, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_init_writes_config_file() {
+        let (answers, _) = ask_with("cloudflare\n\ntoken123\nexample.com\nhome.example.com\n2\neth0\n");
+        assert_eq!(answers, Answers {
+            protocol: "cloudflare".to_string(),
+            login: String::new(),
+            password: "token123".to_string(),
+            zone: "example.com".to_string(),
+            hosts: "home.example.com".to_string(),
+            use_method: "if".to_string(),
+            use_value: "eth0".to_string(),
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rddclient.conf");
+        assert_eq!(write_config(&path, &render(&answers)).unwrap(), None);
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content,
+            "# Generated by rddclient init\nuse=if\nif=eth0\nprotocol=cloudflare\npassword=token123\nzone=example.com\nhome.example.com\n"
+        );
+        let config = Config::from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(config.protocol.as_deref(), Some("cloudflare"));
+        assert_eq!(config.password.as_deref(), Some("token123"));
+        assert_eq!(config.host.as_deref(), Some("home.example.com"));
+        assert_eq!(run_command(&answers, &path), format!("rddclient --file {} --use-method if --if-name eth0", path.display()));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_init_backs_up_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rddclient.conf");
+        fs::write(&path, "protocol=duckdns\n").unwrap();
+
+        let backup = write_config(&path, "protocol=noip\n").unwrap().unwrap();
+        assert_eq!(backup, dir.path().join("rddclient.conf.bak"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "protocol=duckdns\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "protocol=noip\n");
+    }
+
+    #[test]
+    fn test_init_reprompts_invalid_answers() {
+        // Unknown provider, then an alias; empty hostname, then a value; command detection
+        let (answers, output) = ask_with("nosuch\ndyndns2\nuser\npass\n\n\na.example.com,b.example.com\ncmd\ncurl -s https://ifconfig.me\n");
+        assert_eq!(answers.protocol, "dyndns");
+        assert_eq!(answers.hosts, "a.example.com,b.example.com");
+        assert!(output.contains("Please choose one of the listed options"));
+        assert!(output.contains("A value is required"));
+
+        // The command can't go in the file, only on the command line
+        let content = render(&answers);
+        assert!(content.contains("use=cmd\nprotocol=dyndns\nlogin=user\npassword=pass\na.example.com,b.example.com\n"));
+        assert!(run_command(&answers, Path::new("x.conf")).ends_with("--use-method cmd --cmd 'curl -s https://ifconfig.me'"));
+    }
+
+    #[test]
+    fn test_init_end_of_input() {
+        let mut output = Vec::new();
+        assert!(ask(&mut LinePrompter::new(Cursor::new("duckdns\n"), &mut output)).is_err());
+    }
+}
@@ -1,47 +1,185 @@
 use std::error::Error;
-use std::net::IpAddr;
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Mutex, OnceLock};
 
 /// IP detection method
 #[derive(Debug, Clone, PartialEq)]
 pub enum IpDetectionMethod {
     /// Manual IP address
     Manual(String),
-    /// Web service (default)
-    Web(Option<String>),
+    /// Web services: user-supplied URLs tried before the built-in list (empty: built-in only).
+    /// Responses matching the `skip` regex (`web-skip=`) are passed over.
+    Web { urls: Vec<String>, skip: Option<String> },
     /// Network interface
     Interface(String),
     /// Execute command
     Command(String),
+    /// Most recent lease in an ISC dhclient lease file (DHCPv4 or DHCPv6)
+    DhcpLease(PathBuf),
+    /// Fetch a page and take the IP from the first capture group of `pattern`
+    WebRegex { url: String, pattern: String },
+    /// Address of the interface carrying the default route
+    DefaultInterface,
 }
 
 impl Default for IpDetectionMethod {
     fn default() -> Self {
-        Self::Web(None)
+        Self::Web { urls: Vec::new(), skip: None }
     }
 }
 
+impl fmt::Display for IpDetectionMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Manual(ip) => write!(f, "manual IP {}", ip),
+            Self::Web { urls, .. } if urls.is_empty() => f.write_str("web service (auto)"),
+            Self::Web { urls, .. } => write!(f, "web service {}", urls.join(", ")),
+            Self::Interface(name) => write!(f, "interface {}", name),
+            // Commands may carry credentials, so they aren't echoed
+            Self::Command(_) => f.write_str("command"),
+            Self::DhcpLease(path) => write!(f, "DHCP lease file {}", path.display()),
+            Self::WebRegex { url, .. } => write!(f, "web page {}", url),
+            Self::DefaultInterface => f.write_str("default route interface"),
+        }
+    }
+}
+
+/// Built-in web services in their initial order; `ServiceRegistry` moves failing ones back
+/// Matches ddclient's built-in web services for compatibility
+const WEB_SERVICES: &[&str] = &[
+    "https://api.ipify.org",           // ipify (most popular, supports both v4/v6)
+    "https://checkip.dns.he.net",      // Hurricane Electric
+    "http://checkip.amazonaws.com",    // AWS (reliable)
+    "http://icanhazip.com",            // Simple service
+    "https://ip4only.me/api",          // IPv4-specific
+    "https://ipv4.nsupdate.info/myip", // nsupdate.info
+    "http://ifconfig.me/ip",           // ifconfig.me
+];
+
+/// A service with more failures than this within `FAILURE_WINDOW_SECS` is skipped
+const MAX_RECENT_FAILURES: u32 = 5;
+const FAILURE_WINDOW_SECS: u64 = 3600;
+
+/// Failure tracking for one web IP service
+#[derive(Debug, Clone)]
+pub struct ServiceState {
+    pub url: String,
+    /// Consecutive failures, reset on success
+    pub failure_count: u32,
+    /// Time of the most recent failure (Unix epoch seconds)
+    pub last_failure: Option<u64>,
+}
+
+/// Web IP services ordered by reliability: fewest failures first
+pub struct ServiceRegistry {
+    services: Vec<ServiceState>,
+}
+
+impl ServiceRegistry {
+    pub fn new(urls: &[&str]) -> Self {
+        Self {
+            services: urls.iter()
+                .map(|url| ServiceState { url: url.to_string(), failure_count: 0, last_failure: None })
+                .collect(),
+        }
+    }
+
+    /// Service URLs in the order they will be tried
+    pub fn services_by_reliability(&self) -> Vec<&str> {
+        self.services.iter().map(|service| service.url.as_str()).collect()
+    }
+
+    /// Services to try at `now`, skipping those that keep failing.
+    /// If every service is being skipped they are all tried anyway.
+    fn candidates(&self, now: u64) -> Vec<String> {
+        let usable: Vec<String> = self.services.iter()
+            .filter(|service| !Self::is_failing(service, now))
+            .map(|service| service.url.clone())
+            .collect();
+        if usable.is_empty() {
+            self.services.iter().map(|service| service.url.clone()).collect()
+        } else {
+            usable
+        }
+    }
+
+    fn is_failing(service: &ServiceState, now: u64) -> bool {
+        service.failure_count > MAX_RECENT_FAILURES
+            && service.last_failure.is_some_and(|time| now.saturating_sub(time) < FAILURE_WINDOW_SECS)
+    }
+
+    pub fn record_failure(&mut self, url: &str, now: u64) {
+        if let Some(service) = self.services.iter_mut().find(|service| service.url == url) {
+            service.failure_count = service.failure_count.saturating_add(1);
+            service.last_failure = Some(now);
+        }
+        self.sort();
+    }
+
+    pub fn record_success(&mut self, url: &str) {
+        if let Some(service) = self.services.iter_mut().find(|service| service.url == url) {
+            service.failure_count = 0;
+        }
+        self.sort();
+    }
+
+    /// Stable, so services with equal failure counts keep their built-in order
+    fn sort(&mut self) {
+        self.services.sort_by_key(|service| service.failure_count);
+    }
+}
+
+/// Registry shared by every lookup in this process, so the order persists in daemon mode
+fn service_registry() -> &'static Mutex<ServiceRegistry> {
+    static REGISTRY: OnceLock<Mutex<ServiceRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(ServiceRegistry::new(WEB_SERVICES)))
+}
+
 /// Get external IP address from a public service
-pub fn get_external_ip() -> Result<IpAddr, Box<dyn Error>> {
-    // Try multiple services in case one is down
-    // Matches ddclient's built-in web services for compatibility
-    let services = [
-        "https://api.ipify.org",           // ipify (most popular, supports both v4/v6)
-        "https://checkip.dns.he.net",      // Hurricane Electric
-        "http://checkip.amazonaws.com",    // AWS (reliable)
-        "http://icanhazip.com",            // Simple service
-        "https://ip4only.me/api",          // IPv4-specific
-        "https://ipv4.nsupdate.info/myip", // nsupdate.info
-        "http://ifconfig.me/ip",           // ifconfig.me
-    ];
+/// `preferred` URLs (from --web) are tried first, in order, then the built-in services;
+/// a service whose response matches `skip` is treated as failed
+pub fn get_external_ip(preferred: &[String], skip: &Option<String>) -> Result<IpAddr, Box<dyn Error>> {
+    if let Some(pattern) = skip {
+        regex::Regex::new(pattern).map_err(|e| format!("Invalid web-skip pattern: {}", e))?;
+    }
+    get_external_ip_from(preferred, service_registry(), |url| try_service(url, skip))
+}
+
+/// Try the preferred URLs, then the registry's services in order of reliability,
+/// until one answers
+fn get_external_ip_from(
+    preferred: &[String],
+    registry: &Mutex<ServiceRegistry>,
+    try_service: impl Fn(&str) -> Result<IpAddr, Box<dyn Error>>,
+) -> Result<IpAddr, Box<dyn Error>> {
+    let built_in = {
+        let registry = registry.lock().map_err(|_| "IP service registry poisoned")?;
+        log::debug!("Web IP services by reliability: {:?}", registry.services_by_reliability());
+        registry.candidates(crate::state::current_timestamp())
+    };
+    let services: Vec<String> = preferred.iter()
+        .cloned()
+        .chain(built_in.into_iter().filter(|url| !preferred.contains(url)))
+        .collect();
 
     let mut last_error = None;
 
+    // The lock isn't held during requests; results are recorded as they come in
     for service in &services {
-        match try_service(service) {
-            Ok(ip) => return Ok(ip),
+        let result = try_service(service);
+        let mut registry = registry.lock().map_err(|_| "IP service registry poisoned")?;
+        match result {
+            Ok(ip) => {
+                registry.record_success(service);
+                return Ok(ip);
+            }
             Err(e) => {
                 log::debug!("Failed to get IP from {}: {}", service, e);
+                registry.record_failure(service, crate::state::current_timestamp());
                 last_error = Some(e);
             }
         }
@@ -51,14 +189,46 @@ pub fn get_external_ip() -> Result<IpAddr, Box<dyn Error>> {
 }
 
 /// Try to get IP from a specific service
-fn try_service(url: &str) -> Result<IpAddr, Box<dyn Error>> {
+fn try_service(url: &str, skip: &Option<String>) -> Result<IpAddr, Box<dyn Error>> {
     let resp = minreq::get(url)
         .with_timeout(10)
         .send()?;
-    let ip: IpAddr = resp.as_str()?.trim().parse()?;
+    let body = resp.as_str()?;
+    if apply_web_skip(body, skip) {
+        return Err("response matches web-skip pattern".into());
+    }
+    let ip: IpAddr = body.trim().parse()?;
     Ok(ip)
 }
 
+/// True when `skip_pattern` is set and matches the response body,
+/// e.g. an HTML error page served with status 200
+fn apply_web_skip(body: &str, skip_pattern: &Option<String>) -> bool {
+    skip_pattern.as_deref()
+        .and_then(|pattern| regex::Regex::new(pattern).ok())
+        .is_some_and(|regex| regex.is_match(body))
+}
+
+/// Fetch `url` and parse the first capture group of `pattern` in the body as the IP,
+/// for pages (e.g. router status pages) that embed the address in HTML
+pub fn get_ip_with_regex(url: &str, pattern: &str) -> Result<IpAddr, Box<dyn Error>> {
+    let regex = regex::Regex::new(pattern)
+        .map_err(|e| format!("Invalid --web-regex pattern: {}", e))?;
+    if regex.captures_len() < 2 {
+        return Err(format!("--web-regex pattern '{}' has no capture group", pattern).into());
+    }
+
+    let resp = minreq::get(url)
+        .with_timeout(10)
+        .send()?;
+    let body = resp.as_str()?;
+
+    let captured = regex.captures(body)
+        .and_then(|captures| captures.get(1))
+        .ok_or_else(|| format!("--web-regex pattern '{}' captured nothing in the response from {}", pattern, url))?;
+    parse_ip(captured.as_str().trim())
+}
+
 /// Parse and validate a provided IP address string
 pub fn parse_ip(ip_str: &str) -> Result<IpAddr, Box<dyn Error>> {
     ip_str.parse().map_err(|e| {
@@ -66,11 +236,70 @@ pub fn parse_ip(ip_str: &str) -> Result<IpAddr, Box<dyn Error>> {
     })
 }
 
+/// Name of the interface carrying the IPv4 default route
+pub fn get_default_interface() -> Result<String, Box<dyn Error>> {
+    #[cfg(target_os = "linux")]
+    {
+        let routes = fs::read_to_string("/proc/net/route")
+            .map_err(|e| format!("Failed to read /proc/net/route: {}", e))?;
+        default_interface_from_proc_route(&routes).ok_or_else(|| "No default route found in /proc/net/route".into())
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("route").args(["-n", "get", "default"]).output()?;
+        if !output.status.success() {
+            return Err("`route get default` failed; is there a default route?".into());
+        }
+        default_interface_from_route_get(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| "No interface in `route get default` output".into())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Err("Default route detection is only supported on Linux and macOS".into())
+    }
+}
+
+/// Interface of the lowest-metric usable default route in `/proc/net/route`
+/// Columns: Iface Destination Gateway Flags RefCnt Use Metric Mask ...
+#[cfg(any(target_os = "linux", test))]
+fn default_interface_from_proc_route(routes: &str) -> Option<String> {
+    const RTF_UP: u32 = 0x1;
+
+    routes.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (iface, destination, flags, metric) = (fields.first()?, fields.get(1)?, fields.get(3)?, fields.get(6)?);
+            let flags = u32::from_str_radix(flags, 16).ok()?;
+            (*destination == "00000000" && flags & RTF_UP != 0)
+                .then(|| (metric.parse::<u32>().unwrap_or(u32::MAX), iface.to_string()))
+        })
+        .min_by_key(|(metric, _)| *metric)
+        .map(|(_, iface)| iface)
+}
+
+/// `interface:` line of macOS `route get default` output
+#[cfg(any(target_os = "macos", test))]
+fn default_interface_from_route_get(output: &str) -> Option<String> {
+    output.lines()
+        .find_map(|line| line.trim().strip_prefix("interface:"))
+        .map(|iface| iface.trim().to_string())
+        .filter(|iface| !iface.is_empty())
+}
+
 /// Get IP from network interface
 pub fn get_ip_from_interface(interface: &str) -> Result<IpAddr, Box<dyn Error>> {
     #[cfg(target_os = "linux")]
     {
-        // Try `ip` command first (modern Linux)
+        // Ask the kernel directly, falling back to the command line tools below
+        match get_ip_from_interface_netlink(interface) {
+            Ok(ip) => return Ok(ip),
+            Err(e) => log::debug!("netlink lookup for '{}' failed, trying `ip addr`: {}", interface, e),
+        }
+
+        // Try `ip` command
         if let Ok(output) = Command::new("ip")
             .args(["-o", "addr", "show", "dev", interface, "scope", "global"])
             .output()
@@ -124,6 +353,116 @@ pub fn get_ip_from_interface(interface: &str) -> Result<IpAddr, Box<dyn Error>>
     Err(format!("Failed to get IP from interface '{}'", interface).into())
 }
 
+/// A netlink request/response channel; a trait so the query can be tested without a kernel socket
+#[cfg(target_os = "linux")]
+trait NetlinkTransport {
+    fn send(&mut self, request: &[u8]) -> Result<(), Box<dyn Error>>;
+    /// Receive one datagram, which may hold several netlink messages
+    fn recv(&mut self) -> Result<Vec<u8>, Box<dyn Error>>;
+}
+
+#[cfg(target_os = "linux")]
+impl NetlinkTransport for netlink_sys::Socket {
+    fn send(&mut self, request: &[u8]) -> Result<(), Box<dyn Error>> {
+        netlink_sys::Socket::send(self, request, 0)?;
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.recv_from_full()?.0)
+    }
+}
+
+/// Get the first global address of an interface via rtnetlink (RTM_GETLINK + RTM_GETADDR)
+#[cfg(target_os = "linux")]
+fn get_ip_from_interface_netlink(interface: &str) -> Result<IpAddr, Box<dyn Error>> {
+    let mut socket = netlink_sys::Socket::new(netlink_sys::protocols::NETLINK_ROUTE)?;
+    socket.bind_auto()?;
+    socket.connect(&netlink_sys::SocketAddr::new(0, 0))?;
+    query_interface_ip(&mut socket, interface)
+}
+
+#[cfg(target_os = "linux")]
+fn query_interface_ip(transport: &mut dyn NetlinkTransport, interface: &str) -> Result<IpAddr, Box<dyn Error>> {
+    use netlink_packet_route::address::{AddressAttribute, AddressMessage, AddressScope};
+    use netlink_packet_route::link::{LinkAttribute, LinkMessage};
+    use netlink_packet_route::RouteNetlinkMessage;
+
+    let index = netlink_dump(transport, RouteNetlinkMessage::GetLink(LinkMessage::default()), 1)?
+        .into_iter()
+        .find_map(|message| match message {
+            RouteNetlinkMessage::NewLink(link)
+                if link.attributes.iter().any(|attr| matches!(attr, LinkAttribute::IfName(name) if name == interface)) =>
+            {
+                Some(link.header.index)
+            }
+            _ => None,
+        })
+        .ok_or_else(|| format!("No such interface: {}", interface))?;
+
+    netlink_dump(transport, RouteNetlinkMessage::GetAddress(AddressMessage::default()), 2)?
+        .into_iter()
+        .filter_map(|message| match message {
+            RouteNetlinkMessage::NewAddress(address)
+                if address.header.index == index && address.header.scope == AddressScope::Universe =>
+            {
+                // IFA_LOCAL is the interface's own address; IFA_ADDRESS is the peer on point-to-point links
+                let local = address.attributes.iter().find_map(|attr| match attr {
+                    AddressAttribute::Local(ip) => Some(*ip),
+                    _ => None,
+                });
+                local.or_else(|| address.attributes.iter().find_map(|attr| match attr {
+                    AddressAttribute::Address(ip) => Some(*ip),
+                    _ => None,
+                }))
+            }
+            _ => None,
+        })
+        .find(|ip| !ip.is_loopback() && !ip.is_multicast())
+        .ok_or_else(|| format!("No global address on interface '{}'", interface).into())
+}
+
+/// Send a dump request and collect every reply up to NLMSG_DONE
+#[cfg(target_os = "linux")]
+fn netlink_dump(
+    transport: &mut dyn NetlinkTransport,
+    request: netlink_packet_route::RouteNetlinkMessage,
+    sequence_number: u32,
+) -> Result<Vec<netlink_packet_route::RouteNetlinkMessage>, Box<dyn Error>> {
+    use netlink_packet_core::{NetlinkMessage, NetlinkPayload, NLM_F_DUMP, NLM_F_REQUEST};
+
+    let mut packet = NetlinkMessage::from(request);
+    packet.header.flags = NLM_F_DUMP | NLM_F_REQUEST;
+    packet.header.sequence_number = sequence_number;
+    packet.finalize();
+    let mut buf = vec![0; packet.buffer_len()];
+    packet.serialize(&mut buf);
+    transport.send(&buf)?;
+
+    let mut replies = Vec::new();
+    loop {
+        let datagram = transport.recv()?;
+        let mut offset = 0;
+        while offset < datagram.len() {
+            let message: NetlinkMessage<netlink_packet_route::RouteNetlinkMessage> =
+                NetlinkMessage::deserialize(&datagram[offset..])?;
+            match message.payload {
+                NetlinkPayload::Done(_) => return Ok(replies),
+                NetlinkPayload::Error(e) => return Err(format!("netlink error: {}", e).into()),
+                NetlinkPayload::InnerMessage(inner) => replies.push(inner),
+                _ => {}
+            }
+            if message.header.length == 0 {
+                break;
+            }
+            offset += message.header.length as usize;
+        }
+        if datagram.is_empty() {
+            return Err("netlink socket closed before the dump completed".into());
+        }
+    }
+}
+
 /// Extract IP address from command output (Linux/macOS)
 fn extract_ip_from_output(output: &str) -> Option<IpAddr> {
     use std::str::FromStr;
@@ -215,27 +554,375 @@ pub fn get_ip_from_command(cmd: &str) -> Result<IpAddr, Box<dyn Error>> {
     parse_ip(trimmed)
 }
 
+/// Get the address of the most recent lease in an ISC dhclient lease file
+///
+/// DHCPv4 `lease { .. }` blocks provide `fixed-address` and are ordered by their `renew`
+/// time; DHCPv6 `lease6 { .. }` blocks provide `iaaddr` and are ordered by `starts`.
+/// Blocks without a timestamp rank by position, since dhclient appends new leases.
+pub fn get_ip_from_dhcp_lease(path: &Path) -> Result<IpAddr, Box<dyn Error>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read DHCP lease file {}: {}", path.display(), e))?;
+
+    lease_blocks(&content)
+        .iter()
+        .filter_map(|block| Some((lease_time(block), lease_address(block)?)))
+        .enumerate()
+        .max_by_key(|(position, (time, _))| (time.clone(), *position))
+        .map(|(_, (_, ip))| ip)
+        .ok_or_else(|| format!("No lease with an address found in {}", path.display()).into())
+}
+
+/// Bodies of the top-level `lease` / `lease6` blocks
+fn lease_blocks(content: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut depth = 0;
+    let mut start = None;
+
+    for (index, c) in content.char_indices() {
+        match c {
+            '{' => {
+                if depth == 0 {
+                    let header = content[..index].rsplit(['\n', ';', '}']).next().unwrap_or("").trim();
+                    if header == "lease" || header == "lease6" {
+                        start = Some(index + 1);
+                    }
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    if let Some(start) = start.take() {
+                        blocks.push(&content[start..index]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Sortable lease time: the DHCPv4 `renew` date (`YYYY/MM/DD HH:MM:SS`) or the
+/// zero-padded DHCPv6 `starts` epoch
+fn lease_time(block: &str) -> Option<String> {
+    let statements = || block.lines().map(str::trim).map(|line| line.trim_end_matches(';'));
+
+    if let Some(renew) = statements().find_map(|line| line.strip_prefix("renew ")) {
+        // "renew <weekday> <date> <time>"; DHCPv6 renew values are relative seconds
+        let mut fields = renew.split_whitespace();
+        if let (Some(_), Some(date), Some(time)) = (fields.next(), fields.next(), fields.next()) {
+            return Some(format!("{} {}", date, time));
+        }
+    }
+
+    statements()
+        .filter_map(|line| line.strip_prefix("starts ")?.trim().parse::<u64>().ok())
+        .max()
+        .map(|starts| format!("{:020}", starts))
+}
+
+fn lease_address(block: &str) -> Option<IpAddr> {
+    block.lines().map(str::trim).find_map(|line| {
+        let value = line.strip_prefix("fixed-address ")
+            .or_else(|| line.strip_prefix("iaaddr "))?;
+        value.trim_end_matches(['{', ';']).trim().parse().ok()
+    })
+}
+
 /// Get IP address using specified detection method
 pub fn get_ip_with_method(method: &IpDetectionMethod) -> Result<IpAddr, Box<dyn Error>> {
-    match method {
+    let ip = match method {
         IpDetectionMethod::Manual(ip_str) => parse_ip(ip_str),
-        IpDetectionMethod::Web(Some(url)) => {
-            // Use custom web service
-            try_service(url)
-        }
-        IpDetectionMethod::Web(None) => {
-            // Use default web services
-            get_external_ip()
-        }
+        IpDetectionMethod::Web { urls, skip } => get_external_ip(urls, skip),
         IpDetectionMethod::Interface(iface) => get_ip_from_interface(iface),
         IpDetectionMethod::Command(cmd) => get_ip_from_command(cmd),
+        IpDetectionMethod::DhcpLease(path) => get_ip_from_dhcp_lease(path),
+        IpDetectionMethod::WebRegex { url, pattern } => get_ip_with_regex(url, pattern),
+        IpDetectionMethod::DefaultInterface => {
+            let iface = get_default_interface()?;
+            log::debug!("Default route is on interface {}", iface);
+            get_ip_from_interface(&iface)
+        }
+    }?;
+    Ok(normalize_ip(ip))
+}
+
+/// Convert IPv4-mapped (`::ffff:a.b.c.d`) and IPv4-translated (`::ffff:0:a.b.c.d`)
+/// IPv6 addresses to plain IPv4, so they are published as A records
+pub fn normalize_ip(ip: IpAddr) -> IpAddr {
+    let IpAddr::V6(v6) = ip else {
+        return ip;
+    };
+    if let Some(v4) = v6.to_ipv4_mapped() {
+        return IpAddr::V4(v4);
+    }
+    match v6.segments() {
+        [0, 0, 0, 0, 0xffff, 0, high, low] => IpAddr::V4(Ipv4Addr::from(u32::from(high) << 16 | u32::from(low))),
+        _ => ip,
+    }
+}
+
+/// Address range an IP belongs to, used to catch addresses that shouldn't be published
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpClassification {
+    /// Globally routable
+    Public,
+    /// RFC 1918 private range (10/8, 172.16/12, 192.168/16)
+    Rfc1918,
+    /// Link-local (169.254/16, fe80::/10)
+    LinkLocal,
+    /// Loopback (127/8, ::1)
+    Loopback,
+    /// RFC 6598 carrier-grade NAT shared space (100.64/10)
+    Cgnat,
+    /// NAT64 translated IPv6 (64:ff9b::/96, 64:ff9b:1::/48)
+    Cgnat6,
+}
+
+impl fmt::Display for IpClassification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Public => "public",
+            Self::Rfc1918 => "private (RFC 1918)",
+            Self::LinkLocal => "link-local",
+            Self::Loopback => "loopback",
+            Self::Cgnat => "carrier-grade NAT (RFC 6598)",
+            Self::Cgnat6 => "NAT64",
+        })
+    }
+}
+
+/// Classify an address by the special-purpose range it falls in
+pub fn ip_classification(ip: IpAddr) -> IpClassification {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            if ip.is_loopback() {
+                IpClassification::Loopback
+            } else if ip.is_private() {
+                IpClassification::Rfc1918
+            } else if ip.is_link_local() {
+                IpClassification::LinkLocal
+            } else if a == 100 && (64..128).contains(&b) {
+                IpClassification::Cgnat
+            } else {
+                IpClassification::Public
+            }
+        }
+        IpAddr::V6(ip) => {
+            // An IPv4-mapped address (::ffff:a.b.c.d) is classified as its IPv4 address
+            if let Some(ipv4) = ip.to_ipv4_mapped() {
+                return ip_classification(IpAddr::V4(ipv4));
+            }
+            let segments = ip.segments();
+            if ip.is_loopback() {
+                IpClassification::Loopback
+            } else if segments[0] & 0xffc0 == 0xfe80 {
+                IpClassification::LinkLocal
+            } else if is_nat64(&ip) {
+                IpClassification::Cgnat6
+            } else {
+                IpClassification::Public
+            }
+        }
     }
 }
 
+/// Well-known (64:ff9b::/96) and local-use (64:ff9b:1::/48) NAT64 prefixes
+fn is_nat64(ip: &Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    segments[0] == 0x64
+        && segments[1] == 0xff9b
+        && (segments[2..6] == [0, 0, 0, 0] || segments[2] == 1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const SERVICES: &[&str] = &["https://a.example", "https://b.example", "https://c.example"];
+
+    #[test]
+    fn test_registry_reorders_after_failures() {
+        let mut registry = ServiceRegistry::new(SERVICES);
+        assert_eq!(registry.services_by_reliability(), SERVICES);
+
+        registry.record_failure("https://a.example", 1000);
+        registry.record_failure("https://a.example", 1001);
+        registry.record_failure("https://b.example", 1002);
+        assert_eq!(registry.services_by_reliability(), vec!["https://c.example", "https://b.example", "https://a.example"]);
+
+        // A success clears the count; ties keep their current order
+        registry.record_success("https://a.example");
+        assert_eq!(registry.services_by_reliability(), vec!["https://c.example", "https://a.example", "https://b.example"]);
+    }
+
+    #[test]
+    fn test_registry_skips_services_failing_within_the_hour() {
+        let mut registry = ServiceRegistry::new(SERVICES);
+        for n in 0..=MAX_RECENT_FAILURES as u64 {
+            registry.record_failure("https://b.example", 1000 + n);
+        }
+        assert_eq!(registry.candidates(2000), vec!["https://a.example", "https://c.example"]);
+        // Retried once the last failure is an hour old
+        assert_eq!(registry.candidates(1005 + FAILURE_WINDOW_SECS).len(), 3);
+
+        // Five failures are still tolerated
+        let mut registry = ServiceRegistry::new(SERVICES);
+        for n in 0..MAX_RECENT_FAILURES as u64 {
+            registry.record_failure("https://b.example", 1000 + n);
+        }
+        assert_eq!(registry.candidates(2000).len(), 3);
+    }
+
+    #[test]
+    fn test_registry_tries_all_when_all_failing() {
+        let mut registry = ServiceRegistry::new(&SERVICES[..1]);
+        for n in 0..10 {
+            registry.record_failure("https://a.example", 1000 + n);
+        }
+        assert_eq!(registry.candidates(1020), vec!["https://a.example"]);
+    }
+
+    #[test]
+    fn test_external_ip_prefers_reliable_services() {
+        let registry = Mutex::new(ServiceRegistry::new(SERVICES));
+        let calls = Mutex::new(Vec::new());
+        let lookup = |url: &str| -> Result<IpAddr, Box<dyn Error>> {
+            calls.lock().unwrap().push(url.to_string());
+            match url {
+                "https://c.example" => Ok("203.0.113.1".parse().unwrap()),
+                _ => Err("connection refused".into()),
+            }
+        };
+
+        assert_eq!(get_external_ip_from(&[], &registry, lookup).unwrap(), "203.0.113.1".parse::<IpAddr>().unwrap());
+        assert_eq!(calls.lock().unwrap().len(), 3);
+
+        // The working service is now tried first
+        calls.lock().unwrap().clear();
+        assert!(get_external_ip_from(&[], &registry, lookup).is_ok());
+        assert_eq!(*calls.lock().unwrap(), vec!["https://c.example"]);
+        assert_eq!(registry.lock().unwrap().services_by_reliability()[0], "https://c.example");
+    }
+
+    #[test]
+    fn test_external_ip_tries_user_urls_first() {
+        let registry = Mutex::new(ServiceRegistry::new(SERVICES));
+        let preferred = vec!["https://mine.example".to_string(), "https://b.example".to_string()];
+        let calls = Mutex::new(Vec::new());
+        let lookup = |url: &str| -> Result<IpAddr, Box<dyn Error>> {
+            calls.lock().unwrap().push(url.to_string());
+            match url {
+                "https://c.example" => Ok("203.0.113.1".parse().unwrap()),
+                _ => Err("connection refused".into()),
+            }
+        };
+
+        assert!(get_external_ip_from(&preferred, &registry, lookup).is_ok());
+        // User URLs in order, then the built-in ones not already tried
+        assert_eq!(*calls.lock().unwrap(), vec!["https://mine.example", "https://b.example", "https://a.example", "https://c.example"]);
+
+        calls.lock().unwrap().clear();
+        let lookup = |url: &str| -> Result<IpAddr, Box<dyn Error>> {
+            match url {
+                "https://mine.example" => Ok("198.51.100.7".parse().unwrap()),
+                _ => Err("connection refused".into()),
+            }
+        };
+        assert_eq!(get_external_ip_from(&preferred, &registry, lookup).unwrap(), "198.51.100.7".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_external_ip_all_services_exhausted() {
+        let registry = Mutex::new(ServiceRegistry::new(SERVICES));
+        let calls = Mutex::new(0);
+        let lookup = |url: &str| -> Result<IpAddr, Box<dyn Error>> {
+            *calls.lock().unwrap() += 1;
+            Err(format!("{} is down", url).into())
+        };
+
+        let err = get_external_ip_from(&["https://mine.example".to_string()], &registry, lookup).unwrap_err();
+        assert_eq!(*calls.lock().unwrap(), 4);
+        assert!(err.to_string().ends_with("is down"));
+    }
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    #[test]
+    fn test_apply_web_skip() {
+        let html = fs::read_to_string(fixture("web_error.html")).unwrap();
+        let plain = fs::read_to_string(fixture("web_ip.txt")).unwrap();
+        let skip = Some("(?i)<html".to_string());
+
+        assert!(apply_web_skip(&html, &skip));
+        assert!(!apply_web_skip(&plain, &skip));
+        assert!(!apply_web_skip(&html, &None));
+    }
+
+    #[test]
+    fn test_web_skip_moves_on_to_next_service() {
+        let mut server = mockito::Server::new();
+        let _error = server.mock("GET", "/error")
+            .with_body(fs::read_to_string(fixture("web_error.html")).unwrap())
+            .create();
+        let _plain = server.mock("GET", "/plain")
+            .with_body(fs::read_to_string(fixture("web_ip.txt")).unwrap())
+            .create();
+
+        let preferred = vec![format!("{}/error", server.url()), format!("{}/plain", server.url())];
+        let registry = Mutex::new(ServiceRegistry::new(&[]));
+        let skip = Some("<html".to_string());
+
+        let err = try_service(&preferred[0], &skip).unwrap_err();
+        assert_eq!(err.to_string(), "response matches web-skip pattern");
+        let ip = get_external_ip_from(&preferred, &registry, |url| try_service(url, &skip)).unwrap();
+        assert_eq!(ip, "203.0.113.7".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_web_skip_invalid_pattern() {
+        let err = get_external_ip(&[], &Some("(".to_string())).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid web-skip pattern"));
+    }
+
+    #[test]
+    fn test_dhcp_lease_most_recent_renewal() {
+        let ip = get_ip_from_dhcp_lease(&fixture("dhclient.leases")).unwrap();
+        assert_eq!(ip, "198.51.100.23".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_dhcp6_lease_most_recent_start() {
+        let ip = get_ip_from_dhcp_lease(&fixture("dhclient6.leases")).unwrap();
+        assert_eq!(ip, "2001:db8::1:2a".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_dhcp_lease_blocks() {
+        let content = "lease {\n  option routers 192.0.2.1;\n  fixed-address 192.0.2.5;\n}\nlease {\n  fixed-address 192.0.2.6;\n}\n";
+        let blocks = lease_blocks(content);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(lease_address(blocks[0]), Some("192.0.2.5".parse().unwrap()));
+        // Without renew times the last lease in the file wins
+        let path = std::env::temp_dir().join(format!("rddclient-lease-{}", std::process::id()));
+        fs::write(&path, content).unwrap();
+        assert_eq!(get_ip_from_dhcp_lease(&path).unwrap(), "192.0.2.6".parse::<IpAddr>().unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_dhcp_lease_errors() {
+        assert!(get_ip_from_dhcp_lease(&fixture("missing.leases")).is_err());
+        let path = std::env::temp_dir().join(format!("rddclient-empty-lease-{}", std::process::id()));
+        fs::write(&path, "default-duid \"\\000\";\n").unwrap();
+        assert!(get_ip_from_dhcp_lease(&path).unwrap_err().to_string().contains("No lease"));
+        fs::remove_file(&path).unwrap();
+    }
+
     #[test]
     fn test_parse_valid_ipv4() {
         let result = parse_ip("8.8.8.8");
@@ -361,12 +1048,132 @@ mod tests {
 
     #[test]
     fn test_ip_detection_method_web_default() {
-        let method = IpDetectionMethod::Web(None);
+        let method = IpDetectionMethod::default();
         // This test requires internet connectivity
         // Just verify it doesn't panic
         let _ = get_ip_with_method(&method);
     }
 
+    const ROUTER_PAGE: &str = "<html><body><table>\
+        <tr><td>LAN IP</td><td>192.168.1.1</td></tr>\
+        <tr><td>WAN IP</td><td id=\"wan\">203.0.113.42</td></tr>\
+        </table></body></html>";
+
+    fn router_server() -> mockito::ServerGuard {
+        let mut server = mockito::Server::new();
+        server.mock("GET", "/status").with_body(ROUTER_PAGE).create();
+        server
+    }
+
+    #[test]
+    fn test_web_regex_extracts_capture() {
+        let server = router_server();
+        let url = format!("{}/status", server.url());
+
+        let ip = get_ip_with_regex(&url, r#"id="wan">([^<]+)<"#).unwrap();
+        assert_eq!(ip, "203.0.113.42".parse::<IpAddr>().unwrap());
+
+        // The first group is used even when the pattern has more
+        let ip = get_ip_with_regex(&url, r"(\d+\.\d+\.\d+\.\d+)</td></tr>(\S*)").unwrap();
+        assert_eq!(ip, "192.168.1.1".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_web_regex_errors() {
+        let server = router_server();
+        let url = format!("{}/status", server.url());
+
+        let err = get_ip_with_regex(&url, r"WAN IP").unwrap_err();
+        assert!(err.to_string().contains("has no capture group"));
+
+        let err = get_ip_with_regex(&url, r"PPPoE IP</td><td>([^<]+)").unwrap_err();
+        assert!(err.to_string().contains("captured nothing"));
+
+        let err = get_ip_with_regex(&url, r"<td>(WAN IP)</td>").unwrap_err();
+        assert!(err.to_string().contains("invalid IP address"));
+
+        let err = get_ip_with_regex(&url, r"(unclosed").unwrap_err();
+        assert!(err.to_string().starts_with("Invalid --web-regex pattern"));
+    }
+
+    #[test]
+    fn test_web_regex_method() {
+        let server = router_server();
+        let method = IpDetectionMethod::WebRegex {
+            url: format!("{}/status", server.url()),
+            pattern: r"WAN IP</td><td[^>]*>([^<]+)".to_string(),
+        };
+        assert_eq!(get_ip_with_method(&method).unwrap(), "203.0.113.42".parse::<IpAddr>().unwrap());
+    }
+
+    const PROC_NET_ROUTE: &str = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0
+eth0\t00000000\t0100000A\t0003\t0\t0\t100\t00000000\t0\t0\t0
+eth0\t0000000A\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0
+";
+
+    #[test]
+    fn test_default_interface_from_proc_route() {
+        assert_eq!(default_interface_from_proc_route(PROC_NET_ROUTE).as_deref(), Some("eth0"));
+
+        // Only the wlan0 default route is left once eth0's is down (flags without RTF_UP)
+        let eth0_down = PROC_NET_ROUTE.replace("0100000A\t0003", "0100000A\t0002");
+        assert_eq!(default_interface_from_proc_route(&eth0_down).as_deref(), Some("wlan0"));
+
+        let no_default: String = PROC_NET_ROUTE.lines().filter(|line| line.split_whitespace().nth(1) != Some("00000000")).collect::<Vec<_>>().join("\n");
+        assert_eq!(default_interface_from_proc_route(&no_default), None);
+    }
+
+    #[test]
+    fn test_default_interface_from_route_get() {
+        let output = "   route to: default
+destination: default
+       mask: default
+    gateway: 192.168.1.1
+  interface: en0
+      flags: <UP,GATEWAY,DONE,STATIC,PRCLONING,GLOBAL>
+";
+        assert_eq!(default_interface_from_route_get(output).as_deref(), Some("en0"));
+        assert_eq!(default_interface_from_route_get("route: writing to routing socket: not in table"), None);
+    }
+
+    #[test]
+    fn test_detection_method_display() {
+        let cases = [
+            (IpDetectionMethod::Manual("203.0.113.1".to_string()), "manual IP 203.0.113.1"),
+            (IpDetectionMethod::Web { urls: vec!["https://api.ipify.org".to_string()], skip: None }, "web service https://api.ipify.org"),
+            (
+                IpDetectionMethod::Web { urls: vec!["https://a.example".to_string(), "https://b.example".to_string()], skip: None },
+                "web service https://a.example, https://b.example",
+            ),
+            (IpDetectionMethod::default(), "web service (auto)"),
+            (IpDetectionMethod::Interface("eth0".to_string()), "interface eth0"),
+            (IpDetectionMethod::Command("curl -s https://ifconfig.me".to_string()), "command"),
+            (IpDetectionMethod::DhcpLease(PathBuf::from("/var/lib/dhcp/dhclient.leases")), "DHCP lease file /var/lib/dhcp/dhclient.leases"),
+            (
+                IpDetectionMethod::WebRegex { url: "http://192.168.1.1/status".to_string(), pattern: "(.*)".to_string() },
+                "web page http://192.168.1.1/status",
+            ),
+            (IpDetectionMethod::DefaultInterface, "default route interface"),
+        ];
+        for (method, expected) in cases {
+            assert_eq!(method.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_normalize_ip() {
+        let normalize = |ip: &str| normalize_ip(ip.parse().unwrap()).to_string();
+        assert_eq!(normalize("::ffff:1.2.3.4"), "1.2.3.4");
+        assert_eq!(normalize("::ffff:0:1.2.3.4"), "1.2.3.4");
+        assert_eq!(normalize("::1"), "::1");
+        assert_eq!(normalize("2606:4700:4700::1111"), "2606:4700:4700::1111");
+        assert_eq!(normalize("203.0.113.1"), "203.0.113.1");
+        assert_eq!(get_ip_with_method(&IpDetectionMethod::Manual("::ffff:203.0.113.1".to_string())).unwrap().to_string(), "203.0.113.1");
+    }
+
     #[test]
     fn test_get_ip_from_command() {
         // Test with echo command
@@ -404,4 +1211,129 @@ mod tests {
         assert!(ip.is_some());
         assert_eq!(ip.unwrap().to_string(), "10.0.0.5");
     }
+
+    #[test]
+    fn test_ip_classification_ipv4() {
+        let classify = |ip: &str| ip_classification(ip.parse().unwrap());
+        assert_eq!(classify("203.0.113.1"), IpClassification::Public);
+        assert_eq!(classify("8.8.8.8"), IpClassification::Public);
+        assert_eq!(classify("10.1.2.3"), IpClassification::Rfc1918);
+        assert_eq!(classify("172.16.0.1"), IpClassification::Rfc1918);
+        assert_eq!(classify("172.31.255.255"), IpClassification::Rfc1918);
+        assert_eq!(classify("172.32.0.1"), IpClassification::Public);
+        assert_eq!(classify("192.168.1.1"), IpClassification::Rfc1918);
+        assert_eq!(classify("100.64.0.1"), IpClassification::Cgnat);
+        assert_eq!(classify("100.127.255.255"), IpClassification::Cgnat);
+        assert_eq!(classify("100.128.0.1"), IpClassification::Public);
+        assert_eq!(classify("169.254.10.20"), IpClassification::LinkLocal);
+        assert_eq!(classify("127.0.0.1"), IpClassification::Loopback);
+    }
+
+    #[test]
+    fn test_ip_classification_ipv6() {
+        let classify = |ip: &str| ip_classification(ip.parse().unwrap());
+        assert_eq!(classify("2001:db8::1"), IpClassification::Public);
+        assert_eq!(classify("fe80::1"), IpClassification::LinkLocal);
+        assert_eq!(classify("febf::1"), IpClassification::LinkLocal);
+        assert_eq!(classify("fec0::1"), IpClassification::Public);
+        assert_eq!(classify("::1"), IpClassification::Loopback);
+        assert_eq!(classify("64:ff9b::c000:221"), IpClassification::Cgnat6);
+        assert_eq!(classify("64:ff9b:1::1"), IpClassification::Cgnat6);
+        assert_eq!(classify("::ffff:192.168.1.1"), IpClassification::Rfc1918);
+    }
+
+    /// Replays canned netlink datagrams instead of talking to the kernel
+    #[cfg(target_os = "linux")]
+    struct MockNetlink {
+        replies: std::collections::VecDeque<Vec<u8>>,
+        requests: usize,
+    }
+
+    #[cfg(target_os = "linux")]
+    impl NetlinkTransport for MockNetlink {
+        fn send(&mut self, _request: &[u8]) -> Result<(), Box<dyn Error>> {
+            self.requests += 1;
+            Ok(())
+        }
+
+        fn recv(&mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+            Ok(self.replies.pop_front().unwrap_or_default())
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn netlink_datagram(messages: Vec<netlink_packet_core::NetlinkPayload<netlink_packet_route::RouteNetlinkMessage>>) -> Vec<u8> {
+        let mut datagram = Vec::new();
+        for payload in messages {
+            let mut message = netlink_packet_core::NetlinkMessage::new(Default::default(), payload);
+            message.finalize();
+            let mut buf = vec![0; message.buffer_len()];
+            message.serialize(&mut buf);
+            datagram.extend(buf);
+        }
+        datagram
+    }
+
+    #[cfg(target_os = "linux")]
+    fn mock_netlink() -> MockNetlink {
+        use netlink_packet_core::{DoneMessage, NetlinkPayload};
+        use netlink_packet_route::address::{AddressAttribute, AddressMessage, AddressScope};
+        use netlink_packet_route::link::{LinkAttribute, LinkMessage};
+        use netlink_packet_route::RouteNetlinkMessage;
+
+        let link = |index: u32, name: &str| {
+            let mut link = LinkMessage::default();
+            link.header.index = index;
+            link.attributes.push(LinkAttribute::IfName(name.to_string()));
+            NetlinkPayload::InnerMessage(RouteNetlinkMessage::NewLink(link))
+        };
+        let address = |index: u32, scope: AddressScope, ip: &str| {
+            let mut address = AddressMessage::default();
+            address.header.index = index;
+            address.header.scope = scope;
+            address.attributes.push(AddressAttribute::Local(ip.parse().unwrap()));
+            NetlinkPayload::InnerMessage(RouteNetlinkMessage::NewAddress(address))
+        };
+        let done = || NetlinkPayload::Done(DoneMessage::default());
+
+        MockNetlink {
+            replies: vec![
+                netlink_datagram(vec![link(1, "lo"), link(2, "eth0")]),
+                netlink_datagram(vec![done()]),
+                netlink_datagram(vec![
+                    address(1, AddressScope::Host, "127.0.0.1"),
+                    address(2, AddressScope::Link, "fe80::1"),
+                    address(2, AddressScope::Universe, "203.0.113.7"),
+                    done(),
+                ]),
+            ]
+            .into(),
+            requests: 0,
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_netlink_interface_ip() {
+        let mut transport = mock_netlink();
+        let ip = query_interface_ip(&mut transport, "eth0").unwrap();
+        assert_eq!(ip, "203.0.113.7".parse::<IpAddr>().unwrap());
+        assert_eq!(transport.requests, 2);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_netlink_unknown_interface() {
+        let mut transport = mock_netlink();
+        let err = query_interface_ip(&mut transport, "wlan0").unwrap_err();
+        assert!(err.to_string().contains("No such interface"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_netlink_no_global_address() {
+        let mut transport = mock_netlink();
+        let err = query_interface_ip(&mut transport, "lo").unwrap_err();
+        assert!(err.to_string().contains("No global address"));
+    }
 }
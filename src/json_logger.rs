@@ -0,0 +1,136 @@
+/// `--log-format json` backend: one JSON object per line on stderr
+///
+/// Key-value pairs attached to a record (e.g. `hostname`, `ip` on update events) become
+/// extra top-level fields.
+use log::kv::{Error as KvError, Key, Value, VisitSource};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::json;
+use std::error::Error;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct JsonLogger {
+    level: LevelFilter,
+}
+
+impl JsonLogger {
+    /// Install the JSON logger as the global logger
+    pub fn init(level: LevelFilter) -> Result<(), Box<dyn Error>> {
+        log::set_boxed_logger(Box::new(Self { level }))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_record(record, SystemTime::now());
+        // Nowhere left to report a failed stderr write
+        let _ = writeln!(std::io::stderr().lock(), "{}", line);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Collects a record's key-value pairs into a JSON object
+struct Fields<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), KvError> {
+        self.0.insert(key.to_string(), json!(value.to_string()));
+        Ok(())
+    }
+}
+
+/// Serialize a record as a single-line JSON object
+fn format_record(record: &Record, now: SystemTime) -> String {
+    let mut entry = json!({
+        "level": record.level().as_str(),
+        "timestamp": format_rfc3339(now),
+        "module": record.module_path().unwrap_or(record.target()),
+        "message": record.args().to_string(),
+    });
+    if let Some(object) = entry.as_object_mut() {
+        let _ = record.key_values().visit(&mut Fields(object));
+    }
+    entry.to_string()
+}
+
+/// UTC timestamp in RFC 3339 format with second precision (e.g. 2024-01-01T00:00:00Z)
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, rem) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, rem / 3600, (rem % 3600) / 60, rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(UNIX_EPOCH + Duration::from_secs(1_704_067_200)), "2024-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(UNIX_EPOCH + Duration::from_secs(1_709_208_000 + 3723)), "2024-02-29T13:02:03Z");
+    }
+
+    #[test]
+    fn test_format_record() {
+        let kvs = [("hostname", "home.example.com"), ("ip", "203.0.113.1")];
+        let line = format_record(
+            &Record::builder()
+                .level(Level::Info)
+                .target("rddclient")
+                .module_path(Some("rddclient::clients::cloudflare"))
+                .args(format_args!("Updated {}", "home.example.com"))
+                .key_values(&kvs)
+                .build(),
+            UNIX_EPOCH + Duration::from_secs(1_704_067_200),
+        );
+
+        assert!(!line.contains('\n'));
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["timestamp"], "2024-01-01T00:00:00Z");
+        assert_eq!(json["module"], "rddclient::clients::cloudflare");
+        assert_eq!(json["message"], "Updated home.example.com");
+        assert_eq!(json["hostname"], "home.example.com");
+        assert_eq!(json["ip"], "203.0.113.1");
+    }
+
+    #[test]
+    fn test_format_record_without_context() {
+        let line = format_record(
+            &Record::builder().level(Level::Warn).target("rddclient").args(format_args!("careful")).build(),
+            UNIX_EPOCH,
+        );
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["module"], "rddclient");
+        assert!(json.get("hostname").is_none());
+    }
+}
@@ -1,40 +1,202 @@
 mod args;
+mod check;
 mod clients;
 mod config;
+mod daemon;
+mod error;
+mod hooks;
+mod http;
+mod init;
 mod ip;
+mod json_logger;
+mod network_events;
+mod notify;
 mod state;
+mod state_sqlite;
+mod status;
+mod systemd;
+#[cfg(unix)]
+mod syslog_logger;
+mod utils;
+mod validate;
+mod verify;
+mod watch;
 
 use clap::CommandFactory;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use error::RddclientError;
+use state::StateStore;
 use std::error::Error;
 
 /// User-Agent header value for HTTP requests
-pub const USER_AGENT: &str = concat!("rddclient/", env!("CARGO_PKG_VERSION"));
+pub const DEFAULT_USER_AGENT: &str = concat!("rddclient/", env!("CARGO_PKG_VERSION"));
 
-fn init_logger(verbose: bool, test: bool, debug: bool, quiet: bool) {
-    let log_level = if quiet {
+/// How often a waiting daemon checks for shutdown and pings the systemd watchdog
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+fn init_logger(args: &args::Args) {
+    let log_level = if args.quiet {
         log::LevelFilter::Error
-    } else if debug {
+    } else if args.debug {
         log::LevelFilter::Debug
-    } else if verbose || test {
+    } else if args.verbose || args.test {
         log::LevelFilter::Info
     } else {
         log::LevelFilter::Warn
     };
 
+    // With --syslog nothing is logged to the terminal unless syslog is unreachable
+    #[cfg(unix)]
+    if args.syslog {
+        match syslog_logger::SyslogLogger::init(log_level) {
+            Ok(()) => return,
+            Err(e) => eprintln!("{}; logging to stderr instead", e),
+        }
+    }
+
+    if args.log_format == "json" {
+        match json_logger::JsonLogger::init(log_level) {
+            Ok(()) => return,
+            Err(e) => eprintln!("{}; using text log format", e),
+        }
+    }
+
     env_logger::builder()
         .filter(None, log_level)
         .init();
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> std::process::ExitCode {
+    match run() {
+        Ok(()) => error::ExitCode::Success.into(),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            error::ExitCode::for_error(e.as_ref()).into()
+        }
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
     let args = args::Args::new();
-    let test = args.test;
 
-    init_logger(args.verbose, test, args.debug, args.quiet);
+    // Subcommands that don't need any configuration
+    if let Some(args::Command::Completions { shell }) = args.command {
+        args::write_completions(shell, &mut std::io::stdout());
+        return Ok(());
+    }
+    if let Some(args::Command::Init { output }) = &args.command {
+        init::run(output).map_err(|e| RddclientError::Config(e.to_string()))?;
+        return Ok(());
+    }
+    init_logger(&args);
 
     // Load and merge configuration
-    let config = config::Config::load(&args)?;
-    config.validate()?;
+    let mut config = config::Config::load(&args).map_err(|e| RddclientError::Config(e.to_string()))?;
+
+    if args.print_config {
+        let rendered = if args.show_secrets { config.to_ddclient_format() } else { config.to_ddclient_format_masked() };
+        print!("{}", rendered);
+        return Ok(());
+    }
+
+    if let Some(args::Command::Validate) = args.command {
+        let checks = validate::run_checks(&config, &args);
+        print!("{}", validate::render(&checks));
+        std::process::exit(if checks.iter().all(|check| check.passed) { 0 } else { 1 });
+    }
+
+    // Initialize state management
+    let cache_path = args.cache.as_ref().map(std::path::PathBuf::from);
+    let state_format: state::StateFormat = args.state_format.parse().map_err(RddclientError::Config)?;
+
+    if let Some(target) = args.convert_state.as_deref() {
+        let source = match &cache_path {
+            Some(path) => path.clone(),
+            None => state::StateManager::default_cache_path()?,
+        };
+        state::convert_state_file(&source, state_format, Path::new(target))
+            .map_err(|e| RddclientError::State(e.to_string()))?;
+        println!("Wrote {} as {} state to {}", source.display(), args.state_format.to_ascii_lowercase(), target);
+        return Ok(());
+    }
+    let mut state_manager: Box<dyn StateStore> = open_state(&args, cache_path, state_format)
+        .map_err(|e| RddclientError::State(e.to_string()))?;
+
+    // Forget hosts that were removed from the config long enough ago
+    if let Some(days) = args.state_max_age {
+        let hostnames = config.dns_records();
+        let active: Vec<&str> = hostnames.iter().map(String::as_str).collect();
+        for hostname in state_manager.prune_expired(days.saturating_mul(86400), &active) {
+            log::warn!("Skipping expired state entry for {} (not updated in over {} days)", hostname, days);
+        }
+    }
+
+    // Parse rate limiting intervals (defaults match ddclient)
+    let interval_arg = |interval: &Option<String>| {
+        interval.as_deref()
+            .map(config::parse_interval)
+            .transpose()
+            .map_err(|e| RddclientError::Config(e.to_string()))
+    };
+    let min_interval = interval_arg(&args.min_interval)?.or(Some(30)); // Default: 30 seconds
+    let max_interval = interval_arg(&args.max_interval)?.or(Some(25 * 86400)); // Default: 25 days
+    let min_error_interval = interval_arg(&args.min_error_interval)?.or(Some(300)); // Default: 5 minutes
+
+    // Migrate hosts from an existing ddclient installation
+    if let Some(ddclient_cache) = args.import_ddclient_cache.as_deref() {
+        let source = state::StateManager::from_ddclient_file(ddclient_cache.into())?;
+        let actions = state::import_states(state_manager.as_mut(), &source, args.force, args.dry_run_import);
+
+        let verb = if args.dry_run_import { "would import" } else { "imported" };
+        for (hostname, action) in &actions {
+            match action {
+                state::ImportAction::Imported => println!("{}: {}", hostname, verb),
+                state::ImportAction::Replaced => println!("{}: {} (replacing existing state)", hostname, verb),
+                state::ImportAction::Skipped => println!("{}: skipped (already tracked, use --force to replace)", hostname),
+            }
+        }
+
+        let skipped = actions.iter().filter(|(_, action)| *action == state::ImportAction::Skipped).count();
+        println!("{} host(s) {}, {} skipped", actions.len() - skipped, verb, skipped);
+
+        if !args.dry_run_import {
+            state_manager.save().map_err(|e| RddclientError::State(e.to_string()))?;
+        }
+        return Ok(());
+    }
+
+    // Clear a permanent failure so the host is retried on the next run
+    if let Some(hostname) = args.reenable.as_deref() {
+        if state_manager.get(hostname).is_none() {
+            return Err(format!("{} is not tracked in the state file", hostname).into());
+        }
+        state_manager.get_mut(hostname).reenable();
+        state_manager.save()?;
+        println!("{}: re-enabled", hostname);
+        return Ok(());
+    }
+
+    if let Some(hostname) = args.history.as_deref() {
+        let host_state = state_manager.get(hostname)
+            .ok_or_else(|| format!("{} is not tracked in the state file", hostname))?;
+        print!("{}", status::render_history(host_state, state::current_timestamp()));
+        return Ok(());
+    }
+
+    // Report tracked hosts from the state file without touching the network
+    if args.status {
+        let rows: Vec<_> = state_manager.iter().collect();
+        print!("{}", status::render_table(&rows, state::current_timestamp(), |hostname| {
+            config.intervals_for(hostname).0.or(min_interval).unwrap_or(0)
+        }));
+        std::process::exit(if status::all_ok(&rows) { 0 } else { 1 });
+    }
+
+    config.validate().map_err(|e| RddclientError::Config(e.to_string()))?;
+    if let Some(path) = config.ca_cert_path.as_deref() {
+        http::check_ca_cert(path).map_err(|e| RddclientError::Config(e.to_string()))?;
+    }
 
     // Display help if no host is configured
     if config.host.is_none() {
@@ -43,40 +205,222 @@ fn main() -> Result<(), Box<dyn Error>> {
         return Ok(());
     }
 
-    let protocol = config.protocol.as_ref()
-        .ok_or("Protocol not specified (use --protocol)")?;
+    let limits = RateLimits {
+        min: min_interval,
+        max: max_interval,
+        min_error: min_error_interval,
+        // 0 disables the limit
+        max_retries: Some(args.max_retries).filter(|&n| n > 0),
+    };
 
-    log::info!("Starting {} DNS updater...", protocol);
+    // Report which hosts would be updated; only IP detection touches the network
+    if args.check {
+        let ip = match detection_method(&args, &config).and_then(|method| ip::get_ip_with_method(&method)) {
+            Ok(ip) => ip,
+            Err(e) => {
+                eprintln!("IP detection failed: {}", e);
+                std::process::exit(2);
+            }
+        };
+        let checks = check::evaluate(state_manager.as_mut(), &config.dns_records(), ip, args.force, &limits, |hostname| {
+            config.intervals_for(hostname)
+        });
+        if args.json {
+            println!("{}", check::render_json(&checks));
+        } else {
+            print!("{}", check::render_text(&checks));
+        }
+        std::process::exit(check::exit_code(&checks));
+    }
 
-    // Initialize state management
-    let cache_path = args.cache.as_ref().map(std::path::PathBuf::from);
-    let mut state_manager = state::StateManager::new(cache_path)?;
+    let notifiers = notify::Notifiers::new(&args);
 
-    // Parse rate limiting intervals (defaults match ddclient)
-    let min_interval = args.min_interval.as_deref()
-        .map(config::parse_interval)
-        .transpose()?
-        .or(Some(30)); // Default: 30 seconds
-    
-    let max_interval = args.max_interval.as_deref()
-        .map(config::parse_interval)
-        .transpose()?
-        .or(Some(25 * 86400)); // Default: 25 days
-    
-    let min_error_interval = args.min_error_interval.as_deref()
-        .map(config::parse_interval)
-        .transpose()?
-        .or(Some(300)); // Default: 5 minutes
+    let systemd = systemd::SystemdNotifier::new(args.systemd);
+    systemd.ready();
 
-    // Determine IP detection method
-    let detection_method = if let Some(ip_str) = config.ip.as_deref() {
+    // With --watch, re-run updates whenever the config file changes
+    let watcher = if args.watch {
+        Some(watch::ConfigWatcher::new(Path::new(config::Config::file_path(&args)))
+            .map_err(|e| RddclientError::Config(e.to_string()))?)
+    } else {
+        None
+    };
+
+    // Daemon and watch modes keep running until SIGINT/SIGTERM
+    let shutdown = if args.daemon || args.watch {
+        Some(daemon::Shutdown::install()?)
+    } else {
+        None
+    };
+    let _pid_file = args.pid_file.as_deref()
+        .map(|path| daemon::PidFile::create(Path::new(path)))
+        .transpose()
+        .map_err(|e| RddclientError::State(e.to_string()))?;
+
+    // Re-check as soon as an interface address changes
+    let network_events = args.network_events
+        .then(|| network_events::NetworkEvents::start(args.if_name.as_deref()))
+        .transpose()
+        .map_err(|e| RddclientError::Config(e.to_string()))?;
+
+    // Time between update passes in daemon mode (default: min-interval)
+    let interval = interval_arg(&args.interval)?.or(min_interval).unwrap_or(30);
+
+    loop {
+        let result = run_updates(&args, &config, state_manager.as_mut(), &limits, &notifiers, &systemd);
+
+        let Some(shutdown) = &shutdown else {
+            result?;
+            break;
+        };
+        if let Err(e) = result {
+            log::error!("{}", e);
+        }
+
+        let next_pass = args.daemon.then(|| Duration::from_secs(interval));
+        let wakers = Wakers { watcher: watcher.as_ref(), network_events: network_events.as_ref() };
+        if !wait_for_next_pass(next_pass, &wakers, shutdown, &systemd, &mut config, &args) {
+            break;
+        }
+    }
+
+    systemd.stopping();
+
+    Ok(())
+}
+
+/// Open the state backend: the SQLite database with --db, otherwise the cache file
+fn open_state(
+    args: &args::Args,
+    cache_path: Option<std::path::PathBuf>,
+    state_format: state::StateFormat,
+) -> Result<Box<dyn StateStore>, Box<dyn Error>> {
+    let store: Box<dyn StateStore> = match args.db.as_deref() {
+        Some(db_path) => {
+            let mut db = state_sqlite::SqliteStateManager::open(Path::new(db_path))?;
+            // Carry over an existing text cache file the first time the database is used
+            if db.is_empty() {
+                let cache = state::StateManager::new(cache_path, state_format, args.strict_state, !args.no_lock)?;
+                let imported = db.import(&cache);
+                if imported > 0 {
+                    log::info!("Imported {} host(s) from cache file into {}", imported, db_path);
+                }
+            }
+            Box::new(db)
+        }
+        None => {
+            let mut cache = state::StateManager::new(cache_path, state_format, args.strict_state, !args.no_lock)?;
+            cache.set_backup_count(if args.no_backup { 0 } else { args.backup_count });
+            Box::new(cache)
+        }
+    };
+    Ok(store)
+}
+
+/// Events that start the next update pass early
+struct Wakers<'a> {
+    watcher: Option<&'a watch::ConfigWatcher>,
+    network_events: Option<&'a network_events::NetworkEvents>,
+}
+
+/// Wait until the next update pass is due: after `interval` in daemon mode, as soon as
+/// the config file changes with --watch (the config is reloaded first), or when an
+/// interface address changes with --network-events.
+/// Returns false once shutdown has been requested.
+fn wait_for_next_pass(
+    interval: Option<Duration>,
+    wakers: &Wakers,
+    shutdown: &daemon::Shutdown,
+    systemd: &systemd::SystemdNotifier,
+    config: &mut config::Config,
+    args: &args::Args,
+) -> bool {
+    let deadline = interval.map(|interval| Instant::now() + interval);
+
+    loop {
+        if shutdown.requested() {
+            return false;
+        }
+
+        let step = match deadline {
+            Some(deadline) if Instant::now() >= deadline => return true,
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()).min(POLL_INTERVAL),
+            None => POLL_INTERVAL,
+        };
+
+        let address_changed = match (wakers.watcher, wakers.network_events) {
+            (Some(watcher), events) => {
+                if watcher.wait_for_change(step) {
+                    watch::reload_config(config, args);
+                    return true;
+                }
+                events.is_some_and(|events| events.wait_for_change(Duration::ZERO))
+            }
+            (None, Some(events)) => events.wait_for_change(step),
+            (None, None) => {
+                shutdown.sleep(step);
+                false
+            }
+        };
+        if address_changed {
+            log::info!("Network address changed, checking the IP now");
+            return true;
+        }
+
+        systemd.watchdog();
+    }
+}
+
+/// Rate limiting settings (intervals in seconds)
+struct RateLimits {
+    min: Option<u64>,
+    max: Option<u64>,
+    min_error: Option<u64>,
+    max_retries: Option<u32>,
+}
+
+/// Outcome of one update pass, printed after the host loop
+#[derive(Default)]
+struct UpdateSummary {
+    updated: Vec<String>,
+    skipped: Vec<String>,
+    /// (hostname, error)
+    failed: Vec<(String, String)>,
+}
+
+impl UpdateSummary {
+    fn render_text(&self) -> String {
+        format!("Updated: {} host(s), Skipped: {}, Failed: {}", self.updated.len(), self.skipped.len(), self.failed.len())
+    }
+
+    fn render_json(&self) -> String {
+        serde_json::json!({
+            "updated": self.updated,
+            "skipped": self.skipped,
+            "failed": self.failed.iter()
+                .map(|(hostname, error)| serde_json::json!({"hostname": hostname, "error": error}))
+                .collect::<Vec<_>>(),
+        }).to_string()
+    }
+}
+
+/// IP detection method selected by --ip / --use and its options
+fn detection_method(args: &args::Args, config: &config::Config) -> Result<ip::IpDetectionMethod, Box<dyn Error>> {
+    let method = if let Some(ip_str) = config.ip.as_deref() {
         ip::IpDetectionMethod::Manual(ip_str.to_string())
     } else if let Some(use_method) = args.use_method.as_deref() {
         match use_method {
             "ip" => {
                 return Err("--use=ip requires --ip parameter".into());
             }
-            "web" => ip::IpDetectionMethod::Web(args.web.clone()),
+            "web" => match args.web_regex.as_deref() {
+                Some(pattern) => {
+                    let url = args.web.first()
+                        .ok_or("--web-regex requires --web parameter")?;
+                    ip::IpDetectionMethod::WebRegex { url: url.to_string(), pattern: pattern.to_string() }
+                }
+                None => ip::IpDetectionMethod::Web { urls: args.web.clone(), skip: config.web_skip.clone() },
+            },
             "if" => {
                 let iface = args.if_name.as_deref()
                     .ok_or("--use=if requires --if parameter")?;
@@ -87,75 +431,221 @@ fn main() -> Result<(), Box<dyn Error>> {
                     .ok_or("--use=cmd requires --cmd parameter")?;
                 ip::IpDetectionMethod::Command(cmd.to_string())
             }
+            "default-if" => ip::IpDetectionMethod::DefaultInterface,
+            "dhcp" => {
+                let path = args.dhcp_lease.as_ref()
+                    .ok_or("--use=dhcp requires --dhcp-lease parameter")?;
+                ip::IpDetectionMethod::DhcpLease(path.clone())
+            }
             _ => {
                 return Err(format!("Unknown IP detection method: {}", use_method).into());
             }
         }
     } else {
-        ip::IpDetectionMethod::Web(None)
+        ip::IpDetectionMethod::Web { urls: Vec::new(), skip: config.web_skip.clone() }
     };
+    Ok(method)
+}
+
+/// Run a pre/post update hook; with --ignore-hook-errors a failure is only logged, otherwise
+/// it is a configuration error
+fn run_hook(cmd: &str, env: &std::collections::HashMap<String, String>, ignore_errors: bool) -> Result<(), Box<dyn Error>> {
+    match hooks::run_hook(cmd, env) {
+        Err(e) if ignore_errors => {
+            log::warn!("{} (ignored)", e);
+            Ok(())
+        }
+        result => result.map_err(|e| RddclientError::Config(e.to_string()).into()),
+    }
+}
+
+/// Detect the current IP and update every configured host, then save state
+fn run_updates(
+    args: &args::Args,
+    config: &config::Config,
+    state_manager: &mut dyn StateStore,
+    limits: &RateLimits,
+    notifiers: &notify::Notifiers,
+    systemd: &systemd::SystemdNotifier,
+) -> Result<(), Box<dyn Error>> {
+    let protocol = config.protocol.as_ref()
+        .ok_or("Protocol not specified (use --protocol)")?;
+
+    log::info!("Starting {} DNS updater...", protocol);
+
+    let detection_method = detection_method(args, config)
+        .map_err(|e| RddclientError::Config(e.to_string()))?;
 
     // Get IP address using the chosen method
-    let ip = ip::get_ip_with_method(&detection_method)?;
-    log::info!("IP address: {} (detected via {:?})", ip, detection_method);
+    let ip = ip::get_ip_with_method(&detection_method)
+        .map_err(|e| RddclientError::IpDetection(e.to_string()))?;
+    log::info!("IP address: {} (detected via {})", ip, detection_method);
+
+    match ip::ip_classification(ip) {
+        ip::IpClassification::Public => {}
+        ip::IpClassification::Loopback => {
+            return Err(RddclientError::IpDetection(
+                format!("Detected IP {} is a loopback address; refusing to publish it", ip)
+            ).into());
+        }
+        classification => {
+            if !args.allow_private {
+                log::warn!(
+                    "Detected IP {} is a {} address and is probably not reachable from the internet (use --allow-private to silence)",
+                    ip, classification
+                );
+            }
+        }
+    }
 
     // Create the appropriate DNS client
-    let client = clients::create_client(protocol, &config)?;
-    client.validate_config()?;
+    let mut client = clients::create_client(protocol, config)
+        .and_then(|client| client.validate_config().map(|()| client))
+        .map_err(|e| RddclientError::Config(e.to_string()))?;
+    if args.dry_run {
+        client = Box::new(clients::DryRunClient::new(client));
+    }
     
     log::info!("Using provider: {}", client.provider_name());
 
-    // Update each DNS record
-    for hostname in config.dns_records() {
+    let hostnames = config.dns_records();
+    systemd.status(&format!("Updating {} hosts", hostnames.len()));
+
+    if let Some(cmd) = args.pre_update_cmd.as_deref() {
+        run_hook(cmd, &hooks::pre_update_env(&ip.to_string(), &hostnames), args.ignore_hook_errors)?;
+    }
+
+    // Work out which DNS records need an update
+    let mut summary = UpdateSummary::default();
+    let mut pending = Vec::new();
+    for hostname in &hostnames {
+        systemd.watchdog();
+
         // Check if IP has changed
-        let host_state = state_manager.get(&hostname);
+        let host_state = state_manager.get(hostname);
         let ip_changed = host_state.is_none_or(|state| state.ip_changed(ip));
         
+        // Per-host intervals from the config file take precedence over the global ones
+        let (host_min_interval, host_max_interval) = config.intervals_for(hostname);
+
         // Check rate limits
         let (should_update, skip_reason) = state_manager.should_update(
-            &hostname,
+            hostname,
             ip_changed,
             args.force,
-            min_interval,
-            max_interval,
-            min_error_interval,
+            host_min_interval.or(limits.min),
+            host_max_interval.or(limits.max),
+            limits.min_error,
+            limits.max_retries,
         );
         
         if !should_update {
             if let Some(reason) = skip_reason {
-                log::info!("{}: {}", hostname, reason);
+                log::info!(hostname = hostname.as_str(), ip:% = ip; "{}: {}", hostname, reason);
             }
+            summary.skipped.push(hostname.clone());
             continue;
         }
         
         // Log if update was forced
         if let Some(reason) = skip_reason {
-            log::info!("{}: {}", hostname, reason);
+            log::info!(hostname = hostname.as_str(), ip:% = ip; "{}: {}", hostname, reason);
         }
         
-        if test {
-            log::info!("TEST MODE: Would update {} to {}", hostname, ip);
+        if args.test {
+            log::info!(hostname = hostname.as_str(), ip:% = ip; "TEST MODE: Would update {} to {}", hostname, ip);
+            summary.skipped.push(hostname.clone());
+            continue;
+        }
+
+        // Nothing is sent, so the state is left as it was
+        if args.dry_run {
+            match client.update_record(hostname, ip) {
+                Ok(()) => summary.skipped.push(hostname.clone()),
+                Err(e) => {
+                    log::error!(hostname = hostname.as_str(), ip:% = ip; "Update of {} would fail: {}", hostname, e);
+                    summary.failed.push((hostname.clone(), e.to_string()));
+                }
+            }
             continue;
         }
 
-        match client.update_record(&hostname, ip) {
-            Ok(_) => {
-                log::info!("Successfully updated {}", hostname);
+        pending.push(hostname.clone());
+    }
+
+    // Providers with a batch API update every pending host in one request
+    let batch = if pending.len() > 1 { client.update_records(&pending, ip) } else { None };
+    let results: Vec<(String, Result<(), String>)> = match batch {
+        Some(results) => results.into_iter()
+            .map(|(hostname, result)| (hostname, result.map_err(|e| e.to_string())))
+            .collect(),
+        None => pending.iter()
+            .map(|hostname| {
+                systemd.watchdog();
+                (hostname.clone(), client.update_record(hostname, ip).map_err(|e| e.to_string()))
+            })
+            .collect(),
+    };
+
+    for (hostname, result) in results {
+        match result {
+            Ok(()) => {
+                log::info!(hostname = hostname.as_str(), ip:% = ip; "Successfully updated {}", hostname);
                 // Update state with success
                 let state = state_manager.get_mut(&hostname);
+                let old_ip = state.ip;
                 state.update_success(ip, "good".to_string());
+
+                // Notification failures must not prevent the state from being saved
+                notifiers.notify(&hostname, old_ip, ip);
+                summary.updated.push(hostname);
             }
             Err(e) => {
-                log::error!("Failed to update {}: {}", hostname, e);
+                log::error!(hostname = hostname.as_str(), ip:% = ip; "Failed to update {}: {}", hostname, e);
                 // Update state with failure
                 let state = state_manager.get_mut(&hostname);
-                state.update_failure(e.to_string());
+                state.update_failure(e.clone());
+                summary.failed.push((hostname, e));
             }
         }
     }
 
-    // Save state to cache file
-    state_manager.save()?;
+    // Drop long-expired hosts that are no longer configured before writing
+    if let Some(days) = args.state_max_age {
+        let active: Vec<&str> = hostnames.iter().map(String::as_str).collect();
+        for hostname in state_manager.prune_expired(days.saturating_mul(86400), &active) {
+            log::debug!("Pruned expired state entry for {}", hostname);
+        }
+    }
 
+    // Informational only: the updates above already count as succeeded
+    if let Some(verifier) = verify::Verifier::new(args) {
+        for hostname in &summary.updated {
+            verifier.verify(hostname, ip);
+        }
+    }
+
+    // Save state to cache file; a state error outranks update failures
+    state_manager.save().map_err(|e| RddclientError::State(e.to_string()))?;
+
+    let updated: Vec<&str> = summary.updated.iter().map(String::as_str).collect();
+    let failed: Vec<&str> = summary.failed.iter().map(|(hostname, _)| hostname.as_str()).collect();
+
+    if let Some(cmd) = args.post_update_cmd.as_deref() {
+        let env = hooks::post_update_env(&ip.to_string(), &hostnames, &updated, &failed);
+        run_hook(cmd, &env, args.ignore_hook_errors)?;
+    }
+
+    if args.json {
+        println!("{}", summary.render_json());
+    } else if !args.quiet {
+        println!("{}", summary.render_text());
+    }
+
+    if !failed.is_empty() {
+        return Err(RddclientError::Provider(format!(
+            "{} of {} host(s) failed to update: {}", failed.len(), hostnames.len(), failed.join(", ")
+        )).into());
+    }
     Ok(())
 }
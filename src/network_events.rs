@@ -0,0 +1,183 @@
+/// Address change notifications for daemon mode (`--network-events`, Linux only)
+///
+/// A background thread listens on a NETLINK_ROUTE socket subscribed to the IPv4 and IPv6
+/// address groups and signals the main loop whenever an address is added or removed, so
+/// a new IP is picked up without waiting for the next scheduled pass.
+use std::error::Error;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// Quiet period used to coalesce the burst of messages one address change produces
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub struct NetworkEvents {
+    events: Receiver<()>,
+}
+
+impl NetworkEvents {
+    /// Start listening for address changes, on `interface` only when given
+    #[cfg(target_os = "linux")]
+    pub fn start(interface: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let index = interface.map(interface_index).transpose()?;
+        let mut socket = netlink_sys::Socket::new(netlink_sys::protocols::NETLINK_ROUTE)?;
+        socket.bind(&netlink_sys::SocketAddr::new(0, RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR))
+            .map_err(|e| format!("Failed to subscribe to netlink address events: {}", e))?;
+
+        log::debug!("Listening for address changes on {}", interface.unwrap_or("all interfaces"));
+        Ok(Self::listen(socket, index))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn start(_interface: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        Err("--network-events is only supported on Linux".into())
+    }
+
+    /// Read datagrams from `source` in a background thread until it fails
+    #[cfg(target_os = "linux")]
+    fn listen(mut source: impl EventSource + Send + 'static, index: Option<u32>) -> Self {
+        let (tx, events) = std::sync::mpsc::channel();
+        std::thread::spawn(move || loop {
+            match source.recv() {
+                Ok(datagram) if address_changed(&datagram, index) => {
+                    if tx.send(()).is_err() {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!("Stopped listening for network events: {}", e);
+                    return;
+                }
+            }
+        });
+        Self { events }
+    }
+
+    /// Block for up to `timeout` until an address changes
+    /// Returns true if a change was seen
+    pub fn wait_for_change(&self, timeout: Duration) -> bool {
+        if self.events.recv_timeout(timeout).is_err() {
+            return false;
+        }
+        // Drain the rest of the burst so one change triggers one re-check
+        while self.events.recv_timeout(DEBOUNCE).is_ok() {}
+        true
+    }
+}
+
+/// Multicast groups for IPv4 and IPv6 address changes (linux/rtnetlink.h)
+#[cfg(target_os = "linux")]
+const RTMGRP_IPV4_IFADDR: u32 = 0x10;
+#[cfg(target_os = "linux")]
+const RTMGRP_IPV6_IFADDR: u32 = 0x100;
+
+/// Source of netlink datagrams; a trait so events can be tested without a kernel socket
+#[cfg(target_os = "linux")]
+trait EventSource {
+    fn recv(&mut self) -> Result<Vec<u8>, Box<dyn Error>>;
+}
+
+#[cfg(target_os = "linux")]
+impl EventSource for netlink_sys::Socket {
+    fn recv(&mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.recv_from_full()?.0)
+    }
+}
+
+/// Kernel index of a network interface
+#[cfg(target_os = "linux")]
+fn interface_index(interface: &str) -> Result<u32, Box<dyn Error>> {
+    let path = format!("/sys/class/net/{}/ifindex", interface);
+    let index = std::fs::read_to_string(&path)
+        .map_err(|_| format!("No such interface: {}", interface))?;
+    Ok(index.trim().parse()?)
+}
+
+/// Whether a datagram holds an RTM_NEWADDR or RTM_DELADDR message (for interface `index`)
+#[cfg(target_os = "linux")]
+fn address_changed(datagram: &[u8], index: Option<u32>) -> bool {
+    use netlink_packet_core::{NetlinkMessage, NetlinkPayload};
+    use netlink_packet_route::RouteNetlinkMessage;
+
+    let mut offset = 0;
+    while offset < datagram.len() {
+        let Ok(message) = NetlinkMessage::<RouteNetlinkMessage>::deserialize(&datagram[offset..]) else {
+            return false;
+        };
+        if let NetlinkPayload::InnerMessage(
+            RouteNetlinkMessage::NewAddress(address) | RouteNetlinkMessage::DelAddress(address),
+        ) = &message.payload
+        {
+            if index.is_none_or(|index| address.header.index == index) {
+                return true;
+            }
+        }
+        if message.header.length == 0 {
+            break;
+        }
+        offset += message.header.length as usize;
+    }
+    false
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use netlink_packet_core::{NetlinkMessage, NetlinkPayload};
+    use netlink_packet_route::address::{AddressAttribute, AddressMessage};
+    use netlink_packet_route::link::LinkMessage;
+    use netlink_packet_route::RouteNetlinkMessage;
+    use std::collections::VecDeque;
+
+    /// Replays canned datagrams, then fails like a closed socket
+    struct MockSocket {
+        datagrams: VecDeque<Vec<u8>>,
+    }
+
+    impl EventSource for MockSocket {
+        fn recv(&mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+            self.datagrams.pop_front().ok_or_else(|| "socket closed".into())
+        }
+    }
+
+    fn datagram(message: RouteNetlinkMessage) -> Vec<u8> {
+        let mut message = NetlinkMessage::new(Default::default(), NetlinkPayload::InnerMessage(message));
+        message.finalize();
+        let mut buf = vec![0; message.buffer_len()];
+        message.serialize(&mut buf);
+        buf
+    }
+
+    fn new_address(index: u32, ip: &str) -> Vec<u8> {
+        let mut address = AddressMessage::default();
+        address.header.index = index;
+        address.attributes.push(AddressAttribute::Local(ip.parse().unwrap()));
+        datagram(RouteNetlinkMessage::NewAddress(address))
+    }
+
+    #[test]
+    fn test_address_changed() {
+        assert!(address_changed(&new_address(2, "203.0.113.1"), None));
+        assert!(address_changed(&new_address(2, "203.0.113.1"), Some(2)));
+        assert!(!address_changed(&new_address(3, "203.0.113.1"), Some(2)));
+
+        let mut removed = AddressMessage::default();
+        removed.header.index = 2;
+        assert!(address_changed(&datagram(RouteNetlinkMessage::DelAddress(removed)), Some(2)));
+
+        assert!(!address_changed(&datagram(RouteNetlinkMessage::NewLink(LinkMessage::default())), None));
+        assert!(!address_changed(b"garbage", None));
+    }
+
+    #[test]
+    fn test_mock_socket_event_signals_main_loop() {
+        let socket = MockSocket {
+            datagrams: VecDeque::from(vec![new_address(3, "198.51.100.1"), new_address(2, "2001:db8::1")]),
+        };
+        let events = NetworkEvents::listen(socket, Some(2));
+
+        assert!(events.wait_for_change(Duration::from_secs(5)));
+        // The single relevant message was consumed; the source is closed now
+        assert!(!events.wait_for_change(Duration::from_millis(50)));
+    }
+}
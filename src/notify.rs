@@ -0,0 +1,197 @@
+/// Notifications sent after successful DNS updates
+///
+/// Notification failures are logged but never abort an update run,
+/// so state is always saved regardless of whether a notification was delivered.
+use crate::args::Args;
+use serde_json::json;
+use std::error::Error;
+use std::net::IpAddr;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub mod ntfy;
+
+/// Number of delivery attempts before giving up
+const MAX_ATTEMPTS: u32 = 3;
+
+/// The notification channels enabled on the command line
+pub struct Notifiers {
+    webhook: Option<WebhookNotifier>,
+    ntfy: Option<ntfy::NtfyNotifier>,
+}
+
+impl Notifiers {
+    pub fn new(args: &Args) -> Self {
+        Self {
+            webhook: args.webhook.as_deref().map(WebhookNotifier::new),
+            ntfy: args.ntfy_topic.as_deref()
+                .map(|topic| ntfy::NtfyNotifier::new(&args.ntfy_server, topic, args.ntfy_token.as_deref())),
+        }
+    }
+
+    /// Notify every enabled channel; failures are logged, never returned
+    pub fn notify(&self, hostname: &str, old_ip: Option<IpAddr>, new_ip: IpAddr) {
+        if let Some(webhook) = &self.webhook {
+            if let Err(e) = webhook.notify(hostname, old_ip, new_ip) {
+                log::warn!("{}", e);
+            }
+        }
+        if let Some(ntfy) = &self.ntfy {
+            if let Err(e) = ntfy.notify(hostname, old_ip, new_ip) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+}
+
+/// Posts a JSON payload to a user-supplied URL after each successful update
+pub struct WebhookNotifier {
+    url: String,
+    /// Delay before the first retry, doubled on each subsequent retry
+    retry_delay: Duration,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+
+    /// Send an update notification, retrying with exponential backoff on failure
+    pub fn notify(&self, hostname: &str, old_ip: Option<IpAddr>, new_ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let payload = json!({
+            "hostname": hostname,
+            "old_ip": old_ip.map(|ip| ip.to_string()),
+            "new_ip": new_ip.to_string(),
+            "timestamp": timestamp,
+        });
+
+        let mut delay = self.retry_delay;
+        let mut last_error = String::new();
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            match self.send(&payload) {
+                Ok(()) => {
+                    log::debug!("Webhook notification for {} delivered to {}", hostname, self.url);
+                    return Ok(());
+                }
+                Err(e) => {
+                    log::debug!("Webhook attempt {}/{} failed: {}", attempt, MAX_ATTEMPTS, e);
+                    last_error = e.to_string();
+                }
+            }
+
+            if attempt < MAX_ATTEMPTS {
+                thread::sleep(delay);
+                delay *= 2;
+            }
+        }
+
+        Err(format!("Webhook to {} failed after {} attempts: {}", self.url, MAX_ATTEMPTS, last_error).into())
+    }
+
+    fn send(&self, payload: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+        let response = minreq::post(&self.url)
+            .with_json(payload)?
+            .with_timeout(10)
+            .send()?;
+
+        if (200..300).contains(&response.status_code) {
+            Ok(())
+        } else {
+            Err(format!("HTTP {}", response.status_code).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn test_notifier(url: &str) -> WebhookNotifier {
+        WebhookNotifier {
+            url: url.to_string(),
+            retry_delay: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/hook")
+            .match_header("content-type", Matcher::Regex("^application/json".to_string()))
+            .match_body(Matcher::AllOf(vec![
+                Matcher::PartialJsonString(r#"{"hostname":"home.example.com","old_ip":"192.0.2.1","new_ip":"203.0.113.1"}"#.to_string()),
+                Matcher::Regex(r#""timestamp":\d+"#.to_string()),
+            ]))
+            .with_status(200)
+            .create();
+
+        let notifier = test_notifier(&format!("{}/hook", server.url()));
+        let result = notifier.notify(
+            "home.example.com",
+            Some("192.0.2.1".parse().unwrap()),
+            "203.0.113.1".parse().unwrap(),
+        );
+
+        assert!(result.is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_webhook_payload_without_old_ip() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/hook")
+            .match_body(Matcher::PartialJsonString(r#"{"old_ip":null,"new_ip":"2001:db8::1"}"#.to_string()))
+            .with_status(204)
+            .create();
+
+        let notifier = test_notifier(&format!("{}/hook", server.url()));
+        assert!(notifier.notify("home.example.com", None, "2001:db8::1".parse().unwrap()).is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_webhook_retries_then_fails() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/hook")
+            .with_status(500)
+            .expect(3)
+            .create();
+
+        let notifier = test_notifier(&format!("{}/hook", server.url()));
+        let result = notifier.notify("home.example.com", None, "203.0.113.1".parse().unwrap());
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("after 3 attempts"));
+        mock.assert();
+    }
+
+    #[test]
+    fn test_webhook_retry_succeeds() {
+        let mut server = mockito::Server::new();
+        // Mocks still missing their expected hits are matched first
+        let failing = server.mock("POST", "/hook")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let ok = server.mock("POST", "/hook")
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let notifier = test_notifier(&format!("{}/hook", server.url()));
+        assert!(notifier.notify("home.example.com", None, "203.0.113.1".parse().unwrap()).is_ok());
+
+        failing.assert();
+        ok.assert();
+    }
+}
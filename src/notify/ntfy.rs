@@ -0,0 +1,99 @@
+/// ntfy push notifications (https://ntfy.sh or a self-hosted server)
+use std::error::Error;
+use std::net::IpAddr;
+
+/// Publishes a plain-text message to an ntfy topic after each successful update
+pub struct NtfyNotifier {
+    url: String,
+    token: Option<String>,
+}
+
+impl NtfyNotifier {
+    pub fn new(server: &str, topic: &str, token: Option<&str>) -> Self {
+        Self {
+            url: format!("{}/{}", server.trim_end_matches('/'), topic),
+            token: token.map(str::to_string),
+        }
+    }
+
+    fn message(hostname: &str, old_ip: Option<IpAddr>, new_ip: IpAddr) -> String {
+        match old_ip {
+            Some(old_ip) => format!("{} updated to {} (was {})", hostname, new_ip, old_ip),
+            None => format!("{} updated to {}", hostname, new_ip),
+        }
+    }
+
+    /// Publish an update notification
+    pub fn notify(&self, hostname: &str, old_ip: Option<IpAddr>, new_ip: IpAddr) -> Result<(), Box<dyn Error>> {
+        let mut request = minreq::post(&self.url)
+            .with_header("Priority", "3")
+            .with_header("Tags", "arrows_counterclockwise")
+            .with_body(Self::message(hostname, old_ip, new_ip))
+            .with_timeout(10);
+        if let Some(token) = &self.token {
+            request = request.with_header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send()?;
+        if (200..300).contains(&response.status_code) {
+            log::debug!("ntfy notification for {} published to {}", hostname, self.url);
+            Ok(())
+        } else {
+            Err(format!("ntfy publish to {} failed: HTTP {}", self.url, response.status_code).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    #[test]
+    fn test_ntfy_message_and_headers() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/rddclient-alerts")
+            .match_header("priority", "3")
+            .match_header("tags", "arrows_counterclockwise")
+            .match_header("authorization", Matcher::Missing)
+            .match_body("home.example.com updated to 203.0.113.1 (was 192.0.2.1)")
+            .with_status(200)
+            .create();
+
+        let notifier = NtfyNotifier::new(&server.url(), "rddclient-alerts", None);
+        let result = notifier.notify(
+            "home.example.com",
+            Some("192.0.2.1".parse().unwrap()),
+            "203.0.113.1".parse().unwrap(),
+        );
+
+        assert!(result.is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_ntfy_token_and_first_update() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/alerts")
+            .match_header("authorization", "Bearer tk_secret")
+            .match_body("home.example.com updated to 2001:db8::1")
+            .with_status(200)
+            .create();
+
+        let notifier = NtfyNotifier::new(&format!("{}/", server.url()), "alerts", Some("tk_secret"));
+        assert!(notifier.notify("home.example.com", None, "2001:db8::1".parse().unwrap()).is_ok());
+        mock.assert();
+    }
+
+    #[test]
+    fn test_ntfy_error_status() {
+        let mut server = mockito::Server::new();
+        let _mock = server.mock("POST", "/alerts")
+            .with_status(403)
+            .create();
+
+        let notifier = NtfyNotifier::new(&server.url(), "alerts", None);
+        let result = notifier.notify("home.example.com", None, "203.0.113.1".parse().unwrap());
+        assert!(result.unwrap_err().to_string().contains("HTTP 403"));
+    }
+}
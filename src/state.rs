@@ -9,17 +9,52 @@
 /// - Linux/macOS: /var/cache/rddclient/<host>.cache or ~/.cache/rddclient/<host>.cache
 /// - Windows: %LOCALAPPDATA%\rddclient\cache\<host>.cache
 ///
-/// Format is ddclient-compatible: simple key=value pairs per hostname
+/// Format is ddclient-compatible by default: simple key=value pairs per hostname.
+/// A JSON format is also available; the format is auto-detected on load.
+/// ddclient-format files end with a `## sha256: <hex>` line used to detect corruption.
+/// Files written by this version carry a `## rddclient-state-version=2` header; files
+/// without one (version 1, including ddclient's own cache) are migrated when loaded.
+/// Reads take a shared and writes an exclusive advisory lock on the file, so concurrent
+/// instances don't interleave their writes. The previous file is kept as `<path>.bak`
+/// (plus `.bak.1`, `.bak.2`, ... with --backup-count) and restored if a save fails.
+use aws_lc_rs::digest;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
-use std::io::Write;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::net::IpAddr;
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// On-disk format of the state file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StateFormat {
+    /// ddclient-compatible `key=value,... hostname` lines
+    #[default]
+    DdClient,
+    /// JSON object mapping hostname to state
+    Json,
+}
+
+impl FromStr for StateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ddclient" => Ok(StateFormat::DdClient),
+            "json" => Ok(StateFormat::Json),
+            _ => Err(format!("Unknown state format: {}. Use ddclient or json", s)),
+        }
+    }
+}
 
 /// State for a single host
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct HostState {
     /// Last known IP address
     pub ip: Option<IpAddr>,
@@ -27,24 +62,43 @@ pub struct HostState {
     /// Last successful update timestamp (Unix epoch seconds)
     pub mtime: Option<u64>,
     
-    /// Last update status (e.g., "good", "nochg", error message)
+    /// Last update status (e.g., "good", "nochg", or "FAILED")
     pub status: Option<String>,
+
+    /// Error message of the last update, if it failed
+    pub last_error: Option<String>,
     
     /// Number of consecutive update failures
     pub atime: Option<u64>,  // ddclient calls this "atime" (access time for error tracking)
     
     /// Warning counter
     pub wtime: Option<u64>,  // ddclient uses this for warning tracking
+
+    /// Number of consecutive update failures, used for exponential backoff
+    pub retry_count: u32,
+
+    /// Set once `retry_count` reaches --max-retries; the host is skipped until re-enabled
+    pub permanent_failure: bool,
+
+    /// Past IP changes as (timestamp, IP), newest first, at most `HISTORY_LIMIT` entries
+    pub ip_history: Vec<(u64, IpAddr)>,
 }
 
+/// Number of IP changes kept per host
+pub const HISTORY_LIMIT: usize = 10;
+
 impl HostState {
     pub fn new() -> Self {
         Self {
             ip: None,
             mtime: None,
             status: None,
+            last_error: None,
             atime: None,
             wtime: None,
+            retry_count: 0,
+            permanent_failure: false,
+            ip_history: Vec::new(),
         }
     }
     
@@ -58,34 +112,187 @@ impl HostState {
     
     /// Update state after successful DNS update
     pub fn update_success(&mut self, ip: IpAddr, status: String) {
+        let now = current_timestamp();
+        if self.ip_changed(ip) {
+            self.ip_history.insert(0, (now, ip));
+            self.ip_history.truncate(HISTORY_LIMIT);
+        }
         self.ip = Some(ip);
-        self.mtime = Some(current_timestamp());
+        self.mtime = Some(now);
         self.status = Some(status);
+        self.last_error = None;
         self.atime = None;  // Reset error counter on success
+        self.retry_count = 0;
+        self.permanent_failure = false;
     }
     
     /// Update state after failed DNS update
     pub fn update_failure(&mut self, error: String) {
-        self.status = Some(format!("FAILED: {}", error));
+        self.status = Some("FAILED".to_string());
+        self.last_error = Some(error);
         self.atime = Some(current_timestamp());
+        self.retry_count = self.retry_count.saturating_add(1);
+    }
+
+    /// Split a combined `FAILED: <error>` status written by older versions
+    pub fn split_legacy_status(&mut self) {
+        if self.last_error.is_some() {
+            return;
+        }
+        if let Some(error) = self.status.as_deref().and_then(|s| s.strip_prefix("FAILED: ")) {
+            self.last_error = Some(error.to_string());
+            self.status = Some("FAILED".to_string());
+        }
+    }
+
+    /// Clear a permanent failure so the host is retried (--reenable)
+    pub fn reenable(&mut self) {
+        self.permanent_failure = false;
+        self.retry_count = 0;
     }
 }
 
-impl Default for HostState {
-    fn default() -> Self {
-        Self::new()
+/// Interface shared by the state backends (text cache file and SQLite database)
+pub trait StateStore {
+    /// Get state for a specific host
+    fn get(&self, hostname: &str) -> Option<&HostState>;
+
+    /// Get mutable state for a specific host (creates if doesn't exist)
+    fn get_mut(&mut self, hostname: &str) -> &mut HostState;
+
+    /// Iterate over all tracked hosts, sorted by hostname
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, &HostState)> + '_>;
+
+    /// Persist all host states
+    fn save(&self) -> Result<(), Box<dyn Error>>;
+
+    /// Drop hosts not in `active_hosts` whose last update (mtime) is more than
+    /// `max_age_secs` old. Returns the pruned hostnames.
+    fn prune_expired(&mut self, max_age_secs: u64, active_hosts: &[&str]) -> Vec<String>;
+
+    /// Check if an update should be allowed based on rate limits
+    /// Returns (should_update, reason_if_skipped)
+    /// A host that has failed `max_retries` times in a row is marked as permanently failed.
+    #[allow(clippy::too_many_arguments)]
+    fn should_update(
+        &mut self,
+        hostname: &str,
+        ip_changed: bool,
+        force: bool,
+        min_interval: Option<u64>,      // seconds
+        max_interval: Option<u64>,      // seconds
+        min_error_interval: Option<u64>, // seconds
+        max_retries: Option<u32>,
+    ) -> (bool, Option<String>) {
+        // Always allow if force flag is set
+        if force {
+            return (true, None);
+        }
+
+        if let Some(state) = self.get(hostname) {
+            let exceeded = max_retries.is_some_and(|max| state.retry_count >= max);
+            if state.permanent_failure || exceeded {
+                self.get_mut(hostname).permanent_failure = true;
+                return (false, Some("max retries exceeded, host disabled".to_string()));
+            }
+        }
+
+        let state = self.get(hostname);
+        if state.is_none() {
+            // No previous state, allow update
+            return (true, None);
+        }
+
+        let state = state.unwrap();
+        let now = current_timestamp();
+
+        // Check max-interval: Force update if too much time has passed since last successful update
+        if let (Some(mtime), Some(max_int)) = (state.mtime, max_interval) {
+            if now >= mtime + max_int {
+                let days = max_int / 86400;
+                return (true, Some(format!(
+                    "update forced because it has been {} days since the previous update",
+                    days
+                )));
+            }
+        }
+
+        // If IP hasn't changed and we're not being forced, no need to update
+        if !ip_changed {
+            return (false, Some("IP address hasn't changed".to_string()));
+        }
+
+        // IP has changed - check min-interval for successful updates
+        if let Some(status) = &state.status {
+            if status.starts_with("good") || status.starts_with("nochg") {
+                // Last update was successful, check min-interval
+                if let (Some(mtime), Some(min_int)) = (state.mtime, min_interval) {
+                    if now < mtime + min_int {
+                        let remaining = (mtime + min_int) - now;
+                        return (false, Some(format!(
+                            "skipped update due to min-interval ({}s remaining)",
+                            remaining
+                        )));
+                    }
+                }
+            } else {
+                // Last update failed, check min-error-interval with exponential backoff
+                if let (Some(atime), Some(min_err_int)) = (state.atime, min_error_interval) {
+                    let jitter = if min_err_int >= 2 {
+                        use rand::RngExt;
+                        rand::rng().random_range(0..min_err_int / 2)
+                    } else {
+                        0
+                    };
+                    let delay = error_backoff(min_err_int, state.retry_count, max_interval, jitter);
+                    if now < atime + delay {
+                        let remaining = (atime + delay) - now;
+                        return (false, Some(format!(
+                            "skipped update due to min-error-interval ({}s remaining after previous failure)",
+                            remaining
+                        )));
+                    }
+                }
+            }
+        }
+
+        // All checks passed, allow update
+        (true, None)
     }
 }
 
 /// State manager - handles reading/writing cache file
 pub struct StateManager {
     cache_file: PathBuf,
+    format: StateFormat,
     states: HashMap<String, HostState>,
+    /// Fail to load on a checksum mismatch instead of warning (--strict-state)
+    strict: bool,
+    /// Lock the file during I/O (disabled with --no-lock)
+    lock: bool,
+    /// How long to wait for another process to release the lock
+    lock_timeout: Duration,
+    /// Format version of the loaded file (`STATE_VERSION` until a file is loaded)
+    version: u32,
+    /// Number of backups kept when saving (0 disables backups)
+    backup_count: u32,
 }
 
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Prefix of the checksum line at the bottom of a ddclient-format state file
+const CHECKSUM_PREFIX: &str = "## sha256: ";
+
+/// Current ddclient-format state file version; version 1 files have no header and lack
+/// `retry_count` and `permanent_failure`
+const STATE_VERSION: u32 = 2;
+const VERSION_PREFIX: &str = "## rddclient-state-version=";
+
 impl StateManager {
-    /// Create new state manager with cache file path
-    pub fn new(cache_file: Option<PathBuf>) -> Result<Self, Box<dyn Error>> {
+    /// Create new state manager with cache file path and the format used when saving.
+    /// With `strict`, a checksum mismatch when loading is an error rather than a warning;
+    /// with `lock`, the file is locked while it is read or written.
+    pub fn new(cache_file: Option<PathBuf>, format: StateFormat, strict: bool, lock: bool) -> Result<Self, Box<dyn Error>> {
         let cache_file = match cache_file {
             Some(path) => path,
             None => Self::default_cache_path()?,
@@ -93,7 +300,13 @@ impl StateManager {
         
         let mut manager = Self {
             cache_file,
+            format,
             states: HashMap::new(),
+            strict,
+            lock,
+            lock_timeout: LOCK_TIMEOUT,
+            version: STATE_VERSION,
+            backup_count: 1,
         };
         
         // Try to load existing state
@@ -104,8 +317,27 @@ impl StateManager {
         Ok(manager)
     }
     
+    /// Read an existing ddclient cache file (e.g. /var/cache/ddclient/ddclient.cache)
+    pub fn from_ddclient_file(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read ddclient cache {}: {}", path.display(), e))?;
+
+        let mut manager = Self {
+            cache_file: path,
+            format: StateFormat::DdClient,
+            states: HashMap::new(),
+            strict: false,
+            lock: false,
+            lock_timeout: LOCK_TIMEOUT,
+            version: STATE_VERSION,
+            backup_count: 0,
+        };
+        manager.load_ddclient(&content)?;
+        Ok(manager)
+    }
+
     /// Get default cache file path based on platform
-    fn default_cache_path() -> Result<PathBuf, Box<dyn Error>> {
+    pub fn default_cache_path() -> Result<PathBuf, Box<dyn Error>> {
         #[cfg(target_os = "linux")]
         {
             // Try /var/cache/rddclient first, fall back to user cache
@@ -155,20 +387,153 @@ impl StateManager {
         Err("Failed to determine cache file location".into())
     }
     
-    /// Get state for a specific host
-    pub fn get(&self, hostname: &str) -> Option<&HostState> {
-        self.states.get(hostname)
+    /// Keep `count` backups of the previous state file on each save (--backup-count, --no-backup)
+    pub fn set_backup_count(&mut self, count: u32) {
+        self.backup_count = count;
     }
-    
-    /// Get mutable state for a specific host (creates if doesn't exist)
-    pub fn get_mut(&mut self, hostname: &str) -> &mut HostState {
-        self.states.entry(hostname.to_string()).or_default()
+
+    /// Path of the `index`th backup: `<path>.bak`, then `<path>.bak.1`, `<path>.bak.2`, ...
+    fn backup_path(&self, index: u32) -> PathBuf {
+        let mut path = self.cache_file.clone().into_os_string();
+        path.push(".bak");
+        if index > 0 {
+            path.push(format!(".{}", index));
+        }
+        PathBuf::from(path)
     }
-    
-    /// Load state from cache file (ddclient format)
+
+    /// Rotate the existing backups and copy the current state file to `.bak`.
+    /// Returns the backup path, or None when there was nothing to back up.
+    fn backup(&self) -> Result<Option<PathBuf>, Box<dyn Error>> {
+        if self.backup_count == 0 || fs::metadata(&self.cache_file).map(|m| m.len()).unwrap_or(0) == 0 {
+            return Ok(None);
+        }
+
+        for index in (1..self.backup_count).rev() {
+            let older = self.backup_path(index - 1);
+            if older.exists() {
+                fs::rename(&older, self.backup_path(index))?;
+            }
+        }
+        let backup = self.backup_path(0);
+        fs::copy(&self.cache_file, &backup)
+            .map_err(|e| format!("Failed to back up state file to {}: {}", backup.display(), e))?;
+        Ok(Some(backup))
+    }
+
+    /// Back up the state file, then truncate it and run `write` with the exclusive lock held.
+    /// If `write` fails, the file's previous contents are put back from the backup.
+    fn write_with_backup(
+        &self,
+        write: impl FnOnce(&mut fs::File) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut file = self.open_locked(true)?;
+        // Backed up under the lock, so no other writer can change the file in between
+        let backup = self.backup()?;
+
+        file.set_len(0)?;
+        let Err(e) = write(&mut file) else {
+            return Ok(());
+        };
+
+        if let Some(backup) = backup {
+            match restore_from(&mut file, &backup) {
+                Ok(()) => log::warn!("Saving state failed; restored {} from {}", self.cache_file.display(), backup.display()),
+                Err(restore_err) => log::error!("Failed to restore state file from {}: {}", backup.display(), restore_err),
+            }
+        }
+        Err(e)
+    }
+
+    /// Load state from cache file, auto-detecting JSON or ddclient format
     pub fn load(&mut self) -> Result<(), Box<dyn Error>> {
-        let content = fs::read_to_string(&self.cache_file)?;
-        
+        let mut content = String::new();
+        self.open_locked(false)?.read_to_string(&mut content)?;
+
+        if content.trim_start().starts_with('{') {
+            self.load_json(&content)
+        } else {
+            self.verify_checksum(&content)?;
+            self.load_ddclient(&content)
+        }
+    }
+
+    /// Open the state file for reading and writing, holding a shared or exclusive lock
+    /// until the handle is dropped. Gives up after `lock_timeout`.
+    fn open_locked(&self, exclusive: bool) -> Result<fs::File, Box<dyn Error>> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.cache_file)?;
+        if !self.lock {
+            return Ok(file);
+        }
+
+        let deadline = Instant::now() + self.lock_timeout;
+        loop {
+            // Qualified calls: std's inherent File locking methods shadow the fs2 ones
+            let locked = if exclusive {
+                FileExt::try_lock_exclusive(&file)
+            } else {
+                FileExt::try_lock_shared(&file)
+            };
+            match locked {
+                Ok(()) => return Ok(file),
+                Err(e) if e.kind() != fs2::lock_contended_error().kind() => return Err(e.into()),
+                Err(_) if Instant::now() >= deadline => {
+                    return Err("state file locked by another process".into());
+                }
+                Err(_) => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    }
+
+    /// Compare the `## sha256:` line (if any) against the file's content lines
+    fn verify_checksum(&self, content: &str) -> Result<(), Box<dyn Error>> {
+        let Some(expected) = content.lines().rev().find_map(|line| line.trim().strip_prefix(CHECKSUM_PREFIX)) else {
+            return Ok(());
+        };
+
+        let lines: Vec<&str> = content.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("##"))
+            .collect();
+        if compute_state_checksum(&lines) == expected.trim() {
+            return Ok(());
+        }
+
+        if self.strict {
+            return Err(format!("state file {} checksum mismatch; file may be corrupted", self.cache_file.display()).into());
+        }
+        log::warn!("state file checksum mismatch; file may be corrupted ({})", self.cache_file.display());
+        Ok(())
+    }
+
+    /// Load state from JSON content
+    fn load_json(&mut self, content: &str) -> Result<(), Box<dyn Error>> {
+        let states: HashMap<String, HostState> = serde_json::from_str(content)
+            .map_err(|e| format!("Invalid JSON state file {}: {}", self.cache_file.display(), e))?;
+        self.states.extend(states.into_iter().map(|(hostname, mut state)| {
+            state.split_legacy_status();
+            (hostname, state)
+        }));
+        Ok(())
+    }
+
+    /// Load state from ddclient format content
+    fn load_ddclient(&mut self, content: &str) -> Result<(), Box<dyn Error>> {
+        self.version = state_version(content);
+        let migrated;
+        let content = if self.version < STATE_VERSION {
+            log::debug!("Migrating version {} state file {}", self.version, self.cache_file.display());
+            migrated = migrate_state_v1_to_v2(content);
+            migrated.as_str()
+        } else {
+            content
+        };
+
         for line in content.lines() {
             let line = line.trim();
             
@@ -210,6 +575,9 @@ impl StateManager {
                             "status" => {
                                 state.status = Some(value.to_string());
                             }
+                            "error" => {
+                                state.last_error = Some(unescape_value(value));
+                            }
                             "atime" => {
                                 if let Ok(timestamp) = value.parse::<u64>() {
                                     state.atime = Some(timestamp);
@@ -220,11 +588,23 @@ impl StateManager {
                                     state.wtime = Some(timestamp);
                                 }
                             }
+                            "retry_count" => {
+                                if let Ok(count) = value.parse::<u32>() {
+                                    state.retry_count = count;
+                                }
+                            }
+                            "permanent_failure" => {
+                                state.permanent_failure = value == "1";
+                            }
+                            "history" => {
+                                state.ip_history = parse_history(value);
+                            }
                             _ => {}  // Ignore unknown keys
                         }
                     }
                 }
-                
+
+                state.split_legacy_status();
                 self.states.insert(hostname.to_string(), state);
             }
         }
@@ -232,22 +612,16 @@ impl StateManager {
         Ok(())
     }
     
-    /// Save state to cache file (ddclient format)
-    pub fn save(&self) -> Result<(), Box<dyn Error>> {
-        // Create parent directory if it doesn't exist
-        if let Some(parent) = self.cache_file.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        
-        let mut file = fs::File::create(&self.cache_file)?;
-        
-        // Write header
-        writeln!(file, "## rddclient cache file")?;
-        writeln!(file, "## last updated at {} ({})", 
-                 format_timestamp(current_timestamp()), 
-                 current_timestamp())?;
-        
-        // Write each host state
+    /// Save state as JSON
+    fn save_json(&self, file: &mut fs::File) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(&self.states)?;
+        file.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Save state in ddclient format
+    fn save_ddclient(&self, file: &mut fs::File) -> Result<(), Box<dyn Error>> {
+        let mut lines = Vec::new();
         for (hostname, state) in &self.states {
             let mut parts = Vec::new();
             
@@ -260,102 +634,289 @@ impl StateManager {
             if let Some(status) = &state.status {
                 parts.push(format!("status={}", status));
             }
+            if let Some(error) = &state.last_error {
+                parts.push(format!("error={}", escape_value(error)));
+            }
             if let Some(atime) = state.atime {
                 parts.push(format!("atime={}", atime));
             }
             if let Some(wtime) = state.wtime {
                 parts.push(format!("wtime={}", wtime));
             }
+            if state.retry_count > 0 {
+                parts.push(format!("retry_count={}", state.retry_count));
+            }
+            if state.permanent_failure {
+                parts.push("permanent_failure=1".to_string());
+            }
+            if !state.ip_history.is_empty() {
+                parts.push(format!("history={}", format_history(&state.ip_history)));
+            }
             
             if !parts.is_empty() {
-                writeln!(file, "{} {}", parts.join(","), hostname)?;
+                lines.push(format!("{} {}", parts.join(","), hostname));
             }
         }
+
+        // Write header
+        writeln!(file, "## rddclient cache file")?;
+        writeln!(file, "{}{}", VERSION_PREFIX, STATE_VERSION)?;
+        writeln!(file, "## last updated at {} ({})", 
+                 format_timestamp(current_timestamp()), 
+                 current_timestamp())?;
+
+        // Write each host state, then the checksum over them
+        for line in &lines {
+            writeln!(file, "{}", line)?;
+        }
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        writeln!(file, "{}{}", CHECKSUM_PREFIX, compute_state_checksum(&lines))?;
         
         Ok(())
     }
+}
 
-    /// Check if an update should be allowed based on rate limits
-    /// Returns (should_update, reason_if_skipped)
-    pub fn should_update(
-        &self,
-        hostname: &str,
-        ip_changed: bool,
-        force: bool,
-        min_interval: Option<u64>,      // seconds
-        max_interval: Option<u64>,      // seconds
-        min_error_interval: Option<u64>, // seconds
-    ) -> (bool, Option<String>) {
-        // Always allow if force flag is set
-        if force {
-            return (true, None);
+impl StateStore for StateManager {
+    /// Get state for a specific host
+    fn get(&self, hostname: &str) -> Option<&HostState> {
+        self.states.get(hostname)
+    }
+    
+    /// Iterate over all tracked hosts, sorted by hostname
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, &HostState)> + '_> {
+        let mut entries: Vec<(&str, &HostState)> = self.states
+            .iter()
+            .map(|(hostname, state)| (hostname.as_str(), state))
+            .collect();
+        entries.sort_by_key(|(hostname, _)| *hostname);
+        Box::new(entries.into_iter())
+    }
+
+    /// Get mutable state for a specific host (creates if doesn't exist)
+    fn get_mut(&mut self, hostname: &str) -> &mut HostState {
+        self.states.entry(hostname.to_string()).or_default()
+    }
+    
+    /// Save state to cache file in the configured format
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        // Create parent directory if it doesn't exist
+        if let Some(parent) = self.cache_file.parent() {
+            fs::create_dir_all(parent)?;
         }
 
-        let state = self.states.get(hostname);
-        if state.is_none() {
-            // No previous state, allow update
-            return (true, None);
+        self.write_with_backup(|file| match self.format {
+            StateFormat::DdClient => self.save_ddclient(file),
+            StateFormat::Json => self.save_json(file),
+        })
+    }
+
+    /// Remove expired entries for hosts that are no longer configured
+    fn prune_expired(&mut self, max_age_secs: u64, active_hosts: &[&str]) -> Vec<String> {
+        let expired = expired_hosts(&self.states, max_age_secs, active_hosts, current_timestamp());
+        for hostname in &expired {
+            self.states.remove(hostname);
         }
+        expired
+    }
+}
 
-        let state = state.unwrap();
-        let now = current_timestamp();
+/// Hosts not in `active_hosts` whose mtime is more than `max_age_secs` before `now`, sorted
+pub fn expired_hosts(
+    states: &HashMap<String, HostState>,
+    max_age_secs: u64,
+    active_hosts: &[&str],
+    now: u64,
+) -> Vec<String> {
+    let mut expired: Vec<String> = states
+        .iter()
+        .filter(|(hostname, _)| !active_hosts.contains(&hostname.as_str()))
+        .filter(|(_, state)| state.mtime.is_some_and(|mtime| now.saturating_sub(mtime) > max_age_secs))
+        .map(|(hostname, _)| hostname.clone())
+        .collect();
+    expired.sort();
+    expired
+}
 
-        // Check max-interval: Force update if too much time has passed since last successful update
-        if let (Some(mtime), Some(max_int)) = (state.mtime, max_interval) {
-            if now >= mtime + max_int {
-                let days = max_int / 86400;
-                return (true, Some(format!(
-                    "update forced because it has been {} days since the previous update",
-                    days
-                )));
-            }
+/// What happened to a host when importing another state file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportAction {
+    /// Host was not tracked yet
+    Imported,
+    /// Host was already tracked and has been overwritten (--force)
+    Replaced,
+    /// Host was already tracked and has been left alone
+    Skipped,
+}
+
+/// Merge host states from `source` into `target`
+/// Hosts already present in `target` are only overwritten when `force` is set.
+/// With `dry_run`, `target` is left untouched and only the planned actions are returned.
+pub fn import_states(
+    target: &mut dyn StateStore,
+    source: &dyn StateStore,
+    force: bool,
+    dry_run: bool,
+) -> Vec<(String, ImportAction)> {
+    let mut actions = Vec::new();
+
+    for (hostname, state) in source.iter() {
+        let action = match (target.get(hostname).is_some(), force) {
+            (false, _) => ImportAction::Imported,
+            (true, true) => ImportAction::Replaced,
+            (true, false) => ImportAction::Skipped,
+        };
+        if !dry_run && action != ImportAction::Skipped {
+            *target.get_mut(hostname) = state.clone();
         }
+        actions.push((hostname.to_string(), action));
+    }
 
-        // If IP hasn't changed and we're not being forced, no need to update
-        if !ip_changed {
-            return (false, Some("IP address hasn't changed".to_string()));
+    actions
+}
+
+/// Write the states in `from` (either format) to `to` in `to_format`
+pub fn convert_state_file(from: &Path, to_format: StateFormat, to: &Path) -> Result<(), Box<dyn Error>> {
+    if !from.exists() {
+        return Err(format!("State file {} not found", from.display()).into());
+    }
+    let source = StateManager::new(Some(from.to_path_buf()), to_format, false, true)?;
+    let target = StateManager {
+        cache_file: to.to_path_buf(),
+        version: STATE_VERSION,
+        ..source
+    };
+    target.save()
+}
+
+/// Overwrite `file` with the contents of `backup`
+fn restore_from(file: &mut fs::File, backup: &Path) -> Result<(), Box<dyn Error>> {
+    let contents = fs::read(backup)?;
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&contents)?;
+    Ok(())
+}
+
+/// Version from the `## rddclient-state-version=N` header; 1 when there is none
+fn state_version(content: &str) -> u32 {
+    content.lines()
+        .find_map(|line| line.trim().strip_prefix(VERSION_PREFIX))
+        .and_then(|version| version.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+/// Rewrite a version 1 ddclient-format state file as version 2: add the version header,
+/// give every host the new fields' defaults and recompute the checksum
+pub fn migrate_state_v1_to_v2(content: &str) -> String {
+    let mut header = Vec::new();
+    let mut hosts = Vec::new();
+
+    for line in content.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if line.starts_with(CHECKSUM_PREFIX) {
+            continue;
+        }
+        if line.starts_with("##") {
+            header.push(line.to_string());
+            continue;
         }
+        let Some((opts, hostname)) = line.rsplit_once(' ') else {
+            continue;
+        };
+        let mut opts = opts.trim().to_string();
+        for (key, default) in [("retry_count", "0"), ("permanent_failure", "0")] {
+            if !opts.split(',').any(|pair| pair.trim().starts_with(&format!("{}=", key))) {
+                opts.push_str(&format!(",{}={}", key, default));
+            }
+        }
+        hosts.push(format!("{} {}", opts.trim_start_matches(','), hostname));
+    }
 
-        // IP has changed - check min-interval for successful updates
-        if let Some(status) = &state.status {
-            if status.starts_with("good") || status.starts_with("nochg") {
-                // Last update was successful, check min-interval
-                if let (Some(mtime), Some(min_int)) = (state.mtime, min_interval) {
-                    if now < mtime + min_int {
-                        let remaining = (mtime + min_int) - now;
-                        return (false, Some(format!(
-                            "skipped update due to min-interval ({}s remaining)",
-                            remaining
-                        )));
-                    }
-                }
-            } else {
-                // Last update failed, check min-error-interval
-                if let (Some(atime), Some(min_err_int)) = (state.atime, min_error_interval) {
-                    if now < atime + min_err_int {
-                        let remaining = (atime + min_err_int) - now;
-                        return (false, Some(format!(
-                            "skipped update due to min-error-interval ({}s remaining after previous failure)",
-                            remaining
-                        )));
-                    }
-                }
-            }
-        }
+    let mut migrated = String::new();
+    let mut lines = header.iter();
+    // Keep the version header right below the title line, as save() writes it
+    if let Some(title) = lines.next() {
+        migrated.push_str(&format!("{}\n", title));
+    }
+    migrated.push_str(&format!("{}{}\n", VERSION_PREFIX, STATE_VERSION));
+    for line in lines.chain(&hosts) {
+        migrated.push_str(&format!("{}\n", line));
+    }
+    let hosts: Vec<&str> = hosts.iter().map(String::as_str).collect();
+    migrated.push_str(&format!("{}{}\n", CHECKSUM_PREFIX, compute_state_checksum(&hosts)));
+    migrated
+}
 
-        // All checks passed, allow update
-        (true, None)
+/// Compute the delay before retrying a failed host:
+/// `min_error_interval * 2^retry_count + jitter`, capped at `max_interval`
+fn error_backoff(min_error_interval: u64, retry_count: u32, max_interval: Option<u64>, jitter: u64) -> u64 {
+    let factor = 1u64.checked_shl(retry_count).unwrap_or(u64::MAX);
+    let delay = min_error_interval.saturating_mul(factor).saturating_add(jitter);
+
+    match max_interval {
+        Some(max) => delay.min(max),
+        None => delay,
     }
 }
 
+/// SHA-256 (hex) over the content lines of a state file, each terminated by a newline
+fn compute_state_checksum(lines: &[&str]) -> String {
+    let mut context = digest::Context::new(&digest::SHA256);
+    for line in lines {
+        context.update(line.as_bytes());
+        context.update(b"\n");
+    }
+    hex::encode(context.finish())
+}
+
 /// Get current Unix timestamp in seconds
-fn current_timestamp() -> u64 {
+pub fn current_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("System time before Unix epoch")
         .as_secs()
 }
 
+/// Serialize IP history as `ts@ip:ts@ip`, the form used in the state file
+pub fn format_history(history: &[(u64, IpAddr)]) -> String {
+    history.iter()
+        .map(|(timestamp, ip)| format!("{}@{}", timestamp, ip))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Escape the characters that would break a `key=value,...` cache line
+fn escape_value(value: &str) -> String {
+    value.replace('%', "%25").replace(',', "%2C").replace('\n', "%0A")
+}
+
+/// Reverse `escape_value`
+fn unescape_value(value: &str) -> String {
+    value.replace("%0A", "\n").replace("%2C", ",").replace("%25", "%")
+}
+
+/// Parse `ts@ip:ts@ip` IP history, skipping malformed entries
+/// IPv6 addresses contain ':' too, so the text is split at '@' instead: every piece
+/// except the first and last is `<ip>:<next timestamp>`.
+pub fn parse_history(value: &str) -> Vec<(u64, IpAddr)> {
+    let pieces: Vec<&str> = value.split('@').collect();
+    let mut history = Vec::new();
+    let mut timestamp = pieces[0];
+
+    for (index, piece) in pieces.iter().enumerate().skip(1) {
+        let (ip, next) = if index + 1 == pieces.len() {
+            (*piece, "")
+        } else {
+            piece.rsplit_once(':').unwrap_or((piece, ""))
+        };
+        if let (Ok(timestamp), Ok(ip)) = (timestamp.parse(), ip.parse()) {
+            history.push((timestamp, ip));
+        }
+        timestamp = next;
+    }
+    history.truncate(HISTORY_LIMIT);
+    history
+}
+
 /// Format timestamp as human-readable string
 fn format_timestamp(timestamp: u64) -> String {
     // Simple UTC format - could use chrono for better formatting
@@ -367,7 +928,7 @@ fn format_timestamp(timestamp: u64) -> String {
 mod tests {
     use super::*;
     use std::net::Ipv4Addr;
-    use tempfile::NamedTempFile;
+    use tempfile::{NamedTempFile, TempDir};
     
     #[test]
     fn test_host_state_ip_changed() {
@@ -408,7 +969,7 @@ mod tests {
         
         // Create and populate state
         {
-            let mut manager = StateManager::new(Some(cache_path.clone())).unwrap();
+            let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
             let state = manager.get_mut("example.com");
             state.update_success(
                 IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)),
@@ -419,7 +980,7 @@ mod tests {
         
         // Load state in new manager
         {
-            let manager = StateManager::new(Some(cache_path)).unwrap();
+            let manager = StateManager::new(Some(cache_path), StateFormat::DdClient, false, true).unwrap();
             let state = manager.get("example.com").unwrap();
             assert_eq!(state.ip, Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))));
             assert_eq!(state.status, Some("good".to_string()));
@@ -441,7 +1002,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
         fs::write(cache_path, content).unwrap();
         
         // Load and verify
-        let manager = StateManager::new(Some(cache_path.to_path_buf())).unwrap();
+        let manager = StateManager::new(Some(cache_path.to_path_buf()), StateFormat::DdClient, false, true).unwrap();
         
         let state1 = manager.get("example.com").unwrap();
         assert_eq!(state1.ip, Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))));
@@ -456,7 +1017,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
     fn test_should_update_no_state() {
         // No previous state should allow update
         let temp_file = NamedTempFile::new().unwrap();
-        let manager = StateManager::new(Some(temp_file.path().to_path_buf())).unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
         
         let (should, reason) = manager.should_update(
             "example.com",
@@ -465,6 +1026,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
             Some(30),    // min_interval
             Some(86400), // max_interval
             Some(300),   // min_error_interval
+            None,        // max_retries
         );
         
         assert!(should);
@@ -475,7 +1037,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
     fn test_should_update_force() {
         // Force flag should always allow update
         let temp_file = NamedTempFile::new().unwrap();
-        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf())).unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
         
         // Set recent successful update
         let state = manager.get_mut("example.com");
@@ -491,6 +1053,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
             Some(3600), // min_interval (1 hour)
             Some(86400), // max_interval
             Some(300),   // min_error_interval
+            None,        // max_retries
         );
         
         assert!(should);
@@ -501,7 +1064,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
     fn test_should_update_min_interval_blocks() {
         // Recent successful update within min-interval should block
         let temp_file = NamedTempFile::new().unwrap();
-        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf())).unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
         
         // Set recent successful update (just now)
         let state = manager.get_mut("example.com");
@@ -517,6 +1080,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
             Some(3600), // min_interval (1 hour - definitely not passed)
             Some(86400), // max_interval
             Some(300),   // min_error_interval
+            None,        // max_retries
         );
         
         assert!(!should);
@@ -528,7 +1092,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
     fn test_should_update_no_ip_change() {
         // No IP change should skip update
         let temp_file = NamedTempFile::new().unwrap();
-        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf())).unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
         
         let state = manager.get_mut("example.com");
         state.update_success(
@@ -543,6 +1107,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
             Some(30),    // min_interval
             Some(86400), // max_interval
             Some(300),   // min_error_interval
+            None,        // max_retries
         );
         
         assert!(!should);
@@ -554,7 +1119,7 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
     fn test_should_update_min_error_interval_blocks() {
         // Recent failed update within min-error-interval should block
         let temp_file = NamedTempFile::new().unwrap();
-        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf())).unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
         
         // Set recent failed update
         let state = manager.get_mut("example.com");
@@ -567,10 +1132,664 @@ ip=5.6.7.8,mtime=1704067201,status=nochg www.example.com
             Some(30),    // min_interval
             Some(86400), // max_interval
             Some(3600),  // min_error_interval (1 hour - definitely not passed)
+            None,        // max_retries
         );
         
         assert!(!should);
         assert!(reason.is_some());
         assert!(reason.unwrap().contains("min-error-interval"));
     }
+
+    #[test]
+    fn test_state_format_from_str() {
+        assert_eq!("ddclient".parse::<StateFormat>().unwrap(), StateFormat::DdClient);
+        assert_eq!("JSON".parse::<StateFormat>().unwrap(), StateFormat::Json);
+        assert!("yaml".parse::<StateFormat>().is_err());
+    }
+
+    #[test]
+    fn test_json_state_round_trip() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache_path = temp_file.path().to_path_buf();
+
+        {
+            let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::Json, false, true).unwrap();
+            manager.get_mut("example.com").update_success(
+                IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)),
+                "good".to_string()
+            );
+            manager.get_mut("fail.example.com").update_failure("Connection refused".to_string());
+            manager.save().unwrap();
+        }
+
+        let content = fs::read_to_string(&cache_path).unwrap();
+        assert!(content.starts_with('{'));
+
+        let manager = StateManager::new(Some(cache_path), StateFormat::Json, false, true).unwrap();
+        let state = manager.get("example.com").unwrap();
+        assert_eq!(state.ip, Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))));
+        assert_eq!(state.status, Some("good".to_string()));
+        assert!(state.mtime.is_some());
+
+        let failed = manager.get("fail.example.com").unwrap();
+        assert_eq!(failed.status, Some("FAILED".to_string()));
+        assert_eq!(failed.last_error, Some("Connection refused".to_string()));
+        assert!(failed.atime.is_some());
+    }
+
+    #[test]
+    fn test_json_to_ddclient_migration() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache_path = temp_file.path().to_path_buf();
+
+        fs::write(&cache_path, r#"{
+  "example.com": {"ip": "5.6.7.8", "mtime": 1704067200, "status": "nochg"}
+}"#).unwrap();
+
+        // Loading auto-detects JSON; saving uses the manager's (ddclient) format
+        {
+            let manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
+            assert_eq!(manager.get("example.com").unwrap().mtime, Some(1704067200));
+            manager.save().unwrap();
+        }
+
+        let content = fs::read_to_string(&cache_path).unwrap();
+        assert!(content.starts_with("## rddclient cache file"));
+        assert!(content.contains("ip=5.6.7.8,mtime=1704067200,status=nochg example.com"));
+
+        let manager = StateManager::new(Some(cache_path), StateFormat::DdClient, false, true).unwrap();
+        let state = manager.get("example.com").unwrap();
+        assert_eq!(state.ip, Some(IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8))));
+        assert_eq!(state.status, Some("nochg".to_string()));
+    }
+
+    #[test]
+    fn test_convert_state_file_both_ways() {
+        let dir = tempfile::tempdir().unwrap();
+        let ddclient_path = dir.path().join("rddclient.cache");
+        let json_path = dir.path().join("rddclient.json");
+        let back_path = dir.path().join("back.cache");
+
+        let mut manager = StateManager::new(Some(ddclient_path.clone()), StateFormat::DdClient, false, true).unwrap();
+        manager.get_mut("example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+        manager.get_mut("fail.example.com").update_failure("timeout".to_string());
+        manager.save().unwrap();
+
+        convert_state_file(&ddclient_path, StateFormat::Json, &json_path).unwrap();
+        assert!(fs::read_to_string(&json_path).unwrap().starts_with('{'));
+        convert_state_file(&json_path, StateFormat::DdClient, &back_path).unwrap();
+        assert!(fs::read_to_string(&back_path).unwrap().starts_with("## rddclient cache file"));
+
+        // A fresh manager loads each file with the same states
+        for path in [&json_path, &back_path] {
+            let loaded = StateManager::new(Some(path.clone()), StateFormat::DdClient, false, true).unwrap();
+            assert_eq!(loaded.get("example.com").unwrap().ip, Some("192.0.2.1".parse().unwrap()));
+            assert_eq!(loaded.get("fail.example.com").unwrap().last_error, Some("timeout".to_string()));
+        }
+
+        assert!(convert_state_file(&dir.path().join("missing"), StateFormat::Json, &json_path).is_err());
+    }
+
+    #[test]
+    fn test_invalid_json_state_file() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), "{ not json").unwrap();
+
+        let result = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_retry_count_tracking() {
+        let mut state = HostState::new();
+        state.update_failure("timeout".to_string());
+        state.update_failure("timeout".to_string());
+        assert_eq!(state.retry_count, 2);
+
+        state.update_success(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), "good".to_string());
+        assert_eq!(state.retry_count, 0);
+    }
+
+    #[test]
+    fn test_error_backoff_doubles() {
+        let base = 300;
+        for retry_count in 0..=5 {
+            let expected = base * (1 << retry_count);
+            assert_eq!(error_backoff(base, retry_count, None, 0), expected);
+
+            // Jitter is bounded by half the base interval
+            let with_jitter = error_backoff(base, retry_count, None, base / 2 - 1);
+            assert!(with_jitter >= expected && with_jitter < expected + base / 2);
+        }
+    }
+
+    #[test]
+    fn test_error_backoff_capped_at_max_interval() {
+        assert_eq!(error_backoff(300, 5, Some(3600), 100), 3600);
+        assert_eq!(error_backoff(300, 64, None, 0), u64::MAX);
+    }
+
+    #[test]
+    fn test_should_update_error_backoff() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
+
+        // Third consecutive failure: 60s * 2^3 = 480s (+ up to 30s jitter)
+        let state = manager.get_mut("example.com");
+        state.status = Some("FAILED".to_string());
+        state.last_error = Some("timeout".to_string());
+        state.retry_count = 3;
+
+        state.atime = Some(current_timestamp() - 400);
+        let (should, reason) = manager.should_update("example.com", true, false, Some(30), None, Some(60), None);
+        assert!(!should);
+        assert!(reason.unwrap().contains("min-error-interval"));
+
+        manager.get_mut("example.com").atime = Some(current_timestamp() - 520);
+        let (should, _) = manager.should_update("example.com", true, false, Some(30), None, Some(60), None);
+        assert!(should);
+    }
+
+    #[test]
+    fn test_retry_count_round_trip() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache_path = temp_file.path().to_path_buf();
+
+        {
+            let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
+            let state = manager.get_mut("example.com");
+            state.update_failure("timeout".to_string());
+            state.update_failure("timeout".to_string());
+            manager.save().unwrap();
+        }
+
+        let content = fs::read_to_string(&cache_path).unwrap();
+        assert!(content.contains("retry_count=2"));
+
+        let manager = StateManager::new(Some(cache_path), StateFormat::DdClient, false, true).unwrap();
+        assert_eq!(manager.get("example.com").unwrap().retry_count, 2);
+    }
+
+    #[test]
+    fn test_iter_sorted_by_hostname() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
+        manager.get_mut("www.example.com");
+        manager.get_mut("api.example.com");
+        manager.get_mut("mail.example.com");
+
+        let hostnames: Vec<&str> = manager.iter().map(|(hostname, _)| hostname).collect();
+        assert_eq!(hostnames, vec!["api.example.com", "mail.example.com", "www.example.com"]);
+    }
+
+    #[test]
+    fn test_max_retries_disables_host() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
+
+        // No min-error-interval so only the retry limit applies
+        for attempt in 1..=3 {
+            let (should, _) = manager.should_update("example.com", true, false, None, None, None, Some(3));
+            assert!(should, "attempt {} should be allowed", attempt);
+            manager.get_mut("example.com").update_failure("Connection refused".to_string());
+        }
+
+        let (should, reason) = manager.should_update("example.com", true, false, None, None, None, Some(3));
+        assert!(!should);
+        assert_eq!(reason.as_deref(), Some("max retries exceeded, host disabled"));
+        assert!(manager.get("example.com").unwrap().permanent_failure);
+
+        // Stays disabled even if the limit is raised, until re-enabled
+        let (should, _) = manager.should_update("example.com", true, false, None, None, None, Some(10));
+        assert!(!should);
+
+        // --force still goes through
+        let (should, _) = manager.should_update("example.com", true, true, None, None, None, Some(3));
+        assert!(should);
+    }
+
+    #[test]
+    fn test_reenable_host() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache_path = temp_file.path().to_path_buf();
+
+        {
+            let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
+            let state = manager.get_mut("example.com");
+            state.update_failure("Connection refused".to_string());
+            state.update_failure("Connection refused".to_string());
+            let (should, _) = manager.should_update("example.com", true, false, None, None, None, Some(2));
+            assert!(!should);
+            manager.save().unwrap();
+        }
+
+        let content = fs::read_to_string(&cache_path).unwrap();
+        assert!(content.contains("permanent_failure=1"));
+
+        let mut manager = StateManager::new(Some(cache_path), StateFormat::DdClient, false, true).unwrap();
+        let state = manager.get_mut("example.com");
+        assert!(state.permanent_failure);
+        state.reenable();
+        assert!(!state.permanent_failure);
+        assert_eq!(state.retry_count, 0);
+
+        let (should, _) = manager.should_update("example.com", true, false, None, None, None, Some(2));
+        assert!(should);
+    }
+
+    #[test]
+    fn test_update_success_clears_permanent_failure() {
+        let mut state = HostState::new();
+        state.permanent_failure = true;
+        state.retry_count = 5;
+        state.update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+        assert!(!state.permanent_failure);
+        assert_eq!(state.retry_count, 0);
+    }
+
+    const DDCLIENT_CACHE: &str = include_str!("../tests/fixtures/ddclient.cache");
+
+    const RDDCLIENT_V1_CACHE: &str = include_str!("../tests/fixtures/rddclient_v1.cache");
+
+    #[test]
+    fn test_load_v1_state_defaults() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+        fs::write(&cache_path, RDDCLIENT_V1_CACHE).unwrap();
+
+        let manager = StateManager::new(Some(cache_path), StateFormat::DdClient, true, true).unwrap();
+        assert_eq!(manager.version, 1);
+        let home = manager.get("home.example.com").unwrap();
+        assert_eq!(home.ip, Some("203.0.113.1".parse().unwrap()));
+        assert_eq!(home.retry_count, 0);
+        assert!(!home.permanent_failure);
+
+        let down = manager.get("down.example.com").unwrap();
+        // The old combined status is split on load
+        assert_eq!(down.status.as_deref(), Some("FAILED"));
+        assert_eq!(down.last_error.as_deref(), Some("Connection refused"));
+        assert_eq!(down.retry_count, 0);
+        assert!(!down.permanent_failure);
+        assert_eq!(manager.iter().count(), 3);
+    }
+
+    #[test]
+    fn test_status_and_error_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+
+        {
+            let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
+            manager.get_mut("ok.example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+            manager.get_mut("fail.example.com").update_failure("HTTP 500, 100% broken".to_string());
+            manager.save().unwrap();
+        }
+
+        let content = fs::read_to_string(&cache_path).unwrap();
+        assert!(content.contains("status=FAILED,error=HTTP 500%2C 100%25 broken,"));
+
+        let manager = StateManager::new(Some(cache_path), StateFormat::DdClient, false, true).unwrap();
+        let ok = manager.get("ok.example.com").unwrap();
+        assert_eq!(ok.status.as_deref(), Some("good"));
+        assert_eq!(ok.last_error, None);
+        let failed = manager.get("fail.example.com").unwrap();
+        assert_eq!(failed.status.as_deref(), Some("FAILED"));
+        assert_eq!(failed.last_error.as_deref(), Some("HTTP 500, 100% broken"));
+
+        let mut recovered = failed.clone();
+        recovered.update_success("192.0.2.2".parse().unwrap(), "good".to_string());
+        assert_eq!(recovered.last_error, None);
+    }
+
+    #[test]
+    fn test_migrate_state_v1_to_v2() {
+        let migrated = migrate_state_v1_to_v2(RDDCLIENT_V1_CACHE);
+        let lines: Vec<&str> = migrated.lines().collect();
+        assert_eq!(lines[0], "## rddclient cache file");
+        assert_eq!(lines[1], "## rddclient-state-version=2");
+        assert_eq!(lines[3], "ip=203.0.113.1,mtime=1704067200,status=good,retry_count=0,permanent_failure=0 home.example.com");
+        assert!(lines[6].starts_with(CHECKSUM_PREFIX));
+        assert_eq!(state_version(&migrated), 2);
+
+        // The migrated file passes the strict checksum check and keeps its values
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+        fs::write(&cache_path, &migrated).unwrap();
+        let manager = StateManager::new(Some(cache_path), StateFormat::DdClient, true, true).unwrap();
+        assert_eq!(manager.version, 2);
+        assert_eq!(manager.get("v6.example.com").unwrap().status.as_deref(), Some("nochg"));
+
+        // Existing values are never overwritten by defaults
+        let migrated = migrate_state_v1_to_v2("ip=192.0.2.1,retry_count=3 example.com\n");
+        assert!(migrated.contains("ip=192.0.2.1,retry_count=3,permanent_failure=0 example.com"));
+    }
+
+    #[test]
+    fn test_saved_state_has_version_header() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+        fs::write(&cache_path, RDDCLIENT_V1_CACHE).unwrap();
+
+        let manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
+        manager.save().unwrap();
+        let content = fs::read_to_string(&cache_path).unwrap();
+        assert_eq!(content.lines().nth(1), Some("## rddclient-state-version=2"));
+
+        let manager = StateManager::new(Some(cache_path), StateFormat::DdClient, true, true).unwrap();
+        assert_eq!(manager.version, 2);
+    }
+
+    fn ddclient_fixture(dir: &TempDir) -> StateManager {
+        let path = dir.path().join("ddclient.cache");
+        fs::write(&path, DDCLIENT_CACHE).unwrap();
+        StateManager::from_ddclient_file(path).unwrap()
+    }
+
+    #[test]
+    fn test_parse_real_ddclient_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = ddclient_fixture(&temp_dir);
+
+        assert_eq!(source.iter().count(), 3);
+        let home = source.get("home.example.com").unwrap();
+        assert_eq!(home.ip, Some("198.51.100.7".parse().unwrap()));
+        assert_eq!(home.mtime, Some(1704110400));
+        assert_eq!(home.status, Some("good".to_string()));
+        assert_eq!(source.get("v6.example.com").unwrap().ip, Some("2001:db8::7".parse().unwrap()));
+        assert_eq!(source.get("vpn.example.com").unwrap().status, Some("badauth".to_string()));
+    }
+
+    #[test]
+    fn test_from_ddclient_file_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(StateManager::from_ddclient_file(temp_dir.path().join("missing.cache")).is_err());
+    }
+
+    #[test]
+    fn test_import_skips_existing_hosts() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = ddclient_fixture(&temp_dir);
+
+        let mut target = StateManager::new(Some(temp_dir.path().join("rddclient.cache")), StateFormat::DdClient, false, true).unwrap();
+        target.get_mut("home.example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+
+        let actions = import_states(&mut target, &source, false, false);
+        assert_eq!(actions, vec![
+            ("home.example.com".to_string(), ImportAction::Skipped),
+            ("v6.example.com".to_string(), ImportAction::Imported),
+            ("vpn.example.com".to_string(), ImportAction::Imported),
+        ]);
+        assert_eq!(target.get("home.example.com").unwrap().ip, Some("192.0.2.1".parse().unwrap()));
+        assert_eq!(target.get("vpn.example.com").unwrap().mtime, Some(1703000000));
+    }
+
+    #[test]
+    fn test_import_force_replaces_existing_hosts() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = ddclient_fixture(&temp_dir);
+
+        let mut target = StateManager::new(Some(temp_dir.path().join("rddclient.cache")), StateFormat::DdClient, false, true).unwrap();
+        target.get_mut("home.example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+
+        let actions = import_states(&mut target, &source, true, false);
+        assert_eq!(actions[0], ("home.example.com".to_string(), ImportAction::Replaced));
+        assert_eq!(target.get("home.example.com").unwrap().ip, Some("198.51.100.7".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_import_dry_run_leaves_target_untouched() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = ddclient_fixture(&temp_dir);
+
+        let mut target = StateManager::new(Some(temp_dir.path().join("rddclient.cache")), StateFormat::DdClient, false, true).unwrap();
+        let actions = import_states(&mut target, &source, false, true);
+
+        assert_eq!(actions.len(), 3);
+        assert!(actions.iter().all(|(_, action)| *action == ImportAction::Imported));
+        assert_eq!(target.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_state_checksum_written_and_verified() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache_path = temp_file.path().to_path_buf();
+
+        let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
+        manager.get_mut("example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+        manager.save().unwrap();
+
+        let content = fs::read_to_string(&cache_path).unwrap();
+        let last = content.lines().last().unwrap();
+        assert!(last.starts_with(CHECKSUM_PREFIX));
+        assert_eq!(last.len(), CHECKSUM_PREFIX.len() + 64);
+        assert!(StateManager::new(Some(cache_path), StateFormat::DdClient, true, true).is_ok());
+    }
+
+    #[test]
+    fn test_state_checksum_detects_alteration() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let cache_path = temp_file.path().to_path_buf();
+
+        let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
+        manager.get_mut("example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+        manager.save().unwrap();
+
+        // Change a single character of the recorded IP
+        let content = fs::read_to_string(&cache_path).unwrap();
+        fs::write(&cache_path, content.replacen("ip=192.0.2.1", "ip=192.0.2.7", 1)).unwrap();
+
+        let err = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, true, true).err().unwrap();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        // Without --strict-state the file still loads
+        let manager = StateManager::new(Some(cache_path), StateFormat::DdClient, false, true).unwrap();
+        assert_eq!(manager.get("example.com").unwrap().ip, Some("192.0.2.7".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_compute_state_checksum() {
+        // sha256("a\nb\n")
+        assert_eq!(
+            compute_state_checksum(&["a", "b"]),
+            "911169ddaaf146aff539f58c26c489af3b892dff0fe283c1c264c65ae5aa59a2"
+        );
+        assert_ne!(compute_state_checksum(&["a", "b"]), compute_state_checksum(&["ab"]));
+        // Files without a checksum line (e.g. written by ddclient) aren't checked
+        let manager = StateManager { cache_file: PathBuf::new(), format: StateFormat::DdClient, states: HashMap::new(), strict: true, lock: false, lock_timeout: LOCK_TIMEOUT, version: STATE_VERSION, backup_count: 0 };
+        assert!(manager.verify_checksum("ip=192.0.2.1 example.com\n").is_ok());
+    }
+
+    #[test]
+    fn test_prune_expired_keeps_live_hosts() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut manager = StateManager::new(Some(temp_file.path().to_path_buf()), StateFormat::DdClient, false, true).unwrap();
+        let now = current_timestamp();
+        let ten_days = 10 * 86400;
+
+        manager.get_mut("live.example.com").mtime = Some(now - 100 * 86400);
+        manager.get_mut("removed.example.com").mtime = Some(now - 100 * 86400);
+        manager.get_mut("recent.example.com").mtime = Some(now - 86400);
+        manager.get_mut("never.example.com").status = Some("failed".to_string());
+
+        let pruned = manager.prune_expired(ten_days, &["live.example.com"]);
+        assert_eq!(pruned, vec!["removed.example.com".to_string()]);
+        assert!(manager.get("live.example.com").is_some());
+        assert!(manager.get("recent.example.com").is_some());
+        assert!(manager.get("never.example.com").is_some());
+        assert!(manager.get("removed.example.com").is_none());
+    }
+
+    #[test]
+    fn test_expired_hosts_boundary() {
+        let mut states = HashMap::new();
+        states.insert("a.example.com".to_string(), HostState { mtime: Some(1000), ..HostState::new() });
+        states.insert("b.example.com".to_string(), HostState { mtime: Some(999), ..HostState::new() });
+
+        assert_eq!(expired_hosts(&states, 500, &[], 1500), vec!["b.example.com".to_string()]);
+        assert_eq!(expired_hosts(&states, 500, &["b.example.com"], 1500), Vec::<String>::new());
+        assert_eq!(expired_hosts(&states, 0, &[], 1000), vec!["b.example.com".to_string()]);
+    }
+
+    #[test]
+    fn test_ip_history_newest_first() {
+        let mut state = HostState::new();
+        let first: IpAddr = "192.0.2.1".parse().unwrap();
+        let second: IpAddr = "2001:db8::1".parse().unwrap();
+
+        state.update_success(first, "good".to_string());
+        state.update_success(first, "nochg".to_string());
+        state.update_success(second, "good".to_string());
+
+        let ips: Vec<IpAddr> = state.ip_history.iter().map(|(_, ip)| *ip).collect();
+        assert_eq!(ips, vec![second, first]);
+        assert!(state.ip_history[0].0 >= state.ip_history[1].0);
+    }
+
+    #[test]
+    fn test_ip_history_capped() {
+        let mut state = HostState::new();
+        for n in 1..=15u8 {
+            state.update_success(IpAddr::V4(Ipv4Addr::new(192, 0, 2, n)), "good".to_string());
+        }
+        assert_eq!(state.ip_history.len(), HISTORY_LIMIT);
+        assert_eq!(state.ip_history[0].1, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 15)));
+        assert_eq!(state.ip_history[9].1, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 6)));
+    }
+
+    #[test]
+    fn test_history_format_and_parse() {
+        let history = vec![
+            (1_700_000_000, "1.2.3.4".parse().unwrap()),
+            (1_699_995_000, "2001:db8::1".parse().unwrap()),
+            (1_699_990_000, "5.6.7.8".parse().unwrap()),
+        ];
+        let text = format_history(&history);
+        assert_eq!(text, "1700000000@1.2.3.4:1699995000@2001:db8::1:1699990000@5.6.7.8");
+        assert_eq!(parse_history(&text), history);
+
+        assert_eq!(parse_history(""), Vec::new());
+        assert_eq!(parse_history("bogus@1.2.3.4:1699990000@5.6.7.8"), vec![(1_699_990_000, "5.6.7.8".parse().unwrap())]);
+    }
+
+    #[test]
+    fn test_ip_history_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+        let history = vec![
+            (1_700_000_000, "1.2.3.4".parse().unwrap()),
+            (1_699_990_000, "2001:db8::1".parse().unwrap()),
+        ];
+
+        for format in [StateFormat::DdClient, StateFormat::Json] {
+            let mut manager = StateManager::new(Some(cache_path.clone()), format, false, true).unwrap();
+            manager.get_mut("example.com").ip_history = history.clone();
+            manager.save().unwrap();
+
+            let manager = StateManager::new(Some(cache_path.clone()), format, false, true).unwrap();
+            assert_eq!(manager.get("example.com").unwrap().ip_history, history);
+        }
+    }
+
+    fn saved_manager(cache_path: &Path, ip: &str, backup_count: u32) -> StateManager {
+        let mut manager = StateManager::new(Some(cache_path.to_path_buf()), StateFormat::DdClient, false, true).unwrap();
+        manager.set_backup_count(backup_count);
+        manager.get_mut("example.com").update_success(ip.parse().unwrap(), "good".to_string());
+        manager.save().unwrap();
+        manager
+    }
+
+    fn backup_ip(path: PathBuf) -> Option<String> {
+        let content = fs::read_to_string(path).ok()?;
+        content.split("ip=").nth(1).map(|rest| rest.split(',').next().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_save_creates_backup() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+
+        // Nothing to back up on the first save
+        let manager = saved_manager(&cache_path, "192.0.2.1", 1);
+        assert!(!manager.backup_path(0).exists());
+
+        let manager = saved_manager(&cache_path, "192.0.2.2", 1);
+        assert_eq!(manager.backup_path(0), dir.path().join("rddclient.cache.bak"));
+        assert_eq!(backup_ip(manager.backup_path(0)).as_deref(), Some("192.0.2.1"));
+        assert_eq!(backup_ip(cache_path.clone()).as_deref(), Some("192.0.2.2"));
+        assert!(!manager.backup_path(1).exists());
+
+        // --no-backup
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+        saved_manager(&cache_path, "192.0.2.1", 0);
+        let manager = saved_manager(&cache_path, "192.0.2.2", 0);
+        assert!(!manager.backup_path(0).exists());
+    }
+
+    #[test]
+    fn test_backup_rotation() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+
+        for n in 1..=5 {
+            saved_manager(&cache_path, &format!("192.0.2.{}", n), 3);
+        }
+        let manager = saved_manager(&cache_path, "192.0.2.6", 3);
+        assert_eq!(backup_ip(manager.backup_path(0)).as_deref(), Some("192.0.2.5"));
+        assert_eq!(backup_ip(manager.backup_path(1)).as_deref(), Some("192.0.2.4"));
+        assert_eq!(backup_ip(manager.backup_path(2)).as_deref(), Some("192.0.2.3"));
+        assert!(!manager.backup_path(3).exists());
+    }
+
+    #[test]
+    fn test_failed_save_restores_backup() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+        let manager = saved_manager(&cache_path, "192.0.2.1", 1);
+        let original = fs::read_to_string(&cache_path).unwrap();
+
+        let err = manager.write_with_backup(|file| {
+            file.write_all(b"ip=192.0.2.99,sta")?;
+            Err("disk full".into())
+        }).unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+        assert_eq!(fs::read_to_string(&cache_path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_save_waits_for_exclusive_lock() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+        fs::write(&cache_path, "").unwrap();
+        let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, true).unwrap();
+        manager.lock_timeout = Duration::from_millis(100);
+        manager.get_mut("example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+
+        // A reader holding a shared lock blocks writers but not other readers
+        let reader = fs::File::open(&cache_path).unwrap();
+        FileExt::lock_shared(&reader).unwrap();
+        assert_eq!(manager.save().unwrap_err().to_string(), "state file locked by another process");
+        assert!(manager.load().is_ok());
+
+        FileExt::unlock(&reader).unwrap();
+        manager.save().unwrap();
+        assert!(fs::read_to_string(&cache_path).unwrap().contains("ip=192.0.2.1"));
+    }
+
+    #[test]
+    fn test_load_waits_for_writer_unless_unlocked() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("rddclient.cache");
+        fs::write(&cache_path, "ip=192.0.2.1,status=good example.com\n").unwrap();
+
+        let writer = fs::OpenOptions::new().write(true).open(&cache_path).unwrap();
+        writer.lock_exclusive().unwrap();
+
+        let mut manager = StateManager::new(Some(cache_path.clone()), StateFormat::DdClient, false, false).unwrap();
+        assert!(manager.get("example.com").is_some());
+
+        manager.lock = true;
+        manager.lock_timeout = Duration::from_millis(100);
+        assert_eq!(manager.load().unwrap_err().to_string(), "state file locked by another process");
+    }
 }
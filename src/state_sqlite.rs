@@ -0,0 +1,260 @@
+/// SQLite-backed state storage (`--db <path>`)
+///
+/// An alternative to the flat text cache file. All rows are loaded when the
+/// database is opened and written back in a single transaction on save.
+use crate::state::{self, HostState, StateStore};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+pub struct SqliteStateManager {
+    conn: Connection,
+    states: HashMap<String, HostState>,
+}
+
+impl SqliteStateManager {
+    /// Open (or create) the state database at `path`
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open state database {}: {}", path.display(), e))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS host_states (
+                hostname TEXT PRIMARY KEY,
+                ip TEXT,
+                mtime INTEGER,
+                status TEXT,
+                atime INTEGER,
+                wtime INTEGER,
+                retry_count INTEGER,
+                permanent_failure INTEGER,
+                ip_history TEXT,
+                last_error TEXT
+            )",
+            [],
+        )?;
+
+        // Databases created by older versions lack the newer columns
+        for (column, column_type) in [("permanent_failure", "INTEGER"), ("ip_history", "TEXT"), ("last_error", "TEXT")] {
+            let exists = conn
+                .prepare("SELECT 1 FROM pragma_table_info('host_states') WHERE name = ?1")?
+                .exists([column])?;
+            if !exists {
+                conn.execute(&format!("ALTER TABLE host_states ADD COLUMN {} {}", column, column_type), [])?;
+            }
+        }
+
+        let mut manager = Self {
+            conn,
+            states: HashMap::new(),
+        };
+        manager.load()?;
+        Ok(manager)
+    }
+
+    fn load(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT hostname, ip, mtime, status, atime, wtime, retry_count, permanent_failure, ip_history, last_error FROM host_states",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let ip: Option<String> = row.get(1)?;
+            let mut state = HostState {
+                ip: ip.and_then(|ip| ip.parse().ok()),
+                mtime: row.get::<_, Option<i64>>(2)?.map(|t| t as u64),
+                status: row.get(3)?,
+                last_error: row.get(9)?,
+                atime: row.get::<_, Option<i64>>(4)?.map(|t| t as u64),
+                wtime: row.get::<_, Option<i64>>(5)?.map(|t| t as u64),
+                retry_count: row.get::<_, Option<u32>>(6)?.unwrap_or(0),
+                permanent_failure: row.get::<_, Option<bool>>(7)?.unwrap_or(false),
+                ip_history: row.get::<_, Option<String>>(8)?.map(|h| state::parse_history(&h)).unwrap_or_default(),
+            };
+            state.split_legacy_status();
+            Ok((row.get::<_, String>(0)?, state))
+        })?;
+
+        for row in rows {
+            let (hostname, state) = row?;
+            self.states.insert(hostname, state);
+        }
+        Ok(())
+    }
+
+    /// True when the database holds no host states yet
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Copy every host state from another store (e.g. an existing text cache file)
+    pub fn import(&mut self, other: &dyn StateStore) -> usize {
+        let mut count = 0;
+        for (hostname, state) in other.iter() {
+            self.states.insert(hostname.to_string(), state.clone());
+            count += 1;
+        }
+        count
+    }
+}
+
+impl StateStore for SqliteStateManager {
+    fn get(&self, hostname: &str) -> Option<&HostState> {
+        self.states.get(hostname)
+    }
+
+    fn get_mut(&mut self, hostname: &str) -> &mut HostState {
+        self.states.entry(hostname.to_string()).or_default()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&str, &HostState)> + '_> {
+        let mut entries: Vec<(&str, &HostState)> = self.states
+            .iter()
+            .map(|(hostname, state)| (hostname.as_str(), state))
+            .collect();
+        entries.sort_by_key(|(hostname, _)| *hostname);
+        Box::new(entries.into_iter())
+    }
+
+    /// Write all host states back to the database in one transaction
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO host_states (hostname, ip, mtime, status, atime, wtime, retry_count, permanent_failure, ip_history, last_error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for (hostname, state) in &self.states {
+                stmt.execute(params![
+                    hostname,
+                    state.ip.map(|ip| ip.to_string()),
+                    state.mtime.map(|t| t as i64),
+                    state.status,
+                    state.atime.map(|t| t as i64),
+                    state.wtime.map(|t| t as i64),
+                    state.retry_count,
+                    state.permanent_failure,
+                    Some(state::format_history(&state.ip_history)).filter(|h| !h.is_empty()),
+                    state.last_error,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove expired entries from memory and from the database
+    fn prune_expired(&mut self, max_age_secs: u64, active_hosts: &[&str]) -> Vec<String> {
+        let expired = state::expired_hosts(&self.states, max_age_secs, active_hosts, state::current_timestamp());
+        for hostname in &expired {
+            self.states.remove(hostname);
+            if let Err(e) = self.conn.execute("DELETE FROM host_states WHERE hostname = ?1", params![hostname]) {
+                log::warn!("Failed to delete state for {} from the database: {}", hostname, e);
+            }
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{StateFormat, StateManager};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sqlite_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("state.db");
+
+        {
+            let mut manager = SqliteStateManager::open(&db_path).unwrap();
+            assert!(manager.is_empty());
+            manager.get_mut("example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+            let failed = manager.get_mut("fail.example.com");
+            failed.update_failure("Connection refused".to_string());
+            failed.update_failure("Connection refused".to_string());
+            failed.permanent_failure = true;
+            manager.save().unwrap();
+        }
+
+        let manager = SqliteStateManager::open(&db_path).unwrap();
+        let state = manager.get("example.com").unwrap();
+        assert_eq!(state.ip, Some("192.0.2.1".parse().unwrap()));
+        assert_eq!(state.status, Some("good".to_string()));
+        assert!(state.mtime.is_some());
+        assert_eq!(state.retry_count, 0);
+        assert_eq!(state.ip_history.len(), 1);
+        assert_eq!(state.ip_history[0].1, "192.0.2.1".parse::<std::net::IpAddr>().unwrap());
+
+        let failed = manager.get("fail.example.com").unwrap();
+        assert_eq!(failed.ip, None);
+        assert_eq!(failed.status, Some("FAILED".to_string()));
+        assert_eq!(failed.last_error, Some("Connection refused".to_string()));
+        assert_eq!(failed.retry_count, 2);
+        assert!(failed.permanent_failure);
+
+        let hostnames: Vec<&str> = manager.iter().map(|(hostname, _)| hostname).collect();
+        assert_eq!(hostnames, vec!["example.com", "fail.example.com"]);
+    }
+
+    #[test]
+    fn test_sqlite_save_updates_existing_rows() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("state.db");
+
+        let mut manager = SqliteStateManager::open(&db_path).unwrap();
+        manager.get_mut("example.com").update_success("192.0.2.1".parse().unwrap(), "good".to_string());
+        manager.save().unwrap();
+        manager.get_mut("example.com").update_success("192.0.2.2".parse().unwrap(), "good".to_string());
+        manager.save().unwrap();
+        drop(manager);
+
+        let manager = SqliteStateManager::open(&db_path).unwrap();
+        assert_eq!(manager.iter().count(), 1);
+        assert_eq!(manager.get("example.com").unwrap().ip, Some("192.0.2.2".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_sqlite_import_text_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_path = temp_dir.path().join("rddclient.cache");
+        fs::write(&cache_path, "ip=203.0.113.5,mtime=1704067200,status=good example.com\n").unwrap();
+
+        let text = StateManager::new(Some(cache_path), StateFormat::DdClient, false, true).unwrap();
+        let db_path = temp_dir.path().join("state.db");
+        {
+            let mut manager = SqliteStateManager::open(&db_path).unwrap();
+            assert_eq!(manager.import(&text), 1);
+            manager.save().unwrap();
+        }
+
+        let manager = SqliteStateManager::open(&db_path).unwrap();
+        let state = manager.get("example.com").unwrap();
+        assert_eq!(state.ip, Some("203.0.113.5".parse().unwrap()));
+        assert_eq!(state.mtime, Some(1704067200));
+    }
+
+    #[test]
+    fn test_sqlite_prune_expired() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("state.db");
+
+        {
+            let mut manager = SqliteStateManager::open(&db_path).unwrap();
+            manager.get_mut("old.example.com").mtime = Some(1);
+            manager.get_mut("live.example.com").mtime = Some(1);
+            manager.save().unwrap();
+
+            assert_eq!(manager.prune_expired(86400, &["live.example.com"]), vec!["old.example.com".to_string()]);
+            manager.save().unwrap();
+        }
+
+        let manager = SqliteStateManager::open(&db_path).unwrap();
+        assert!(manager.get("old.example.com").is_none());
+        assert!(manager.get("live.example.com").is_some());
+    }
+}
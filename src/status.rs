@@ -0,0 +1,189 @@
+/// `--status` report of all hosts tracked in the state file
+///
+/// Built purely from cached state; no network calls are made.
+use crate::state::HostState;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Render the status table
+/// `min_interval_for` returns the min-interval (seconds) that applies to a host
+pub fn render_table(
+    rows: &[(&str, &HostState)],
+    now: u64,
+    min_interval_for: impl Fn(&str) -> u64,
+) -> String {
+    let header = ["HOSTNAME", "LAST IP", "LAST UPDATED", "STATUS", "NEXT UPDATE"];
+
+    let lines: Vec<[String; 5]> = rows
+        .iter()
+        .map(|(hostname, state)| {
+            let ip = state.ip.map(|ip| ip.to_string()).unwrap_or_else(|| "-".to_string());
+            let updated = match state.mtime {
+                Some(mtime) => format!("{} ago", format_duration(now.saturating_sub(mtime))),
+                None => "never".to_string(),
+            };
+            let status = match (&state.status, &state.last_error) {
+                (Some(status), Some(error)) => format!("{}: {}", status, error),
+                (Some(status), None) => status.clone(),
+                (None, _) => "-".to_string(),
+            };
+            let next = match state.mtime {
+                Some(mtime) if mtime + min_interval_for(hostname) > now => {
+                    format!("in {}", format_duration(mtime + min_interval_for(hostname) - now))
+                }
+                _ => "now".to_string(),
+            };
+            [hostname.to_string(), ip, updated, status, next]
+        })
+        .collect();
+
+    // Size each column to its widest cell
+    let mut widths = header.map(str::len);
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let format_row = |cells: &[&str]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("{}\n", padded.join(" | ").trim_end())
+    };
+
+    let mut table = format_row(&header);
+    for line in &lines {
+        table.push_str(&format_row(&line.each_ref().map(String::as_str)));
+    }
+    table
+}
+
+/// Render a host's IP history, newest change first
+pub fn render_history(state: &HostState, now: u64) -> String {
+    if state.ip_history.is_empty() {
+        return "No IP changes recorded\n".to_string();
+    }
+
+    let width = state.ip_history.iter().map(|(_, ip)| ip.to_string().len()).max().unwrap_or(0);
+    state.ip_history
+        .iter()
+        .map(|(timestamp, ip)| {
+            let time = crate::json_logger::format_rfc3339(UNIX_EPOCH + Duration::from_secs(*timestamp));
+            format!(
+                "{:<width$}  {} ({} ago)\n",
+                ip.to_string(), time, format_duration(now.saturating_sub(*timestamp)), width = width
+            )
+        })
+        .collect()
+}
+
+/// True when no tracked host is in a failed state
+pub fn all_ok(rows: &[(&str, &HostState)]) -> bool {
+    rows.iter().all(|(_, state)| {
+        !state.status.as_deref().is_some_and(|s| s.starts_with("FAILED"))
+    })
+}
+
+/// Format a duration in seconds as a short human-readable string (e.g. "2d 3h")
+fn format_duration(secs: u64) -> String {
+    let days = secs / 86400;
+    let hours = (secs % 86400) / 3600;
+    let minutes = (secs % 3600) / 60;
+    let seconds = secs % 60;
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::IpAddr;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn host(ip: &str, mtime: Option<u64>, status: &str) -> HostState {
+        HostState {
+            ip: Some(ip.parse::<IpAddr>().unwrap()),
+            mtime,
+            status: Some(status.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(125), "2m 5s");
+        assert_eq!(format_duration(3 * 3600 + 120), "3h 2m");
+        assert_eq!(format_duration(2 * 86400 + 5 * 3600), "2d 5h");
+    }
+
+    #[test]
+    fn test_render_table() {
+        let a = host("192.0.2.1", Some(NOW - 2 * 86400), "good");
+        let b = host("2001:db8::1", Some(NOW - 10), "nochg");
+        let rows = vec![("a.example.com", &a), ("b.example.com", &b)];
+
+        let table = render_table(&rows, NOW, |_| 30);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "HOSTNAME      | LAST IP     | LAST UPDATED | STATUS | NEXT UPDATE");
+        assert_eq!(lines[1], "a.example.com | 192.0.2.1   | 2d 0h ago    | good   | now");
+        assert_eq!(lines[2], "b.example.com | 2001:db8::1 | 10s ago      | nochg  | in 20s");
+    }
+
+    #[test]
+    fn test_render_table_uses_per_host_interval() {
+        let a = host("192.0.2.1", Some(NOW - 60), "good");
+        let rows = vec![("a.example.com", &a)];
+
+        let table = render_table(&rows, NOW, |_| 3600);
+        assert!(table.lines().nth(1).unwrap().ends_with("in 59m 0s"));
+    }
+
+    #[test]
+    fn test_render_table_never_updated() {
+        let state = HostState::new();
+        let rows = vec![("new.example.com", &state)];
+
+        let table = render_table(&rows, NOW, |_| 30);
+        assert_eq!(table.lines().nth(1).unwrap(), "new.example.com | -       | never        | -      | now");
+    }
+
+    #[test]
+    fn test_all_ok() {
+        let good = host("192.0.2.1", Some(NOW), "good");
+        let mut failed = host("192.0.2.2", Some(NOW), "FAILED");
+        failed.last_error = Some("Connection refused".to_string());
+
+        assert!(all_ok(&[("a", &good)]));
+        assert!(!all_ok(&[("a", &good), ("b", &failed)]));
+    }
+
+    #[test]
+    fn test_render_history() {
+        let mut state = host("192.0.2.1", Some(NOW), "good");
+        assert_eq!(render_history(&state, NOW), "No IP changes recorded\n");
+
+        state.ip_history = vec![
+            (NOW - 90, "2001:db8::1".parse().unwrap()),
+            (NOW - 2 * 86400, "192.0.2.1".parse().unwrap()),
+        ];
+        assert_eq!(
+            render_history(&state, NOW),
+            "2001:db8::1  2023-11-14T22:11:50Z (1m 30s ago)\n\
+             192.0.2.1    2023-11-12T22:13:20Z (2d 0h ago)\n"
+        );
+    }
+}
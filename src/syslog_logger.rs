@@ -0,0 +1,84 @@
+/// syslog logging backend (`--syslog`, Unix only)
+///
+/// Messages go to the local syslog socket (/dev/log on Linux, /var/run/syslog on
+/// macOS) in RFC 3164 format, tagged `rddclient[<pid>]` with the daemon facility.
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::error::Error;
+use std::sync::Mutex;
+use syslog::{Facility, Formatter3164, Logger, LoggerBackend, Severity};
+
+pub struct SyslogLogger {
+    level: LevelFilter,
+    logger: Mutex<Logger<LoggerBackend, Formatter3164>>,
+}
+
+impl SyslogLogger {
+    /// Connect to the local syslog daemon and install it as the global logger
+    pub fn init(level: LevelFilter) -> Result<(), Box<dyn Error>> {
+        let formatter = Formatter3164 {
+            facility: Facility::LOG_DAEMON,
+            hostname: None,
+            process: env!("CARGO_PKG_NAME").to_string(),
+            pid: std::process::id(),
+        };
+        let logger = syslog::unix(formatter)
+            .map_err(|e| format!("Failed to connect to syslog: {}", e))?;
+
+        log::set_boxed_logger(Box::new(Self {
+            level,
+            logger: Mutex::new(logger),
+        }))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+/// Map a log level to the syslog severity it is reported with
+fn severity(level: Level) -> Severity {
+    match level {
+        Level::Error => Severity::LOG_ERR,
+        Level::Warn => Severity::LOG_WARNING,
+        Level::Info => Severity::LOG_INFO,
+        Level::Debug | Level::Trace => Severity::LOG_DEBUG,
+    }
+}
+
+impl Log for SyslogLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = record.args().to_string();
+        let Ok(mut logger) = self.logger.lock() else {
+            return;
+        };
+        // Nowhere left to report a failed syslog write
+        let _ = match severity(record.level()) {
+            Severity::LOG_ERR => logger.err(message),
+            Severity::LOG_WARNING => logger.warning(message),
+            Severity::LOG_INFO => logger.info(message),
+            _ => logger.debug(message),
+        };
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_mapping() {
+        assert!(matches!(severity(Level::Error), Severity::LOG_ERR));
+        assert!(matches!(severity(Level::Warn), Severity::LOG_WARNING));
+        assert!(matches!(severity(Level::Info), Severity::LOG_INFO));
+        assert!(matches!(severity(Level::Debug), Severity::LOG_DEBUG));
+        assert!(matches!(severity(Level::Trace), Severity::LOG_DEBUG));
+    }
+}
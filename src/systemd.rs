@@ -0,0 +1,98 @@
+/// systemd service manager integration (sd_notify protocol)
+///
+/// Notifications are only sent when rddclient is built with the `systemd` feature
+/// and started with `--systemd`; otherwise every method is a no-op.
+pub struct SystemdNotifier {
+    #[cfg_attr(not(all(feature = "systemd", unix)), allow(dead_code))]
+    enabled: bool,
+}
+
+impl SystemdNotifier {
+    pub fn new(enabled: bool) -> Self {
+        let supported = cfg!(all(feature = "systemd", unix));
+        if enabled && !supported {
+            log::warn!("--systemd ignored: rddclient was built without the 'systemd' feature");
+        }
+
+        Self {
+            enabled: enabled && supported,
+        }
+    }
+
+    /// Tell the service manager that startup has finished (READY=1)
+    pub fn ready(&self) {
+        #[cfg(all(feature = "systemd", unix))]
+        self.send(sd_notify::NotifyState::Ready);
+    }
+
+    /// Update the free-form status shown by `systemctl status` (STATUS=...)
+    pub fn status(&self, status: &str) {
+        #[cfg(all(feature = "systemd", unix))]
+        self.send(sd_notify::NotifyState::Status(status));
+        #[cfg(not(all(feature = "systemd", unix)))]
+        let _ = status;
+    }
+
+    /// Ping the service watchdog (WATCHDOG=1) if the unit has one configured
+    pub fn watchdog(&self) {
+        #[cfg(all(feature = "systemd", unix))]
+        {
+            let mut usec = 0;
+            if sd_notify::watchdog_enabled(false, &mut usec) {
+                self.send(sd_notify::NotifyState::Watchdog);
+            }
+        }
+    }
+
+    /// Tell the service manager that we are shutting down (STOPPING=1)
+    pub fn stopping(&self) {
+        #[cfg(all(feature = "systemd", unix))]
+        self.send(sd_notify::NotifyState::Stopping);
+    }
+
+    #[cfg(all(feature = "systemd", unix))]
+    fn send(&self, state: sd_notify::NotifyState) {
+        if !self.enabled {
+            return;
+        }
+        if let Err(e) = sd_notify::notify(false, &[state]) {
+            log::debug!("Failed to notify systemd: {}", e);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "systemd", unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn test_systemd_notifications_on_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("notify.sock");
+        let socket = UnixDatagram::bind(&socket_path).unwrap();
+        std::env::set_var("NOTIFY_SOCKET", &socket_path);
+
+        let mut buf = [0u8; 256];
+
+        let notifier = SystemdNotifier::new(true);
+        notifier.ready();
+        let n = socket.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1\n");
+
+        notifier.status("Updating 2 hosts");
+        let n = socket.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"STATUS=Updating 2 hosts\n");
+
+        notifier.stopping();
+        let n = socket.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"STOPPING=1\n");
+
+        // Without --systemd nothing is sent
+        socket.set_nonblocking(true).unwrap();
+        SystemdNotifier::new(false).ready();
+        assert!(socket.recv(&mut buf).is_err());
+
+        std::env::remove_var("NOTIFY_SOCKET");
+    }
+}
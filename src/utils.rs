@@ -0,0 +1,33 @@
+/// Mask a secret for log output, keeping only the last 4 characters
+/// e.g. "abcdef123456" -> "****3456"; secrets shorter than 8 characters are fully redacted
+pub fn mask_credential(s: &str) -> String {
+    let len = s.chars().count();
+    if len < 8 {
+        return "[REDACTED]".to_string();
+    }
+    let tail: String = s.chars().skip(len - 4).collect();
+    format!("****{}", tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_credential_keeps_last_four() {
+        assert_eq!(mask_credential("abcdef123456"), "****3456");
+        assert_eq!(mask_credential("12345678"), "****5678");
+    }
+
+    #[test]
+    fn test_mask_credential_short_secrets() {
+        assert_eq!(mask_credential(""), "[REDACTED]");
+        assert_eq!(mask_credential("hunter2"), "[REDACTED]");
+    }
+
+    #[test]
+    fn test_mask_credential_multibyte() {
+        assert_eq!(mask_credential("pässwörd-ünïcode"), "****code");
+        assert_eq!(mask_credential("ääääääää"), "****ääää");
+    }
+}
@@ -0,0 +1,297 @@
+/// `rddclient validate` configuration checklist
+///
+/// Every check runs against the loaded configuration only; no network calls are made.
+use crate::args::Args;
+use crate::clients;
+use crate::config::{self, Config};
+
+/// Protocols whose clients need `zone=` to locate the record
+const ZONE_REQUIRED: &[&str] = &[
+    "azure", "azure-dns", "cloudflare", "desec", "easydns", "gcloud", "google-cloud-dns",
+    "hetzner", "linode", "luadns", "nfsn", "yandex",
+];
+
+/// Protocols that don't authenticate with a password or API token
+const NO_PASSWORD: &[&str] = &["emailonly"];
+
+/// Longest TTL accepted (one day)
+const MAX_TTL: u32 = 86400;
+
+/// Outcome of a single validation check
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, passed: true, detail: detail.into() }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, passed: false, detail: detail.into() }
+    }
+}
+
+/// Run every check against `config`; `args` supplies the command-line intervals
+pub fn run_checks(config: &Config, args: &Args) -> Vec<Check> {
+    let protocol = config.protocol.as_deref().unwrap_or("").to_ascii_lowercase();
+
+    vec![
+        check_protocol(&protocol),
+        check_credentials(config, &protocol),
+        check_zone(config, &protocol),
+        check_provider(config, &protocol),
+        check_hostnames(config),
+        check_intervals(config, args),
+        check_ttl(config),
+    ]
+}
+
+/// Render checks as `[OK]`/`[FAIL]` lines
+pub fn render(checks: &[Check]) -> String {
+    checks
+        .iter()
+        .map(|check| {
+            let status = if check.passed { "[OK]  " } else { "[FAIL]" };
+            format!("{} {}: {}\n", status, check.name, check.detail)
+        })
+        .collect()
+}
+
+fn check_protocol(protocol: &str) -> Check {
+    if protocol.is_empty() {
+        Check::fail("protocol", "protocol is not set")
+    } else if clients::is_supported(protocol) {
+        Check::ok("protocol", protocol)
+    } else {
+        Check::fail("protocol", format!("unknown protocol '{}'", protocol))
+    }
+}
+
+fn check_credentials(config: &Config, protocol: &str) -> Check {
+    if config.login.as_deref().is_some_and(|login| login.trim().is_empty()) {
+        return Check::fail("credentials", "login is empty");
+    }
+    if NO_PASSWORD.contains(&protocol) {
+        return Check::ok("credentials", format!("not needed for {}", protocol));
+    }
+    match config.password.as_deref() {
+        None => Check::fail("credentials", "password is not set"),
+        Some(password) if password.trim().is_empty() => Check::fail("credentials", "password is empty"),
+        Some(_) => Check::ok("credentials", "password set"),
+    }
+}
+
+fn check_zone(config: &Config, protocol: &str) -> Check {
+    let zone = config.zone.as_deref().unwrap_or("").trim();
+    if !zone.is_empty() {
+        Check::ok("zone", zone)
+    } else if ZONE_REQUIRED.contains(&protocol) {
+        Check::fail("zone", format!("zone is required for {}", protocol))
+    } else {
+        Check::ok("zone", "not required")
+    }
+}
+
+/// Provider-specific settings, as checked by the client itself
+fn check_provider(config: &Config, protocol: &str) -> Check {
+    if !clients::is_supported(protocol) {
+        return Check::fail("provider settings", "skipped (unknown protocol)");
+    }
+    match clients::create_client(protocol, config).and_then(|client| client.validate_config()) {
+        Ok(()) => Check::ok("provider settings", "complete"),
+        Err(e) => Check::fail("provider settings", e.to_string()),
+    }
+}
+
+fn check_hostnames(config: &Config) -> Check {
+    let hostnames = config.dns_records();
+    if hostnames.is_empty() {
+        return Check::fail("hostnames", "no host configured");
+    }
+
+    let invalid: Vec<&str> = hostnames
+        .iter()
+        .map(String::as_str)
+        .filter(|hostname| !is_valid_fqdn(hostname))
+        .collect();
+    if invalid.is_empty() {
+        Check::ok("hostnames", hostnames.join(", "))
+    } else {
+        Check::fail("hostnames", format!("not a valid FQDN: {}", invalid.join(", ")))
+    }
+}
+
+/// Syntactic FQDN check: at least two labels of 1-63 letters, digits or hyphens
+/// (no leading/trailing hyphen), 253 characters at most. A leading `*.` is allowed.
+fn is_valid_fqdn(hostname: &str) -> bool {
+    let name = hostname.strip_suffix('.').unwrap_or(hostname);
+    let name = name.strip_prefix("*.").unwrap_or(name);
+    if name.is_empty() || name.len() > 253 {
+        return false;
+    }
+
+    let labels: Vec<&str> = name.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+fn check_intervals(config: &Config, args: &Args) -> Check {
+    let parse = |value: Option<&str>, default: u64| -> Result<u64, String> {
+        value
+            .map(config::parse_interval)
+            .transpose()
+            .map(|parsed| parsed.unwrap_or(default))
+            .map_err(|e| e.to_string())
+    };
+    // Defaults match the ones applied in main
+    let (min, max) = match (parse(args.min_interval.as_deref(), 30), parse(args.max_interval.as_deref(), 25 * 86400)) {
+        (Ok(min), Ok(max)) => (min, max),
+        (Err(e), _) | (_, Err(e)) => return Check::fail("intervals", e),
+    };
+
+    for hostname in config.dns_records() {
+        let (host_min, host_max) = config.intervals_for(&hostname);
+        let (host_min, host_max) = (host_min.unwrap_or(min), host_max.unwrap_or(max));
+        if host_min >= host_max {
+            return Check::fail(
+                "intervals",
+                format!("{}: min-interval ({}s) must be less than max-interval ({}s)", hostname, host_min, host_max),
+            );
+        }
+    }
+    Check::ok("intervals", "min-interval < max-interval")
+}
+
+fn check_ttl(config: &Config) -> Check {
+    match config.ttl {
+        None => Check::ok("ttl", "not set (provider default)"),
+        Some(ttl) if (1..=MAX_TTL).contains(&ttl) => Check::ok("ttl", format!("{}s", ttl)),
+        Some(ttl) => Check::fail("ttl", format!("{} is outside 1-{}", ttl, MAX_TTL)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn valid_config() -> Config {
+        Config {
+            protocol: Some("cloudflare".to_string()),
+            login: Some("token".to_string()),
+            password: Some("api-token".to_string()),
+            zone: Some("example.com".to_string()),
+            host: Some("home.example.com".to_string()),
+            ttl: Some(300),
+            ..Default::default()
+        }
+    }
+
+    fn no_args() -> Args {
+        Args::try_parse_from(["rddclient"]).unwrap()
+    }
+
+    fn failed(config: &Config, args: &Args) -> Vec<&'static str> {
+        run_checks(config, args)
+            .into_iter()
+            .filter(|check| !check.passed)
+            .map(|check| check.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_config_passes() {
+        assert!(failed(&valid_config(), &no_args()).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_protocol() {
+        let mut config = valid_config();
+        config.protocol = Some("nosuchdns".to_string());
+        assert_eq!(failed(&config, &no_args()), vec!["protocol", "provider settings"]);
+    }
+
+    #[test]
+    fn test_empty_credentials() {
+        let mut config = valid_config();
+        config.password = Some("".to_string());
+        assert!(failed(&config, &no_args()).contains(&"credentials"));
+
+        let mut config = valid_config();
+        config.login = Some(" ".to_string());
+        assert!(failed(&config, &no_args()).contains(&"credentials"));
+    }
+
+    #[test]
+    fn test_missing_zone() {
+        let mut config = valid_config();
+        config.zone = None;
+        assert!(failed(&config, &no_args()).contains(&"zone"));
+
+        // Zone is optional for providers that don't need it
+        config.protocol = Some("duckdns".to_string());
+        assert!(failed(&config, &no_args()).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_hostnames() {
+        let mut config = valid_config();
+        config.host = Some("home.example.com,bad_-.example.com,-x.example.com,localhost".to_string());
+        let checks = run_checks(&config, &no_args());
+        let hostnames = checks.iter().find(|check| check.name == "hostnames").unwrap();
+        assert!(!hostnames.passed);
+        assert_eq!(hostnames.detail, "not a valid FQDN: bad_-.example.com, -x.example.com, localhost");
+    }
+
+    #[test]
+    fn test_fqdn_syntax() {
+        assert!(is_valid_fqdn("example.com"));
+        assert!(is_valid_fqdn("a-b.example.com."));
+        assert!(is_valid_fqdn("*.example.com"));
+        assert!(!is_valid_fqdn("example..com"));
+        assert!(!is_valid_fqdn(&format!("{}.com", "a".repeat(64))));
+        assert!(!is_valid_fqdn("exa mple.com"));
+    }
+
+    #[test]
+    fn test_interval_order() {
+        let args = Args::try_parse_from(["rddclient", "--min-interval", "1h", "--max-interval", "30m"]).unwrap();
+        assert_eq!(failed(&valid_config(), &args), vec!["intervals"]);
+
+        let args = Args::try_parse_from(["rddclient", "--min-interval", "5x"]).unwrap();
+        assert_eq!(failed(&valid_config(), &args), vec!["intervals"]);
+
+        // Per-host overrides from the config file are checked too
+        let mut config = valid_config();
+        config.min_interval = Some(86400);
+        config.max_interval = Some(3600);
+        assert_eq!(failed(&config, &no_args()), vec!["intervals"]);
+    }
+
+    #[test]
+    fn test_ttl_range() {
+        let mut config = valid_config();
+        config.ttl = Some(0);
+        assert_eq!(failed(&config, &no_args()), vec!["ttl"]);
+        config.ttl = Some(86401);
+        assert_eq!(failed(&config, &no_args()), vec!["ttl"]);
+        config.ttl = None;
+        assert!(failed(&config, &no_args()).is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let checks = vec![Check::ok("protocol", "cloudflare"), Check::fail("ttl", "0 is outside 1-86400")];
+        assert_eq!(render(&checks), "[OK]   protocol: cloudflare\n[FAIL] ttl: 0 is outside 1-86400\n");
+    }
+}
@@ -0,0 +1,284 @@
+/// Post-update DNS verification (--verify-after-update)
+///
+/// Sends a plain A/AAAA query over UDP straight to the configured resolver, so the check
+/// sees what that resolver serves rather than the local system's cache. Verification is
+/// informational: a mismatch is logged but never turns a successful update into a failure.
+use crate::args::Args;
+use rand::RngExt;
+use std::error::Error;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+const QTYPE_A: u16 = 1;
+const QTYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+
+/// How long to wait for the resolver to answer one query
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Re-checks updated records against a resolver
+pub struct Verifier {
+    resolver: String,
+    retries: u32,
+    /// Pause between attempts, giving the provider time to publish the record
+    retry_delay: Duration,
+}
+
+impl Verifier {
+    /// The verifier enabled on the command line, if any
+    pub fn new(args: &Args) -> Option<Self> {
+        args.verify_after_update.then(|| Self {
+            resolver: args.verify_resolver.clone(),
+            retries: args.verify_retries,
+            retry_delay: Duration::from_secs(5),
+        })
+    }
+
+    /// Check that `hostname` resolves to `expected`, retrying on mismatch.
+    /// Returns whether the record was confirmed; problems are only logged.
+    pub fn verify(&self, hostname: &str, expected: IpAddr) -> bool {
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                thread::sleep(self.retry_delay);
+            }
+            match verify_dns_record(hostname, expected, &self.resolver) {
+                Ok(true) => {
+                    log::info!("Verified {} resolves to {} via {}", hostname, expected, self.resolver);
+                    return true;
+                }
+                Ok(false) => log::warn!(
+                    "{} does not resolve to {} via {} yet (attempt {} of {})",
+                    hostname, expected, self.resolver, attempt + 1, self.retries + 1
+                ),
+                Err(e) => log::warn!("Failed to verify {} via {}: {}", hostname, self.resolver, e),
+            }
+        }
+        false
+    }
+}
+
+/// Ask `resolver` (an IP, optionally with a port) whether `hostname` has `expected`
+/// among its A or AAAA records
+pub fn verify_dns_record(hostname: &str, expected: IpAddr, resolver: &str) -> Result<bool, Box<dyn Error>> {
+    let resolver = resolver_addr(resolver)?;
+    let qtype = if expected.is_ipv4() { QTYPE_A } else { QTYPE_AAAA };
+    let id: u16 = rand::rng().random();
+
+    let bind_addr: SocketAddr = if resolver.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind_addr)?;
+    socket.set_read_timeout(Some(QUERY_TIMEOUT))?;
+    socket.connect(resolver)?;
+    socket.send(&build_query(id, hostname, qtype)?)?;
+
+    let mut buf = [0u8; 1500];
+    let len = socket.recv(&mut buf)?;
+    let addresses = parse_response(&buf[..len], id)?;
+    Ok(addresses.contains(&expected))
+}
+
+/// `8.8.8.8`, `2001:4860:4860::8888` or `127.0.0.1:5353`; port 53 unless given
+fn resolver_addr(resolver: &str) -> Result<SocketAddr, Box<dyn Error>> {
+    if let Ok(addr) = resolver.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    let ip: IpAddr = resolver.parse()
+        .map_err(|_| format!("Invalid DNS resolver address: {}", resolver))?;
+    Ok(SocketAddr::new(ip, 53))
+}
+
+/// A recursive query for one name
+fn build_query(id: u16, hostname: &str, qtype: u16) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut query = Vec::with_capacity(hostname.len() + 18);
+    query.extend_from_slice(&id.to_be_bytes());
+    query.extend_from_slice(&[0x01, 0x00]); // recursion desired
+    query.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]); // one question, no other records
+
+    for label in hostname.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid hostname for DNS query: {}", hostname).into());
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&qtype.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(query)
+}
+
+/// A and AAAA addresses in the answer section of a response to query `id`.
+/// NXDOMAIN yields no addresses; other error codes are errors.
+fn parse_response(packet: &[u8], id: u16) -> Result<Vec<IpAddr>, Box<dyn Error>> {
+    let truncated = || -> Box<dyn Error> { "Truncated DNS response".into() };
+    let u16_at = |pos: usize| -> Result<u16, Box<dyn Error>> {
+        packet.get(pos..pos + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(truncated)
+    };
+
+    if packet.len() < 12 {
+        return Err(truncated());
+    }
+    if u16_at(0)? != id {
+        return Err("DNS response ID does not match the query".into());
+    }
+    match packet[3] & 0x0f {
+        0 => {}
+        3 => return Ok(Vec::new()),
+        rcode => return Err(format!("DNS server returned error code {}", rcode).into()),
+    }
+
+    let questions = u16_at(4)?;
+    let answers = u16_at(6)?;
+    let mut pos = 12;
+
+    for _ in 0..questions {
+        pos = skip_name(packet, pos).ok_or_else(truncated)? + 4;
+    }
+
+    let mut addresses = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(packet, pos).ok_or_else(truncated)?;
+        let rtype = u16_at(pos)?;
+        let rdlength = u16_at(pos + 8)? as usize;
+        let rdata = packet.get(pos + 10..pos + 10 + rdlength).ok_or_else(truncated)?;
+        match (rtype, rdata.len()) {
+            (QTYPE_A, 4) => addresses.push(IpAddr::from(<[u8; 4]>::try_from(rdata)?)),
+            (QTYPE_AAAA, 16) => addresses.push(IpAddr::from(<[u8; 16]>::try_from(rdata)?)),
+            // CNAMEs and anything else are skipped; the resolver follows the chain for us
+            _ => {}
+        }
+        pos += 10 + rdlength;
+    }
+    Ok(addresses)
+}
+
+/// Position just past the (possibly compressed) name starting at `pos`
+fn skip_name(packet: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *packet.get(pos)?;
+        match len {
+            0 => return Some(pos + 1),
+            // Compression pointer: two bytes, ends the name
+            l if l & 0xc0 == 0xc0 => return Some(pos + 2),
+            l => pos += 1 + l as usize,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A response to `query` answering with `addresses` (name compressed to the question)
+    fn response(query: &[u8], rcode: u8, addresses: &[IpAddr]) -> Vec<u8> {
+        let mut packet = query.to_vec();
+        packet[2] = 0x81;
+        packet[3] = 0x80 | rcode;
+        packet[6..8].copy_from_slice(&(addresses.len() as u16).to_be_bytes());
+        for address in addresses {
+            let (rtype, rdata) = match address {
+                IpAddr::V4(ip) => (QTYPE_A, ip.octets().to_vec()),
+                IpAddr::V6(ip) => (QTYPE_AAAA, ip.octets().to_vec()),
+            };
+            packet.extend_from_slice(&[0xc0, 0x0c]);
+            packet.extend_from_slice(&rtype.to_be_bytes());
+            packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+            packet.extend_from_slice(&300u32.to_be_bytes());
+            packet.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            packet.extend_from_slice(&rdata);
+        }
+        packet
+    }
+
+    /// Local UDP resolver answering each query in turn with the next entry of `answers`
+    fn mock_resolver(answers: Vec<Vec<IpAddr>>) -> (String, thread::JoinHandle<Vec<Vec<u8>>>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let mut queries = Vec::new();
+            for addresses in answers {
+                let mut buf = [0u8; 512];
+                let (len, peer) = socket.recv_from(&mut buf).unwrap();
+                socket.send_to(&response(&buf[..len], 0, &addresses), peer).unwrap();
+                queries.push(buf[..len].to_vec());
+            }
+            queries
+        });
+        (addr, handle)
+    }
+
+    #[test]
+    fn test_build_query() {
+        let query = build_query(0x1234, "home.example.com", QTYPE_AAAA).unwrap();
+        assert_eq!(&query[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&query[12..], b"\x04home\x07example\x03com\x00\x00\x1c\x00\x01");
+        assert!(build_query(1, "bad..example.com", QTYPE_A).is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let query = build_query(7, "home.example.com", QTYPE_A).unwrap();
+        let ips: Vec<IpAddr> = vec!["192.0.2.1".parse().unwrap(), "2001:db8::1".parse().unwrap()];
+        assert_eq!(parse_response(&response(&query, 0, &ips), 7).unwrap(), ips);
+
+        // NXDOMAIN has no addresses, SERVFAIL and mismatched IDs are errors
+        assert!(parse_response(&response(&query, 3, &[]), 7).unwrap().is_empty());
+        assert!(parse_response(&response(&query, 2, &[]), 7).is_err());
+        assert!(parse_response(&response(&query, 0, &ips), 8).is_err());
+        assert!(parse_response(&response(&query, 0, &ips)[..40], 7).is_err());
+    }
+
+    #[test]
+    fn test_parse_short_response() {
+        // Shorter than the 12-byte header, e.g. a 2- or 3-byte reply
+        for len in [0, 2, 3, 11] {
+            let err = parse_response(&[0, 7, 0x81, 0x80, 0, 0, 0, 0, 0, 0, 0][..len], 7).unwrap_err();
+            assert_eq!(err.to_string(), "Truncated DNS response");
+        }
+    }
+
+    #[test]
+    fn test_verify_dns_record_against_mock_resolver() {
+        let expected: IpAddr = "203.0.113.1".parse().unwrap();
+        let (resolver, handle) = mock_resolver(vec![vec![expected], vec!["198.51.100.7".parse().unwrap()]]);
+
+        assert!(verify_dns_record("home.example.com", expected, &resolver).unwrap());
+        assert!(!verify_dns_record("home.example.com", expected, &resolver).unwrap());
+        let queries = handle.join().unwrap();
+        assert!(queries[0].ends_with(b"\x04home\x07example\x03com\x00\x00\x01\x00\x01"));
+    }
+
+    #[test]
+    fn test_verifier_retries_until_record_matches() {
+        let expected: IpAddr = "2001:db8::1".parse().unwrap();
+        let (resolver, handle) = mock_resolver(vec![vec![], vec!["2001:db8::2".parse().unwrap()], vec![expected]]);
+
+        let verifier = Verifier { resolver, retries: 3, retry_delay: Duration::from_millis(1) };
+        assert!(verifier.verify("home.example.com", expected));
+        assert_eq!(handle.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_verifier_gives_up_after_retries() {
+        let expected: IpAddr = "203.0.113.1".parse().unwrap();
+        let (resolver, handle) = mock_resolver(vec![vec![], vec![]]);
+
+        let verifier = Verifier { resolver, retries: 1, retry_delay: Duration::from_millis(1) };
+        assert!(!verifier.verify("home.example.com", expected));
+        assert_eq!(handle.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_resolver_addr() {
+        assert_eq!(resolver_addr("8.8.8.8").unwrap(), "8.8.8.8:53".parse().unwrap());
+        assert_eq!(resolver_addr("127.0.0.1:5353").unwrap(), "127.0.0.1:5353".parse().unwrap());
+        assert_eq!(resolver_addr("2001:4860:4860::8888").unwrap(), "[2001:4860:4860::8888]:53".parse().unwrap());
+        assert!(resolver_addr("dns.google").is_err());
+    }
+}
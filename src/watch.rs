@@ -0,0 +1,153 @@
+/// Config file watching for hot reload (`--watch`)
+///
+/// The parent directory is watched rather than the file itself so that editors
+/// which save by replacing the file (write to temp + rename) are still noticed.
+use crate::args::Args;
+use crate::config::Config;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::error::Error;
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+/// Quiet period used to coalesce the burst of events a single save produces
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+pub struct ConfigWatcher {
+    file_name: OsString,
+    events: Receiver<notify::Result<Event>>,
+    // Dropping the watcher stops event delivery
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Start watching `path` for create/write events
+    pub fn new(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file_name = path.file_name()
+            .ok_or_else(|| format!("Cannot watch {}: not a file path", path.display()))?
+            .to_os_string();
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+        log::debug!("Watching {} for changes", path.display());
+
+        Ok(Self {
+            file_name,
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Block for up to `timeout` until the config file is created or written
+    /// Returns true if a change was seen
+    pub fn wait_for_change(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(remaining) {
+                Ok(Ok(event)) if self.is_relevant(&event) => break,
+                Ok(Ok(_)) => continue,
+                Ok(Err(e)) => log::warn!("Config watch error: {}", e),
+                Err(_) => return false,
+            }
+        }
+
+        // Drain the rest of the burst so one save triggers one reload
+        while self.events.recv_timeout(DEBOUNCE).is_ok() {}
+        true
+    }
+
+    fn is_relevant(&self, event: &Event) -> bool {
+        matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && event.paths.iter().any(|p| p.file_name() == Some(self.file_name.as_os_str()))
+    }
+}
+
+/// Reload and re-validate the configuration
+/// On failure the error is logged and `current` is left untouched.
+/// Returns true if the new configuration was applied.
+pub fn reload_config(current: &mut Config, args: &Args) -> bool {
+    match Config::load(args).and_then(|config| config.validate().map(|_| config)) {
+        Ok(config) => {
+            log::info!("Configuration reloaded");
+            *current = config;
+            true
+        }
+        Err(e) => {
+            log::error!("Ignoring invalid configuration, keeping previous one: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_hot_reload_picks_up_new_hosts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("rddclient.conf");
+        fs::write(&config_path, "protocol=dyndns2\nlogin=user\npassword=pass\nhost1.example.com\n").unwrap();
+
+        let args = Args::parse_from(["rddclient", "--file", config_path.to_str().unwrap()]);
+        let mut config = Config::load(&args).unwrap();
+        let watcher = ConfigWatcher::new(&config_path).unwrap();
+
+        // Mock daemon loop: one pass per iteration, config edited after the first pass
+        let mut seen = Vec::new();
+        for iteration in 0..2 {
+            seen.push(config.dns_records());
+
+            if iteration == 0 {
+                fs::write(
+                    &config_path,
+                    "protocol=dyndns2\nlogin=user\npassword=pass\nhost2.example.com\n",
+                ).unwrap();
+
+                assert!(watcher.wait_for_change(Duration::from_secs(5)));
+                assert!(reload_config(&mut config, &args));
+            }
+        }
+
+        assert_eq!(seen[0], vec!["host1.example.com"]);
+        assert_eq!(seen[1], vec!["host2.example.com"]);
+    }
+
+    #[test]
+    fn test_invalid_reload_keeps_previous_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("rddclient.conf");
+        fs::write(&config_path, "protocol=dyndns2\nhost1.example.com\n").unwrap();
+
+        let args = Args::parse_from(["rddclient", "--file", config_path.to_str().unwrap()]);
+        let mut config = Config::load(&args).unwrap();
+
+        // No host left: fails validation
+        fs::write(&config_path, "protocol=dyndns2\n").unwrap();
+        assert!(!reload_config(&mut config, &args));
+        assert_eq!(config.dns_records(), vec!["host1.example.com"]);
+    }
+
+    #[test]
+    fn test_unrelated_files_are_ignored() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("rddclient.conf");
+        fs::write(&config_path, "protocol=dyndns2\n").unwrap();
+
+        let watcher = ConfigWatcher::new(&config_path).unwrap();
+        fs::write(temp_dir.path().join("other.conf"), "x").unwrap();
+        assert!(!watcher.wait_for_change(Duration::from_millis(300)));
+    }
+}
@@ -0,0 +1,61 @@
+use crate::common::rddclient_with;
+
+/// Updated an hour ago, so min-interval doesn't apply
+fn cache(ip: &str) -> String {
+    let mtime = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        - 3600;
+    format!("ip={},mtime={},status=good home.example.com\n", ip, mtime)
+}
+
+const ARGS: &[&str] = &[
+    "--check",
+    "--protocol", "duckdns",
+    // Never contacted in --check mode
+    "--server", "http://127.0.0.1:9",
+    "--password", "duck-token",
+    "--host", "home.example.com",
+];
+
+#[test]
+fn test_check_nothing_to_update() {
+    let run = rddclient_with(&cache("203.0.113.1"), "203.0.113.1", ARGS);
+
+    assert_eq!(run.output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&run.output.stdout);
+    assert!(stdout.contains("home.example.com: detected 203.0.113.1, cached 203.0.113.1: no update"));
+}
+
+#[test]
+fn test_check_update_needed() {
+    let run = rddclient_with(&cache("198.51.100.7"), "203.0.113.1", ARGS);
+
+    assert_eq!(run.output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&run.output.stdout);
+    assert!(stdout.contains("cached 198.51.100.7: update needed"));
+    // The state file is left untouched
+    assert!(run.cache().contains("ip=198.51.100.7"));
+}
+
+#[test]
+fn test_check_json() {
+    let args: Vec<&str> = ARGS.iter().copied().chain(["--json"]).collect();
+    let run = rddclient_with(&cache("198.51.100.7"), "203.0.113.1", &args);
+
+    assert_eq!(run.output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&run.output.stdout).unwrap();
+    assert_eq!(json[0]["hostname"], "home.example.com");
+    assert_eq!(json[0]["detected_ip"], "203.0.113.1");
+    assert_eq!(json[0]["cached_ip"], "198.51.100.7");
+    assert_eq!(json[0]["would_update"], true);
+}
+
+#[test]
+fn test_check_ip_detection_error() {
+    let run = rddclient_with(&cache("198.51.100.7"), "not-an-ip", ARGS);
+
+    assert_eq!(run.output.status.code(), Some(2));
+    assert!(run.stderr().contains("IP detection failed"));
+}
@@ -0,0 +1,73 @@
+use crate::common::{rddclient, USER_AGENT};
+use mockito::{Matcher, Server};
+
+const BEARER: &str = "Bearer cf-token";
+
+fn run(server: &Server) -> crate::common::Run {
+    rddclient(&[
+        "--protocol", "cloudflare",
+        "--server", &server.url(),
+        "--login", "token",
+        "--password", "cf-token",
+        "--zone", "example.com",
+        "--host", "home.example.com",
+    ])
+}
+
+fn mock_lookups(server: &mut Server) -> (mockito::Mock, mockito::Mock) {
+    let zone = server.mock("GET", "/zones/")
+        .match_query(Matcher::UrlEncoded("name".to_string(), "example.com".to_string()))
+        .match_header("authorization", BEARER)
+        .match_header("user-agent", USER_AGENT)
+        .with_body(r#"{"success":true,"result":[{"id":"zone123"}]}"#)
+        .create();
+    let record = server.mock("GET", "/zones/zone123/dns_records")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("type".to_string(), "A".to_string()),
+            Matcher::UrlEncoded("name".to_string(), "home.example.com".to_string()),
+        ]))
+        .match_header("authorization", BEARER)
+        .match_header("user-agent", USER_AGENT)
+        .with_body(r#"{"success":true,"result":[{"id":"rec456"}]}"#)
+        .create();
+    (zone, record)
+}
+
+#[test]
+fn test_cloudflare_good() {
+    let mut server = Server::new();
+    let (zone, record) = mock_lookups(&mut server);
+    let update = server.mock("PUT", "/zones/zone123/dns_records/rec456")
+        .match_header("authorization", BEARER)
+        .match_header("user-agent", USER_AGENT)
+        .match_body(Matcher::PartialJsonString(
+            r#"{"type":"A","name":"home.example.com","content":"203.0.113.1"}"#.to_string(),
+        ))
+        .with_body(r#"{"success":true}"#)
+        .create();
+
+    let run = run(&server);
+
+    assert!(run.output.status.success());
+    zone.assert();
+    record.assert();
+    update.assert();
+    assert!(run.cache().contains("status=good"));
+}
+
+#[test]
+fn test_cloudflare_badauth() {
+    let mut server = Server::new();
+    let zone = server.mock("GET", "/zones/")
+        .match_query(Matcher::Any)
+        .match_header("authorization", BEARER)
+        .with_status(403)
+        .with_body(r#"{"success":false,"errors":[{"code":9109,"message":"Invalid access token"}]}"#)
+        .create();
+
+    let run = run(&server);
+
+    zone.assert();
+    assert!(run.stderr().contains("Error getting zone ID"));
+    assert!(run.cache().contains("status=FAILED"));
+}
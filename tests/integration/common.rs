@@ -0,0 +1,56 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// User-Agent every provider request must carry
+pub const USER_AGENT: &str = concat!("rddclient/", env!("CARGO_PKG_VERSION"));
+
+/// One rddclient invocation with its own isolated state file
+pub struct Run {
+    pub output: Output,
+    cache: PathBuf,
+    _dir: TempDir,
+}
+
+impl Run {
+    /// State file contents written by the run
+    pub fn cache(&self) -> String {
+        fs::read_to_string(&self.cache).unwrap_or_default()
+    }
+
+    pub fn stderr(&self) -> String {
+        String::from_utf8_lossy(&self.output.stderr).into_owned()
+    }
+}
+
+/// Run rddclient with `args`, a fixed IP and a fresh cache file
+/// No config file is read so only the given arguments apply.
+pub fn rddclient(args: &[&str]) -> Run {
+    rddclient_with("", "203.0.113.1", args)
+}
+
+/// Like `rddclient`, but with `--ip ip` and the cache file pre-filled with `cache`
+pub fn rddclient_with(cache_contents: &str, ip: &str, args: &[&str]) -> Run {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = dir.path().join("rddclient.cache");
+    if !cache_contents.is_empty() {
+        fs::write(&cache, cache_contents).unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rddclient"))
+        .args(args)
+        .args(["--ip", ip])
+        .arg("--file")
+        .arg(dir.path().join("missing.conf"))
+        .arg("--cache")
+        .arg(&cache)
+        .output()
+        .unwrap();
+
+    Run {
+        output,
+        cache,
+        _dir: dir,
+    }
+}
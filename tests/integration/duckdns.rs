@@ -0,0 +1,120 @@
+use crate::common::{rddclient, USER_AGENT};
+use mockito::{Matcher, Server};
+
+fn run(server: &Server) -> crate::common::Run {
+    rddclient(&[
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "myhost.duckdns.org",
+    ])
+}
+
+fn mock_update(server: &mut Server, body: &str) -> mockito::Mock {
+    server.mock("GET", "/update")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("domains".to_string(), "myhost".to_string()),
+            Matcher::UrlEncoded("token".to_string(), "duck-token".to_string()),
+            Matcher::UrlEncoded("ip".to_string(), "203.0.113.1".to_string()),
+        ]))
+        .match_header("user-agent", USER_AGENT)
+        .with_body(body)
+        .create()
+}
+
+#[test]
+fn test_duckdns_good() {
+    let mut server = Server::new();
+    let mock = mock_update(&mut server, "OK");
+
+    let run = run(&server);
+
+    assert!(run.output.status.success());
+    mock.assert();
+    assert!(run.cache().contains("status=good"));
+}
+
+#[test]
+fn test_duckdns_badauth() {
+    let mut server = Server::new();
+    let mock = mock_update(&mut server, "KO");
+
+    let run = run(&server);
+
+    mock.assert();
+    assert!(run.stderr().contains("check your token"));
+    assert!(run.cache().contains("status=FAILED"));
+}
+
+#[test]
+fn test_duckdns_extra_headers() {
+    let mut server = Server::new();
+    let mock = server.mock("GET", "/update")
+        .match_query(Matcher::Any)
+        .match_header("x-tenant", "home")
+        .match_header("user-agent", USER_AGENT)
+        .with_body("OK")
+        .create();
+
+    let run = rddclient(&[
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "myhost.duckdns.org",
+        "--header", "X-Tenant: home",
+    ]);
+
+    assert!(run.output.status.success());
+    mock.assert();
+}
+
+#[test]
+fn test_duckdns_json_logs() {
+    let mut server = Server::new();
+    let _mock = mock_update(&mut server, "OK");
+
+    let run = rddclient(&[
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "myhost.duckdns.org",
+        "--verbose",
+        "--log-format", "json",
+    ]);
+
+    assert!(run.output.status.success());
+    let entries: Vec<serde_json::Value> = run.stderr()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(entries.iter().all(|entry| entry["level"].is_string() && entry["timestamp"].is_string()));
+
+    let updated = entries.iter()
+        .find(|entry| entry["message"] == "Successfully updated myhost.duckdns.org")
+        .unwrap();
+    assert_eq!(updated["level"], "INFO");
+    assert_eq!(updated["module"], "rddclient");
+    assert_eq!(updated["hostname"], "myhost.duckdns.org");
+    assert_eq!(updated["ip"], "203.0.113.1");
+}
+
+#[test]
+fn test_duckdns_custom_user_agent() {
+    let mut server = Server::new();
+    let mock = server.mock("GET", "/update")
+        .match_query(Matcher::Any)
+        .match_header("user-agent", "my-router/1.0")
+        .with_body("OK")
+        .create();
+
+    let run = rddclient(&[
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "myhost.duckdns.org",
+        "--user-agent", "my-router/1.0",
+    ]);
+
+    assert!(run.output.status.success());
+    mock.assert();
+}
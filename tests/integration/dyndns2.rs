@@ -0,0 +1,65 @@
+use crate::common::{rddclient, USER_AGENT};
+use mockito::{Matcher, Server};
+
+// base64("user:pass")
+const BASIC_AUTH: &str = "Basic dXNlcjpwYXNz";
+
+fn run(server: &Server) -> crate::common::Run {
+    rddclient(&[
+        "--protocol", "dyndns2",
+        "--server", &server.url(),
+        "--login", "user",
+        "--password", "pass",
+        "--host", "home.example.com",
+    ])
+}
+
+fn mock_update(server: &mut Server, body: &str) -> mockito::Mock {
+    server.mock("GET", "/nic/update")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("hostname".to_string(), "home.example.com".to_string()),
+            Matcher::UrlEncoded("myip".to_string(), "203.0.113.1".to_string()),
+        ]))
+        .match_header("authorization", BASIC_AUTH)
+        .match_header("user-agent", USER_AGENT)
+        .with_body(body)
+        .create()
+}
+
+#[test]
+fn test_dyndns2_good() {
+    let mut server = Server::new();
+    let mock = mock_update(&mut server, "good 203.0.113.1");
+
+    let run = run(&server);
+
+    assert!(run.output.status.success());
+    mock.assert();
+    assert!(run.cache().contains("ip=203.0.113.1"));
+    assert!(run.cache().contains("status=good"));
+}
+
+#[test]
+fn test_dyndns2_nochg() {
+    let mut server = Server::new();
+    let mock = mock_update(&mut server, "nochg 203.0.113.1");
+
+    let run = run(&server);
+
+    assert!(run.output.status.success());
+    mock.assert();
+    assert!(run.cache().contains("ip=203.0.113.1"));
+}
+
+#[test]
+fn test_dyndns2_badauth() {
+    let mut server = Server::new();
+    let mock = mock_update(&mut server, "badauth");
+
+    let run = run(&server);
+
+    mock.assert();
+    assert!(run.stderr().contains("Bad authorization"));
+    assert!(run.cache().contains("status=FAILED"));
+    assert!(!run.cache().contains("ip=203.0.113.1"));
+}
@@ -0,0 +1,86 @@
+use crate::common::{rddclient, rddclient_with};
+use mockito::Server;
+use std::process::Command;
+
+#[test]
+fn test_invalid_config_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("rddclient.conf");
+    std::fs::write(&config, "protocol=duckdns\npassword=${RDDCLIENT_EXIT_CODE_TEST_UNSET}\nhome.example.com\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rddclient"))
+        .arg("--file")
+        .arg(&config)
+        .arg("--cache")
+        .arg(dir.path().join("rddclient.cache"))
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("RDDCLIENT_EXIT_CODE_TEST_UNSET is not set"));
+}
+
+#[test]
+fn test_unknown_provider_exit_code() {
+    let run = rddclient(&["--protocol", "nosuchdns", "--password", "x", "--host", "home.example.com"]);
+    assert_eq!(run.output.status.code(), Some(2));
+}
+
+#[test]
+fn test_ip_detection_exit_code() {
+    let run = rddclient_with("", "127.0.0.1", &["--protocol", "duckdns", "--password", "x", "--host", "home.example.com"]);
+    assert_eq!(run.output.status.code(), Some(3));
+    assert!(run.stderr().contains("loopback"));
+}
+
+#[test]
+fn test_update_failure_exit_code() {
+    let mut server = Server::new();
+    let _mock = server.mock("GET", "/update")
+        .match_query(mockito::Matcher::Any)
+        .with_body("KO")
+        .create();
+
+    let run = rddclient(&[
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "myhost.duckdns.org",
+    ]);
+    assert_eq!(run.output.status.code(), Some(1));
+    assert!(run.stderr().contains("1 of 1 host(s) failed to update: myhost.duckdns.org"));
+}
+
+#[test]
+fn test_ca_cert_refused_before_any_request() {
+    let mut server = Server::new();
+    let mock = server.mock("GET", "/update")
+        .match_query(mockito::Matcher::Any)
+        .expect(0)
+        .create();
+
+    let ca_cert = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ca.pem");
+    let run = rddclient(&[
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "myhost.duckdns.org",
+        "--ca-cert", ca_cert,
+    ]);
+    assert_eq!(run.output.status.code(), Some(2));
+    assert!(run.stderr().contains("only trusts its built-in root certificates"));
+    mock.assert();
+}
+
+#[test]
+fn test_unwritable_pid_file_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let not_a_dir = dir.path().join("file");
+    std::fs::write(&not_a_dir, "").unwrap();
+    let pid_file = not_a_dir.join("rddclient.pid");
+    let run = rddclient(&[
+        "--protocol", "duckdns", "--password", "x", "--host", "home.example.com",
+        "--pid-file", pid_file.to_str().unwrap(),
+    ]);
+    assert_eq!(run.output.status.code(), Some(4));
+}
@@ -0,0 +1,57 @@
+use crate::common::rddclient;
+use mockito::{Matcher, Server};
+
+fn duckdns_args<'a>(server: &'a str, extra: &[&'a str]) -> Vec<&'a str> {
+    let mut args = vec![
+        "--protocol", "duckdns",
+        "--server", server,
+        "--password", "duck-token",
+        "--host", "myhost.duckdns.org",
+    ];
+    args.extend_from_slice(extra);
+    args
+}
+
+#[test]
+fn test_hooks_receive_update_details() {
+    let mut server = Server::new();
+    let _mock = server.mock("GET", "/update").match_query(Matcher::Any).with_body("OK").create();
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("hooks.out");
+
+    let pre = format!("echo \"pre $RDDCLIENT_IP $RDDCLIENT_HOSTNAMES\" >> {}", out.display());
+    let post = format!("echo \"post $RDDCLIENT_UPDATED|$RDDCLIENT_FAILED\" >> {}", out.display());
+    let url = server.url();
+    let run = rddclient(&duckdns_args(&url, &["--pre-update-cmd", &pre, "--post-update-cmd", &post]));
+
+    assert!(run.output.status.success(), "{}", run.stderr());
+    assert_eq!(
+        std::fs::read_to_string(&out).unwrap(),
+        "pre 203.0.113.1 myhost.duckdns.org\npost myhost.duckdns.org|\n"
+    );
+}
+
+#[test]
+fn test_failing_pre_hook_aborts() {
+    let mut server = Server::new();
+    let mock = server.mock("GET", "/update").match_query(Matcher::Any).with_body("OK").expect(0).create();
+
+    let url = server.url();
+    let run = rddclient(&duckdns_args(&url, &["--pre-update-cmd", "exit 1"]));
+
+    assert_eq!(run.output.status.code(), Some(2));
+    assert!(run.stderr().contains("Hook 'exit 1' failed"));
+    mock.assert();
+}
+
+#[test]
+fn test_ignore_hook_errors() {
+    let mut server = Server::new();
+    let mock = server.mock("GET", "/update").match_query(Matcher::Any).with_body("OK").create();
+
+    let url = server.url();
+    let run = rddclient(&duckdns_args(&url, &["--pre-update-cmd", "exit 1", "--ignore-hook-errors"]));
+
+    assert!(run.output.status.success());
+    mock.assert();
+}
@@ -0,0 +1,24 @@
+use crate::common::rddclient;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ddclient.cache");
+
+#[test]
+fn test_import_ddclient_cache() {
+    let run = rddclient(&["--import-ddclient-cache", FIXTURE]);
+
+    assert!(run.output.status.success());
+    let stdout = String::from_utf8_lossy(&run.output.stdout);
+    assert!(stdout.contains("home.example.com: imported"));
+    assert!(stdout.contains("3 host(s) imported, 0 skipped"));
+    assert!(run.cache().contains("ip=198.51.100.7,mtime=1704110400,status=good"));
+}
+
+#[test]
+fn test_dry_run_import_writes_nothing() {
+    let run = rddclient(&["--import-ddclient-cache", FIXTURE, "--dry-run-import"]);
+
+    assert!(run.output.status.success());
+    let stdout = String::from_utf8_lossy(&run.output.stdout);
+    assert!(stdout.contains("vpn.example.com: would import"));
+    assert!(run.cache().is_empty());
+}
@@ -0,0 +1,39 @@
+use fs2::FileExt;
+use std::fs;
+use std::process::{Command, Output};
+
+/// Run `rddclient --check` against `cache` while this process may hold a lock on it
+fn check(cache: &std::path::Path, extra: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rddclient"))
+        .args(["--check", "--protocol", "duckdns", "--server", "http://127.0.0.1:9"])
+        .args(["--password", "duck-token", "--host", "home.example.com", "--ip", "203.0.113.1"])
+        .arg("--file")
+        .arg(cache.with_file_name("missing.conf"))
+        .arg("--cache")
+        .arg(cache)
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_state_file_locked_by_another_process() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = dir.path().join("rddclient.cache");
+    let mtime = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() - 3600;
+    fs::write(&cache, format!("ip=203.0.113.1,mtime={},status=good home.example.com\n", mtime)).unwrap();
+
+    let holder = fs::OpenOptions::new().read(true).write(true).open(&cache).unwrap();
+    holder.lock_exclusive().unwrap();
+
+    let output = check(&cache, &[]);
+    assert_ne!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("state file locked by another process"));
+
+    // --no-lock reads the file regardless
+    let output = check(&cache, &["--no-lock"]);
+    assert_eq!(output.status.code(), Some(0));
+
+    FileExt::unlock(&holder).unwrap();
+    assert_eq!(check(&cache, &[]).status.code(), Some(0));
+}
@@ -0,0 +1,15 @@
+//! End-to-end tests: run the rddclient binary against per-test mock HTTP servers.
+//!
+//! No network access is needed; every provider URL points at a local mockito server.
+
+mod check;
+mod cloudflare;
+mod common;
+mod duckdns;
+mod exit_codes;
+#[cfg(unix)]
+mod hooks;
+mod dyndns2;
+mod import;
+mod locking;
+mod summary;
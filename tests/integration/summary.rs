@@ -0,0 +1,78 @@
+use crate::common::{rddclient, rddclient_with};
+use mockito::{Matcher, Server, ServerGuard};
+
+/// DuckDNS mock accepting `good` and rejecting `bad`
+fn mock_server() -> (ServerGuard, Vec<mockito::Mock>) {
+    let mut server = Server::new();
+    let mocks = vec![
+        server.mock("GET", "/update")
+            .match_query(Matcher::UrlEncoded("domains".to_string(), "good".to_string()))
+            .with_body("OK")
+            .create(),
+        server.mock("GET", "/update")
+            .match_query(Matcher::UrlEncoded("domains".to_string(), "bad".to_string()))
+            .with_body("KO")
+            .create(),
+    ];
+    (server, mocks)
+}
+
+#[test]
+fn test_summary_mixed_results() {
+    let (server, _mocks) = mock_server();
+    // unchanged.duckdns.org was updated to the same IP an hour ago
+    let mtime = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        - 3600;
+    let cache = format!("ip=203.0.113.1,mtime={},status=good unchanged.duckdns.org\n", mtime);
+
+    let run = rddclient_with(&cache, "203.0.113.1", &[
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "good.duckdns.org,bad.duckdns.org,unchanged.duckdns.org",
+    ]);
+
+    assert_eq!(run.output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&run.output.stdout);
+    assert!(stdout.contains("Updated: 1 host(s), Skipped: 1, Failed: 1"), "{}", stdout);
+}
+
+#[test]
+fn test_summary_json() {
+    let (server, _mocks) = mock_server();
+
+    let run = rddclient(&[
+        "--json",
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "good.duckdns.org,bad.duckdns.org",
+    ]);
+
+    assert_eq!(run.output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&run.output.stdout);
+    let summary: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    assert_eq!(summary["updated"], serde_json::json!(["good.duckdns.org"]));
+    assert_eq!(summary["skipped"], serde_json::json!([]));
+    assert_eq!(summary["failed"][0]["hostname"], "bad.duckdns.org");
+    assert!(summary["failed"][0]["error"].as_str().unwrap().contains("DuckDNS update failed"));
+}
+
+#[test]
+fn test_summary_all_updated() {
+    let (server, _mocks) = mock_server();
+
+    let run = rddclient(&[
+        "--protocol", "duckdns",
+        "--server", &server.url(),
+        "--password", "duck-token",
+        "--host", "good.duckdns.org",
+    ]);
+
+    assert_eq!(run.output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&run.output.stdout);
+    assert!(stdout.contains("Updated: 1 host(s), Skipped: 0, Failed: 0"));
+}